@@ -0,0 +1,117 @@
+//! Compares the sharded keyspace against a single globally locked map
+//! (`MemoryStore::with_shards(1)`, the previous design) under concurrent
+//! load. Run with `cargo bench --bench keyspace`.
+
+use std::sync::Arc;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use tokio::runtime::Runtime;
+use tokio_util::bytes::Bytes;
+use yars::store::{
+    memory::{DEFAULT_SHARDS, MemoryStore},
+    traits::Store,
+    types::{Entry, Expiry},
+};
+
+const TASKS: usize = 8;
+const OPS_PER_TASK: usize = 1_000;
+const KEYSPACE: usize = 10_000;
+const MSET_BATCH: usize = 100;
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(TASKS)
+        .build()
+        .expect("tokio runtime")
+}
+
+fn keys() -> Arc<Vec<Bytes>> {
+    Arc::new(
+        (0..KEYSPACE)
+            .map(|i| Bytes::from(format!("key:{i}")))
+            .collect(),
+    )
+}
+
+/// Each task mixes one SET for every three GETs over the shared keyspace.
+async fn mixed_load(store: Arc<MemoryStore>, keys: Arc<Vec<Bytes>>) {
+    let tasks: Vec<_> = (0..TASKS)
+        .map(|t| {
+            let store = store.clone();
+            let keys = keys.clone();
+            tokio::spawn(async move {
+                for i in 0..OPS_PER_TASK {
+                    let key = &keys[(t * 7919 + i * 104_729) % keys.len()];
+                    if i % 4 == 0 {
+                        let entry = Entry {
+                            value: Bytes::from_static(b"0123456789abcdef").into(),
+                            exp: Expiry::None,
+                        };
+                        store.set(key.clone(), entry).await;
+                    } else {
+                        store.get(key).await;
+                    }
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.expect("task panicked");
+    }
+}
+
+/// Half the tasks issue large MSETs while the rest read single keys.
+async fn mset_with_readers(store: Arc<MemoryStore>, keys: Arc<Vec<Bytes>>) {
+    let tasks: Vec<_> = (0..TASKS)
+        .map(|t| {
+            let store = store.clone();
+            let keys = keys.clone();
+            tokio::spawn(async move {
+                for i in 0..OPS_PER_TASK / MSET_BATCH {
+                    if t % 2 == 0 {
+                        let start = (t * MSET_BATCH + i * 977) % (keys.len() - MSET_BATCH);
+                        let items: Vec<_> = keys[start..start + MSET_BATCH]
+                            .iter()
+                            .map(|k| (k.clone(), Bytes::from_static(b"v")))
+                            .collect();
+                        store.mset(&items).await;
+                    } else {
+                        for j in 0..MSET_BATCH {
+                            store.get(&keys[(i * MSET_BATCH + j) % keys.len()]).await;
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.expect("task panicked");
+    }
+}
+
+fn bench_keyspace(c: &mut Criterion) {
+    let rt = runtime();
+    let keys = keys();
+
+    let mut group = c.benchmark_group("keyspace");
+    group.throughput(Throughput::Elements((TASKS * OPS_PER_TASK) as u64));
+    for shards in [1, DEFAULT_SHARDS] {
+        let store = Arc::new(MemoryStore::with_shards(shards));
+        group.bench_with_input(BenchmarkId::new("mixed", shards), &store, |b, store| {
+            b.to_async(&rt)
+                .iter(|| mixed_load(store.clone(), keys.clone()));
+        });
+        group.bench_with_input(
+            BenchmarkId::new("mset_with_readers", shards),
+            &store,
+            |b, store| {
+                b.to_async(&rt)
+                    .iter(|| mset_with_readers(store.clone(), keys.clone()));
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_keyspace);
+criterion_main!(benches);
@@ -0,0 +1,442 @@
+//! Load generator for YARS (and any other RESP server), modelled on
+//! `redis-benchmark`.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use futures::{SinkExt, StreamExt};
+use hdrhistogram::Histogram;
+use tokio::net::TcpStream;
+use tokio_util::{bytes::Bytes, codec::Framed};
+use yars::{
+    protocol::resp::{Frame, RespCodec},
+    utils::random::random_u64,
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "yars-benchmark", version, disable_help_flag = true)]
+struct Args {
+    /// Server hostname.
+    #[arg(short = 'h', long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// Server port.
+    #[arg(short, long, default_value_t = 6379)]
+    port: u16,
+
+    /// Number of parallel connections.
+    #[arg(short, long, default_value_t = 50)]
+    clients: usize,
+
+    /// Total number of requests.
+    #[arg(short = 'n', long, default_value_t = 100_000)]
+    requests: u64,
+
+    /// Pipeline <numreq> requests per round trip.
+    #[arg(short = 'P', long, default_value_t = 1)]
+    pipeline: usize,
+
+    /// Use random keys from `key:0` to `key:<keyspace - 1>`.
+    #[arg(short = 'r', long, default_value_t = 10_000)]
+    keyspace: u64,
+
+    /// Value size in bytes for SET and MSET.
+    #[arg(short = 'd', long, default_value_t = 3)]
+    data_size: usize,
+
+    /// Number of keys per MSET and MGET.
+    #[arg(long, default_value_t = 10)]
+    multi_keys: usize,
+
+    /// Weighted command mix, e.g. `get:3,set:1,incr`.
+    #[arg(short = 't', long, default_value = "set,get")]
+    tests: Mix,
+
+    /// Output results as CSV.
+    #[arg(long)]
+    csv: bool,
+
+    /// Print help.
+    #[arg(long, action = clap::ArgAction::Help)]
+    help: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum BenchCommand {
+    Ping,
+    Set,
+    Get,
+    Incr,
+    Mset,
+    Mget,
+    Del,
+}
+
+impl BenchCommand {
+    fn name(&self) -> &'static str {
+        match self {
+            BenchCommand::Ping => "PING",
+            BenchCommand::Set => "SET",
+            BenchCommand::Get => "GET",
+            BenchCommand::Incr => "INCR",
+            BenchCommand::Mset => "MSET",
+            BenchCommand::Mget => "MGET",
+            BenchCommand::Del => "DEL",
+        }
+    }
+
+    fn frame(&self, args: &Args, value: &Bytes) -> Frame {
+        let key = || Frame::BulkString(format!("key:{}", random_u64() % args.keyspace).into());
+        let mut parts = vec![Frame::BulkString(self.name().into())];
+        match self {
+            BenchCommand::Ping => {}
+            BenchCommand::Get | BenchCommand::Del => parts.push(key()),
+            BenchCommand::Incr => parts.push(Frame::BulkString(
+                format!("counter:{}", random_u64() % args.keyspace).into(),
+            )),
+            BenchCommand::Set => {
+                parts.push(key());
+                parts.push(Frame::BulkString(value.clone()));
+            }
+            BenchCommand::Mset => {
+                for _ in 0..args.multi_keys {
+                    parts.push(key());
+                    parts.push(Frame::BulkString(value.clone()));
+                }
+            }
+            BenchCommand::Mget => parts.extend((0..args.multi_keys).map(|_| key())),
+        }
+        Frame::Array(parts)
+    }
+}
+
+impl FromStr for BenchCommand {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "ping" => BenchCommand::Ping,
+            "set" => BenchCommand::Set,
+            "get" => BenchCommand::Get,
+            "incr" => BenchCommand::Incr,
+            "mset" => BenchCommand::Mset,
+            "mget" => BenchCommand::Mget,
+            "del" => BenchCommand::Del,
+            other => bail!("unsupported test '{other}'"),
+        })
+    }
+}
+
+/// Commands with relative weights; each request picks one at random.
+#[derive(Clone, Debug, PartialEq)]
+struct Mix {
+    weighted: Vec<(BenchCommand, u32)>,
+    total: u32,
+}
+
+impl Mix {
+    fn pick(&self, roll: u64) -> BenchCommand {
+        let mut roll = (roll % self.total as u64) as u32;
+        for (command, weight) in &self.weighted {
+            if roll < *weight {
+                return *command;
+            }
+            roll -= weight;
+        }
+        unreachable!("roll is below the total weight")
+    }
+}
+
+impl FromStr for Mix {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut weighted = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, weight) = match part.split_once(':') {
+                Some((name, weight)) => (
+                    name,
+                    weight
+                        .parse::<u32>()
+                        .map_err(|_| anyhow!("invalid weight in '{part}'"))?,
+                ),
+                None => (part, 1),
+            };
+            if weight > 0 {
+                weighted.push((name.parse()?, weight));
+            }
+        }
+        let total = weighted.iter().map(|(_, w)| w).sum();
+        if total == 0 {
+            bail!("no tests selected");
+        }
+        Ok(Self { weighted, total })
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    latencies: BTreeMap<BenchCommand, Histogram<u64>>,
+    errors: u64,
+}
+
+impl Stats {
+    fn record(&mut self, command: BenchCommand, latency: Duration) {
+        let histogram = self.latencies.entry(command).or_insert_with(new_histogram);
+        histogram.saturating_record(latency.as_micros() as u64);
+    }
+
+    fn merge(&mut self, other: Stats) {
+        for (command, histogram) in other.latencies {
+            self.latencies
+                .entry(command)
+                .or_insert_with(new_histogram)
+                .add(&histogram)
+                .expect("histograms share bounds");
+        }
+        self.errors += other.errors;
+    }
+
+    fn total(&self) -> Histogram<u64> {
+        let mut total = new_histogram();
+        for histogram in self.latencies.values() {
+            total.add(histogram).expect("histograms share bounds");
+        }
+        total
+    }
+}
+
+/// Microsecond resolution, up to one minute.
+fn new_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, 60_000_000, 3).expect("valid histogram bounds")
+}
+
+async fn run_client(args: Arc<Args>, issued: Arc<AtomicU64>) -> Result<Stats> {
+    let stream = TcpStream::connect((args.host.as_str(), args.port)).await?;
+    stream.set_nodelay(true)?;
+    let mut framed = Framed::new(stream, RespCodec);
+    let value = Bytes::from(vec![b'x'; args.data_size]);
+    let mut stats = Stats::default();
+    let mut in_flight = Vec::with_capacity(args.pipeline);
+
+    loop {
+        let claimed = issued.fetch_add(args.pipeline as u64, Ordering::Relaxed);
+        if claimed >= args.requests {
+            break;
+        }
+        let batch = (args.requests - claimed).min(args.pipeline as u64);
+
+        let sent = Instant::now();
+        for _ in 0..batch {
+            let command = args.tests.pick(random_u64());
+            framed.feed(command.frame(&args, &value)).await?;
+            in_flight.push(command);
+        }
+        framed.flush().await?;
+
+        for command in in_flight.drain(..) {
+            let reply = framed
+                .next()
+                .await
+                .ok_or_else(|| anyhow!("server closed the connection"))??;
+            if matches!(reply, Frame::Error(_)) {
+                stats.errors += 1;
+            }
+            stats.record(command, sent.elapsed());
+        }
+    }
+    Ok(stats)
+}
+
+fn ms(micros: u64) -> f64 {
+    micros as f64 / 1000.0
+}
+
+fn text_report(stats: &Stats, elapsed: Duration, args: &Args) -> String {
+    let mut out = String::new();
+    let mut section = |name: &str, histogram: &Histogram<u64>| {
+        let _ = writeln!(out, "====== {name} ======");
+        let _ = writeln!(
+            out,
+            "  {} requests completed in {:.2} seconds",
+            histogram.len(),
+            elapsed.as_secs_f64()
+        );
+        let _ = writeln!(
+            out,
+            "  {} parallel clients, {} bytes payload, pipeline {}",
+            args.clients, args.data_size, args.pipeline
+        );
+        let _ = writeln!(
+            out,
+            "  throughput: {:.2} requests per second",
+            histogram.len() as f64 / elapsed.as_secs_f64()
+        );
+        let _ = writeln!(out, "  latency distribution (ms):");
+        for quantile in [0.5, 0.75, 0.9, 0.99, 0.999, 1.0] {
+            let _ = writeln!(
+                out,
+                "    {:>7.3}% <= {:.3}",
+                quantile * 100.0,
+                ms(histogram.value_at_quantile(quantile))
+            );
+        }
+        let _ = writeln!(out);
+    };
+
+    for (command, histogram) in &stats.latencies {
+        section(command.name(), histogram);
+    }
+    if stats.latencies.len() > 1 {
+        section("ALL", &stats.total());
+    }
+    if stats.errors > 0 {
+        let _ = writeln!(out, "{} requests returned an error", stats.errors);
+    }
+    out
+}
+
+fn csv_report(stats: &Stats, elapsed: Duration) -> String {
+    let mut out = String::from(
+        "\"test\",\"rps\",\"avg_latency_ms\",\"p50_latency_ms\",\"p99_latency_ms\",\"p999_latency_ms\",\"max_latency_ms\"\n",
+    );
+    let total = stats.total();
+    let rows = stats
+        .latencies
+        .iter()
+        .map(|(command, histogram)| (command.name(), histogram))
+        .chain((stats.latencies.len() > 1).then_some(("ALL", &total)));
+    for (name, histogram) in rows {
+        let _ = writeln!(
+            out,
+            "\"{name}\",\"{:.2}\",\"{:.3}\",\"{:.3}\",\"{:.3}\",\"{:.3}\",\"{:.3}\"",
+            histogram.len() as f64 / elapsed.as_secs_f64(),
+            histogram.mean() / 1000.0,
+            ms(histogram.value_at_quantile(0.5)),
+            ms(histogram.value_at_quantile(0.99)),
+            ms(histogram.value_at_quantile(0.999)),
+            ms(histogram.max()),
+        );
+    }
+    out
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Arc::new(Args::parse());
+    if args.clients == 0 || args.pipeline == 0 || args.keyspace == 0 {
+        bail!("--clients, --pipeline and --keyspace must be positive");
+    }
+
+    let issued = Arc::new(AtomicU64::new(0));
+    let started = Instant::now();
+    let clients: Vec<_> = (0..args.clients)
+        .map(|_| tokio::spawn(run_client(args.clone(), issued.clone())))
+        .collect();
+
+    let mut stats = Stats::default();
+    for client in clients {
+        stats.merge(client.await??);
+    }
+    let elapsed = started.elapsed();
+
+    if args.csv {
+        print!("{}", csv_report(&stats, elapsed));
+    } else {
+        print!("{}", text_report(&stats, elapsed, &args));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mix_parses_weights_and_picks_proportionally() {
+        let mix: Mix = "get:3, set".parse().unwrap();
+        assert_eq!(
+            mix.weighted,
+            vec![(BenchCommand::Get, 3), (BenchCommand::Set, 1)]
+        );
+        assert_eq!(mix.total, 4);
+        let picks: Vec<_> = (0..4).map(|roll| mix.pick(roll)).collect();
+        assert_eq!(
+            picks,
+            vec![
+                BenchCommand::Get,
+                BenchCommand::Get,
+                BenchCommand::Get,
+                BenchCommand::Set
+            ]
+        );
+    }
+
+    #[test]
+    fn mix_rejects_unknown_and_empty() {
+        assert!("hset".parse::<Mix>().is_err());
+        assert!("get:x".parse::<Mix>().is_err());
+        assert!("get:0".parse::<Mix>().is_err());
+    }
+
+    #[test]
+    fn frames_use_keyspace_and_payload() {
+        let args = Args::parse_from(["yars-benchmark", "-r", "1", "--multi-keys", "2"]);
+        let value = Bytes::from_static(b"xyz");
+        let key = Frame::BulkString("key:0".into());
+        let payload = Frame::BulkString(value.clone());
+
+        assert_eq!(
+            BenchCommand::Set.frame(&args, &value),
+            Frame::Array(vec![
+                Frame::BulkString("SET".into()),
+                key.clone(),
+                payload.clone()
+            ])
+        );
+        assert_eq!(
+            BenchCommand::Mget.frame(&args, &value),
+            Frame::Array(vec![
+                Frame::BulkString("MGET".into()),
+                key.clone(),
+                key.clone()
+            ])
+        );
+        let Frame::Array(parts) = BenchCommand::Mset.frame(&args, &value) else {
+            panic!("expected array");
+        };
+        assert_eq!(parts.len(), 5);
+    }
+
+    #[test]
+    fn reports_include_percentiles() {
+        let mut stats = Stats::default();
+        for micros in 1..=1000 {
+            stats.record(BenchCommand::Get, Duration::from_micros(micros));
+            stats.record(BenchCommand::Set, Duration::from_micros(micros * 2));
+        }
+        let csv = csv_report(&stats, Duration::from_secs(1));
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("\"SET\",\"1000.00\""));
+        assert!(lines[2].starts_with("\"GET\",\"1000.00\""));
+        assert!(lines[3].starts_with("\"ALL\",\"2000.00\""));
+
+        let args = Args::parse_from(["yars-benchmark"]);
+        let text = text_report(&stats, Duration::from_secs(1), &args);
+        assert!(text.contains("====== GET ======"));
+        assert!(text.contains("  1000 requests completed in 1.00 seconds"));
+        assert!(text.contains(" 50.000% <= 0.500"));
+        assert!(text.contains("====== ALL ======"));
+    }
+}
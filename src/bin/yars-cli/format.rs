@@ -0,0 +1,260 @@
+//! Reply rendering and input splitting, mirroring `redis-cli`.
+
+use yars::protocol::resp::Frame;
+
+/// Human-friendly rendering used when stdout is a terminal.
+pub fn format_tty(frame: &Frame) -> String {
+    let mut out = String::new();
+    write_tty(&mut out, frame, "");
+    out
+}
+
+fn write_tty(out: &mut String, frame: &Frame, prefix: &str) {
+    match frame {
+        Frame::SimpleString(s) => out.push_str(s),
+        Frame::Error(e) => {
+            out.push_str("(error) ");
+            out.push_str(e);
+        }
+        Frame::Integer(n) => out.push_str(&format!("(integer) {n}")),
+        Frame::BulkString(bytes) => out.push_str(&quote(bytes)),
+        Frame::NullBulkString | Frame::NullArray => out.push_str("(nil)"),
+        Frame::Array(items) if items.is_empty() => out.push_str("(empty array)"),
+        Frame::Array(items) => {
+            let width = items.len().to_string().len();
+            let nested = format!("{prefix}{}", " ".repeat(width + 2));
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(prefix);
+                }
+                out.push_str(&format!("{:>width$}) ", i + 1));
+                write_tty(out, item, &nested);
+                if !matches!(item, Frame::Array(inner) if !inner.is_empty()) {
+                    out.push('\n');
+                }
+            }
+            return;
+        }
+    }
+    if prefix.is_empty() {
+        out.push('\n');
+    }
+}
+
+/// Plain rendering used when output is piped: values only, one per line.
+pub fn format_raw(frame: &Frame) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_raw(&mut out, frame);
+    out.push(b'\n');
+    out
+}
+
+fn write_raw(out: &mut Vec<u8>, frame: &Frame) {
+    match frame {
+        Frame::SimpleString(s) | Frame::Error(s) => out.extend_from_slice(s.as_bytes()),
+        Frame::Integer(n) => out.extend_from_slice(n.to_string().as_bytes()),
+        Frame::BulkString(bytes) => out.extend_from_slice(bytes),
+        Frame::NullBulkString | Frame::NullArray => {}
+        Frame::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b'\n');
+                }
+                write_raw(out, item);
+            }
+        }
+    }
+}
+
+/// Double-quoted representation with C-style escapes for unprintable bytes.
+pub fn quote(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() + 2);
+    out.push('"');
+    for &b in bytes {
+        match b {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x07 => out.push_str("\\a"),
+            0x08 => out.push_str("\\b"),
+            b' '..=b'~' => out.push(b as char),
+            _ => out.push_str(&format!("\\x{b:02x}")),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Splits a command line into arguments. Double quotes accept C-style
+/// escapes including `\xHH`; single quotes only escape `\'`. A closing quote
+/// must be followed by whitespace or the end of the line.
+pub fn split_args(line: &str) -> Result<Vec<Vec<u8>>, &'static str> {
+    const INVALID: &str = "Invalid argument(s)";
+    let bytes = line.as_bytes();
+    let mut args = Vec::new();
+    let mut i = 0;
+
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i == bytes.len() {
+            return Ok(args);
+        }
+
+        let mut arg = Vec::new();
+        match bytes[i] {
+            b'"' => {
+                i += 1;
+                loop {
+                    match bytes.get(i) {
+                        None => return Err(INVALID),
+                        Some(b'"') => break,
+                        Some(b'\\') if bytes.get(i + 1) == Some(&b'x') => {
+                            let hex = bytes
+                                .get(i + 2..i + 4)
+                                .and_then(|h| std::str::from_utf8(h).ok())
+                                .and_then(|h| u8::from_str_radix(h, 16).ok());
+                            match hex {
+                                Some(b) => {
+                                    arg.push(b);
+                                    i += 4;
+                                }
+                                None => {
+                                    arg.push(b'x');
+                                    i += 2;
+                                }
+                            }
+                        }
+                        Some(b'\\') => {
+                            let escaped = *bytes.get(i + 1).ok_or(INVALID)?;
+                            arg.push(match escaped {
+                                b'n' => b'\n',
+                                b'r' => b'\r',
+                                b't' => b'\t',
+                                b'b' => 0x08,
+                                b'a' => 0x07,
+                                other => other,
+                            });
+                            i += 2;
+                        }
+                        Some(&b) => {
+                            arg.push(b);
+                            i += 1;
+                        }
+                    }
+                }
+                i += 1;
+            }
+            b'\'' => {
+                i += 1;
+                loop {
+                    match bytes.get(i) {
+                        None => return Err(INVALID),
+                        Some(b'\'') => break,
+                        Some(b'\\') if bytes.get(i + 1) == Some(&b'\'') => {
+                            arg.push(b'\'');
+                            i += 2;
+                        }
+                        Some(&b) => {
+                            arg.push(b);
+                            i += 1;
+                        }
+                    }
+                }
+                i += 1;
+            }
+            _ => {
+                while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                    arg.push(bytes[i]);
+                    i += 1;
+                }
+                args.push(arg);
+                continue;
+            }
+        }
+        if i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+            return Err(INVALID);
+        }
+        args.push(arg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(s: &str) -> Frame {
+        Frame::BulkString(s.to_string().into())
+    }
+
+    #[test]
+    fn tty_scalars() {
+        assert_eq!(format_tty(&Frame::SimpleString("OK".into())), "OK\n");
+        assert_eq!(
+            format_tty(&Frame::Error("ERR nope".into())),
+            "(error) ERR nope\n"
+        );
+        assert_eq!(format_tty(&Frame::Integer(-3)), "(integer) -3\n");
+        assert_eq!(format_tty(&bulk("a\"b\n")), "\"a\\\"b\\n\"\n");
+        assert_eq!(
+            format_tty(&Frame::BulkString(vec![0u8, 0xff].into())),
+            "\"\\x00\\xff\"\n"
+        );
+        assert_eq!(format_tty(&Frame::NullBulkString), "(nil)\n");
+        assert_eq!(format_tty(&Frame::NullArray), "(nil)\n");
+        assert_eq!(format_tty(&Frame::Array(vec![])), "(empty array)\n");
+    }
+
+    #[test]
+    fn tty_nested_arrays_align_like_redis_cli() {
+        let frame = Frame::Array(vec![
+            Frame::Array(vec![bulk("a"), Frame::Integer(1)]),
+            bulk("b"),
+            Frame::Array(vec![]),
+        ]);
+        assert_eq!(
+            format_tty(&frame),
+            "1) 1) \"a\"\n   2) (integer) 1\n2) \"b\"\n3) (empty array)\n"
+        );
+
+        let wide = Frame::Array((0..10).map(Frame::Integer).collect());
+        let out = format_tty(&wide);
+        assert!(out.starts_with(" 1) (integer) 0\n"));
+        assert!(out.ends_with("10) (integer) 9\n"));
+    }
+
+    #[test]
+    fn raw_output_is_unquoted() {
+        assert_eq!(format_raw(&bulk("v")), b"v\n");
+        assert_eq!(format_raw(&Frame::Integer(5)), b"5\n");
+        assert_eq!(format_raw(&Frame::NullBulkString), b"\n");
+        assert_eq!(
+            format_raw(&Frame::Array(vec![
+                bulk("a"),
+                Frame::Array(vec![bulk("b")])
+            ])),
+            b"a\nb\n"
+        );
+    }
+
+    #[test]
+    fn split_args_handles_quotes_and_escapes() {
+        let args = split_args(r#"set "a b" 'it\'s' "\x41\n" plain"#).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                b"set".to_vec(),
+                b"a b".to_vec(),
+                b"it's".to_vec(),
+                b"A\n".to_vec(),
+                b"plain".to_vec()
+            ]
+        );
+        assert_eq!(split_args("   ").unwrap(), Vec::<Vec<u8>>::new());
+        assert!(split_args(r#"get "unterminated"#).is_err());
+        assert!(split_args(r#"get "a"b"#).is_err());
+    }
+}
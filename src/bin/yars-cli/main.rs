@@ -0,0 +1,501 @@
+//! Command-line client for YARS, modelled on `redis-cli`.
+
+mod format;
+
+use std::{
+    io::{IsTerminal, Read, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use clap::Parser;
+use futures::{SinkExt, StreamExt};
+use rustyline::{DefaultEditor, error::ReadlineError};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    runtime::Runtime,
+};
+use tokio_util::{
+    bytes::{Bytes, BytesMut},
+    codec::{Encoder, Framed, FramedRead},
+};
+use yars::{
+    protocol::resp::{Frame, RespCodec},
+    utils::random::random_u64,
+};
+
+use crate::format::{format_raw, format_tty, split_args};
+
+#[derive(Parser, Debug)]
+#[command(name = "yars-cli", version, disable_help_flag = true)]
+struct Args {
+    /// Server hostname.
+    #[arg(short = 'h', long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// Server port.
+    #[arg(short, long, default_value_t = 6379)]
+    port: u16,
+
+    /// Server socket (overrides hostname and port).
+    #[arg(short, long)]
+    socket: Option<PathBuf>,
+
+    /// Password to use when connecting to the server.
+    #[arg(short = 'a', long)]
+    pass: Option<String>,
+
+    /// Use raw formatting for replies (default when stdout is not a tty).
+    #[arg(long, conflicts_with = "no_raw")]
+    raw: bool,
+
+    /// Force formatted output even when stdout is not a tty.
+    #[arg(long)]
+    no_raw: bool,
+
+    /// Transfer raw RESP (or one command per line) from stdin to the server.
+    #[arg(long)]
+    pipe: bool,
+
+    /// List all keys using the SCAN command.
+    #[arg(long)]
+    scan: bool,
+
+    /// Keys pattern when using --scan.
+    #[arg(long, default_value = "*")]
+    pattern: String,
+
+    /// Print rolling stats about the server: keys, memory, clients, requests.
+    #[arg(long)]
+    stat: bool,
+
+    /// Seconds between --stat samples.
+    #[arg(short = 'i', long, default_value_t = 1.0)]
+    interval: f64,
+
+    /// Print help.
+    #[arg(long, action = clap::ArgAction::Help)]
+    help: Option<bool>,
+
+    /// Command to run once instead of starting the interactive prompt.
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    command: Vec<String>,
+}
+
+impl Args {
+    fn endpoint(&self) -> String {
+        match &self.socket {
+            Some(path) => path.display().to_string(),
+            None => format!("{}:{}", self.host, self.port),
+        }
+    }
+
+    fn raw_output(&self) -> bool {
+        self.raw || (!self.no_raw && !std::io::stdout().is_terminal())
+    }
+}
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+async fn open(args: &Args) -> Result<Box<dyn Stream>> {
+    let stream: Box<dyn Stream> = match &args.socket {
+        #[cfg(unix)]
+        Some(path) => Box::new(tokio::net::UnixStream::connect(path).await?),
+        #[cfg(not(unix))]
+        Some(_) => bail!("unix sockets are not supported on this platform"),
+        None => {
+            let stream = TcpStream::connect((args.host.as_str(), args.port)).await?;
+            stream.set_nodelay(true)?;
+            Box::new(stream)
+        }
+    };
+    Ok(stream)
+}
+
+fn command_frame<I, A>(parts: I) -> Frame
+where
+    I: IntoIterator<Item = A>,
+    A: Into<Bytes>,
+{
+    Frame::Array(
+        parts
+            .into_iter()
+            .map(|part| Frame::BulkString(part.into()))
+            .collect(),
+    )
+}
+
+struct Client {
+    framed: Framed<Box<dyn Stream>, RespCodec>,
+}
+
+impl Client {
+    async fn connect(args: &Args) -> Result<Self> {
+        let stream = open(args)
+            .await
+            .with_context(|| format!("Could not connect to YARS at {}", args.endpoint()))?;
+        let mut client = Self {
+            framed: Framed::new(stream, RespCodec),
+        };
+        if let Some(pass) = &args.pass
+            && let Frame::Error(err) = client
+                .call(command_frame(["AUTH", pass.as_str()].map(String::from)))
+                .await?
+        {
+            eprintln!("Warning: AUTH failed: {err}");
+        }
+        Ok(client)
+    }
+
+    async fn call(&mut self, frame: Frame) -> Result<Frame> {
+        self.framed.send(frame).await?;
+        self.framed
+            .next()
+            .await
+            .ok_or_else(|| anyhow!("Server closed the connection"))?
+    }
+}
+
+fn print_reply(frame: &Frame, raw: bool) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    if raw {
+        stdout.write_all(&format_raw(frame))?;
+    } else {
+        stdout.write_all(format_tty(frame).as_bytes())?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+fn history_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".yars_cli_history"))
+}
+
+fn repl(rt: &Runtime, args: &Args) -> Result<()> {
+    let mut client = rt.block_on(Client::connect(args))?;
+    let mut editor = DefaultEditor::new()?;
+    let history = history_path();
+    if let Some(path) = &history {
+        let _ = editor.load_history(path);
+    }
+    let prompt = format!("{}> ", args.endpoint());
+    let raw = args.raw;
+
+    loop {
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+        let parts = match split_args(&line) {
+            Ok(parts) if parts.is_empty() => continue,
+            Ok(parts) => parts,
+            Err(err) => {
+                eprintln!("{err}");
+                continue;
+            }
+        };
+        let _ = editor.add_history_entry(line.as_str());
+        if parts[0].eq_ignore_ascii_case(b"quit") || parts[0].eq_ignore_ascii_case(b"exit") {
+            break;
+        }
+
+        match rt.block_on(client.call(command_frame(parts))) {
+            Ok(reply) => print_reply(&reply, raw)?,
+            Err(err) => {
+                eprintln!("{err}");
+                break;
+            }
+        }
+    }
+
+    if let Some(path) = &history {
+        let _ = editor.save_history(path);
+    }
+    Ok(())
+}
+
+/// Converts `--pipe` input to RESP: raw protocol passes through unchanged,
+/// anything else is read as one inline command per line.
+fn pipe_payload(input: Vec<u8>) -> Result<Vec<u8>> {
+    if input.first() == Some(&b'*') {
+        return Ok(input);
+    }
+    let text = String::from_utf8(input).context("pipe input is neither RESP nor UTF-8 text")?;
+    let mut out = BytesMut::new();
+    for (lineno, line) in text.lines().enumerate() {
+        let parts = split_args(line).map_err(|err| anyhow!("line {}: {err}", lineno + 1))?;
+        if !parts.is_empty() {
+            RespCodec.encode(command_frame(parts), &mut out)?;
+        }
+    }
+    Ok(out.to_vec())
+}
+
+/// Streams stdin to the server while counting replies. A trailing ECHO with
+/// a random marker tells us when the last reply has arrived.
+async fn pipe(args: &Args) -> Result<bool> {
+    let mut input = Vec::new();
+    std::io::stdin().read_to_end(&mut input)?;
+    let mut payload = pipe_payload(input)?;
+
+    let marker = format!("{:016x}{:016x}", random_u64(), random_u64());
+    let mut echo = BytesMut::new();
+    RespCodec.encode(
+        command_frame(["ECHO".to_string(), marker.clone()]),
+        &mut echo,
+    )?;
+    payload.extend_from_slice(&echo);
+
+    let (reader, mut writer) = tokio::io::split(open(args).await?);
+    let mut replies = FramedRead::new(reader, RespCodec);
+
+    let send = async {
+        writer.write_all(&payload).await?;
+        writer.flush().await?;
+        eprintln!("All data transferred. Waiting for the last reply...");
+        anyhow::Ok(())
+    };
+    let receive = async {
+        let (mut count, mut errors) = (0u64, 0u64);
+        while let Some(reply) = replies.next().await {
+            match reply? {
+                Frame::BulkString(b) if b.as_ref() == marker.as_bytes() => {
+                    return Ok((count, errors));
+                }
+                Frame::Error(err) => {
+                    errors += 1;
+                    count += 1;
+                    eprintln!("{err}");
+                }
+                _ => count += 1,
+            }
+        }
+        bail!("Server closed the connection before the last reply")
+    };
+    let ((), (count, errors)) = tokio::try_join!(send, receive)?;
+
+    eprintln!("Last reply received from server.");
+    eprintln!("errors: {errors}, replies: {count}");
+    Ok(errors == 0)
+}
+
+async fn scan(args: &Args) -> Result<()> {
+    let mut client = Client::connect(args).await?;
+    let mut cursor = "0".to_string();
+    let mut stdout = std::io::stdout().lock();
+    loop {
+        let reply = client
+            .call(command_frame(
+                ["SCAN", &cursor, "MATCH", &args.pattern, "COUNT", "1000"].map(String::from),
+            ))
+            .await?;
+        let Frame::Array(parts) = reply else {
+            bail!("{}", format_tty(&reply).trim_end());
+        };
+        let [Frame::BulkString(next), Frame::Array(keys)] = parts.as_slice() else {
+            bail!("unexpected SCAN reply");
+        };
+        for key in keys {
+            stdout.write_all(&format_raw(key))?;
+        }
+        cursor = String::from_utf8_lossy(next).into_owned();
+        if cursor == "0" {
+            return Ok(());
+        }
+    }
+}
+
+/// One `--stat` sample, parsed from INFO.
+#[derive(Debug, Default, PartialEq)]
+struct StatSample {
+    keys: Option<u64>,
+    memory: Option<u64>,
+    clients: Option<u64>,
+    requests: Option<u64>,
+    connections: Option<u64>,
+}
+
+impl StatSample {
+    fn parse(info: &str) -> Self {
+        let mut sample = Self::default();
+        for line in info.lines() {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let number = value.trim().parse::<u64>().ok();
+            match name {
+                "db_keys" => sample.keys = number,
+                "used_memory" => sample.memory = number,
+                "connected_clients" => sample.clients = number,
+                "total_commands" | "total_commands_processed" => sample.requests = number,
+                "total_connections_received" => sample.connections = number,
+                // Redis-style keyspace line: `db0:keys=1,expires=0,...`.
+                db if db.starts_with("db") => {
+                    let keys = value
+                        .split(',')
+                        .find_map(|kv| kv.strip_prefix("keys="))
+                        .and_then(|n| n.parse::<u64>().ok());
+                    if let Some(keys) = keys {
+                        sample.keys = Some(sample.keys.unwrap_or(0) + keys);
+                    }
+                }
+                _ => {}
+            }
+        }
+        sample
+    }
+
+    fn row(&self, previous: Option<&StatSample>) -> String {
+        let show = |v: Option<u64>| v.map_or("-".to_string(), |v| v.to_string());
+        let delta = |now: Option<u64>, before: Option<u64>| match (now, before) {
+            (Some(now), Some(before)) => format!("(+{})", now.saturating_sub(before)),
+            _ => String::new(),
+        };
+        let prev = |f: fn(&StatSample) -> Option<u64>| previous.and_then(f);
+        format!(
+            "{:<10} {:<8} {:<7} {:<19} {}",
+            show(self.keys),
+            self.memory.map_or("-".to_string(), human_bytes),
+            show(self.clients),
+            format!(
+                "{} {}",
+                show(self.requests),
+                delta(self.requests, prev(|s| s.requests))
+            )
+            .trim_end(),
+            format!(
+                "{} {}",
+                show(self.connections),
+                delta(self.connections, prev(|s| s.connections))
+            )
+            .trim_end(),
+        )
+    }
+}
+
+fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.2}{}", UNITS[unit])
+}
+
+async fn stat(args: &Args) -> Result<()> {
+    let mut client = Client::connect(args).await?;
+    let interval = Duration::from_secs_f64(args.interval.max(0.01));
+    let mut previous: Option<StatSample> = None;
+    for line in 0u64.. {
+        let reply = client.call(command_frame(["INFO".to_string()])).await?;
+        let Frame::BulkString(info) = reply else {
+            bail!("{}", format_tty(&reply).trim_end());
+        };
+        let sample = StatSample::parse(&String::from_utf8_lossy(&info));
+        if line.is_multiple_of(20) {
+            println!("------- data ------ ------------- load -------------");
+            println!("keys       mem      clients requests            connections");
+        }
+        println!("{}", sample.row(previous.as_ref()));
+        previous = Some(sample);
+        tokio::time::sleep(interval).await;
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    if args.pipe {
+        if !rt.block_on(pipe(&args))? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if args.scan {
+        return rt.block_on(scan(&args));
+    }
+    if args.stat {
+        return rt.block_on(stat(&args));
+    }
+    if args.command.is_empty() {
+        return repl(&rt, &args);
+    }
+
+    let raw = args.raw_output();
+    let reply = rt.block_on(async {
+        let mut client = Client::connect(&args).await?;
+        client.call(command_frame(args.command.clone())).await
+    })?;
+    print_reply(&reply, raw)?;
+    if matches!(reply, Frame::Error(_)) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_flags() {
+        let args = Args::parse_from(["yars-cli", "-h", "example", "-p", "7000", "get", "k"]);
+        assert_eq!(args.endpoint(), "example:7000");
+        assert_eq!(args.command, vec!["get", "k"]);
+
+        let args = Args::parse_from(["yars-cli", "-s", "/tmp/yars.sock", "-a", "secret"]);
+        assert_eq!(args.endpoint(), "/tmp/yars.sock");
+        assert_eq!(args.pass.as_deref(), Some("secret"));
+        assert!(args.command.is_empty());
+    }
+
+    #[test]
+    fn pipe_payload_converts_inline_commands() {
+        let resp = b"*1\r\n$4\r\nPING\r\n".to_vec();
+        assert_eq!(pipe_payload(resp.clone()).unwrap(), resp);
+
+        let inline = b"SET k \"a b\"\n\nINCR n\n".to_vec();
+        assert_eq!(
+            pipe_payload(inline).unwrap(),
+            b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$3\r\na b\r\n*2\r\n$4\r\nINCR\r\n$1\r\nn\r\n"
+        );
+    }
+
+    #[test]
+    fn stat_sample_reads_info_fields() {
+        let sample = StatSample::parse("db_keys:3\r\nused_memory:2048\r\ntotal_commands:10\r\n");
+        assert_eq!(
+            sample,
+            StatSample {
+                keys: Some(3),
+                memory: Some(2048),
+                requests: Some(10),
+                ..StatSample::default()
+            }
+        );
+        let redis = StatSample::parse("# Keyspace\r\ndb0:keys=2,expires=0\r\ndb1:keys=1\r\n");
+        assert_eq!(redis.keys, Some(3));
+
+        let next = StatSample {
+            requests: Some(15),
+            ..StatSample::parse("db_keys:3\r\nused_memory:2048\r\n")
+        };
+        assert_eq!(
+            next.row(Some(&sample)),
+            "3          2.00K    -       15 (+5)             -"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Command-line arguments for the server binary. Besides a few fixed flags,
+//! any `--<directive> <value>` pair is passed through as a config override,
+//! mirroring `redis-server`.
+
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow};
+
+pub const USAGE: &str = "\
+Usage: yars [/path/to/yars.toml] [options]
+
+Options:
+  -c, --config <path>       Config file (default: $YARS_CONFIG_PATH or the platform config dir)
+      --port <port>         TCP port to listen on
+      --bind <addr>         Address to listen on
+      --dir <path>          Directory for the append-only file
+      --appendonly <yes|no> Enable AOF persistence
+      --<directive> <value> Override any other config option, e.g. --maxmemory 100mb
+      --test-config         Validate the configuration and exit
+  -v, --version             Print version and exit
+  -h, --help                Print this help and exit
+
+Precedence, lowest to highest: defaults, config file, YARS_* env vars, flags.";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ServerArgs {
+    pub config_path: Option<PathBuf>,
+    /// Directive overrides in the order given.
+    pub overrides: Vec<(String, String)>,
+    pub test_config: bool,
+    pub version: bool,
+    pub help: bool,
+}
+
+impl ServerArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().peekable();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => parsed.help = true,
+                "-v" | "--version" => parsed.version = true,
+                "--test-config" => parsed.test_config = true,
+                "-c" | "--config" => {
+                    let path = args
+                        .next()
+                        .ok_or_else(|| anyhow!("{arg} requires a path"))?;
+                    parsed.config_path = Some(PathBuf::from(path));
+                }
+                _ => {
+                    let Some(flag) = arg.strip_prefix("--") else {
+                        if parsed.config_path.is_none() && parsed.overrides.is_empty() {
+                            parsed.config_path = Some(PathBuf::from(arg));
+                            continue;
+                        }
+                        return Err(anyhow!("Unexpected argument: {arg}"));
+                    };
+                    let (name, value) = match flag.split_once('=') {
+                        Some((name, value)) => (name.to_string(), value.to_string()),
+                        None => {
+                            let value = args
+                                .next_if(|next| !next.starts_with("--"))
+                                .ok_or_else(|| anyhow!("{arg} requires a value"))?;
+                            (flag.to_string(), value)
+                        }
+                    };
+                    if name == "config" {
+                        parsed.config_path = Some(PathBuf::from(value));
+                    } else {
+                        parsed.overrides.push((name, value));
+                    }
+                }
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<ServerArgs> {
+        ServerArgs::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn fixed_flags() {
+        let args = parse(&["--test-config", "-v", "--config", "/etc/yars.toml"]).unwrap();
+        assert!(args.test_config);
+        assert!(args.version);
+        assert_eq!(args.config_path, Some(PathBuf::from("/etc/yars.toml")));
+        assert!(parse(&["-h"]).unwrap().help);
+    }
+
+    #[test]
+    fn directives_keep_order_and_accept_equals() {
+        let args = parse(&[
+            "/etc/yars.toml",
+            "--port",
+            "7000",
+            "--maxmemory-policy=allkeys-lru",
+            "--appendonly",
+            "no",
+            "--port",
+            "7001",
+        ])
+        .unwrap();
+        assert_eq!(args.config_path, Some(PathBuf::from("/etc/yars.toml")));
+        assert_eq!(
+            args.overrides,
+            vec![
+                ("port".into(), "7000".into()),
+                ("maxmemory-policy".into(), "allkeys-lru".into()),
+                ("appendonly".into(), "no".into()),
+                ("port".into(), "7001".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_values_and_stray_arguments_are_errors() {
+        assert!(parse(&["--port"]).is_err());
+        assert!(parse(&["--port", "--bind", "0.0.0.0"]).is_err());
+        assert!(parse(&["--config"]).is_err());
+        assert!(parse(&["--port", "1", "stray"]).is_err());
+    }
+}
@@ -0,0 +1,930 @@
+pub mod registry;
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FsyncMode {
+    Always,
+    #[default]
+    EverySec,
+    No,
+}
+
+impl FromStr for FsyncMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "always" => Ok(Self::Always),
+            "everysec" => Ok(Self::EverySec),
+            "no" => Ok(Self::No),
+            other => Err(anyhow!("Invalid fsync mode: {other}")),
+        }
+    }
+}
+
+impl FsyncMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::EverySec => "everysec",
+            Self::No => "no",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize, Default)]
+pub enum EvictionPolicy {
+    #[default]
+    #[serde(rename = "noeviction")]
+    NoEviction,
+    #[serde(rename = "allkeys-lru")]
+    AllKeysLru,
+    #[serde(rename = "volatile-lru")]
+    VolatileLru,
+    #[serde(rename = "allkeys-lfu")]
+    AllKeysLfu,
+    #[serde(rename = "volatile-lfu")]
+    VolatileLfu,
+    #[serde(rename = "allkeys-random")]
+    AllKeysRandom,
+    #[serde(rename = "volatile-random")]
+    VolatileRandom,
+    #[serde(rename = "volatile-ttl")]
+    VolatileTtl,
+}
+
+impl FromStr for EvictionPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "noeviction" => Ok(Self::NoEviction),
+            "allkeys-lru" => Ok(Self::AllKeysLru),
+            "volatile-lru" => Ok(Self::VolatileLru),
+            "allkeys-lfu" => Ok(Self::AllKeysLfu),
+            "volatile-lfu" => Ok(Self::VolatileLfu),
+            "allkeys-random" => Ok(Self::AllKeysRandom),
+            "volatile-random" => Ok(Self::VolatileRandom),
+            "volatile-ttl" => Ok(Self::VolatileTtl),
+            other => Err(anyhow!("Invalid maxmemory policy: {other}")),
+        }
+    }
+}
+
+impl EvictionPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NoEviction => "noeviction",
+            Self::AllKeysLru => "allkeys-lru",
+            Self::VolatileLru => "volatile-lru",
+            Self::AllKeysLfu => "allkeys-lfu",
+            Self::VolatileLfu => "volatile-lfu",
+            Self::AllKeysRandom => "allkeys-random",
+            Self::VolatileRandom => "volatile-random",
+            Self::VolatileTtl => "volatile-ttl",
+        }
+    }
+
+    /// Whether only keys with a TTL may be evicted.
+    pub fn is_volatile(self) -> bool {
+        matches!(
+            self,
+            Self::VolatileLru | Self::VolatileLfu | Self::VolatileRandom | Self::VolatileTtl
+        )
+    }
+}
+
+/// Minimum severity written to the log, named as in Redis.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Verbose,
+    #[default]
+    Notice,
+    Warning,
+}
+
+impl FromStr for LogLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "debug" => Ok(Self::Debug),
+            "verbose" => Ok(Self::Verbose),
+            "notice" => Ok(Self::Notice),
+            "warning" => Ok(Self::Warning),
+            other => Err(anyhow!("Invalid log level: {other}")),
+        }
+    }
+}
+
+impl LogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Verbose => "verbose",
+            Self::Notice => "notice",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line, for log shippers.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(anyhow!("Invalid log format: {other}")),
+        }
+    }
+}
+
+impl LogFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Where finished tracing spans go.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceExporter {
+    #[default]
+    None,
+    /// One JSON object per span, appended to `trace-file`.
+    File,
+    /// OTLP/HTTP JSON, posted to `trace-endpoint`.
+    Otlp,
+}
+
+impl FromStr for TraceExporter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "file" => Ok(Self::File),
+            "otlp" => Ok(Self::Otlp),
+            other => Err(anyhow!("Invalid trace exporter: {other}")),
+        }
+    }
+}
+
+impl TraceExporter {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::File => "file",
+            Self::Otlp => "otlp",
+        }
+    }
+}
+
+/// Parses a byte count with an optional Redis-style unit suffix
+/// (`k`/`m`/`g` are powers of 1000, `kb`/`mb`/`gb` powers of 1024).
+pub fn parse_memory(s: &str) -> Result<u64> {
+    let s = s.trim().to_ascii_lowercase();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (digits, unit) = s.split_at(split);
+    let multiplier: u64 = match unit {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return Err(anyhow!("Invalid memory unit: {unit}")),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| anyhow!("Invalid memory amount: {s}"))
+}
+
+/// Parses a boolean directive, accepting Redis-style `yes`/`no`.
+pub fn parse_bool(s: &str) -> Result<bool> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "yes" | "true" => Ok(true),
+        "no" | "false" => Ok(false),
+        other => Err(anyhow!("Invalid boolean: {other} (expected yes or no)")),
+    }
+}
+
+/// Environment variables and the directive each one sets.
+const ENV_DIRECTIVES: &[(&str, &str)] = &[
+    ("YARS_BIND", "bind"),
+    ("YARS_PORT", "port"),
+    ("YARS_DIR", "dir"),
+    ("YARS_APPEND_ONLY", "appendonly"),
+    ("YARS_AOF_FILENAME", "appendfilename"),
+    ("YARS_AOF_FSYNC", "appendfsync"),
+    ("YARS_MAXMEMORY", "maxmemory"),
+    ("YARS_MAXMEMORY_POLICY", "maxmemory-policy"),
+    ("YARS_MAXCLIENTS", "maxclients"),
+    ("YARS_LOGLEVEL", "loglevel"),
+    ("YARS_LOGFILE", "logfile"),
+    ("YARS_METRICS_PORT", "metrics-port"),
+    ("YARS_TRACE_EXPORTER", "trace-exporter"),
+];
+
+#[derive(Clone, Debug)]
+pub struct AppConfig {
+    pub bind: String,
+    pub port: u16,
+    pub append_only: bool,
+    pub aof_path: PathBuf,
+    pub fsync_mode: FsyncMode,
+    pub config_path: PathBuf,
+    pub data_dir: PathBuf,
+    pub maxmemory: u64,
+    pub maxmemory_policy: EvictionPolicy,
+    pub maxclients: usize,
+    pub slowlog_log_slower_than: i64,
+    pub slowlog_max_len: usize,
+    /// Reload the config file whenever it changes, not just on SIGHUP.
+    pub watch_config: bool,
+    /// Seconds a shutdown waits for in-flight commands before going ahead.
+    pub shutdown_timeout: u64,
+    pub log_level: LogLevel,
+    /// Log to this file instead of stdout.
+    pub log_file: Option<PathBuf>,
+    pub log_format: LogFormat,
+    /// Port for the Prometheus `/metrics` listener; 0 turns it off.
+    pub metrics_port: u16,
+    pub trace_exporter: TraceExporter,
+    /// Span file for the `file` exporter.
+    pub trace_file: PathBuf,
+    /// OTLP/HTTP traces URL for the `otlp` exporter.
+    pub trace_endpoint: String,
+}
+
+const CONFIG_HEADER: &str = "\
+# YARS configuration file\n\
+";
+
+fn default_bind() -> String {
+    String::from("127.0.0.1")
+}
+
+fn default_port() -> u16 {
+    6379
+}
+
+fn default_config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("./config"))
+        .join("yars")
+        .join("yars.toml")
+}
+
+fn default_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("./data"))
+        .join("yars")
+}
+
+fn default_maxclients() -> usize {
+    10000
+}
+
+fn default_slowlog_log_slower_than() -> i64 {
+    10000
+}
+
+fn default_slowlog_max_len() -> usize {
+    128
+}
+
+fn default_shutdown_timeout() -> u64 {
+    10
+}
+
+fn default_watch_config() -> bool {
+    false
+}
+
+fn default_trace_file() -> String {
+    String::from("traces.jsonl")
+}
+
+fn default_trace_endpoint() -> String {
+    String::from("http://127.0.0.1:4318/v1/traces")
+}
+
+fn default_append_only() -> bool {
+    true
+}
+
+fn default_aof_filename() -> String {
+    String::from("data.aof")
+}
+
+#[derive(Deserialize, Serialize)]
+struct TomlConfig {
+    #[serde(default = "default_bind")]
+    bind: String,
+    #[serde(default = "default_port")]
+    port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dir: Option<PathBuf>,
+    #[serde(default = "default_append_only")]
+    append_only: bool,
+    #[serde(default = "default_aof_filename")]
+    append_filename: String,
+    #[serde(default)]
+    fsync_mode: FsyncMode,
+    #[serde(default)]
+    maxmemory: u64,
+    #[serde(default)]
+    maxmemory_policy: EvictionPolicy,
+    #[serde(default = "default_maxclients")]
+    maxclients: usize,
+    #[serde(default = "default_slowlog_log_slower_than")]
+    slowlog_log_slower_than: i64,
+    #[serde(default = "default_slowlog_max_len")]
+    slowlog_max_len: usize,
+    #[serde(default = "default_watch_config")]
+    watch_config: bool,
+    #[serde(default = "default_shutdown_timeout")]
+    shutdown_timeout: u64,
+    #[serde(default)]
+    loglevel: LogLevel,
+    #[serde(default)]
+    logfile: String,
+    #[serde(default)]
+    log_format: LogFormat,
+    #[serde(default)]
+    metrics_port: u16,
+    #[serde(default)]
+    trace_exporter: TraceExporter,
+    #[serde(default = "default_trace_file")]
+    trace_file: String,
+    #[serde(default = "default_trace_endpoint")]
+    trace_endpoint: String,
+}
+
+impl Default for TomlConfig {
+    fn default() -> Self {
+        Self {
+            bind: default_bind(),
+            port: default_port(),
+            dir: None,
+            append_only: default_append_only(),
+            append_filename: default_aof_filename(),
+            fsync_mode: FsyncMode::default(),
+            maxmemory: 0,
+            maxmemory_policy: EvictionPolicy::default(),
+            maxclients: default_maxclients(),
+            slowlog_log_slower_than: default_slowlog_log_slower_than(),
+            slowlog_max_len: default_slowlog_max_len(),
+            watch_config: default_watch_config(),
+            shutdown_timeout: default_shutdown_timeout(),
+            loglevel: LogLevel::default(),
+            logfile: String::new(),
+            log_format: LogFormat::default(),
+            metrics_port: 0,
+            trace_exporter: TraceExporter::default(),
+            trace_file: default_trace_file(),
+            trace_endpoint: default_trace_endpoint(),
+        }
+    }
+}
+
+fn commented_defaults() -> String {
+    let defaults = TomlConfig::default();
+    let toml_str = toml_edit::ser::to_string_pretty(&defaults).unwrap_or_default();
+    toml_str
+        .lines()
+        .map(|line| format!("# {line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl AppConfig {
+    /// Loads the configuration, creating a commented default file at the
+    /// default location when none exists. Sources are layered in increasing
+    /// precedence: built-in defaults, the config file, `YARS_*` environment
+    /// variables, then `overrides` (command-line directives, in order).
+    pub fn load(config_path: Option<PathBuf>, overrides: &[(String, String)]) -> Result<Self> {
+        if config_path.is_none() && std::env::var_os("YARS_CONFIG_PATH").is_none() {
+            let path = default_config_path();
+            if !path.exists() {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, format!("{CONFIG_HEADER}\n{}", commented_defaults()))?;
+            }
+        }
+        Self::resolve(config_path, overrides)
+    }
+
+    /// Same layering as [`AppConfig::load`] without touching the filesystem
+    /// beyond reading the config file; used by `--test-config`.
+    pub fn resolve(config_path: Option<PathBuf>, overrides: &[(String, String)]) -> Result<Self> {
+        Self::resolve_with(config_path, overrides, |name| std::env::var(name).ok())
+    }
+
+    fn resolve_with(
+        config_path: Option<PathBuf>,
+        overrides: &[(String, String)],
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let explicit = config_path.is_some();
+        let config_path = config_path
+            .or_else(|| env("YARS_CONFIG_PATH").map(PathBuf::from))
+            .unwrap_or_else(default_config_path);
+
+        let file_vals: TomlConfig = if config_path.exists() {
+            let raw = std::fs::read_to_string(&config_path)?;
+            toml_edit::de::from_str(&raw).map_err(|e| anyhow!("{}: {e}", config_path.display()))?
+        } else if explicit {
+            return Err(anyhow!("Config file not found: {}", config_path.display()));
+        } else {
+            TomlConfig::default()
+        };
+
+        let data_dir = file_vals.dir.unwrap_or_else(default_data_dir);
+        let mut config = Self {
+            bind: file_vals.bind,
+            port: file_vals.port,
+            append_only: file_vals.append_only,
+            aof_path: data_dir.join(&file_vals.append_filename),
+            fsync_mode: file_vals.fsync_mode,
+            config_path,
+            data_dir,
+            maxmemory: file_vals.maxmemory,
+            maxmemory_policy: file_vals.maxmemory_policy,
+            maxclients: file_vals.maxclients,
+            slowlog_log_slower_than: file_vals.slowlog_log_slower_than,
+            slowlog_max_len: file_vals.slowlog_max_len,
+            watch_config: file_vals.watch_config,
+            shutdown_timeout: file_vals.shutdown_timeout,
+            log_level: file_vals.loglevel,
+            log_file: (!file_vals.logfile.is_empty()).then(|| PathBuf::from(file_vals.logfile)),
+            log_format: file_vals.log_format,
+            metrics_port: file_vals.metrics_port,
+            trace_exporter: file_vals.trace_exporter,
+            trace_file: PathBuf::from(file_vals.trace_file),
+            trace_endpoint: file_vals.trace_endpoint,
+        };
+
+        for (var, directive) in ENV_DIRECTIVES {
+            if let Some(value) = env(var) {
+                config
+                    .apply_directive(directive, &value)
+                    .map_err(|e| anyhow!("{var}: {e}"))?;
+            }
+        }
+        for (directive, value) in overrides {
+            config
+                .apply_directive(directive, value)
+                .map_err(|e| anyhow!("--{directive}: {e}"))?;
+        }
+        Ok(config)
+    }
+
+    /// Sets one option by its directive name. Accepts the Redis-style names
+    /// used by CONFIG SET as well as the keys used in `yars.toml`.
+    pub fn apply_directive(&mut self, name: &str, value: &str) -> Result<()> {
+        match registry::lookup(name) {
+            Some(param) => param.set(self, value),
+            None => Err(anyhow!("Unknown directive: {}", name.to_ascii_lowercase())),
+        }
+    }
+
+    /// Address to listen on, bracketing IPv6 literals.
+    pub fn listen_addr(&self) -> String {
+        self.addr_with_port(self.port)
+    }
+
+    /// Address of the `/metrics` listener, if it is on.
+    pub fn metrics_addr(&self) -> Option<String> {
+        (self.metrics_port != 0).then(|| self.addr_with_port(self.metrics_port))
+    }
+
+    fn addr_with_port(&self, port: u16) -> String {
+        if self.bind.contains(':') {
+            format!("[{}]:{port}", self.bind)
+        } else {
+            format!("{}:{port}", self.bind)
+        }
+    }
+
+    pub fn write_to_file(&self) -> Result<()> {
+        let content = if self.config_path.exists() {
+            let raw = std::fs::read_to_string(&self.config_path)?;
+            let mut doc: toml_edit::DocumentMut = raw.parse()?;
+            if doc.as_table().is_empty() {
+                self.build_fresh()?
+            } else {
+                for param in registry::PARAMS {
+                    if !param.is_default(self) || doc.contains_key(param.toml_key) {
+                        doc[param.toml_key] = toml_edit::value(param.toml_value(self)?);
+                    }
+                }
+                doc.to_string()
+            }
+        } else {
+            self.build_fresh()?
+        };
+
+        if let Some(parent) = self.config_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = self.config_path.with_extension("toml.tmp");
+        std::fs::write(&tmp_path, &content)?;
+        std::fs::rename(&tmp_path, &self.config_path)?;
+
+        Ok(())
+    }
+
+    fn build_fresh(&self) -> Result<String> {
+        let mut active = String::new();
+        for param in registry::PARAMS {
+            if !param.is_default(self) {
+                active.push_str(&format!(
+                    "{} = {}\n",
+                    param.toml_key,
+                    param.toml_value(self)?
+                ));
+            }
+        }
+        Ok(format!(
+            "{CONFIG_HEADER}\n{}\n{active}",
+            commented_defaults()
+        ))
+    }
+
+    pub fn set_fsync_mode(&mut self, fsync: &str) -> Result<()> {
+        self.fsync_mode = FsyncMode::from_str(fsync)?;
+        Ok(())
+    }
+
+    pub fn set_maxmemory(&mut self, value: &str) -> Result<()> {
+        self.maxmemory = parse_memory(value)?;
+        Ok(())
+    }
+
+    pub fn set_maxmemory_policy(&mut self, value: &str) -> Result<()> {
+        self.maxmemory_policy = EvictionPolicy::from_str(value)?;
+        Ok(())
+    }
+
+    pub fn set_maxclients(&mut self, value: &str) -> Result<()> {
+        self.maxclients = match value.trim().parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => return Err(anyhow!("Invalid maxclients: {value}")),
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fsync_mode_from_str_valid() {
+        assert_eq!(FsyncMode::from_str("always").unwrap(), FsyncMode::Always);
+        assert_eq!(
+            FsyncMode::from_str("everysec").unwrap(),
+            FsyncMode::EverySec
+        );
+        assert_eq!(FsyncMode::from_str("no").unwrap(), FsyncMode::No);
+    }
+
+    #[test]
+    fn fsync_mode_from_str_case_insensitive_and_trimmed() {
+        assert_eq!(
+            FsyncMode::from_str("  ALWAYS  ").unwrap(),
+            FsyncMode::Always
+        );
+        assert_eq!(
+            FsyncMode::from_str("EverySec").unwrap(),
+            FsyncMode::EverySec
+        );
+        assert_eq!(FsyncMode::from_str("NO").unwrap(), FsyncMode::No);
+    }
+
+    #[test]
+    fn fsync_mode_from_str_invalid() {
+        assert!(FsyncMode::from_str("sometimes").is_err());
+    }
+
+    #[test]
+    fn fsync_mode_as_str_round_trip() {
+        assert_eq!(FsyncMode::Always.as_str(), "always");
+        assert_eq!(FsyncMode::EverySec.as_str(), "everysec");
+        assert_eq!(FsyncMode::No.as_str(), "no");
+    }
+
+    #[test]
+    fn commented_defaults_contains_expected_keys() {
+        let defs = commented_defaults();
+        assert!(defs.contains("append_only"));
+        assert!(defs.contains("append_filename"));
+        assert!(defs.contains("fsync_mode"));
+        for line in defs.lines() {
+            assert!(line.starts_with("# "));
+        }
+    }
+
+    #[test]
+    fn set_fsync_mode_valid() {
+        let mut cfg = AppConfig {
+            bind: default_bind(),
+            port: default_port(),
+            append_only: true,
+            aof_path: PathBuf::from("/tmp/a.aof"),
+            fsync_mode: FsyncMode::Always,
+            config_path: PathBuf::from("/tmp/c.toml"),
+            data_dir: PathBuf::from("/tmp"),
+            maxmemory: 0,
+            maxmemory_policy: EvictionPolicy::NoEviction,
+            maxclients: 10000,
+            slowlog_log_slower_than: 10000,
+            slowlog_max_len: 128,
+            watch_config: false,
+            shutdown_timeout: 10,
+            log_level: LogLevel::Notice,
+            log_file: None,
+            log_format: LogFormat::Text,
+            metrics_port: 0,
+            trace_exporter: TraceExporter::None,
+            trace_file: PathBuf::from("traces.jsonl"),
+            trace_endpoint: "http://127.0.0.1:4318/v1/traces".into(),
+        };
+        cfg.set_fsync_mode("no").unwrap();
+        assert_eq!(cfg.fsync_mode, FsyncMode::No);
+    }
+
+    #[test]
+    fn set_fsync_mode_invalid() {
+        let mut cfg = AppConfig {
+            bind: default_bind(),
+            port: default_port(),
+            append_only: true,
+            aof_path: PathBuf::from("/tmp/a.aof"),
+            fsync_mode: FsyncMode::Always,
+            config_path: PathBuf::from("/tmp/c.toml"),
+            data_dir: PathBuf::from("/tmp"),
+            maxmemory: 0,
+            maxmemory_policy: EvictionPolicy::NoEviction,
+            maxclients: 10000,
+            slowlog_log_slower_than: 10000,
+            slowlog_max_len: 128,
+            watch_config: false,
+            shutdown_timeout: 10,
+            log_level: LogLevel::Notice,
+            log_file: None,
+            log_format: LogFormat::Text,
+            metrics_port: 0,
+            trace_exporter: TraceExporter::None,
+            trace_file: PathBuf::from("traces.jsonl"),
+            trace_endpoint: "http://127.0.0.1:4318/v1/traces".into(),
+        };
+        assert!(cfg.set_fsync_mode("invalid").is_err());
+    }
+
+    fn fresh_config(append_only: bool, fsync_mode: FsyncMode) -> AppConfig {
+        AppConfig {
+            bind: default_bind(),
+            port: default_port(),
+            append_only,
+            aof_path: PathBuf::from("/tmp/a.aof"),
+            fsync_mode,
+            config_path: PathBuf::from("/tmp/c.toml"),
+            data_dir: default_data_dir(),
+            maxmemory: 0,
+            maxmemory_policy: EvictionPolicy::NoEviction,
+            maxclients: 10000,
+            slowlog_log_slower_than: 10000,
+            slowlog_max_len: 128,
+            watch_config: false,
+            shutdown_timeout: 10,
+            log_level: LogLevel::Notice,
+            log_file: None,
+            log_format: LogFormat::Text,
+            metrics_port: 0,
+            trace_exporter: TraceExporter::None,
+            trace_file: PathBuf::from("traces.jsonl"),
+            trace_endpoint: "http://127.0.0.1:4318/v1/traces".into(),
+        }
+    }
+
+    #[test]
+    fn build_fresh_with_defaults_is_mostly_empty() {
+        let mut cfg = fresh_config(true, FsyncMode::EverySec);
+        cfg.aof_path = PathBuf::from("/tmp/data.aof");
+        let s = cfg.build_fresh().unwrap();
+        assert!(s.contains("# YARS configuration file"));
+        let lines: Vec<&str> = s.lines().collect();
+        let uncommented: Vec<&str> = lines
+            .iter()
+            .filter(|l| !l.starts_with("#"))
+            .copied()
+            .collect();
+        assert!(uncommented.is_empty() || uncommented.iter().all(|l| l.trim().is_empty()));
+    }
+
+    #[test]
+    fn build_fresh_with_non_defaults_emits_them() {
+        let mut cfg = fresh_config(false, FsyncMode::No);
+        cfg.maxmemory = 1024;
+        cfg.maxmemory_policy = EvictionPolicy::AllKeysLru;
+        cfg.aof_path = PathBuf::from("/tmp/custom.aof");
+        let s = cfg.build_fresh().unwrap();
+        assert!(s.contains("append_only = false\n"));
+        assert!(s.contains("append_filename = \"custom.aof\"\n"));
+        assert!(s.contains("fsync_mode = \"no\"\n"));
+        assert!(s.contains("maxmemory = 1024\n"));
+        assert!(s.contains("maxmemory_policy = \"allkeys-lru\"\n"));
+    }
+
+    #[test]
+    fn eviction_policy_round_trip() {
+        for name in [
+            "noeviction",
+            "allkeys-lru",
+            "volatile-lru",
+            "allkeys-lfu",
+            "volatile-lfu",
+            "allkeys-random",
+            "volatile-random",
+            "volatile-ttl",
+        ] {
+            assert_eq!(EvictionPolicy::from_str(name).unwrap().as_str(), name);
+        }
+        assert!(EvictionPolicy::from_str("lru").is_err());
+        assert!(EvictionPolicy::VolatileTtl.is_volatile());
+        assert!(!EvictionPolicy::AllKeysLfu.is_volatile());
+    }
+
+    #[test]
+    fn parse_memory_units() {
+        assert_eq!(parse_memory("100").unwrap(), 100);
+        assert_eq!(parse_memory("1k").unwrap(), 1000);
+        assert_eq!(parse_memory("1KB").unwrap(), 1024);
+        assert_eq!(parse_memory("2mb").unwrap(), 2 * 1024 * 1024);
+        assert_eq!(parse_memory("1g").unwrap(), 1_000_000_000);
+        assert!(parse_memory("1tb").is_err());
+        assert!(parse_memory("mb").is_err());
+        assert!(parse_memory("-1").is_err());
+    }
+
+    #[test]
+    fn write_to_file_creates_and_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("yars.toml");
+        let cfg = AppConfig {
+            bind: default_bind(),
+            port: default_port(),
+            append_only: false,
+            aof_path: dir.path().join("data.aof"),
+            fsync_mode: FsyncMode::No,
+            config_path: config_path.clone(),
+            data_dir: dir.path().to_path_buf(),
+            maxmemory: 0,
+            maxmemory_policy: EvictionPolicy::NoEviction,
+            maxclients: 10000,
+            slowlog_log_slower_than: 10000,
+            slowlog_max_len: 128,
+            watch_config: false,
+            shutdown_timeout: 10,
+            log_level: LogLevel::Notice,
+            log_file: None,
+            log_format: LogFormat::Text,
+            metrics_port: 0,
+            trace_exporter: TraceExporter::None,
+            trace_file: PathBuf::from("traces.jsonl"),
+            trace_endpoint: "http://127.0.0.1:4318/v1/traces".into(),
+        };
+        cfg.write_to_file().unwrap();
+        assert!(config_path.exists());
+        let contents = std::fs::read_to_string(&config_path).unwrap();
+        assert!(contents.contains("append_only = false"));
+    }
+
+    fn env_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: std::collections::HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    fn overrides(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn precedence_is_defaults_file_env_flags() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("yars.toml");
+        std::fs::write(
+            &path,
+            "port = 7000\nbind = \"0.0.0.0\"\nmaxmemory = 100\nfsync_mode = \"always\"\n",
+        )
+        .unwrap();
+
+        let env = env_from(&[("YARS_PORT", "7001"), ("YARS_MAXMEMORY", "200")]);
+        let cfg = AppConfig::resolve_with(Some(path.clone()), &overrides(&[("port", "7002")]), env)
+            .unwrap();
+        assert_eq!(cfg.port, 7002);
+        assert_eq!(cfg.maxmemory, 200);
+        assert_eq!(cfg.bind, "0.0.0.0");
+        assert_eq!(cfg.fsync_mode, FsyncMode::Always);
+        assert_eq!(cfg.maxmemory_policy, EvictionPolicy::NoEviction);
+        assert_eq!(cfg.config_path, path);
+    }
+
+    #[test]
+    fn dir_and_filename_directives_compose() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("yars.toml");
+        std::fs::write(&path, "append_filename = \"file.aof\"\n").unwrap();
+        let cfg = AppConfig::resolve_with(
+            Some(path),
+            &overrides(&[("dir", "/srv/yars"), ("appendonly", "no")]),
+            env_from(&[]),
+        )
+        .unwrap();
+        assert_eq!(cfg.data_dir, PathBuf::from("/srv/yars"));
+        assert_eq!(cfg.aof_path, PathBuf::from("/srv/yars/file.aof"));
+        assert!(!cfg.append_only);
+    }
+
+    #[test]
+    fn resolve_reports_bad_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.toml");
+        assert!(AppConfig::resolve_with(Some(missing), &[], env_from(&[])).is_err());
+
+        let path = dir.path().join("yars.toml");
+        std::fs::write(&path, "").unwrap();
+        let err = AppConfig::resolve_with(
+            Some(path.clone()),
+            &overrides(&[("maxmemory-policy", "lru")]),
+            env_from(&[]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("--maxmemory-policy:"));
+
+        let err =
+            AppConfig::resolve_with(Some(path.clone()), &[], env_from(&[("YARS_PORT", "99999")]))
+                .unwrap_err();
+        assert!(err.to_string().starts_with("YARS_PORT:"));
+
+        assert!(
+            AppConfig::resolve_with(
+                Some(path),
+                &overrides(&[("no-such-option", "1")]),
+                env_from(&[])
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn listen_addr_brackets_ipv6() {
+        let mut cfg = fresh_config(true, FsyncMode::EverySec);
+        assert_eq!(cfg.listen_addr(), "127.0.0.1:6379");
+        cfg.bind = "::1".into();
+        assert_eq!(cfg.listen_addr(), "[::1]:6379");
+    }
+
+    #[test]
+    fn parse_bool_accepts_yes_no() {
+        assert!(parse_bool("yes").unwrap());
+        assert!(parse_bool("TRUE").unwrap());
+        assert!(!parse_bool("no").unwrap());
+        assert!(parse_bool("maybe").is_err());
+    }
+}
@@ -0,0 +1,448 @@
+//! Every configuration parameter, declared once: its type, default, how a
+//! value is validated and stored, whether it may change at runtime, and what
+//! the server must do when it does. CONFIG GET/SET, directives from the
+//! environment and command line, and CONFIG REWRITE all go through here.
+
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::{Result, anyhow};
+
+use crate::config::{
+    AppConfig, EvictionPolicy, FsyncMode, LogFormat, LogLevel, TraceExporter, default_aof_filename,
+    default_append_only, default_bind, default_data_dir, default_maxclients, default_port,
+    default_shutdown_timeout, default_slowlog_log_slower_than, default_slowlog_max_len,
+    default_trace_endpoint, default_trace_file, default_watch_config, parse_bool,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamKind {
+    /// `yes`/`no`.
+    Bool,
+    Integer,
+    /// Bytes, accepting unit suffixes such as `100mb`.
+    Memory,
+    /// One of a fixed set of names.
+    Enum,
+    String,
+}
+
+/// Work the server must do for a new value to take effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hook {
+    /// Open, close or move the append-only file.
+    Persistence,
+    Fsync,
+    SlowLog,
+    /// Reconfigure the logger, reopening the log file.
+    Logging,
+    /// Swap the span exporter, flushing the old one.
+    Tracing,
+}
+
+pub struct Param {
+    /// Name used by CONFIG GET/SET and as a directive.
+    pub name: &'static str,
+    /// Key in `yars.toml`, also accepted as a directive.
+    pub toml_key: &'static str,
+    pub kind: ParamKind,
+    /// Whether CONFIG SET may change it; others need a restart.
+    pub mutable: bool,
+    pub hook: Option<Hook>,
+    default: fn() -> String,
+    get: fn(&AppConfig) -> String,
+    /// Validates `value` and stores it.
+    set: fn(&mut AppConfig, &str) -> Result<()>,
+}
+
+impl Param {
+    pub fn default_value(&self) -> String {
+        (self.default)()
+    }
+
+    pub fn get(&self, config: &AppConfig) -> String {
+        (self.get)(config)
+    }
+
+    pub fn set(&self, config: &mut AppConfig, value: &str) -> Result<()> {
+        (self.set)(config, value)
+    }
+
+    pub fn is_default(&self, config: &AppConfig) -> bool {
+        self.get(config) == self.default_value()
+    }
+
+    /// The current value as written to `yars.toml`, whose integers are
+    /// signed 64-bit.
+    pub fn toml_value(&self, config: &AppConfig) -> Result<toml_edit::Value> {
+        let value = self.get(config);
+        Ok(match self.kind {
+            ParamKind::Bool => parse_bool(&value)?.into(),
+            ParamKind::Integer => parse_int::<i64>(self.name, &value)?.into(),
+            ParamKind::Memory => i64::try_from(parse_int::<u64>(self.name, &value)?)
+                .map_err(|_| anyhow!("Invalid {}: {value} is too large to save", self.name))?
+                .into(),
+            ParamKind::Enum | ParamKind::String => value.into(),
+        })
+    }
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_string()
+}
+
+fn parse_int<T: FromStr>(name: &str, value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("Invalid {name}: {value}"))
+}
+
+pub static PARAMS: &[Param] = &[
+    Param {
+        name: "bind",
+        toml_key: "bind",
+        kind: ParamKind::String,
+        mutable: false,
+        hook: None,
+        default: default_bind,
+        get: |c| c.bind.clone(),
+        set: |c, v| {
+            if v.trim().is_empty() {
+                return Err(anyhow!("bind address cannot be empty"));
+            }
+            c.bind = v.trim().to_string();
+            Ok(())
+        },
+    },
+    Param {
+        name: "port",
+        toml_key: "port",
+        kind: ParamKind::Integer,
+        mutable: false,
+        hook: None,
+        default: || default_port().to_string(),
+        get: |c| c.port.to_string(),
+        set: |c, v| {
+            c.port = parse_int("port", v)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "dir",
+        toml_key: "dir",
+        kind: ParamKind::String,
+        mutable: true,
+        hook: Some(Hook::Persistence),
+        default: || default_data_dir().to_string_lossy().into_owned(),
+        get: |c| c.data_dir.to_string_lossy().into_owned(),
+        set: |c, v| {
+            if v.is_empty() {
+                return Err(anyhow!("dir cannot be empty"));
+            }
+            let filename = c.aof_path.file_name().map(|n| n.to_owned());
+            c.data_dir = PathBuf::from(v);
+            if let Some(filename) = filename {
+                c.aof_path = c.data_dir.join(filename);
+            }
+            Ok(())
+        },
+    },
+    Param {
+        name: "appendonly",
+        toml_key: "append_only",
+        kind: ParamKind::Bool,
+        mutable: true,
+        hook: Some(Hook::Persistence),
+        default: || yes_no(default_append_only()),
+        get: |c| yes_no(c.append_only),
+        set: |c, v| {
+            c.append_only = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "appendfilename",
+        toml_key: "append_filename",
+        kind: ParamKind::String,
+        mutable: true,
+        hook: Some(Hook::Persistence),
+        default: default_aof_filename,
+        get: |c| {
+            c.aof_path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(default_aof_filename)
+        },
+        set: |c, v| {
+            if v.is_empty() {
+                return Err(anyhow!("empty filename"));
+            }
+            c.aof_path = c.data_dir.join(v);
+            Ok(())
+        },
+    },
+    Param {
+        name: "appendfsync",
+        toml_key: "fsync_mode",
+        kind: ParamKind::Enum,
+        mutable: true,
+        hook: Some(Hook::Fsync),
+        default: || FsyncMode::default().as_str().to_string(),
+        get: |c| c.fsync_mode.as_str().to_string(),
+        set: |c, v| c.set_fsync_mode(v),
+    },
+    Param {
+        name: "maxmemory",
+        toml_key: "maxmemory",
+        kind: ParamKind::Memory,
+        mutable: true,
+        hook: None,
+        default: || "0".to_string(),
+        get: |c| c.maxmemory.to_string(),
+        set: |c, v| c.set_maxmemory(v),
+    },
+    Param {
+        name: "maxmemory-policy",
+        toml_key: "maxmemory_policy",
+        kind: ParamKind::Enum,
+        mutable: true,
+        hook: None,
+        default: || EvictionPolicy::default().as_str().to_string(),
+        get: |c| c.maxmemory_policy.as_str().to_string(),
+        set: |c, v| c.set_maxmemory_policy(v),
+    },
+    Param {
+        name: "maxclients",
+        toml_key: "maxclients",
+        kind: ParamKind::Integer,
+        mutable: true,
+        hook: None,
+        default: || default_maxclients().to_string(),
+        get: |c| c.maxclients.to_string(),
+        set: |c, v| c.set_maxclients(v),
+    },
+    Param {
+        name: "slowlog-log-slower-than",
+        toml_key: "slowlog_log_slower_than",
+        kind: ParamKind::Integer,
+        mutable: true,
+        hook: Some(Hook::SlowLog),
+        default: || default_slowlog_log_slower_than().to_string(),
+        get: |c| c.slowlog_log_slower_than.to_string(),
+        set: |c, v| {
+            c.slowlog_log_slower_than = parse_int("slowlog-log-slower-than", v)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "slowlog-max-len",
+        toml_key: "slowlog_max_len",
+        kind: ParamKind::Integer,
+        mutable: true,
+        hook: Some(Hook::SlowLog),
+        default: || default_slowlog_max_len().to_string(),
+        get: |c| c.slowlog_max_len.to_string(),
+        set: |c, v| {
+            c.slowlog_max_len = parse_int("slowlog-max-len", v)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "shutdown-timeout",
+        toml_key: "shutdown_timeout",
+        kind: ParamKind::Integer,
+        mutable: true,
+        hook: None,
+        default: || default_shutdown_timeout().to_string(),
+        get: |c| c.shutdown_timeout.to_string(),
+        set: |c, v| {
+            c.shutdown_timeout = parse_int("shutdown-timeout", v)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "loglevel",
+        toml_key: "loglevel",
+        kind: ParamKind::Enum,
+        mutable: true,
+        hook: Some(Hook::Logging),
+        default: || LogLevel::default().as_str().to_string(),
+        get: |c| c.log_level.as_str().to_string(),
+        set: |c, v| {
+            c.log_level = v.parse()?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "logfile",
+        toml_key: "logfile",
+        kind: ParamKind::String,
+        // Like Redis, never settable by clients, who could otherwise have
+        // the server create and append to any file it can write.
+        mutable: false,
+        hook: None,
+        default: String::new,
+        get: |c| {
+            c.log_file
+                .as_ref()
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        },
+        set: |c, v| {
+            c.log_file = (!v.is_empty()).then(|| PathBuf::from(v));
+            Ok(())
+        },
+    },
+    Param {
+        name: "log-format",
+        toml_key: "log_format",
+        kind: ParamKind::Enum,
+        mutable: true,
+        hook: Some(Hook::Logging),
+        default: || LogFormat::default().as_str().to_string(),
+        get: |c| c.log_format.as_str().to_string(),
+        set: |c, v| {
+            c.log_format = v.parse()?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "metrics-port",
+        toml_key: "metrics_port",
+        kind: ParamKind::Integer,
+        mutable: false,
+        hook: None,
+        default: || "0".to_string(),
+        get: |c| c.metrics_port.to_string(),
+        set: |c, v| {
+            c.metrics_port = parse_int("metrics-port", v)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "trace-exporter",
+        toml_key: "trace_exporter",
+        kind: ParamKind::Enum,
+        mutable: true,
+        hook: Some(Hook::Tracing),
+        default: || TraceExporter::default().as_str().to_string(),
+        get: |c| c.trace_exporter.as_str().to_string(),
+        set: |c, v| {
+            c.trace_exporter = v.parse()?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "trace-file",
+        toml_key: "trace_file",
+        kind: ParamKind::String,
+        mutable: false,
+        hook: None,
+        default: default_trace_file,
+        get: |c| c.trace_file.to_string_lossy().into_owned(),
+        set: |c, v| {
+            if v.is_empty() {
+                return Err(anyhow!("trace-file cannot be empty"));
+            }
+            c.trace_file = PathBuf::from(v);
+            Ok(())
+        },
+    },
+    Param {
+        name: "trace-endpoint",
+        toml_key: "trace_endpoint",
+        kind: ParamKind::String,
+        // Where spans go is fixed at startup; only whether they are
+        // exported at all can be switched with `trace-exporter`.
+        mutable: false,
+        hook: None,
+        default: default_trace_endpoint,
+        get: |c| c.trace_endpoint.clone(),
+        set: |c, v| {
+            if !v.starts_with("http://") {
+                return Err(anyhow!("trace-endpoint must be an http:// URL"));
+            }
+            c.trace_endpoint = v.to_string();
+            Ok(())
+        },
+    },
+    Param {
+        name: "watch-config",
+        toml_key: "watch_config",
+        kind: ParamKind::Bool,
+        mutable: true,
+        hook: None,
+        default: || yes_no(default_watch_config()),
+        get: |c| yes_no(c.watch_config),
+        set: |c, v| {
+            c.watch_config = parse_bool(v)?;
+            Ok(())
+        },
+    },
+];
+
+/// Finds a parameter by its CONFIG name or `yars.toml` key, ignoring case.
+pub fn lookup(name: &str) -> Option<&'static Param> {
+    PARAMS
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name) || p.toml_key.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_unique_lowercase_and_resolvable() {
+        for param in PARAMS {
+            assert_eq!(param.name, param.name.to_ascii_lowercase());
+            assert!(std::ptr::eq(lookup(param.name).unwrap(), param));
+            assert!(std::ptr::eq(lookup(param.toml_key).unwrap(), param));
+        }
+        assert!(lookup("MAXMEMORY-POLICY").is_some());
+        assert!(lookup("nope").is_none());
+    }
+
+    #[test]
+    fn file_paths_are_immutable() {
+        for name in ["logfile", "trace-file", "trace-endpoint"] {
+            assert!(!lookup(name).unwrap().mutable, "{name}");
+        }
+        assert!(lookup("trace-exporter").unwrap().mutable);
+    }
+
+    #[test]
+    fn defaults_round_trip_through_setters() {
+        let mut config = AppConfig::resolve_with(None, &[], |_| None).unwrap();
+        for param in PARAMS {
+            param.set(&mut config, &param.default_value()).unwrap();
+            assert!(param.is_default(&config), "{}", param.name);
+        }
+    }
+
+    #[test]
+    fn toml_values_follow_kind() {
+        let mut config = AppConfig::resolve_with(None, &[], |_| None).unwrap();
+        let get = |name| lookup(name).unwrap();
+        get("appendonly").set(&mut config, "no").unwrap();
+        get("maxmemory").set(&mut config, "1kb").unwrap();
+        assert_eq!(get("appendonly").get(&config), "no");
+        assert_eq!(
+            get("appendonly").toml_value(&config).unwrap().as_bool(),
+            Some(false)
+        );
+        assert_eq!(
+            get("maxmemory").toml_value(&config).unwrap().as_integer(),
+            Some(1024)
+        );
+        assert_eq!(
+            get("appendfsync").toml_value(&config).unwrap().as_str(),
+            Some("everysec")
+        );
+        assert!(get("maxclients").set(&mut config, "0").is_err());
+
+        config.maxmemory = u64::MAX;
+        let err = get("maxmemory").toml_value(&config).unwrap_err();
+        assert!(err.to_string().contains("too large"), "{err}");
+    }
+}
@@ -1,6 +1,9 @@
+pub mod cli;
 pub mod config;
+pub mod logging;
 pub mod net;
 pub mod protocol;
 pub mod service;
 pub mod store;
+pub mod telemetry;
 pub mod utils;
@@ -0,0 +1,281 @@
+//! The server log, behind the `log` facade.
+//!
+//! Lines follow Redis: `pid:role date time level-mark message`, where the
+//! mark is `.` for debug, `-` verbose, `*` notice and `#` warning. With
+//! `log-format json` each line is a JSON object instead. The `log` levels map
+//! onto Redis ones as trace → debug, debug → verbose, info → notice, and
+//! warn/error → warning.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::{
+    config::{AppConfig, LogFormat, LogLevel},
+    utils::time::{UtcDateTime, get_current_millis},
+};
+
+/// Replication role shown after the pid; this server is always a master.
+const ROLE: char = 'M';
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+static LOGGER: Logger = Logger::new();
+
+/// Installs the server log and applies `config`. Call once, at startup.
+pub fn init(config: &AppConfig) -> Result<()> {
+    log::set_logger(&LOGGER).map_err(|e| anyhow::anyhow!("{e}"))?;
+    configure(config)
+}
+
+/// Applies the log settings in `config`, reopening the log file. On error the
+/// previous settings stay in place.
+pub fn configure(config: &AppConfig) -> Result<()> {
+    LOGGER.configure(config.log_level, config.log_format, config.log_file.clone())?;
+    log::set_max_level(level_filter(config.log_level));
+    Ok(())
+}
+
+/// Reopens the log file, so one moved away by logrotate is recreated.
+pub fn reopen() -> Result<()> {
+    LOGGER.reopen()
+}
+
+fn level_filter(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Debug => LevelFilter::Trace,
+        LogLevel::Verbose => LevelFilter::Debug,
+        LogLevel::Notice => LevelFilter::Info,
+        LogLevel::Warning => LevelFilter::Warn,
+    }
+}
+
+fn redis_level(level: Level) -> LogLevel {
+    match level {
+        Level::Trace => LogLevel::Debug,
+        Level::Debug => LogLevel::Verbose,
+        Level::Info => LogLevel::Notice,
+        Level::Warn | Level::Error => LogLevel::Warning,
+    }
+}
+
+fn mark(level: LogLevel) -> char {
+    match level {
+        LogLevel::Debug => '.',
+        LogLevel::Verbose => '-',
+        LogLevel::Notice => '*',
+        LogLevel::Warning => '#',
+    }
+}
+
+/// One log line, without the trailing newline.
+fn format_line(format: LogFormat, pid: u32, ms: u64, record: &Record) -> String {
+    let t = UtcDateTime::from_millis(ms);
+    let level = redis_level(record.level());
+    match format {
+        LogFormat::Text => format!(
+            "{pid}:{ROLE} {:02} {} {} {:02}:{:02}:{:02}.{:03} {} {}",
+            t.day,
+            MONTHS[t.month as usize - 1],
+            t.year,
+            t.hour,
+            t.minute,
+            t.second,
+            t.millis,
+            mark(level),
+            record.args()
+        ),
+        LogFormat::Json => serde_json::json!({
+            "ts": format!(
+                "{}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+                t.year, t.month, t.day, t.hour, t.minute, t.second, t.millis
+            ),
+            "pid": pid,
+            "role": "master",
+            "level": level.as_str(),
+            "target": record.target(),
+            "msg": record.args().to_string(),
+        })
+        .to_string(),
+    }
+}
+
+struct Sink {
+    level: LogLevel,
+    format: LogFormat,
+    path: Option<PathBuf>,
+    /// `None` writes to stdout.
+    file: Option<File>,
+}
+
+struct Logger {
+    sink: Mutex<Sink>,
+}
+
+impl Logger {
+    const fn new() -> Self {
+        Self {
+            sink: Mutex::new(Sink {
+                level: LogLevel::Notice,
+                format: LogFormat::Text,
+                path: None,
+                file: None,
+            }),
+        }
+    }
+
+    fn configure(&self, level: LogLevel, format: LogFormat, path: Option<PathBuf>) -> Result<()> {
+        let file = path.as_deref().map(open).transpose()?;
+        let mut sink = self.sink.lock().unwrap();
+        *sink = Sink {
+            level,
+            format,
+            path,
+            file,
+        };
+        Ok(())
+    }
+
+    fn reopen(&self) -> Result<()> {
+        let mut sink = self.sink.lock().unwrap();
+        if let Some(path) = &sink.path {
+            sink.file = Some(open(path)?);
+        }
+        Ok(())
+    }
+}
+
+fn open(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("can't open log file {}", path.display()))
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        redis_level(metadata.level()) >= self.sink.lock().unwrap().level
+    }
+
+    fn log(&self, record: &Record) {
+        let mut sink = self.sink.lock().unwrap();
+        if redis_level(record.level()) < sink.level {
+            return;
+        }
+        let mut line = format_line(
+            sink.format,
+            std::process::id(),
+            get_current_millis(),
+            record,
+        );
+        line.push('\n');
+        // Nowhere to report a failed log write; drop the line.
+        let _ = match &mut sink.file {
+            Some(file) => file.write_all(line.as_bytes()),
+            None => std::io::stdout().lock().write_all(line.as_bytes()),
+        };
+    }
+
+    fn flush(&self) {
+        let mut sink = self.sink.lock().unwrap();
+        let _ = match &mut sink.file {
+            Some(file) => file.flush(),
+            None => std::io::stdout().flush(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-10-19 08:05:03.042 UTC
+    const MS: u64 = 1_792_397_103_042;
+
+    fn line(format: LogFormat, level: Level) -> String {
+        format_line(
+            format,
+            42,
+            MS,
+            &Record::builder()
+                .level(level)
+                .target("yars::net")
+                .args(format_args!("Ready \"now\""))
+                .build(),
+        )
+    }
+
+    #[test]
+    fn text_lines_match_redis_layout() {
+        assert_eq!(
+            line(LogFormat::Text, Level::Info),
+            "42:M 19 Oct 2026 08:05:03.042 * Ready \"now\""
+        );
+        assert!(line(LogFormat::Text, Level::Error).contains(" # "));
+        assert!(line(LogFormat::Text, Level::Trace).contains(" . "));
+    }
+
+    #[test]
+    fn json_lines_are_objects() {
+        let value: serde_json::Value =
+            serde_json::from_str(&line(LogFormat::Json, Level::Debug)).unwrap();
+        assert_eq!(value["ts"], "2026-10-19T08:05:03.042Z");
+        assert_eq!(value["pid"], 42);
+        assert_eq!(value["level"], "verbose");
+        assert_eq!(value["target"], "yars::net");
+        assert_eq!(value["msg"], "Ready \"now\"");
+    }
+
+    #[test]
+    fn filters_by_level_and_reopens_moved_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("yars.log");
+        let logger = Logger::new();
+        logger
+            .configure(LogLevel::Notice, LogFormat::Text, Some(path.clone()))
+            .unwrap();
+        let log = |level, msg: &str| {
+            logger.log(
+                &Record::builder()
+                    .level(level)
+                    .args(format_args!("{msg}"))
+                    .build(),
+            )
+        };
+
+        log(Level::Debug, "hidden");
+        log(Level::Info, "first");
+        std::fs::rename(&path, dir.path().join("yars.log.1")).unwrap();
+        log(Level::Warn, "second");
+        logger.reopen().unwrap();
+        log(Level::Warn, "third");
+
+        let rotated = std::fs::read_to_string(dir.path().join("yars.log.1")).unwrap();
+        assert!(rotated.contains(" * first\n"));
+        assert!(rotated.ends_with(" # second\n"));
+        assert!(!rotated.contains("hidden"));
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .ends_with(" # third\n")
+        );
+        assert!(
+            logger
+                .configure(
+                    LogLevel::Notice,
+                    LogFormat::Text,
+                    Some(dir.path().join("no/such/dir"))
+                )
+                .is_err()
+        );
+    }
+}
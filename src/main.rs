@@ -1,12 +1,62 @@
+use std::net::ToSocketAddrs;
+
 use anyhow::Result;
-use yars::{config::AppConfig, net::server::Server};
+use yars::{
+    cli::{ServerArgs, USAGE},
+    config::AppConfig,
+    logging,
+    net::server::Server,
+    telemetry,
+};
+
+#[cfg(feature = "tracking-allocator")]
+#[global_allocator]
+static GLOBAL: yars::utils::alloc::TrackingAllocator = yars::utils::alloc::TrackingAllocator;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
-    let cfg = AppConfig::load()?;
-    let server = Server::bind("127.0.0.1:6379", cfg).await?;
-    println!("Server is running on port 6379");
-    server.run().await?;
-    Ok(())
+    let args = match ServerArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            std::process::exit(1);
+        }
+    };
+    if args.help {
+        println!("{USAGE}");
+        return Ok(());
+    }
+    if args.version {
+        println!("yars v{}", env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
+    if args.test_config {
+        let checked = AppConfig::resolve(args.config_path, &args.overrides).and_then(|cfg| {
+            cfg.listen_addr().to_socket_addrs()?;
+            Ok(cfg)
+        });
+        match checked {
+            Ok(cfg) => {
+                println!("Configuration OK: {}", cfg.config_path.display());
+                return Ok(());
+            }
+            Err(err) => {
+                eprintln!("Configuration error: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let cfg = AppConfig::load(args.config_path, &args.overrides)?;
+    logging::init(&cfg)?;
+    telemetry::configure(&cfg)?;
+    let addr = cfg.listen_addr();
+    let server = Server::bind(&addr, cfg)
+        .await?
+        .with_overrides(args.overrides);
+    log::info!("Server is running on {addr}");
+    let result = server.run().await;
+    telemetry::shutdown().await;
+    result
 }
@@ -0,0 +1,84 @@
+//! A stream that counts the bytes passing through it.
+
+use std::{
+    io,
+    pin::Pin,
+    sync::{Arc, atomic::Ordering},
+    task::{Context, Poll},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::service::clients::NetTraffic;
+
+pub struct Metered<S> {
+    inner: S,
+    traffic: Arc<NetTraffic>,
+}
+
+impl<S> Metered<S> {
+    pub fn new(inner: S, traffic: Arc<NetTraffic>) -> Self {
+        Self { inner, traffic }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for Metered<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let polled = Pin::new(&mut self.inner).poll_read(cx, buf);
+        let read = buf.filled().len() - before;
+        self.traffic.input.fetch_add(read as u64, Ordering::Relaxed);
+        polled
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Metered<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let polled = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = polled {
+            self.traffic
+                .output
+                .fetch_add(written as u64, Ordering::Relaxed);
+        }
+        polled
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn counts_both_directions() {
+        let (client, server) = tokio::io::duplex(64);
+        let traffic = Arc::new(NetTraffic::default());
+        let mut metered = Metered::new(server, traffic.clone());
+        let mut client = client;
+
+        client.write_all(b"hello").await.unwrap();
+        let mut buf = [0u8; 5];
+        metered.read_exact(&mut buf).await.unwrap();
+        metered.write_all(b"hi").await.unwrap();
+
+        assert_eq!(traffic.input.load(Ordering::Relaxed), 5);
+        assert_eq!(traffic.output.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,105 @@
+//! Minimal HTTP/1.1 listener serving `GET /metrics` for Prometheus.
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::service::{context::ServerContext, metrics};
+
+/// Longest request head accepted; scrapes send a few hundred bytes.
+const MAX_REQUEST: usize = 8 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct MetricsListener {
+    listener: TcpListener,
+    ctx: Arc<ServerContext>,
+}
+
+impl MetricsListener {
+    pub async fn bind(addr: &str, ctx: Arc<ServerContext>) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            ctx,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves scrapes until the server shuts down.
+    pub async fn run(self) {
+        loop {
+            let socket = tokio::select! {
+                _ = self.ctx.cancel.cancelled() => return,
+                accepted = self.listener.accept() => match accepted {
+                    Ok((socket, _)) => socket,
+                    Err(err) => {
+                        log::warn!("Metrics listener accept error: {err}");
+                        continue;
+                    }
+                },
+            };
+            let ctx = Arc::clone(&self.ctx);
+            tokio::spawn(async move {
+                if let Err(err) = serve(socket, &ctx).await {
+                    log::debug!("Metrics request error: {err:?}");
+                }
+            });
+        }
+    }
+}
+
+async fn serve(mut socket: TcpStream, ctx: &ServerContext) -> Result<()> {
+    let head = tokio::time::timeout(READ_TIMEOUT, read_head(&mut socket)).await??;
+    let mut parts = head.split(' ');
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    // Query strings such as Prometheus' `?name[]=` filters are ignored.
+    let path = path.split('?').next().unwrap_or("");
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET" | "HEAD", "/metrics") => {
+            ("200 OK", metrics::CONTENT_TYPE, metrics::render(ctx).await)
+        }
+        (_, "/metrics") => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n".into(),
+        ),
+        _ => ("404 Not Found", "text/plain", "not found\n".into()),
+    };
+
+    let mut response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    if method != "HEAD" {
+        response.push_str(&body);
+    }
+    socket.write_all(response.as_bytes()).await?;
+    socket.shutdown().await?;
+    Ok(())
+}
+
+/// Reads up to the blank line ending the request head and returns its
+/// request line.
+async fn read_head(socket: &mut TcpStream) -> Result<String> {
+    let mut buf = Vec::with_capacity(512);
+    let mut chunk = [0u8; 512];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        if buf.len() > MAX_REQUEST {
+            anyhow::bail!("request head too large");
+        }
+        let n = socket.read(&mut chunk).await?;
+        if n == 0 {
+            anyhow::bail!("connection closed before request head");
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let line = buf.split(|&b| b == b'\r').next().unwrap_or_default();
+    Ok(String::from_utf8_lossy(line).into_owned())
+}
@@ -1,2 +1,4 @@
+pub mod metered;
+pub mod metrics;
 pub mod server;
 pub mod session;
@@ -1,53 +1,157 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use anyhow::Result;
-use tokio::net::TcpListener;
+use tokio::{
+    io::AsyncWriteExt,
+    net::TcpListener,
+    signal::unix::{SignalKind, signal},
+    time::Instant,
+};
 
-use crate::{config::AppConfig, net::session::Session, service::context::ServerContext};
+use crate::{
+    config::AppConfig,
+    net::{metrics::MetricsListener, session::Session},
+    service::{context::ServerContext, reload::Reloader, shutdown::ShutdownOptions},
+};
+
+/// How long sessions get after a shutdown to send the reply they are writing.
+const CLOSE_GRACE: Duration = Duration::from_secs(1);
 
 pub struct Server {
     listener: TcpListener,
     ctx: Arc<ServerContext>,
+    metrics: Option<MetricsListener>,
+    /// Command-line directives, kept so config reloads layer them the same way.
+    overrides: Vec<(String, String)>,
 }
 
 impl Server {
     pub async fn bind(addr: &str, config: AppConfig) -> Result<Self> {
         let listener = TcpListener::bind(addr).await?;
+        let metrics_addr = config.metrics_addr();
         let ctx = ServerContext::new(config).await?;
+        let metrics = match metrics_addr {
+            Some(addr) => Some(MetricsListener::bind(&addr, ctx.clone()).await?),
+            None => None,
+        };
+
+        Ok(Self {
+            listener,
+            ctx,
+            metrics,
+            overrides: Vec::new(),
+        })
+    }
 
-        Ok(Self { listener, ctx })
+    pub fn with_overrides(mut self, overrides: Vec<(String, String)>) -> Self {
+        self.overrides = overrides;
+        self
     }
 
     pub fn local_addr(&self) -> Result<SocketAddr> {
         Ok(self.listener.local_addr()?)
     }
 
-    pub async fn run(self) -> Result<()> {
+    pub fn metrics_addr(&self) -> Option<SocketAddr> {
+        self.metrics.as_ref().and_then(|m| m.local_addr().ok())
+    }
+
+    pub async fn run(mut self) -> Result<()> {
         self.ctx.aof.replay_into(&self.ctx.store).await?;
 
-        let result = tokio::select! {
-            res = self.accept_loop() => res,
-            _ = self.ctx.cancel.cancelled() => {
-                println!("Shutting down...");
-                Ok(())
+        let reloader = Reloader::new(self.ctx.clone(), self.overrides.clone());
+        tokio::spawn(async move {
+            if let Err(err) = reloader.run().await {
+                log::error!("Config reloader stopped: {err:?}");
+            }
+        });
+
+        if let Some(metrics) = self.metrics.take() {
+            log::info!(
+                "Serving metrics on http://{}/metrics",
+                metrics.local_addr()?
+            );
+            tokio::spawn(metrics.run());
+        }
+
+        let mut signals = Signals::new()?;
+        let result = loop {
+            tokio::select! {
+                res = self.accept_loop() => break res,
+                _ = self.ctx.cancel.cancelled() => break Ok(()),
+                name = signals.recv() => self.on_signal(name),
             }
         };
 
+        self.wait_for_sessions().await;
         self.ctx.aof.shutdown().await;
         result
     }
 
+    /// SIGTERM and SIGINT shut down like a plain SHUTDOWN; a second one while
+    /// that is still draining exits at once.
+    fn on_signal(&self, name: &str) {
+        if self.ctx.shutdown.in_progress() {
+            log::warn!("Received {name} during shutdown, exiting now");
+            std::process::exit(1);
+        }
+        log::warn!("Received {name}, shutting down");
+        let ctx = Arc::clone(&self.ctx);
+        tokio::spawn(async move {
+            if let Err(err) = ctx.stop(ShutdownOptions::default()).await {
+                log::warn!("Shutdown failed, server keeps running: {err}");
+            }
+        });
+    }
+
+    async fn wait_for_sessions(&self) {
+        let deadline = Instant::now() + CLOSE_GRACE;
+        while !self.ctx.clients.is_empty() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
     async fn accept_loop(&self) -> Result<()> {
         loop {
-            let (socket, _) = self.listener.accept().await?;
+            let (mut socket, addr) = self.listener.accept().await?;
+            let laddr = socket.local_addr()?;
+            let maxclients = self.ctx.config.read().await.maxclients;
+            let Some(client) = self.ctx.clients.register(addr, laddr, maxclients) else {
+                let _ = socket
+                    .write_all(b"-ERR max number of clients reached\r\n")
+                    .await;
+                continue;
+            };
+            log::debug!("Accepted {addr}");
             let ctx = Arc::clone(&self.ctx);
 
             tokio::spawn(async move {
-                let session = Session::new(socket, ctx);
+                let session = Session::new(socket, ctx, client);
                 if let Err(err) = session.handle().await {
-                    eprintln!("Connection error: {err:?}");
+                    log::debug!("Connection error: {err:?}");
                 }
             });
         }
     }
 }
+
+struct Signals {
+    terminate: tokio::signal::unix::Signal,
+    interrupt: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    fn new() -> Result<Self> {
+        Ok(Self {
+            terminate: signal(SignalKind::terminate())?,
+            interrupt: signal(SignalKind::interrupt())?,
+        })
+    }
+
+    async fn recv(&mut self) -> &'static str {
+        tokio::select! {
+            _ = self.terminate.recv() => "SIGTERM",
+            _ = self.interrupt.recv() => "SIGINT",
+        }
+    }
+}
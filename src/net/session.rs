@@ -6,49 +6,151 @@ use tokio::net::TcpStream;
 use tokio_util::codec::{Decoder, Framed};
 
 use crate::{
-    protocol::{command::Command, resp::RespCodec},
-    service::context::ServerContext,
+    net::metered::Metered,
+    protocol::{
+        command::Command,
+        resp::{Frame, RespCodec},
+    },
+    service::{
+        clients::{ClientHandle, command_label},
+        context::ServerContext,
+    },
+    telemetry,
 };
 
 pub struct Session {
-    framed: Framed<TcpStream, RespCodec>,
+    framed: Framed<Metered<TcpStream>, RespCodec>,
     ctx: Arc<ServerContext>,
+    client: Arc<ClientHandle>,
 }
 
 impl Session {
-    pub fn new(socket: TcpStream, ctx: Arc<ServerContext>) -> Self {
+    pub fn new(socket: TcpStream, ctx: Arc<ServerContext>, client: Arc<ClientHandle>) -> Self {
         Self {
-            framed: RespCodec.framed(socket),
+            framed: RespCodec.framed(Metered::new(socket, ctx.clients.traffic())),
             ctx,
+            client,
         }
     }
 
     pub async fn handle(mut self) -> Result<()> {
         loop {
+            // Root of the command's trace, scoped over decoding its frame.
+            let mut root = telemetry::span("command");
             tokio::select! {
-                frame = self.framed.next() => {
+                frame = root.scope(self.framed.next()) => {
                     let frame = match frame {
                         Some(frame) => frame?,
-                        None => break,
-                    };
-                    let result = match Command::try_from(frame) {
-                        Ok(Command::SHUTDOWN) => {
-                            self.framed
-                                .send(crate::protocol::resp::Frame::SimpleString("OK".into()))
-                                .await?;
-                            self.ctx.cancel.cancel();
+                        None => {
+                            root.discard();
                             break;
                         }
-                        Ok(cmd) => self.ctx.execute(cmd).await,
+                    };
+                    // Time spent waiting for the client isn't part of it.
+                    root.restart();
+                    let args = frame.args();
+                    if root.is_recording() {
+                        root.set_attr("db.operation.name", command_label(&args));
+                        root.set_attr("client.address", self.client.addr);
+                    }
+                    self.client.touch(&args);
+                    self.update_buffers();
+                    let result = match root.sync_scope(|| Command::try_from(frame)) {
+                        Ok(Command::SHUTDOWN { options }) => match self.ctx.stop(options).await {
+                            Ok(()) => {
+                                self.framed
+                                    .send(Frame::SimpleString("OK".into()))
+                                    .await?;
+                                break;
+                            }
+                            Err(err) => Frame::Error(err),
+                        },
+                        Ok(Command::SHUTDOWN_ABORT) => {
+                            if self.ctx.shutdown.abort() {
+                                Frame::SimpleString("OK".into())
+                            } else {
+                                Frame::Error("ERR No shutdown in progress.".into())
+                            }
+                        }
+                        Ok(Command::MONITOR) => {
+                            root.discard();
+                            return self.monitor().await;
+                        }
+                        Ok(cmd) => root.scope(self.ctx.execute(&self.client, &args, cmd)).await,
                         Err(err_frame) => err_frame,
                     };
-                    self.framed.send(result).await?;
+                    if self.client.should_reply() {
+                        self.framed.send(result).await?;
+                    }
+                    self.update_buffers();
+                }
+                _ = self.client.killed() => {
+                    root.discard();
+                    break;
                 }
                 _ = self.ctx.cancel.cancelled() => {
+                    root.discard();
                     break;
                 }
             }
         }
         Ok(())
     }
+
+    /// Push-only mode: forwards every command other clients run until the
+    /// connection closes. Only QUIT is accepted from a monitor.
+    async fn monitor(&mut self) -> Result<()> {
+        // Subscribe before replying so nothing sent after the OK is missed.
+        let mut feed = self.ctx.monitor.subscribe();
+        self.client.set_monitor();
+        self.framed.send(Frame::SimpleString("OK".into())).await?;
+        loop {
+            tokio::select! {
+                line = feed.recv() => {
+                    let Some(line) = line else {
+                        break;
+                    };
+                    self.framed.send(Frame::SimpleString(line)).await?;
+                    self.update_buffers();
+                }
+                frame = self.framed.next() => {
+                    let frame = match frame {
+                        Some(frame) => frame?,
+                        None => break,
+                    };
+                    let is_quit = frame
+                        .args()
+                        .first()
+                        .is_some_and(|cmd| cmd.eq_ignore_ascii_case(b"QUIT"));
+                    if is_quit {
+                        self.framed.send(Frame::SimpleString("OK".into())).await?;
+                        break;
+                    }
+                    self.framed
+                        .send(Frame::Error("ERR only QUIT is allowed in MONITOR mode".into()))
+                        .await?;
+                }
+                _ = self.client.killed() => {
+                    break;
+                }
+                _ = self.ctx.cancel.cancelled() => {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update_buffers(&self) {
+        self.client.set_buffers(
+            self.framed.read_buffer().len(),
+            self.framed.write_buffer().len(),
+        );
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.ctx.clients.unregister(self.client.id);
+    }
 }
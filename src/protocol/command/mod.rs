@@ -1,38 +1,296 @@
 mod parse;
 pub mod spec;
+pub mod table;
+
+use std::sync::Arc;
 
 use tokio_util::bytes::Bytes;
 
-use crate::store::types::Entry;
+use crate::{
+    protocol::command::table::{CommandHandler, CommandListFilter},
+    service::{
+        clients::{ClientKillFilter, ClientType, PauseMode, ReplyMode},
+        shutdown::ShutdownOptions,
+    },
+    store::{
+        ops::{
+            bitmap::{BitOp, BitRange, BitfieldOp},
+            geo::{GeoQuery, GeoUnit},
+            hyperloglog::PfDebugSub,
+        },
+        types::{Entry, Expiry, SetCondition},
+    },
+};
 
 #[derive(Debug)]
 #[allow(clippy::upper_case_acronyms, non_camel_case_types)]
 pub enum Command {
     PING,
-    CONFIG_GET { pattern: Bytes },
-    CONFIG_SET { key: Bytes, value: Bytes },
+    CONFIG_GET {
+        patterns: Vec<Bytes>,
+    },
+    CONFIG_SET {
+        pairs: Vec<(Bytes, Bytes)>,
+    },
     CONFIG_REWRITE,
-    GET { key: Bytes },
-    SET { key: Bytes, entry: Entry },
-    DEL { keys: Vec<Bytes> },
-    EXISTS { keys: Vec<Bytes> },
-    MGET { keys: Vec<Bytes> },
-    MSET { items: Vec<(Bytes, Bytes)> },
-    TTL { key: Bytes },
-    PTTL { key: Bytes },
-    PERSIST { key: Bytes },
-    EXPIRE { key: Bytes, ttl: u64 },
-    PEXPIRE { key: Bytes, ttl: u64 },
-    ECHO { msg: Bytes },
+    CONFIG_RESETSTAT,
+    MEMORY_USAGE {
+        key: Bytes,
+        samples: Option<usize>,
+    },
+    MEMORY_STATS,
+    MEMORY_DOCTOR,
+    CLIENT_LIST {
+        kind: Option<ClientType>,
+        ids: Vec<u64>,
+    },
+    CLIENT_INFO,
+    CLIENT_ID,
+    CLIENT_SETNAME {
+        name: Bytes,
+    },
+    CLIENT_GETNAME,
+    CLIENT_KILL {
+        filter: ClientKillFilter,
+    },
+    CLIENT_PAUSE {
+        timeout_ms: u64,
+        mode: PauseMode,
+    },
+    CLIENT_UNPAUSE,
+    CLIENT_NO_EVICT {
+        on: bool,
+    },
+    CLIENT_REPLY {
+        mode: ReplyMode,
+    },
+    SLOWLOG_GET {
+        count: Option<i64>,
+    },
+    SLOWLOG_LEN,
+    SLOWLOG_RESET,
+    MONITOR,
+    GET {
+        key: Bytes,
+    },
+    SET {
+        key: Bytes,
+        entry: Entry,
+        cond: SetCondition,
+        get: bool,
+    },
+    SETEX {
+        key: Bytes,
+        entry: Entry,
+    },
+    PSETEX {
+        key: Bytes,
+        entry: Entry,
+    },
+    DEL {
+        keys: Vec<Bytes>,
+    },
+    EXISTS {
+        keys: Vec<Bytes>,
+    },
+    MGET {
+        keys: Vec<Bytes>,
+    },
+    MSET {
+        items: Vec<(Bytes, Bytes)>,
+    },
+    MSETNX {
+        items: Vec<(Bytes, Bytes)>,
+    },
+    TTL {
+        key: Bytes,
+    },
+    PTTL {
+        key: Bytes,
+    },
+    PERSIST {
+        key: Bytes,
+    },
+    EXPIRE {
+        key: Bytes,
+        ttl: u64,
+    },
+    PEXPIRE {
+        key: Bytes,
+        ttl: u64,
+    },
+    ECHO {
+        msg: Bytes,
+    },
     DBSIZE,
     FLUSHDB,
-    INFO,
-    GETDEL { key: Bytes },
-    GETSET { key: Bytes, entry: Entry },
-    SETNX { key: Bytes, entry: Entry },
-    INCR { key: Bytes },
-    DECR { key: Bytes },
-    STRLEN { key: Bytes },
-    APPEND { key: Bytes, value: Bytes },
-    SHUTDOWN,
+    SCAN {
+        cursor: u64,
+        pattern: Option<Bytes>,
+        count: usize,
+    },
+    INFO {
+        sections: Vec<String>,
+    },
+    GETDEL {
+        key: Bytes,
+    },
+    GETEX {
+        key: Bytes,
+        exp: Expiry,
+    },
+    GETSET {
+        key: Bytes,
+        entry: Entry,
+    },
+    SETNX {
+        key: Bytes,
+        entry: Entry,
+    },
+    INCR {
+        key: Bytes,
+    },
+    DECR {
+        key: Bytes,
+    },
+    INCRBY {
+        key: Bytes,
+        delta: i64,
+    },
+    DECRBY {
+        key: Bytes,
+        delta: i64,
+    },
+    INCRBYFLOAT {
+        key: Bytes,
+        incr: f64,
+    },
+    GETRANGE {
+        key: Bytes,
+        start: i64,
+        end: i64,
+    },
+    SETRANGE {
+        key: Bytes,
+        offset: usize,
+        value: Bytes,
+    },
+    LCS {
+        key1: Bytes,
+        key2: Bytes,
+        len: bool,
+        idx: bool,
+        min_match_len: usize,
+        with_match_len: bool,
+    },
+    SETBIT {
+        key: Bytes,
+        offset: usize,
+        bit: bool,
+    },
+    GETBIT {
+        key: Bytes,
+        offset: usize,
+    },
+    BITCOUNT {
+        key: Bytes,
+        range: Option<BitRange>,
+    },
+    BITPOS {
+        key: Bytes,
+        bit: bool,
+        range: Option<BitRange>,
+    },
+    BITOP {
+        op: BitOp,
+        dest: Bytes,
+        keys: Vec<Bytes>,
+    },
+    BITFIELD {
+        key: Bytes,
+        ops: Vec<BitfieldOp>,
+    },
+    BITFIELD_RO {
+        key: Bytes,
+        ops: Vec<BitfieldOp>,
+    },
+    PFADD {
+        key: Bytes,
+        elements: Vec<Bytes>,
+    },
+    PFCOUNT {
+        keys: Vec<Bytes>,
+    },
+    PFMERGE {
+        dest: Bytes,
+        keys: Vec<Bytes>,
+    },
+    PFDEBUG {
+        sub: PfDebugSub,
+        key: Bytes,
+    },
+    GEOADD {
+        key: Bytes,
+        cond: SetCondition,
+        ch: bool,
+        items: Vec<(f64, f64, Bytes)>,
+    },
+    GEOPOS {
+        key: Bytes,
+        members: Vec<Bytes>,
+    },
+    GEODIST {
+        key: Bytes,
+        member1: Bytes,
+        member2: Bytes,
+        unit: GeoUnit,
+    },
+    GEOHASH {
+        key: Bytes,
+        members: Vec<Bytes>,
+    },
+    GEOSEARCH {
+        key: Bytes,
+        query: GeoQuery,
+    },
+    GEOSEARCHSTORE {
+        dest: Bytes,
+        key: Bytes,
+        query: GeoQuery,
+        store_dist: bool,
+    },
+    STRLEN {
+        key: Bytes,
+    },
+    APPEND {
+        key: Bytes,
+        value: Bytes,
+    },
+    SHUTDOWN {
+        options: ShutdownOptions,
+    },
+    SHUTDOWN_ABORT,
+    /// Plain COMMAND too; no names means every command.
+    COMMAND_INFO {
+        names: Vec<Bytes>,
+    },
+    COMMAND_COUNT,
+    COMMAND_DOCS {
+        names: Vec<Bytes>,
+    },
+    COMMAND_GETKEYS {
+        args: Vec<Bytes>,
+    },
+    COMMAND_LIST {
+        filter: Option<CommandListFilter>,
+    },
+    /// `HELP` of a container command, named as in the table.
+    HELP {
+        command: &'static str,
+    },
+    /// A command added with `table::register`.
+    REGISTERED {
+        handler: Arc<dyn CommandHandler>,
+        args: Vec<Bytes>,
+    },
 }
@@ -1,96 +1,399 @@
 use crate::{
-    protocol::{command::Command, resp::Frame},
-    store::types::{Entry, Expiry},
+    protocol::{
+        command::{
+            Command,
+            table::{self, CommandListFilter, CommandSpec},
+        },
+        resp::Frame,
+    },
+    service::{
+        clients::{ClientKillFilter, ClientType, PauseMode, ReplyMode},
+        shutdown::{SaveMode, ShutdownOptions},
+    },
+    store::{
+        ops::{
+            bitmap::{
+                BitOp, BitRange, BitUnit, BitfieldOp, BitfieldOverflow, BitfieldType,
+                MAX_BIT_OFFSET,
+            },
+            geo::{self, GeoOrigin, GeoQuery, GeoShape, GeoSort, GeoUnit},
+            hyperloglog::PfDebugSub,
+        },
+        types::{Entry, Expiry, SetCondition},
+    },
+    telemetry,
     utils::time::get_current_millis,
 };
-use nom::AsBytes;
 use tokio_util::bytes::Bytes;
 
 impl TryFrom<Frame> for Command {
     type Error = Frame;
 
     fn try_from(frame: Frame) -> Result<Self, Self::Error> {
-        let Frame::Array(input) = frame else {
-            return Err(Frame::Error("ERR expected array".into()));
-        };
+        let mut span = telemetry::span("command.parse");
+        let parsed = parse_frame(frame);
+        if parsed.is_err() {
+            span.set_attr("error", true);
+        }
+        parsed
+    }
+}
 
-        let Some(Frame::BulkString(cmd)) = input.first() else {
-            return Err(Frame::Error("ERR missing command".into()));
-        };
+fn parse_frame(frame: Frame) -> Result<Command, Frame> {
+    let Frame::Array(input) = frame else {
+        return Err(Frame::Error("ERR expected array".into()));
+    };
+
+    let spec = table_entry(&input)?;
+    if let Some(parse) = spec.parse {
+        return parse(&input);
+    }
+    let handler = table::handler(spec.name).ok_or_else(|| unknown_command(&input))?;
+    let args = input
+        .iter()
+        .map(|frame| match frame {
+            Frame::BulkString(arg) => Ok(arg.clone()),
+            _ => Err(Frame::Error("ERR syntax error".into())),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Command::REGISTERED { handler, args })
+}
+
+/// The table entry `input` runs under, the subcommand's for containers,
+/// once its arity checks out.
+fn table_entry(input: &[Frame]) -> Result<&'static CommandSpec, Frame> {
+    let Some(Frame::BulkString(name)) = input.first() else {
+        return Err(Frame::Error("ERR missing command".into()));
+    };
+    let spec = table::find(name).ok_or_else(|| unknown_command(input))?;
+    if !spec.accepts(input.len()) {
+        return Err(wrong_arity(spec.name));
+    }
+    if spec.subcommands.is_empty() {
+        return Ok(spec);
+    }
 
-        match cmd.to_ascii_uppercase().as_slice() {
-            b"PING" => Ok(Command::PING),
-            b"CONFIG" => parse_config(&input),
-            b"DBSIZE" => Ok(Command::DBSIZE),
-            b"FLUSHDB" => Ok(Command::FLUSHDB),
-            b"INFO" => Ok(Command::INFO),
-            b"GET" => Ok(Command::GET {
-                key: parse_key(&input)?,
-            }),
-            b"SET" => Ok(Command::SET {
-                key: parse_key(&input)?,
-                entry: parse_entry(&input)?,
-            }),
-            b"DEL" => Ok(Command::DEL {
-                keys: parse_keys(&input)?,
-            }),
-            b"EXISTS" => Ok(Command::EXISTS {
-                keys: parse_keys(&input)?,
-            }),
-            b"MGET" => Ok(Command::MGET {
-                keys: parse_keys(&input)?,
-            }),
-            b"MSET" => Ok(Command::MSET {
-                items: parse_items(&input)?,
-            }),
-            b"TTL" => Ok(Command::TTL {
-                key: parse_key(&input)?,
-            }),
-            b"PTTL" => Ok(Command::PTTL {
-                key: parse_key(&input)?,
-            }),
-            b"PERSIST" => Ok(Command::PERSIST {
-                key: parse_key(&input)?,
-            }),
-            b"EXPIRE" => Ok(Command::EXPIRE {
-                key: parse_key(&input)?,
-                ttl: parse_ttl(&input)? * 1000,
-            }),
-            b"PEXPIRE" => Ok(Command::PEXPIRE {
-                key: parse_key(&input)?,
-                ttl: parse_ttl(&input)?,
-            }),
-            b"ECHO" => Ok(Command::ECHO {
-                msg: parse_msg(&input)?,
-            }),
-            b"GETSET" => Ok(Command::GETSET {
-                key: parse_key(&input)?,
-                entry: parse_entry(&input)?,
-            }),
-            b"GETDEL" => Ok(Command::GETDEL {
-                key: parse_key(&input)?,
-            }),
-            b"SETNX" => Ok(Command::SETNX {
-                key: parse_key(&input)?,
-                entry: parse_entry(&input)?,
-            }),
-            b"INCR" => Ok(Command::INCR {
-                key: parse_key(&input)?,
-            }),
-            b"DECR" => Ok(Command::DECR {
-                key: parse_key(&input)?,
-            }),
-            b"STRLEN" => Ok(Command::STRLEN {
-                key: parse_key(&input)?,
-            }),
-            b"APPEND" => Ok(Command::APPEND {
-                key: parse_key(&input)?,
-                value: parse_value(&input)?,
-            }),
-            b"SHUTDOWN" => Ok(Command::SHUTDOWN),
-            _ => Err(Frame::Error("ERR unknown command".into())),
+    let Some(Frame::BulkString(sub)) = input.get(1) else {
+        // Only COMMAND runs without a subcommand.
+        return Ok(spec);
+    };
+    let Some(sub_spec) = spec.subcommand(sub) else {
+        return Err(Frame::Error(format!(
+            "ERR unknown subcommand '{}'. Try {} HELP.",
+            String::from_utf8_lossy(sub),
+            spec.name.to_ascii_uppercase()
+        )));
+    };
+    if !sub_spec.accepts(input.len()) {
+        return Err(wrong_arity(sub_spec.name));
+    }
+    Ok(sub_spec)
+}
+
+fn unknown_command(input: &[Frame]) -> Frame {
+    let name = match input.first() {
+        Some(Frame::BulkString(name)) => String::from_utf8_lossy(name),
+        _ => "".into(),
+    };
+    // Like Redis, quotes at most about 128 bytes of arguments.
+    let mut args = String::new();
+    for arg in &input[1..] {
+        if args.len() >= 128 {
+            break;
+        }
+        if let Frame::BulkString(arg) = arg {
+            let arg = &arg[..arg.len().min(128 - args.len())];
+            args.push_str(&format!("'{}' ", String::from_utf8_lossy(arg)));
         }
     }
+    Frame::Error(format!(
+        "ERR unknown command '{name}', with args beginning with: {args}"
+    ))
+}
+
+fn wrong_arity(name: &str) -> Frame {
+    Frame::Error(format!(
+        "ERR wrong number of arguments for '{name}' command"
+    ))
+}
+
+pub(super) fn parse_ping(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::PING)
+}
+
+pub(super) fn parse_monitor(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::MONITOR)
+}
+
+pub(super) fn parse_dbsize(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::DBSIZE)
+}
+
+pub(super) fn parse_flushdb(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::FLUSHDB)
+}
+
+pub(super) fn parse_get(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::GET {
+        key: parse_key(input)?,
+    })
+}
+
+pub(super) fn parse_setex(input: &[Frame]) -> Result<Command, Frame> {
+    let (key, entry) = parse_timed_set(input, b"EX", "setex")?;
+    Ok(Command::SETEX { key, entry })
+}
+
+pub(super) fn parse_psetex(input: &[Frame]) -> Result<Command, Frame> {
+    let (key, entry) = parse_timed_set(input, b"PX", "psetex")?;
+    Ok(Command::PSETEX { key, entry })
+}
+
+pub(super) fn parse_del(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::DEL {
+        keys: parse_keys(input)?,
+    })
+}
+
+pub(super) fn parse_exists(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::EXISTS {
+        keys: parse_keys(input)?,
+    })
+}
+
+pub(super) fn parse_mget(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::MGET {
+        keys: parse_keys(input)?,
+    })
+}
+
+pub(super) fn parse_mset(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::MSET {
+        items: parse_items(input, "mset")?,
+    })
+}
+
+pub(super) fn parse_msetnx(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::MSETNX {
+        items: parse_items(input, "msetnx")?,
+    })
+}
+
+pub(super) fn parse_ttl(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::TTL {
+        key: parse_key(input)?,
+    })
+}
+
+pub(super) fn parse_pttl(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::PTTL {
+        key: parse_key(input)?,
+    })
+}
+
+pub(super) fn parse_persist(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::PERSIST {
+        key: parse_key(input)?,
+    })
+}
+
+pub(super) fn parse_expire(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::EXPIRE {
+        key: parse_key(input)?,
+        ttl: parse_ttl_arg(input)? * 1000,
+    })
+}
+
+pub(super) fn parse_pexpire(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::PEXPIRE {
+        key: parse_key(input)?,
+        ttl: parse_ttl_arg(input)?,
+    })
+}
+
+pub(super) fn parse_echo(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::ECHO {
+        msg: parse_msg(input)?,
+    })
+}
+
+pub(super) fn parse_getset(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::GETSET {
+        key: parse_key(input)?,
+        entry: parse_entry(input)?,
+    })
+}
+
+pub(super) fn parse_getdel(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::GETDEL {
+        key: parse_key(input)?,
+    })
+}
+
+pub(super) fn parse_setnx(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::SETNX {
+        key: parse_key(input)?,
+        entry: parse_entry(input)?,
+    })
+}
+
+pub(super) fn parse_incr(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::INCR {
+        key: parse_key(input)?,
+    })
+}
+
+pub(super) fn parse_decr(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::DECR {
+        key: parse_key(input)?,
+    })
+}
+
+pub(super) fn parse_incrby(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::INCRBY {
+        key: parse_key(input)?,
+        delta: parse_int(input, 2)?,
+    })
+}
+
+pub(super) fn parse_decrby(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::DECRBY {
+        key: parse_key(input)?,
+        delta: parse_int(input, 2)?,
+    })
+}
+
+pub(super) fn parse_incrbyfloat(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::INCRBYFLOAT {
+        key: parse_key(input)?,
+        incr: parse_float(input, 2)?,
+    })
+}
+
+/// GETRANGE, and SUBSTR, its old name.
+pub(super) fn parse_getrange(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::GETRANGE {
+        key: parse_key(input)?,
+        start: parse_int(input, 2)?,
+        end: parse_int(input, 3)?,
+    })
+}
+
+pub(super) fn parse_setbit(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::SETBIT {
+        key: parse_key(input)?,
+        offset: parse_bit_offset(input, 2)?,
+        bit: parse_bit(input, 3)?,
+    })
+}
+
+pub(super) fn parse_getbit(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::GETBIT {
+        key: parse_key(input)?,
+        offset: parse_bit_offset(input, 2)?,
+    })
+}
+
+pub(super) fn parse_bitcount(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::BITCOUNT {
+        key: parse_key(input)?,
+        range: parse_bit_range(input, 2, true)?,
+    })
+}
+
+pub(super) fn parse_bitpos(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::BITPOS {
+        key: parse_key(input)?,
+        bit: parse_bit(input, 2)?,
+        range: parse_bit_range(input, 3, false)?,
+    })
+}
+
+pub(super) fn parse_bitfield(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::BITFIELD {
+        key: parse_key(input)?,
+        ops: parse_bitfield_ops(input, false)?,
+    })
+}
+
+pub(super) fn parse_bitfield_ro(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::BITFIELD_RO {
+        key: parse_key(input)?,
+        ops: parse_bitfield_ops(input, true)?,
+    })
+}
+
+pub(super) fn parse_pfadd(input: &[Frame]) -> Result<Command, Frame> {
+    let (key, elements) = parse_key_and_rest(input)?;
+    Ok(Command::PFADD { key, elements })
+}
+
+pub(super) fn parse_pfcount(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::PFCOUNT {
+        keys: parse_keys(input)?,
+    })
+}
+
+pub(super) fn parse_pfmerge(input: &[Frame]) -> Result<Command, Frame> {
+    let (dest, keys) = parse_key_and_rest(input)?;
+    Ok(Command::PFMERGE { dest, keys })
+}
+
+pub(super) fn parse_geopos(input: &[Frame]) -> Result<Command, Frame> {
+    let (key, members) = parse_key_and_rest(input)?;
+    Ok(Command::GEOPOS { key, members })
+}
+
+pub(super) fn parse_geohash(input: &[Frame]) -> Result<Command, Frame> {
+    let (key, members) = parse_key_and_rest(input)?;
+    Ok(Command::GEOHASH { key, members })
+}
+
+pub(super) fn parse_geosearch(input: &[Frame]) -> Result<Command, Frame> {
+    parse_geo_query(input, false)
+}
+
+pub(super) fn parse_geosearchstore(input: &[Frame]) -> Result<Command, Frame> {
+    parse_geo_query(input, true)
+}
+
+pub(super) fn parse_strlen(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::STRLEN {
+        key: parse_key(input)?,
+    })
+}
+
+pub(super) fn parse_append(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::APPEND {
+        key: parse_key(input)?,
+        value: parse_value(input)?,
+    })
+}
+
+pub(super) fn parse_shutdown(input: &[Frame]) -> Result<Command, Frame> {
+    let mut options = ShutdownOptions::default();
+    let mut abort = false;
+    for arg in &input[1..] {
+        let Frame::BulkString(arg) = arg else {
+            return Err(Frame::Error("ERR syntax error".into()));
+        };
+        match arg.to_ascii_uppercase().as_slice() {
+            b"NOSAVE" if options.save != SaveMode::Save => options.save = SaveMode::NoSave,
+            b"SAVE" if options.save != SaveMode::NoSave => options.save = SaveMode::Save,
+            b"NOW" => options.now = true,
+            b"FORCE" => options.force = true,
+            b"ABORT" => abort = true,
+            _ => return Err(Frame::Error("ERR syntax error".into())),
+        }
+    }
+    if abort {
+        // ABORT takes no other modifiers.
+        if input.len() != 2 {
+            return Err(Frame::Error("ERR syntax error".into()));
+        }
+        return Ok(Command::SHUTDOWN_ABORT);
+    }
+    Ok(Command::SHUTDOWN { options })
 }
 
 fn parse_key(input: &[Frame]) -> Result<Bytes, Frame> {
@@ -119,7 +422,10 @@ fn parse_entry(input: &[Frame]) -> Result<Entry, Frame> {
     };
 
     let exp = parse_exp(input)?;
-    Ok(Entry { value, exp })
+    Ok(Entry {
+        value: value.into(),
+        exp,
+    })
 }
 
 fn parse_value(input: &[Frame]) -> Result<Bytes, Frame> {
@@ -137,78 +443,129 @@ fn parse_exp(input: &[Frame]) -> Result<Expiry, Frame> {
     };
 
     match sub_command.to_ascii_uppercase().as_slice() {
-        b"EX" => {
-            let Some(Frame::BulkString(bytes)) = input.get(4) else {
-                return Err(Frame::Error("ERR syntax error".into()));
-            };
-
-            let secs = std::str::from_utf8(bytes)
-                .ok()
-                .and_then(|s| s.parse::<u64>().ok())
-                .ok_or_else(|| {
-                    Frame::Error("ERR value is not an integer or out of range".into())
-                })?;
+        b"KEEPTTL" => Ok(Expiry::Keep),
+        unit @ (b"EX" | b"PX" | b"EXAT" | b"PXAT") => parse_expiry(unit, input.get(4), "set"),
+        _ => Err(Frame::Error("ERR syntax error".into())),
+    }
+}
 
-            Ok(Expiry::At(get_current_millis() + secs * 1000))
-        }
-        b"PX" => {
-            let Some(Frame::BulkString(bytes)) = input.get(4) else {
-                return Err(Frame::Error("ERR syntax error".into()));
-            };
+/// Resolves an `EX`/`PX`/`EXAT`/`PXAT` argument into an absolute expiry.
+fn parse_expiry(unit: &[u8], arg: Option<&Frame>, cmd: &str) -> Result<Expiry, Frame> {
+    let Some(Frame::BulkString(bytes)) = arg else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
 
-            let msecs = std::str::from_utf8(bytes)
-                .ok()
-                .and_then(|s| s.parse::<u64>().ok())
-                .ok_or_else(|| {
-                    Frame::Error("ERR value is not an integer or out of range".into())
-                })?;
+    let amount = std::str::from_utf8(bytes)
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .ok_or_else(|| Frame::Error("ERR value is not an integer or out of range".into()))?;
 
-            Ok(Expiry::At(get_current_millis() + msecs))
-        }
-        b"EXAT" => {
-            let Some(Frame::BulkString(bytes)) = input.get(4) else {
-                return Err(Frame::Error("ERR syntax error".into()));
-            };
+    let invalid = || Frame::Error(format!("ERR invalid expire time in '{cmd}' command"));
+    if amount <= 0 {
+        return Err(invalid());
+    }
 
-            let secs = std::str::from_utf8(bytes)
-                .ok()
-                .and_then(|s| s.parse::<u64>().ok())
-                .ok_or_else(|| {
-                    Frame::Error("ERR value is not an integer or out of range".into())
-                })?;
+    let millis = match unit {
+        b"EX" | b"EXAT" => (amount as u64).checked_mul(1000).ok_or_else(invalid)?,
+        _ => amount as u64,
+    };
 
-            if secs * 1000 < get_current_millis() {
+    match unit {
+        b"EX" | b"PX" => get_current_millis()
+            .checked_add(millis)
+            .map(Expiry::At)
+            .ok_or_else(invalid),
+        _ => {
+            if millis < get_current_millis() {
                 return Err(Frame::Error("ERR invalid timestamp".into()));
             }
-
-            Ok(Expiry::At(secs * 1000))
+            Ok(Expiry::At(millis))
         }
-        b"PXAT" => {
-            let Some(Frame::BulkString(bytes)) = input.get(4) else {
-                return Err(Frame::Error("ERR syntax error".into()));
-            };
+    }
+}
 
-            let msecs = std::str::from_utf8(bytes)
-                .ok()
-                .and_then(|s| s.parse::<u64>().ok())
-                .ok_or_else(|| {
-                    Frame::Error("ERR value is not an integer or out of range".into())
-                })?;
+pub(super) fn parse_set(input: &[Frame]) -> Result<Command, Frame> {
+    let key = parse_key(input)?;
+    let value = parse_value(input)?;
 
-            if msecs < get_current_millis() {
-                return Err(Frame::Error("ERR invalid timestamp".into()));
-            }
+    let mut cond = SetCondition::Always;
+    let mut get = false;
+    let mut exp = None;
+
+    let mut idx = 3;
+    while let Some(frame) = input.get(idx) {
+        let Frame::BulkString(opt) = frame else {
+            return Err(Frame::Error("ERR syntax error".into()));
+        };
 
-            Ok(Expiry::At(msecs))
+        match opt.to_ascii_uppercase().as_slice() {
+            b"NX" if cond != SetCondition::IfExists => cond = SetCondition::IfMissing,
+            b"XX" if cond != SetCondition::IfMissing => cond = SetCondition::IfExists,
+            b"GET" => get = true,
+            b"KEEPTTL" if exp.is_none() => exp = Some(Expiry::Keep),
+            unit @ (b"EX" | b"PX" | b"EXAT" | b"PXAT") if exp.is_none() => {
+                idx += 1;
+                exp = Some(parse_expiry(unit, input.get(idx), "set")?);
+            }
+            _ => return Err(Frame::Error("ERR syntax error".into())),
         }
-        b"KEEPTTL" => Ok(Expiry::Keep),
-        _ => Err(Frame::Error("ERR syntax error".into())),
+        idx += 1;
     }
+
+    Ok(Command::SET {
+        key,
+        entry: Entry {
+            value: value.into(),
+            exp: exp.unwrap_or(Expiry::None),
+        },
+        cond,
+        get,
+    })
 }
 
-fn parse_items(input: &[Frame]) -> Result<Vec<(Bytes, Bytes)>, Frame> {
-    if input.len() < 3 || input.len().is_multiple_of(2) {
-        return Err(Frame::Error("ERR wrong number of arguments".into()));
+fn parse_timed_set(input: &[Frame], unit: &[u8], cmd: &str) -> Result<(Bytes, Entry), Frame> {
+    let key = parse_key(input)?;
+    let exp = parse_expiry(unit, input.get(2), cmd)?;
+    let Some(Frame::BulkString(value)) = input.get(3) else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
+
+    Ok((
+        key,
+        Entry {
+            value: value.clone().into(),
+            exp,
+        },
+    ))
+}
+
+pub(super) fn parse_getex(input: &[Frame]) -> Result<Command, Frame> {
+    let key = parse_key(input)?;
+
+    let exp = match input.get(2) {
+        None => Expiry::Keep,
+        Some(Frame::BulkString(opt)) => {
+            let (exp, consumed) = match opt.to_ascii_uppercase().as_slice() {
+                b"PERSIST" => (Expiry::None, 3),
+                unit @ (b"EX" | b"PX" | b"EXAT" | b"PXAT") => {
+                    (parse_expiry(unit, input.get(3), "getex")?, 4)
+                }
+                _ => return Err(Frame::Error("ERR syntax error".into())),
+            };
+            if input.len() > consumed {
+                return Err(Frame::Error("ERR syntax error".into()));
+            }
+            exp
+        }
+        Some(_) => return Err(Frame::Error("ERR syntax error".into())),
+    };
+
+    Ok(Command::GETEX { key, exp })
+}
+
+fn parse_items(input: &[Frame], cmd: &str) -> Result<Vec<(Bytes, Bytes)>, Frame> {
+    if input.len().is_multiple_of(2) {
+        return Err(wrong_arity(cmd));
     }
     let mut items = Vec::with_capacity((input.len() - 1) / 2);
     for chunk in input[1..].chunks_exact(2) {
@@ -225,7 +582,7 @@ fn parse_items(input: &[Frame]) -> Result<Vec<(Bytes, Bytes)>, Frame> {
     Ok(items)
 }
 
-fn parse_ttl(input: &[Frame]) -> Result<u64, Frame> {
+fn parse_ttl_arg(input: &[Frame]) -> Result<u64, Frame> {
     let bytes = match input.get(2) {
         Some(Frame::BulkString(t)) => t,
         _ => return Err(Frame::Error("ERR syntax error".into())),
@@ -238,6 +595,524 @@ fn parse_ttl(input: &[Frame]) -> Result<u64, Frame> {
     Ok(ttl)
 }
 
+fn parse_int(input: &[Frame], idx: usize) -> Result<i64, Frame> {
+    let Some(Frame::BulkString(bytes)) = input.get(idx) else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
+
+    std::str::from_utf8(bytes)
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .ok_or_else(|| Frame::Error("ERR value is not an integer or out of range".into()))
+}
+
+fn parse_float(input: &[Frame], idx: usize) -> Result<f64, Frame> {
+    let Some(Frame::BulkString(bytes)) = input.get(idx) else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
+
+    std::str::from_utf8(bytes)
+        .ok()
+        .and_then(|s| s.parse::<f64>().ok())
+        .filter(|f| !f.is_nan())
+        .ok_or_else(|| Frame::Error("ERR value is not a valid float".into()))
+}
+
+pub(super) fn parse_setrange(input: &[Frame]) -> Result<Command, Frame> {
+    let key = parse_key(input)?;
+    let offset = usize::try_from(parse_int(input, 2)?)
+        .map_err(|_| Frame::Error("ERR offset is out of range".into()))?;
+    let Some(Frame::BulkString(value)) = input.get(3) else {
+        return Err(Frame::Error("ERR missing value".into()));
+    };
+
+    Ok(Command::SETRANGE {
+        key,
+        offset,
+        value: value.clone(),
+    })
+}
+
+pub(super) fn parse_lcs(input: &[Frame]) -> Result<Command, Frame> {
+    let (Some(Frame::BulkString(key1)), Some(Frame::BulkString(key2))) =
+        (input.get(1), input.get(2))
+    else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
+
+    let mut len = false;
+    let mut idx = false;
+    let mut min_match_len = 0;
+    let mut with_match_len = false;
+
+    let mut pos = 3;
+    while let Some(frame) = input.get(pos) {
+        let Frame::BulkString(opt) = frame else {
+            return Err(Frame::Error("ERR syntax error".into()));
+        };
+
+        match opt.to_ascii_uppercase().as_slice() {
+            b"LEN" => len = true,
+            b"IDX" => idx = true,
+            b"WITHMATCHLEN" => with_match_len = true,
+            b"MINMATCHLEN" => {
+                pos += 1;
+                min_match_len = parse_int(input, pos)?.max(0) as usize;
+            }
+            _ => return Err(Frame::Error("ERR syntax error".into())),
+        }
+        pos += 1;
+    }
+
+    if len && idx {
+        return Err(Frame::Error(
+            "ERR If you want both the length and indexes, please just use IDX.".into(),
+        ));
+    }
+
+    Ok(Command::LCS {
+        key1: Bytes::copy_from_slice(key1),
+        key2: Bytes::copy_from_slice(key2),
+        len,
+        idx,
+        min_match_len,
+        with_match_len,
+    })
+}
+
+fn parse_bit_offset(input: &[Frame], idx: usize) -> Result<usize, Frame> {
+    parse_int(input, idx)
+        .ok()
+        .and_then(|offset| usize::try_from(offset).ok())
+        .filter(|offset| *offset <= MAX_BIT_OFFSET)
+        .ok_or_else(|| Frame::Error("ERR bit offset is not an integer or out of range".into()))
+}
+
+fn parse_bit(input: &[Frame], idx: usize) -> Result<bool, Frame> {
+    match parse_int(input, idx) {
+        Ok(0) => Ok(false),
+        Ok(1) => Ok(true),
+        Err(Frame::Error(msg)) if msg == "ERR syntax error" => Err(Frame::Error(msg)),
+        _ => Err(Frame::Error(
+            "ERR bit is not an integer or out of range".into(),
+        )),
+    }
+}
+
+/// Parses the optional `start end [BYTE|BIT]` tail shared by `BITCOUNT` and
+/// `BITPOS`. `BITCOUNT` needs both bounds, `BITPOS` accepts a lone start.
+fn parse_bit_range(
+    input: &[Frame],
+    idx: usize,
+    end_required: bool,
+) -> Result<Option<BitRange>, Frame> {
+    if input.len() <= idx {
+        return Ok(None);
+    }
+    let start = parse_int(input, idx)?;
+    if input.len() == idx + 1 {
+        if end_required {
+            return Err(Frame::Error("ERR syntax error".into()));
+        }
+        return Ok(Some(BitRange {
+            start,
+            end: None,
+            unit: BitUnit::Byte,
+        }));
+    }
+    let end = parse_int(input, idx + 1)?;
+    let unit = match input.get(idx + 2) {
+        None => BitUnit::Byte,
+        Some(Frame::BulkString(unit)) if input.len() == idx + 3 => {
+            match unit.to_ascii_uppercase().as_slice() {
+                b"BYTE" => BitUnit::Byte,
+                b"BIT" => BitUnit::Bit,
+                _ => return Err(Frame::Error("ERR syntax error".into())),
+            }
+        }
+        Some(_) => return Err(Frame::Error("ERR syntax error".into())),
+    };
+
+    Ok(Some(BitRange {
+        start,
+        end: Some(end),
+        unit,
+    }))
+}
+
+pub(super) fn parse_bitop(input: &[Frame]) -> Result<Command, Frame> {
+    let Some(Frame::BulkString(op)) = input.get(1) else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
+    let op = match op.to_ascii_uppercase().as_slice() {
+        b"AND" => BitOp::And,
+        b"OR" => BitOp::Or,
+        b"XOR" => BitOp::Xor,
+        b"NOT" => BitOp::Not,
+        _ => return Err(Frame::Error("ERR syntax error".into())),
+    };
+
+    let mut keys = parse_keys(&input[1..])?;
+    let dest = keys.remove(0);
+    if op == BitOp::Not && keys.len() != 1 {
+        return Err(Frame::Error(
+            "ERR BITOP NOT must be called with a single source key.".into(),
+        ));
+    }
+
+    Ok(Command::BITOP { op, dest, keys })
+}
+
+/// Splits `<cmd> key [arg ...]` into the key and its trailing arguments.
+fn parse_key_and_rest(input: &[Frame]) -> Result<(Bytes, Vec<Bytes>), Frame> {
+    let mut keys = parse_keys(input)?;
+    if keys.is_empty() {
+        return Err(Frame::Error("ERR syntax error".into()));
+    }
+    let key = keys.remove(0);
+    Ok((key, keys))
+}
+
+pub(super) fn parse_pfdebug(input: &[Frame]) -> Result<Command, Frame> {
+    let [_, Frame::BulkString(sub), Frame::BulkString(key)] = input else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
+    let sub = match sub.to_ascii_uppercase().as_slice() {
+        b"GETREG" => PfDebugSub::GetReg,
+        b"DECODE" => PfDebugSub::Decode,
+        b"ENCODING" => PfDebugSub::Encoding,
+        b"TODENSE" => PfDebugSub::ToDense,
+        _ => {
+            return Err(Frame::Error(format!(
+                "ERR Unknown PFDEBUG subcommand '{}'",
+                String::from_utf8_lossy(sub)
+            )));
+        }
+    };
+    Ok(Command::PFDEBUG {
+        sub,
+        key: key.clone(),
+    })
+}
+
+fn parse_coords(input: &[Frame], idx: usize) -> Result<(f64, f64), Frame> {
+    let lon = parse_float(input, idx)?;
+    let lat = parse_float(input, idx + 1)?;
+    if !geo::valid_coords(lon, lat) {
+        return Err(Frame::Error(format!(
+            "ERR invalid longitude,latitude pair {lon:.6},{lat:.6}"
+        )));
+    }
+    Ok((lon, lat))
+}
+
+fn parse_geo_unit(input: &[Frame], idx: usize) -> Result<GeoUnit, Frame> {
+    let Some(Frame::BulkString(unit)) = input.get(idx) else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
+    GeoUnit::parse(unit).ok_or_else(|| {
+        Frame::Error("ERR unsupported unit provided. please use M, KM, FT, MI".into())
+    })
+}
+
+pub(super) fn parse_geoadd(input: &[Frame]) -> Result<Command, Frame> {
+    let key = parse_key(input)?;
+
+    let (mut nx, mut xx, mut ch) = (false, false, false);
+    let mut pos = 2;
+    while let Some(Frame::BulkString(opt)) = input.get(pos) {
+        match opt.to_ascii_uppercase().as_slice() {
+            b"NX" => nx = true,
+            b"XX" => xx = true,
+            b"CH" => ch = true,
+            _ => break,
+        }
+        pos += 1;
+    }
+    if nx && xx {
+        return Err(Frame::Error(
+            "ERR XX and NX options at the same time are not compatible".into(),
+        ));
+    }
+    if pos == input.len() || !(input.len() - pos).is_multiple_of(3) {
+        return Err(Frame::Error("ERR syntax error".into()));
+    }
+
+    let mut items = Vec::with_capacity((input.len() - pos) / 3);
+    for idx in (pos..input.len()).step_by(3) {
+        let (lon, lat) = parse_coords(input, idx)?;
+        let Some(Frame::BulkString(member)) = input.get(idx + 2) else {
+            return Err(Frame::Error("ERR syntax error".into()));
+        };
+        items.push((lon, lat, member.clone()));
+    }
+
+    let cond = match (nx, xx) {
+        (true, _) => SetCondition::IfMissing,
+        (_, true) => SetCondition::IfExists,
+        _ => SetCondition::Always,
+    };
+    Ok(Command::GEOADD {
+        key,
+        cond,
+        ch,
+        items,
+    })
+}
+
+pub(super) fn parse_geodist(input: &[Frame]) -> Result<Command, Frame> {
+    let [
+        _,
+        Frame::BulkString(key),
+        Frame::BulkString(member1),
+        Frame::BulkString(member2),
+        rest @ ..,
+    ] = input
+    else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
+    let unit = match rest.len() {
+        0 => GeoUnit::Meters,
+        1 => parse_geo_unit(input, 4)?,
+        _ => return Err(Frame::Error("ERR syntax error".into())),
+    };
+    Ok(Command::GEODIST {
+        key: key.clone(),
+        member1: member1.clone(),
+        member2: member2.clone(),
+        unit,
+    })
+}
+
+/// Parses GEOSEARCH, or GEOSEARCHSTORE when `store` is set (which takes a
+/// destination key first and allows STOREDIST instead of the WITH* flags).
+fn parse_geo_query(input: &[Frame], store: bool) -> Result<Command, Frame> {
+    let cmd = if store { "geosearchstore" } else { "geosearch" };
+    let mut keys = input.iter().skip(1).take(if store { 2 } else { 1 });
+    let (dest, key) = match (keys.next(), keys.next()) {
+        (Some(Frame::BulkString(dest)), Some(Frame::BulkString(key))) if store => {
+            (Some(dest.clone()), key.clone())
+        }
+        (Some(Frame::BulkString(key)), _) if !store => (None, key.clone()),
+        _ => return Err(Frame::Error("ERR syntax error".into())),
+    };
+
+    let (mut origin, mut shape) = (None, None);
+    let mut unit = GeoUnit::Meters;
+    let mut sort = GeoSort::None;
+    let (mut count, mut any) = (None, false);
+    let (mut with_coord, mut with_dist, mut with_hash, mut store_dist) =
+        (false, false, false, false);
+
+    let one_origin = || {
+        Frame::Error(format!(
+            "ERR exactly one of FROMMEMBER or FROMLONLAT can be specified for {cmd}"
+        ))
+    };
+    let one_shape = || {
+        Frame::Error(format!(
+            "ERR exactly one of BYRADIUS and BYBOX can be specified for {cmd}"
+        ))
+    };
+
+    let mut pos = if store { 3 } else { 2 };
+    while let Some(frame) = input.get(pos) {
+        let Frame::BulkString(opt) = frame else {
+            return Err(Frame::Error("ERR syntax error".into()));
+        };
+        match opt.to_ascii_uppercase().as_slice() {
+            b"FROMMEMBER" => {
+                let Some(Frame::BulkString(member)) = input.get(pos + 1) else {
+                    return Err(Frame::Error("ERR syntax error".into()));
+                };
+                if origin.replace(GeoOrigin::Member(member.clone())).is_some() {
+                    return Err(one_origin());
+                }
+                pos += 1;
+            }
+            b"FROMLONLAT" => {
+                let (lon, lat) = parse_coords(input, pos + 1)?;
+                if origin.replace(GeoOrigin::LonLat(lon, lat)).is_some() {
+                    return Err(one_origin());
+                }
+                pos += 2;
+            }
+            b"BYRADIUS" => {
+                let radius = parse_float(input, pos + 1)?;
+                if radius < 0.0 {
+                    return Err(Frame::Error("ERR radius cannot be negative".into()));
+                }
+                unit = parse_geo_unit(input, pos + 2)?;
+                if shape
+                    .replace(GeoShape::Radius(radius * unit.meters()))
+                    .is_some()
+                {
+                    return Err(one_shape());
+                }
+                pos += 2;
+            }
+            b"BYBOX" => {
+                let width = parse_float(input, pos + 1)?;
+                let height = parse_float(input, pos + 2)?;
+                if width < 0.0 || height < 0.0 {
+                    return Err(Frame::Error(
+                        "ERR height or width cannot be negative".into(),
+                    ));
+                }
+                unit = parse_geo_unit(input, pos + 3)?;
+                let area = GeoShape::Box {
+                    width: width * unit.meters(),
+                    height: height * unit.meters(),
+                };
+                if shape.replace(area).is_some() {
+                    return Err(one_shape());
+                }
+                pos += 3;
+            }
+            b"ASC" => sort = GeoSort::Asc,
+            b"DESC" => sort = GeoSort::Desc,
+            b"COUNT" => {
+                let n = parse_int(input, pos + 1)?;
+                if n <= 0 {
+                    return Err(Frame::Error("ERR COUNT must be > 0".into()));
+                }
+                count = Some(n as usize);
+                pos += 1;
+                if let Some(Frame::BulkString(next)) = input.get(pos + 1)
+                    && next.eq_ignore_ascii_case(b"ANY")
+                {
+                    any = true;
+                    pos += 1;
+                }
+            }
+            b"WITHCOORD" if !store => with_coord = true,
+            b"WITHDIST" if !store => with_dist = true,
+            b"WITHHASH" if !store => with_hash = true,
+            b"STOREDIST" if store => store_dist = true,
+            _ => return Err(Frame::Error("ERR syntax error".into())),
+        }
+        pos += 1;
+    }
+
+    let query = GeoQuery {
+        origin: origin.ok_or_else(one_origin)?,
+        shape: shape.ok_or_else(one_shape)?,
+        unit,
+        sort,
+        count,
+        any,
+        with_coord,
+        with_dist,
+        with_hash,
+    };
+    Ok(match dest {
+        Some(dest) => Command::GEOSEARCHSTORE {
+            dest,
+            key,
+            query,
+            store_dist,
+        },
+        None => Command::GEOSEARCH { key, query },
+    })
+}
+
+fn parse_bitfield_type(input: &[Frame], idx: usize) -> Result<BitfieldType, Frame> {
+    let invalid = || {
+        Frame::Error(
+            "ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is."
+                .into(),
+        )
+    };
+    let Some(Frame::BulkString(ty)) = input.get(idx) else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
+    let signed = match ty.first().map(u8::to_ascii_lowercase) {
+        Some(b'i') => true,
+        Some(b'u') => false,
+        _ => return Err(invalid()),
+    };
+    let bits = std::str::from_utf8(&ty[1..])
+        .ok()
+        .and_then(|s| s.parse::<u32>().ok())
+        .filter(|bits| *bits >= 1 && (*bits <= 63 || (signed && *bits == 64)))
+        .ok_or_else(invalid)?;
+    Ok(BitfieldType { signed, bits })
+}
+
+/// Parses a bitfield offset, where `#N` means "the N-th field of this type".
+fn parse_bitfield_offset(input: &[Frame], idx: usize, ty: BitfieldType) -> Result<usize, Frame> {
+    let invalid = || Frame::Error("ERR bit offset is not an integer or out of range".into());
+    let Some(Frame::BulkString(raw)) = input.get(idx) else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
+    let (multiplier, digits) = match raw.strip_prefix(b"#") {
+        Some(rest) => (ty.bits as usize, rest),
+        None => (1, &raw[..]),
+    };
+    std::str::from_utf8(digits)
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|offset| offset + ty.bits as usize - 1 <= MAX_BIT_OFFSET)
+        .ok_or_else(invalid)
+}
+
+fn parse_bitfield_ops(input: &[Frame], read_only: bool) -> Result<Vec<BitfieldOp>, Frame> {
+    let mut ops = Vec::new();
+    let mut idx = 2;
+    while let Some(frame) = input.get(idx) {
+        let Frame::BulkString(sub) = frame else {
+            return Err(Frame::Error("ERR syntax error".into()));
+        };
+        let sub = sub.to_ascii_uppercase();
+        if read_only && sub != b"GET" {
+            return Err(Frame::Error(
+                "ERR BITFIELD_RO only supports the GET subcommand".into(),
+            ));
+        }
+
+        match sub.as_slice() {
+            b"GET" => {
+                let ty = parse_bitfield_type(input, idx + 1)?;
+                let offset = parse_bitfield_offset(input, idx + 2, ty)?;
+                ops.push(BitfieldOp::Get { ty, offset });
+                idx += 3;
+            }
+            b"SET" => {
+                let ty = parse_bitfield_type(input, idx + 1)?;
+                let offset = parse_bitfield_offset(input, idx + 2, ty)?;
+                let value = parse_int(input, idx + 3)?;
+                ops.push(BitfieldOp::Set { ty, offset, value });
+                idx += 4;
+            }
+            b"INCRBY" => {
+                let ty = parse_bitfield_type(input, idx + 1)?;
+                let offset = parse_bitfield_offset(input, idx + 2, ty)?;
+                let incr = parse_int(input, idx + 3)?;
+                ops.push(BitfieldOp::IncrBy { ty, offset, incr });
+                idx += 4;
+            }
+            b"OVERFLOW" => {
+                let Some(Frame::BulkString(mode)) = input.get(idx + 1) else {
+                    return Err(Frame::Error("ERR syntax error".into()));
+                };
+                let mode = match mode.to_ascii_uppercase().as_slice() {
+                    b"WRAP" => BitfieldOverflow::Wrap,
+                    b"SAT" => BitfieldOverflow::Sat,
+                    b"FAIL" => BitfieldOverflow::Fail,
+                    _ => {
+                        return Err(Frame::Error("ERR Invalid OVERFLOW type specified".into()));
+                    }
+                };
+                ops.push(BitfieldOp::Overflow(mode));
+                idx += 2;
+            }
+            _ => return Err(Frame::Error("ERR syntax error".into())),
+        }
+    }
+    Ok(ops)
+}
+
 fn parse_msg(input: &[Frame]) -> Result<Bytes, Frame> {
     let msg = match input.get(1) {
         Some(Frame::BulkString(msg)) => Bytes::copy_from_slice(msg),
@@ -246,63 +1121,375 @@ fn parse_msg(input: &[Frame]) -> Result<Bytes, Frame> {
     Ok(msg)
 }
 
-fn parse_config(input: &[Frame]) -> Result<Command, Frame> {
-    let Some(Frame::BulkString(sub)) = input.get(1) else {
+pub(super) fn parse_config_get(input: &[Frame]) -> Result<Command, Frame> {
+    let patterns = input[2..]
+        .iter()
+        .map(|frame| match frame {
+            Frame::BulkString(pattern) => Ok(pattern.clone()),
+            _ => Err(Frame::Error("ERR syntax error".into())),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Command::CONFIG_GET { patterns })
+}
+
+pub(super) fn parse_config_set(input: &[Frame]) -> Result<Command, Frame> {
+    if !input.len().is_multiple_of(2) {
+        return Err(wrong_arity("config|set"));
+    }
+
+    let pairs = input[2..]
+        .chunks(2)
+        .map(|pair| match pair {
+            [Frame::BulkString(key), Frame::BulkString(value)] => Ok((key.clone(), value.clone())),
+            _ => Err(Frame::Error("ERR syntax error".into())),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Command::CONFIG_SET { pairs })
+}
+
+pub(super) fn parse_config_rewrite(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::CONFIG_REWRITE)
+}
+
+pub(super) fn parse_config_resetstat(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::CONFIG_RESETSTAT)
+}
+
+pub(super) fn parse_scan(input: &[Frame]) -> Result<Command, Frame> {
+    let Some(Frame::BulkString(cursor)) = input.get(1) else {
         return Err(Frame::Error("ERR syntax error".into()));
     };
+    let cursor = std::str::from_utf8(cursor)
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .ok_or_else(|| Frame::Error("ERR invalid cursor".into()))?;
+
+    let mut pattern = None;
+    let mut count = 10;
+    let mut idx = 2;
+    while idx < input.len() {
+        let Frame::BulkString(opt) = &input[idx] else {
+            return Err(Frame::Error("ERR syntax error".into()));
+        };
+        match opt.to_ascii_uppercase().as_slice() {
+            b"MATCH" => {
+                let Some(Frame::BulkString(p)) = input.get(idx + 1) else {
+                    return Err(Frame::Error("ERR syntax error".into()));
+                };
+                pattern = Some(p.clone());
+            }
+            b"COUNT" => {
+                let n = parse_int(input, idx + 1)?;
+                if n < 1 {
+                    return Err(Frame::Error("ERR syntax error".into()));
+                }
+                count = n as usize;
+            }
+            _ => return Err(Frame::Error("ERR syntax error".into())),
+        }
+        idx += 2;
+    }
 
-    match sub.to_ascii_uppercase().as_bytes() {
-        b"GET" => {
-            if input.len() != 3 {
+    Ok(Command::SCAN {
+        cursor,
+        pattern,
+        count,
+    })
+}
+
+pub(super) fn parse_memory_usage(input: &[Frame]) -> Result<Command, Frame> {
+    let Some(Frame::BulkString(key)) = input.get(2) else {
+        return Err(Frame::Error("ERR syntax error".into()));
+    };
+    let samples = match input.len() {
+        3 => None,
+        5 if matches!(&input[3], Frame::BulkString(opt) if opt.eq_ignore_ascii_case(b"SAMPLES")) => {
+            let samples = parse_int(input, 4)?;
+            if samples < 0 {
                 return Err(Frame::Error(
-                    "ERR wrong number of arguments for 'config|get' command".into(),
+                    "ERR value is out of range, must be positive".into(),
                 ));
             }
+            Some(samples as usize)
+        }
+        _ => return Err(Frame::Error("ERR syntax error".into())),
+    };
+    Ok(Command::MEMORY_USAGE {
+        key: key.clone(),
+        samples,
+    })
+}
 
-            let Some(Frame::BulkString(pattern)) = input.get(2) else {
-                return Err(Frame::Error("ERR syntax error".into()));
-            };
+pub(super) fn parse_memory_stats(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::MEMORY_STATS)
+}
 
-            Ok(Command::CONFIG_GET {
-                pattern: pattern.clone(),
-            })
-        }
-        b"SET" => {
-            if input.len() != 4 {
-                return Err(Frame::Error(
-                    "ERR wrong number of arguments for 'config|set' command".into(),
-                ));
+pub(super) fn parse_memory_doctor(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::MEMORY_DOCTOR)
+}
+
+fn client_arg(input: &[Frame], idx: usize) -> Result<&Bytes, Frame> {
+    match input.get(idx) {
+        Some(Frame::BulkString(b)) => Ok(b),
+        _ => Err(Frame::Error("ERR syntax error".into())),
+    }
+}
+
+pub(super) fn parse_client_list(input: &[Frame]) -> Result<Command, Frame> {
+    let mut kind = None;
+    let mut ids = Vec::new();
+    let mut idx = 2;
+    while idx < input.len() {
+        let opt = client_arg(input, idx)?;
+        if opt.eq_ignore_ascii_case(b"TYPE") {
+            let name = client_arg(input, idx + 1)?;
+            kind = Some(match name.to_ascii_lowercase().as_slice() {
+                b"normal" => ClientType::Normal,
+                b"master" => ClientType::Master,
+                b"replica" | b"slave" => ClientType::Replica,
+                b"pubsub" => ClientType::PubSub,
+                _ => {
+                    return Err(Frame::Error(format!(
+                        "ERR Unknown client type '{}'",
+                        String::from_utf8_lossy(name)
+                    )));
+                }
+            });
+            idx += 2;
+        } else if opt.eq_ignore_ascii_case(b"ID") && idx + 1 < input.len() {
+            for i in idx + 1..input.len() {
+                ids.push(parse_id(client_arg(input, i)?)?);
             }
+            idx = input.len();
+        } else {
+            return Err(Frame::Error("ERR syntax error".into()));
+        }
+    }
+    Ok(Command::CLIENT_LIST { kind, ids })
+}
 
-            let Some(Frame::BulkString(key)) = input.get(2) else {
-                return Err(Frame::Error("ERR syntax error".into()));
-            };
+pub(super) fn parse_client_info(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::CLIENT_INFO)
+}
 
-            let Some(Frame::BulkString(value)) = input.get(3) else {
-                return Err(Frame::Error("ERR syntax error".into()));
-            };
-            Ok(Command::CONFIG_SET {
-                key: key.clone(),
-                value: value.clone(),
-            })
+pub(super) fn parse_client_id(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::CLIENT_ID)
+}
+
+pub(super) fn parse_client_getname(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::CLIENT_GETNAME)
+}
+
+pub(super) fn parse_client_unpause(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::CLIENT_UNPAUSE)
+}
+
+pub(super) fn parse_client_setname(input: &[Frame]) -> Result<Command, Frame> {
+    let name = client_arg(input, 2)?;
+    if name.iter().any(|&b| !(b'!'..=b'~').contains(&b)) {
+        return Err(Frame::Error(
+            "ERR Client names cannot contain spaces, newlines or special characters.".into(),
+        ));
+    }
+    Ok(Command::CLIENT_SETNAME { name: name.clone() })
+}
+
+pub(super) fn parse_client_kill(input: &[Frame]) -> Result<Command, Frame> {
+    if input.len() == 3 {
+        return Ok(Command::CLIENT_KILL {
+            filter: ClientKillFilter {
+                addr: Some(String::from_utf8_lossy(client_arg(input, 2)?).into_owned()),
+                legacy: true,
+                ..Default::default()
+            },
+        });
+    }
+    if !input.len().is_multiple_of(2) {
+        return Err(Frame::Error("ERR syntax error".into()));
+    }
+    let mut filter = ClientKillFilter {
+        skip_me: true,
+        ..Default::default()
+    };
+    for idx in (2..input.len()).step_by(2) {
+        let value = client_arg(input, idx + 1)?;
+        let text = || String::from_utf8_lossy(value).into_owned();
+        match client_arg(input, idx)?.to_ascii_uppercase().as_slice() {
+            b"ID" => filter.id = Some(parse_id(value)?),
+            b"ADDR" => filter.addr = Some(text()),
+            b"LADDR" => filter.laddr = Some(text()),
+            b"USER" => filter.user = Some(text()),
+            b"SKIPME" => {
+                filter.skip_me = match value.to_ascii_lowercase().as_slice() {
+                    b"yes" => true,
+                    b"no" => false,
+                    _ => return Err(Frame::Error("ERR syntax error".into())),
+                }
+            }
+            _ => return Err(Frame::Error("ERR syntax error".into())),
         }
-        b"REWRITE" => {
-            if input.len() != 2 {
+    }
+    Ok(Command::CLIENT_KILL { filter })
+}
+
+pub(super) fn parse_client_pause(input: &[Frame]) -> Result<Command, Frame> {
+    if input.len() > 4 {
+        return Err(wrong_arity("client|pause"));
+    }
+    let timeout_ms = std::str::from_utf8(client_arg(input, 2)?)
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .ok_or_else(|| Frame::Error("ERR timeout is not an integer or out of range".into()))?;
+    let mode = match input.get(3) {
+        None => PauseMode::All,
+        Some(_) => match client_arg(input, 3)?.to_ascii_uppercase().as_slice() {
+            b"ALL" => PauseMode::All,
+            b"WRITE" => PauseMode::Write,
+            _ => return Err(Frame::Error("ERR syntax error".into())),
+        },
+    };
+    Ok(Command::CLIENT_PAUSE { timeout_ms, mode })
+}
+
+pub(super) fn parse_client_no_evict(input: &[Frame]) -> Result<Command, Frame> {
+    match client_arg(input, 2)?.to_ascii_uppercase().as_slice() {
+        b"ON" => Ok(Command::CLIENT_NO_EVICT { on: true }),
+        b"OFF" => Ok(Command::CLIENT_NO_EVICT { on: false }),
+        _ => Err(Frame::Error("ERR syntax error".into())),
+    }
+}
+
+pub(super) fn parse_client_reply(input: &[Frame]) -> Result<Command, Frame> {
+    let mode = match client_arg(input, 2)?.to_ascii_uppercase().as_slice() {
+        b"ON" => ReplyMode::On,
+        b"OFF" => ReplyMode::Off,
+        b"SKIP" => ReplyMode::Skip,
+        _ => return Err(Frame::Error("ERR syntax error".into())),
+    };
+    Ok(Command::CLIENT_REPLY { mode })
+}
+
+pub(super) fn parse_slowlog_get(input: &[Frame]) -> Result<Command, Frame> {
+    let count = match input.len() {
+        2 => None,
+        3 => {
+            let count = parse_int(input, 2)?;
+            if count < -1 {
                 return Err(Frame::Error(
-                    "ERR wrong number of arguments for 'config|rewrite' command".into(),
+                    "ERR count should be greater than or equal to -1".into(),
                 ));
             }
-            Ok(Command::CONFIG_REWRITE)
+            Some(count)
+        }
+        _ => return Err(wrong_arity("slowlog|get")),
+    };
+    Ok(Command::SLOWLOG_GET { count })
+}
+
+pub(super) fn parse_slowlog_len(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::SLOWLOG_LEN)
+}
+
+pub(super) fn parse_slowlog_reset(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::SLOWLOG_RESET)
+}
+
+/// The arguments after `COMMAND <subcommand>`.
+fn command_args(input: &[Frame]) -> Result<Vec<Bytes>, Frame> {
+    input
+        .get(2..)
+        .unwrap_or_default()
+        .iter()
+        .map(|frame| match frame {
+            Frame::BulkString(arg) => Ok(arg.clone()),
+            _ => Err(Frame::Error("ERR syntax error".into())),
+        })
+        .collect()
+}
+
+/// COMMAND INFO, and plain COMMAND.
+pub(super) fn parse_command_info(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::COMMAND_INFO {
+        names: command_args(input)?,
+    })
+}
+
+pub(super) fn parse_help(input: &[Frame]) -> Result<Command, Frame> {
+    let Some(Frame::BulkString(name)) = input.first() else {
+        return Err(Frame::Error("ERR missing command".into()));
+    };
+    let spec = table::find(name).ok_or_else(|| unknown_command(input))?;
+    Ok(Command::HELP { command: spec.name })
+}
+
+pub(super) fn parse_command_count(_: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::COMMAND_COUNT)
+}
+
+pub(super) fn parse_command_docs(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::COMMAND_DOCS {
+        names: command_args(input)?,
+    })
+}
+
+pub(super) fn parse_command_getkeys(input: &[Frame]) -> Result<Command, Frame> {
+    Ok(Command::COMMAND_GETKEYS {
+        args: command_args(input)?,
+    })
+}
+
+pub(super) fn parse_command_list(input: &[Frame]) -> Result<Command, Frame> {
+    let filter = match command_args(input)?.as_slice() {
+        [] => None,
+        [filterby, kind, value] if filterby.eq_ignore_ascii_case(b"FILTERBY") => {
+            let value = String::from_utf8_lossy(value).into_owned();
+            Some(match kind.to_ascii_uppercase().as_slice() {
+                b"MODULE" => CommandListFilter::Module(value),
+                b"ACLCAT" => CommandListFilter::AclCategory(value),
+                b"PATTERN" => CommandListFilter::Pattern(value),
+                _ => return Err(Frame::Error("ERR syntax error".into())),
+            })
         }
-        _ => Err(Frame::Error("ERR unknown command for 'CONFIG' ".into())),
-    }
+        _ => return Err(Frame::Error("ERR syntax error".into())),
+    };
+    Ok(Command::COMMAND_LIST { filter })
+}
+
+pub(super) fn parse_info(input: &[Frame]) -> Result<Command, Frame> {
+    let sections = input[1..]
+        .iter()
+        .map(|frame| match frame {
+            Frame::BulkString(name) => Ok(String::from_utf8_lossy(name).to_ascii_lowercase()),
+            _ => Err(Frame::Error("ERR syntax error".into())),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Command::INFO { sections })
+}
+
+fn parse_id(raw: &[u8]) -> Result<u64, Frame> {
+    std::str::from_utf8(raw)
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .filter(|&id| id > 0)
+        .ok_or_else(|| Frame::Error("ERR client-id should be greater than 0".into()))
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         protocol::{command::Command, resp::Frame},
-        store::types::Expiry,
+        service::{
+            clients::{ClientType, PauseMode, ReplyMode},
+            shutdown::{SaveMode, ShutdownOptions},
+        },
+        store::{
+            ops::{
+                bitmap::{BitOp, BitRange, BitUnit, BitfieldOp, BitfieldOverflow, BitfieldType},
+                geo::{GeoOrigin, GeoShape, GeoSort, GeoUnit},
+                hyperloglog::PfDebugSub,
+            },
+            types::{Expiry, SetCondition},
+        },
     };
     use tokio_util::bytes::Bytes;
 
@@ -335,6 +1522,45 @@ mod tests {
         let frame = cmd_frame(&[bulk("UNKNOWNCMD")]);
         let err = Command::try_from(frame).unwrap_err();
         assert!(matches!(err, Frame::Error(s) if s.contains("unknown command")));
+
+        let frame = cmd_frame(&[bulk("NOPE"), bulk("a"), bulk("b c")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR unknown command 'NOPE', with args beginning with: 'a' 'b c' "
+        );
+        let frame = cmd_frame(&[bulk("CONFIG|GET"), bulk("*")]);
+        assert!(err_text(frame).starts_with("ERR unknown command 'CONFIG|GET'"));
+    }
+
+    #[test]
+    fn arity_comes_from_the_table() {
+        for (args, name) in [
+            (&["GET"][..], "get"),
+            (&["GET", "k", "extra"], "get"),
+            (&["MSET", "a"], "mset"),
+            (&["MSET", "a", "1", "b"], "mset"),
+            (&["CONFIG"], "config"),
+            (&["CONFIG", "SET", "maxmemory"], "config|set"),
+            (&["SLOWLOG", "GET", "1", "2"], "slowlog|get"),
+        ] {
+            let frame = cmd_frame(&args.iter().map(|arg| bulk(arg)).collect::<Vec<_>>());
+            assert_eq!(
+                err_text(frame),
+                format!("ERR wrong number of arguments for '{name}' command"),
+                "{args:?}"
+            );
+        }
+
+        let frame = cmd_frame(&[bulk("CONFIG"), bulk("nope")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR unknown subcommand 'nope'. Try CONFIG HELP."
+        );
+        let frame = cmd_frame(&[bulk("command")]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::COMMAND_INFO { names }) if names.is_empty()
+        ));
     }
 
     #[test]
@@ -358,13 +1584,54 @@ mod tests {
     #[test]
     fn parse_info() {
         let frame = cmd_frame(&[bulk("INFO")]);
-        assert!(matches!(Command::try_from(frame), Ok(Command::INFO)));
+        assert!(
+            matches!(Command::try_from(frame), Ok(Command::INFO { sections }) if sections.is_empty())
+        );
+        let frame = cmd_frame(&[bulk("INFO"), bulk("Keyspace"), bulk("stats")]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::INFO { sections }) if sections == ["keyspace", "stats"]
+        ));
     }
 
     #[test]
     fn parse_shutdown() {
         let frame = cmd_frame(&[bulk("SHUTDOWN")]);
-        assert!(matches!(Command::try_from(frame), Ok(Command::SHUTDOWN)));
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::SHUTDOWN { options }) if options == ShutdownOptions::default()
+        ));
+
+        let frame = cmd_frame(&[bulk("shutdown"), bulk("save"), bulk("NOW"), bulk("force")]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::SHUTDOWN { options }) if options == ShutdownOptions {
+                save: SaveMode::Save,
+                now: true,
+                force: true,
+            }
+        ));
+
+        let frame = cmd_frame(&[bulk("SHUTDOWN"), bulk("abort")]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::SHUTDOWN_ABORT)
+        ));
+
+        for args in [
+            &["SHUTDOWN", "SAVE", "NOSAVE"][..],
+            &["SHUTDOWN", "ABORT", "NOW"],
+            &["SHUTDOWN", "LATER"],
+        ] {
+            let frame = cmd_frame(&args.iter().map(|a| bulk(a)).collect::<Vec<_>>());
+            assert!(Command::try_from(frame).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn parse_monitor() {
+        let frame = cmd_frame(&[bulk("monitor")]);
+        assert!(matches!(Command::try_from(frame), Ok(Command::MONITOR)));
     }
 
     #[test]
@@ -398,10 +1665,17 @@ mod tests {
         let frame = cmd_frame(&[bulk("SET"), bulk("k"), bulk("v")]);
         let cmd = Command::try_from(frame).unwrap();
         match cmd {
-            Command::SET { key, entry } => {
+            Command::SET {
+                key,
+                entry,
+                cond,
+                get,
+            } => {
                 assert_eq!(key.as_ref(), b"k");
-                assert_eq!(entry.value.as_ref(), b"v");
+                assert_eq!(entry.value, "v");
                 assert!(matches!(entry.exp, Expiry::None));
+                assert_eq!(cond, SetCondition::Always);
+                assert!(!get);
             }
             _ => panic!("expected SET"),
         }
@@ -535,7 +1809,7 @@ mod tests {
         let frame = cmd_frame(&[bulk("GETSET"), bulk("k"), bulk("v")]);
         let cmd = Command::try_from(frame).unwrap();
         assert!(
-            matches!(cmd, Command::GETSET { key, entry } if key.as_ref() == b"k" && entry.value.as_ref() == b"v")
+            matches!(cmd, Command::GETSET { key, entry } if key.as_ref() == b"k" && entry.value == "v")
         );
     }
 
@@ -544,7 +1818,7 @@ mod tests {
         let frame = cmd_frame(&[bulk("SETNX"), bulk("k"), bulk("v")]);
         let cmd = Command::try_from(frame).unwrap();
         assert!(
-            matches!(cmd, Command::SETNX { key, entry } if key.as_ref() == b"k" && entry.value.as_ref() == b"v")
+            matches!(cmd, Command::SETNX { key, entry } if key.as_ref() == b"k" && entry.value == "v")
         );
     }
 
@@ -578,18 +1852,44 @@ mod tests {
 
     #[test]
     fn parse_config_get() {
-        let frame = cmd_frame(&[bulk("CONFIG"), bulk("GET"), bulk("*")]);
+        let frame = cmd_frame(&[bulk("CONFIG"), bulk("GET"), bulk("max*"), bulk("port")]);
         let cmd = Command::try_from(frame).unwrap();
-        assert!(matches!(cmd, Command::CONFIG_GET { pattern } if pattern.as_ref() == b"*"));
+        assert!(
+            matches!(cmd, Command::CONFIG_GET { patterns } if patterns == [Bytes::from("max*"), Bytes::from("port")])
+        );
+        let frame = cmd_frame(&[bulk("CONFIG"), bulk("GET")]);
+        assert!(Command::try_from(frame).is_err());
     }
 
     #[test]
     fn parse_config_set() {
-        let frame = cmd_frame(&[bulk("CONFIG"), bulk("SET"), bulk("appendonly"), bulk("no")]);
+        let frame = cmd_frame(&[
+            bulk("CONFIG"),
+            bulk("SET"),
+            bulk("appendonly"),
+            bulk("no"),
+            bulk("maxclients"),
+            bulk("10"),
+        ]);
         let cmd = Command::try_from(frame).unwrap();
-        assert!(
-            matches!(cmd, Command::CONFIG_SET { key, value } if (key.as_ref() == b"appendonly" && value.as_ref() == b"no"))
+        let Command::CONFIG_SET { pairs } = cmd else {
+            panic!("expected CONFIG SET");
+        };
+        assert_eq!(
+            pairs,
+            vec![
+                (Bytes::from("appendonly"), Bytes::from("no")),
+                (Bytes::from("maxclients"), Bytes::from("10")),
+            ]
         );
+        let frame = cmd_frame(&[
+            bulk("CONFIG"),
+            bulk("SET"),
+            bulk("appendonly"),
+            bulk("no"),
+            bulk("port"),
+        ]);
+        assert!(Command::try_from(frame).is_err());
     }
 
     #[test]
@@ -597,6 +1897,208 @@ mod tests {
         let frame = cmd_frame(&[bulk("CONFIG"), bulk("REWRITE")]);
         let cmd = Command::try_from(frame).unwrap();
         assert!(matches!(cmd, Command::CONFIG_REWRITE));
+        let frame = cmd_frame(&[bulk("CONFIG"), bulk("RESETSTAT")]);
+        assert!(matches!(
+            Command::try_from(frame).unwrap(),
+            Command::CONFIG_RESETSTAT
+        ));
+    }
+
+    #[test]
+    fn parse_scan_options() {
+        let frame = cmd_frame(&[bulk("SCAN"), bulk("0")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::SCAN {
+                cursor: 0,
+                pattern: None,
+                count: 10
+            }
+        ));
+
+        let frame = cmd_frame(&[
+            bulk("SCAN"),
+            bulk("7"),
+            bulk("match"),
+            bulk("user:*"),
+            bulk("COUNT"),
+            bulk("100"),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::SCAN { cursor: 7, pattern: Some(p), count: 100 } if p.as_ref() == b"user:*"
+        ));
+
+        let frame = cmd_frame(&[bulk("SCAN"), bulk("x")]);
+        assert!(Command::try_from(frame).is_err());
+        let frame = cmd_frame(&[bulk("SCAN"), bulk("0"), bulk("COUNT"), bulk("0")]);
+        assert!(Command::try_from(frame).is_err());
+        let frame = cmd_frame(&[bulk("SCAN"), bulk("0"), bulk("MATCH")]);
+        assert!(Command::try_from(frame).is_err());
+    }
+
+    #[test]
+    fn parse_memory_usage() {
+        let frame = cmd_frame(&[bulk("MEMORY"), bulk("usage"), bulk("k")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(
+            matches!(cmd, Command::MEMORY_USAGE { key, samples: None } if key.as_ref() == b"k")
+        );
+
+        let frame = cmd_frame(&[
+            bulk("MEMORY"),
+            bulk("USAGE"),
+            bulk("k"),
+            bulk("SAMPLES"),
+            bulk("0"),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::MEMORY_USAGE {
+                samples: Some(0),
+                ..
+            }
+        ));
+
+        let frame = cmd_frame(&[bulk("MEMORY"), bulk("USAGE"), bulk("k"), bulk("SAMPLES")]);
+        assert!(Command::try_from(frame).is_err());
+        let frame = cmd_frame(&[bulk("MEMORY"), bulk("USAGE")]);
+        assert!(Command::try_from(frame).is_err());
+    }
+
+    #[test]
+    fn parse_memory_stats_and_doctor() {
+        let frame = cmd_frame(&[bulk("MEMORY"), bulk("STATS")]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::MEMORY_STATS)
+        ));
+        let frame = cmd_frame(&[bulk("MEMORY"), bulk("doctor")]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::MEMORY_DOCTOR)
+        ));
+        let frame = cmd_frame(&[bulk("MEMORY"), bulk("STATS"), bulk("x")]);
+        assert!(Command::try_from(frame).is_err());
+        let frame = cmd_frame(&[bulk("MEMORY"), bulk("MALLOC-STATS")]);
+        assert!(Command::try_from(frame).is_err());
+    }
+
+    #[test]
+    fn parse_slowlog_subcommands() {
+        let frame = cmd_frame(&[bulk("SLOWLOG"), bulk("get")]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::SLOWLOG_GET { count: None })
+        ));
+        let frame = cmd_frame(&[bulk("SLOWLOG"), bulk("GET"), bulk("-1")]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::SLOWLOG_GET { count: Some(-1) })
+        ));
+        let frame = cmd_frame(&[bulk("SLOWLOG"), bulk("GET"), bulk("-2")]);
+        assert!(Command::try_from(frame).is_err());
+        let frame = cmd_frame(&[bulk("SLOWLOG"), bulk("LEN")]);
+        assert!(matches!(Command::try_from(frame), Ok(Command::SLOWLOG_LEN)));
+        let frame = cmd_frame(&[bulk("SLOWLOG"), bulk("RESET"), bulk("x")]);
+        assert!(Command::try_from(frame).is_err());
+    }
+
+    #[test]
+    fn parse_client_list_filters() {
+        let frame = cmd_frame(&[
+            bulk("CLIENT"),
+            bulk("list"),
+            bulk("TYPE"),
+            bulk("normal"),
+            bulk("ID"),
+            bulk("3"),
+            bulk("7"),
+        ]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::CLIENT_LIST { kind: Some(ClientType::Normal), ids }) if ids == vec![3, 7]
+        ));
+        let frame = cmd_frame(&[bulk("CLIENT"), bulk("LIST"), bulk("TYPE"), bulk("x")]);
+        assert!(Command::try_from(frame).is_err());
+        let frame = cmd_frame(&[bulk("CLIENT"), bulk("LIST"), bulk("ID"), bulk("0")]);
+        assert!(Command::try_from(frame).is_err());
+    }
+
+    #[test]
+    fn parse_client_kill_forms() {
+        let frame = cmd_frame(&[bulk("CLIENT"), bulk("KILL"), bulk("127.0.0.1:5000")]);
+        let Ok(Command::CLIENT_KILL { filter }) = Command::try_from(frame) else {
+            panic!("expected CLIENT KILL");
+        };
+        assert!(filter.legacy);
+        assert_eq!(filter.addr.as_deref(), Some("127.0.0.1:5000"));
+
+        let frame = cmd_frame(&[
+            bulk("CLIENT"),
+            bulk("KILL"),
+            bulk("ID"),
+            bulk("4"),
+            bulk("USER"),
+            bulk("default"),
+            bulk("SKIPME"),
+            bulk("no"),
+        ]);
+        let Ok(Command::CLIENT_KILL { filter }) = Command::try_from(frame) else {
+            panic!("expected CLIENT KILL");
+        };
+        assert_eq!(filter.id, Some(4));
+        assert_eq!(filter.user.as_deref(), Some("default"));
+        assert!(!filter.skip_me && !filter.legacy);
+
+        let frame = cmd_frame(&[
+            bulk("CLIENT"),
+            bulk("KILL"),
+            bulk("ID"),
+            bulk("4"),
+            bulk("X"),
+        ]);
+        assert!(Command::try_from(frame).is_err());
+        let frame = cmd_frame(&[bulk("CLIENT"), bulk("KILL"), bulk("MAXAGE"), bulk("4")]);
+        assert!(Command::try_from(frame).is_err());
+    }
+
+    #[test]
+    fn parse_client_simple_subcommands() {
+        let frame = cmd_frame(&[bulk("CLIENT"), bulk("SETNAME"), bulk("my name")]);
+        assert!(Command::try_from(frame).is_err());
+        let frame = cmd_frame(&[bulk("CLIENT"), bulk("PAUSE"), bulk("100"), bulk("write")]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::CLIENT_PAUSE {
+                timeout_ms: 100,
+                mode: PauseMode::Write
+            })
+        ));
+        let frame = cmd_frame(&[bulk("CLIENT"), bulk("PAUSE"), bulk("-1")]);
+        assert!(Command::try_from(frame).is_err());
+        let frame = cmd_frame(&[bulk("CLIENT"), bulk("REPLY"), bulk("skip")]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::CLIENT_REPLY {
+                mode: ReplyMode::Skip
+            })
+        ));
+        let frame = cmd_frame(&[bulk("CLIENT"), bulk("NO-EVICT"), bulk("on")]);
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::CLIENT_NO_EVICT { on: true })
+        ));
+        let frame = cmd_frame(&[bulk("CLIENT"), bulk("ID"), bulk("x")]);
+        assert!(Command::try_from(frame).is_err());
+        let frame = cmd_frame(&[bulk("CLIENT"), bulk("TRACKING")]);
+        let Err(Frame::Error(err)) = Command::try_from(frame) else {
+            panic!("expected error");
+        };
+        assert_eq!(err, "ERR unknown subcommand 'TRACKING'. Try CLIENT HELP.");
     }
 
     #[test]
@@ -620,4 +2122,665 @@ mod tests {
         let frame = cmd_frame(&[bulk("SET"), bulk("k"), bulk("v"), bulk("EX"), bulk("abc")]);
         assert!(Command::try_from(frame).is_err());
     }
+
+    fn err_text(frame: Frame) -> String {
+        match Command::try_from(frame) {
+            Err(Frame::Error(s)) => s,
+            other => panic!("expected error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_set_nx_with_px() {
+        let frame = cmd_frame(&[
+            bulk("SET"),
+            bulk("lock"),
+            bulk("v"),
+            bulk("NX"),
+            bulk("PX"),
+            bulk("30000"),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::SET { entry, cond: SetCondition::IfMissing, get: false, .. }
+                if matches!(entry.exp, Expiry::At(_))
+        ));
+    }
+
+    #[test]
+    fn parse_set_xx_get_keepttl() {
+        let frame = cmd_frame(&[
+            bulk("SET"),
+            bulk("k"),
+            bulk("v"),
+            bulk("xx"),
+            bulk("get"),
+            bulk("keepttl"),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::SET { entry, cond: SetCondition::IfExists, get: true, .. }
+                if matches!(entry.exp, Expiry::Keep)
+        ));
+    }
+
+    #[test]
+    fn parse_set_with_exat_and_pxat() {
+        let future_secs = crate::utils::time::get_current_millis() / 1000 + 100;
+        let frame = cmd_frame(&[
+            bulk("SET"),
+            bulk("k"),
+            bulk("v"),
+            bulk("EXAT"),
+            bulk(&future_secs.to_string()),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(
+            matches!(cmd, Command::SET { entry, .. } if matches!(entry.exp, Expiry::At(t) if t == future_secs * 1000))
+        );
+
+        let future_ms = future_secs * 1000 + 5;
+        let frame = cmd_frame(&[
+            bulk("SET"),
+            bulk("k"),
+            bulk("v"),
+            bulk("PXAT"),
+            bulk(&future_ms.to_string()),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(
+            matches!(cmd, Command::SET { entry, .. } if matches!(entry.exp, Expiry::At(t) if t == future_ms))
+        );
+    }
+
+    #[test]
+    fn parse_set_conflicting_options_are_syntax_errors() {
+        let cases: &[&[&str]] = &[
+            &["SET", "k", "v", "NX", "XX"],
+            &["SET", "k", "v", "XX", "NX"],
+            &["SET", "k", "v", "EX", "10", "PX", "100"],
+            &["SET", "k", "v", "KEEPTTL", "EX", "10"],
+            &["SET", "k", "v", "PX", "100", "KEEPTTL"],
+            &["SET", "k", "v", "EX"],
+            &["SET", "k", "v", "GET", "BOGUS"],
+        ];
+        for case in cases {
+            let frame = cmd_frame(&case.iter().map(|s| bulk(s)).collect::<Vec<_>>());
+            assert_eq!(err_text(frame), "ERR syntax error", "case {case:?}");
+        }
+    }
+
+    #[test]
+    fn parse_set_non_positive_expire_is_rejected() {
+        let frame = cmd_frame(&[bulk("SET"), bulk("k"), bulk("v"), bulk("EX"), bulk("0")]);
+        assert_eq!(err_text(frame), "ERR invalid expire time in 'set' command");
+
+        let frame = cmd_frame(&[bulk("SET"), bulk("k"), bulk("v"), bulk("PX"), bulk("-5")]);
+        assert_eq!(err_text(frame), "ERR invalid expire time in 'set' command");
+    }
+
+    #[test]
+    fn parse_setex_and_psetex() {
+        let frame = cmd_frame(&[bulk("SETEX"), bulk("k"), bulk("10"), bulk("v")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(
+            matches!(cmd, Command::SETEX { key, entry } if key.as_ref() == b"k" && entry.value == "v" && matches!(entry.exp, Expiry::At(_)))
+        );
+
+        let frame = cmd_frame(&[bulk("PSETEX"), bulk("k"), bulk("1500"), bulk("v")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(cmd, Command::PSETEX { entry, .. } if matches!(entry.exp, Expiry::At(_))));
+    }
+
+    #[test]
+    fn parse_setex_errors() {
+        let frame = cmd_frame(&[bulk("SETEX"), bulk("k"), bulk("10")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR wrong number of arguments for 'setex' command"
+        );
+
+        let frame = cmd_frame(&[bulk("SETEX"), bulk("k"), bulk("0"), bulk("v")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR invalid expire time in 'setex' command"
+        );
+
+        let frame = cmd_frame(&[bulk("PSETEX"), bulk("k"), bulk("abc"), bulk("v")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR value is not an integer or out of range"
+        );
+    }
+
+    #[test]
+    fn parse_getex_variants() {
+        let frame = cmd_frame(&[bulk("GETEX"), bulk("k")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::GETEX {
+                exp: Expiry::Keep,
+                ..
+            }
+        ));
+
+        let frame = cmd_frame(&[bulk("GETEX"), bulk("k"), bulk("PERSIST")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::GETEX {
+                exp: Expiry::None,
+                ..
+            }
+        ));
+
+        let frame = cmd_frame(&[bulk("GETEX"), bulk("k"), bulk("EX"), bulk("10")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::GETEX {
+                exp: Expiry::At(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_getex_rejects_multiple_options() {
+        let frame = cmd_frame(&[
+            bulk("GETEX"),
+            bulk("k"),
+            bulk("EX"),
+            bulk("10"),
+            bulk("PERSIST"),
+        ]);
+        assert_eq!(err_text(frame), "ERR syntax error");
+
+        let frame = cmd_frame(&[bulk("GETEX"), bulk("k"), bulk("PERSIST"), bulk("PX")]);
+        assert_eq!(err_text(frame), "ERR syntax error");
+    }
+
+    #[test]
+    fn parse_msetnx() {
+        let frame = cmd_frame(&[
+            bulk("MSETNX"),
+            bulk("k1"),
+            bulk("v1"),
+            bulk("k2"),
+            bulk("v2"),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(cmd, Command::MSETNX { items } if items.len() == 2));
+    }
+
+    #[test]
+    fn parse_incrby_and_decrby() {
+        let frame = cmd_frame(&[bulk("INCRBY"), bulk("k"), bulk("5")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(cmd, Command::INCRBY { delta: 5, .. }));
+
+        let frame = cmd_frame(&[bulk("DECRBY"), bulk("k"), bulk("-3")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(cmd, Command::DECRBY { delta: -3, .. }));
+
+        let frame = cmd_frame(&[bulk("INCRBY"), bulk("k"), bulk("1.5")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR value is not an integer or out of range"
+        );
+    }
+
+    #[test]
+    fn parse_incrbyfloat() {
+        let frame = cmd_frame(&[bulk("INCRBYFLOAT"), bulk("k"), bulk("0.1")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(cmd, Command::INCRBYFLOAT { incr, .. } if incr == 0.1));
+
+        let frame = cmd_frame(&[bulk("INCRBYFLOAT"), bulk("k"), bulk("abc")]);
+        assert_eq!(err_text(frame), "ERR value is not a valid float");
+    }
+
+    #[test]
+    fn parse_getrange_and_substr() {
+        let frame = cmd_frame(&[bulk("GETRANGE"), bulk("k"), bulk("0"), bulk("-1")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::GETRANGE {
+                start: 0,
+                end: -1,
+                ..
+            }
+        ));
+
+        let frame = cmd_frame(&[bulk("SUBSTR"), bulk("k"), bulk("1"), bulk("3")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::GETRANGE {
+                start: 1,
+                end: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_setrange_negative_offset() {
+        let frame = cmd_frame(&[bulk("SETRANGE"), bulk("k"), bulk("-1"), bulk("v")]);
+        assert_eq!(err_text(frame), "ERR offset is out of range");
+    }
+
+    #[test]
+    fn parse_lcs_options() {
+        let frame = cmd_frame(&[
+            bulk("LCS"),
+            bulk("a"),
+            bulk("b"),
+            bulk("IDX"),
+            bulk("MINMATCHLEN"),
+            bulk("4"),
+            bulk("WITHMATCHLEN"),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::LCS {
+                len: false,
+                idx: true,
+                min_match_len: 4,
+                with_match_len: true,
+                ..
+            }
+        ));
+
+        let frame = cmd_frame(&[bulk("LCS"), bulk("a"), bulk("b"), bulk("LEN"), bulk("IDX")]);
+        assert!(err_text(frame).contains("just use IDX"));
+    }
+
+    #[test]
+    fn parse_setbit_and_getbit() {
+        let frame = cmd_frame(&[bulk("SETBIT"), bulk("k"), bulk("7"), bulk("1")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::SETBIT {
+                offset: 7,
+                bit: true,
+                ..
+            }
+        ));
+
+        let frame = cmd_frame(&[bulk("SETBIT"), bulk("k"), bulk("7"), bulk("2")]);
+        assert_eq!(err_text(frame), "ERR bit is not an integer or out of range");
+
+        let frame = cmd_frame(&[bulk("GETBIT"), bulk("k"), bulk("-1")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR bit offset is not an integer or out of range"
+        );
+
+        let frame = cmd_frame(&[bulk("GETBIT"), bulk("k"), bulk("4294967296")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR bit offset is not an integer or out of range"
+        );
+    }
+
+    #[test]
+    fn parse_bitcount_ranges() {
+        let frame = cmd_frame(&[bulk("BITCOUNT"), bulk("k")]);
+        assert!(matches!(
+            Command::try_from(frame).unwrap(),
+            Command::BITCOUNT { range: None, .. }
+        ));
+
+        let frame = cmd_frame(&[
+            bulk("BITCOUNT"),
+            bulk("k"),
+            bulk("1"),
+            bulk("5"),
+            bulk("bit"),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::BITCOUNT {
+                range: Some(BitRange {
+                    start: 1,
+                    end: Some(5),
+                    unit: BitUnit::Bit
+                }),
+                ..
+            }
+        ));
+
+        let frame = cmd_frame(&[bulk("BITCOUNT"), bulk("k"), bulk("1")]);
+        assert_eq!(err_text(frame), "ERR syntax error");
+    }
+
+    #[test]
+    fn parse_bitpos_with_lone_start() {
+        let frame = cmd_frame(&[bulk("BITPOS"), bulk("k"), bulk("0"), bulk("2")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::BITPOS {
+                bit: false,
+                range: Some(BitRange {
+                    start: 2,
+                    end: None,
+                    ..
+                }),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_bitop() {
+        let frame = cmd_frame(&[bulk("BITOP"), bulk("and"), bulk("d"), bulk("a"), bulk("b")]);
+        let cmd = Command::try_from(frame).unwrap();
+        assert!(
+            matches!(cmd, Command::BITOP { op: BitOp::And, dest, keys } if dest.as_ref() == b"d" && keys.len() == 2)
+        );
+
+        let frame = cmd_frame(&[bulk("BITOP"), bulk("NOT"), bulk("d"), bulk("a"), bulk("b")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR BITOP NOT must be called with a single source key."
+        );
+    }
+
+    #[test]
+    fn parse_pf_commands() {
+        let frame = cmd_frame(&[bulk("PFADD"), bulk("h"), bulk("a"), bulk("b")]);
+        assert!(matches!(
+            Command::try_from(frame).unwrap(),
+            Command::PFADD { key, elements } if key.as_ref() == b"h" && elements.len() == 2
+        ));
+
+        let frame = cmd_frame(&[bulk("PFMERGE"), bulk("d"), bulk("a"), bulk("b")]);
+        assert!(matches!(
+            Command::try_from(frame).unwrap(),
+            Command::PFMERGE { dest, keys } if dest.as_ref() == b"d" && keys.len() == 2
+        ));
+
+        let frame = cmd_frame(&[bulk("PFCOUNT")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR wrong number of arguments for 'pfcount' command"
+        );
+    }
+
+    #[test]
+    fn parse_pfdebug() {
+        let frame = cmd_frame(&[bulk("PFDEBUG"), bulk("todense"), bulk("h")]);
+        assert!(matches!(
+            Command::try_from(frame).unwrap(),
+            Command::PFDEBUG {
+                sub: PfDebugSub::ToDense,
+                ..
+            }
+        ));
+
+        let frame = cmd_frame(&[bulk("PFDEBUG"), bulk("nope"), bulk("h")]);
+        assert_eq!(err_text(frame), "ERR Unknown PFDEBUG subcommand 'nope'");
+    }
+
+    #[test]
+    fn parse_geoadd_options() {
+        let frame = cmd_frame(&[
+            bulk("GEOADD"),
+            bulk("k"),
+            bulk("xx"),
+            bulk("CH"),
+            bulk("13.361389"),
+            bulk("38.115556"),
+            bulk("Palermo"),
+        ]);
+        assert!(matches!(
+            Command::try_from(frame).unwrap(),
+            Command::GEOADD {
+                cond: SetCondition::IfExists,
+                ch: true,
+                items,
+                ..
+            } if items.len() == 1 && items[0].2.as_ref() == b"Palermo"
+        ));
+
+        let frame = cmd_frame(&[
+            bulk("GEOADD"),
+            bulk("k"),
+            bulk("NX"),
+            bulk("XX"),
+            bulk("1"),
+            bulk("2"),
+            bulk("m"),
+        ]);
+        assert_eq!(
+            err_text(frame),
+            "ERR XX and NX options at the same time are not compatible"
+        );
+
+        let frame = cmd_frame(&[bulk("GEOADD"), bulk("k"), bulk("181"), bulk("0"), bulk("m")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR invalid longitude,latitude pair 181.000000,0.000000"
+        );
+
+        let frame = cmd_frame(&[
+            bulk("GEOADD"),
+            bulk("k"),
+            bulk("1"),
+            bulk("2"),
+            bulk("m"),
+            bulk("3"),
+        ]);
+        assert_eq!(err_text(frame), "ERR syntax error");
+    }
+
+    #[test]
+    fn parse_geodist_unit() {
+        let frame = cmd_frame(&[bulk("GEODIST"), bulk("k"), bulk("a"), bulk("b"), bulk("KM")]);
+        assert!(matches!(
+            Command::try_from(frame).unwrap(),
+            Command::GEODIST {
+                unit: GeoUnit::Kilometers,
+                ..
+            }
+        ));
+
+        let frame = cmd_frame(&[bulk("GEODIST"), bulk("k"), bulk("a"), bulk("b"), bulk("yd")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR unsupported unit provided. please use M, KM, FT, MI"
+        );
+    }
+
+    #[test]
+    fn parse_geosearch_options() {
+        let frame = cmd_frame(&[
+            bulk("GEOSEARCH"),
+            bulk("k"),
+            bulk("FROMLONLAT"),
+            bulk("15"),
+            bulk("37"),
+            bulk("BYBOX"),
+            bulk("400"),
+            bulk("200"),
+            bulk("km"),
+            bulk("DESC"),
+            bulk("COUNT"),
+            bulk("2"),
+            bulk("ANY"),
+            bulk("WITHDIST"),
+        ]);
+        let Command::GEOSEARCH { query, .. } = Command::try_from(frame).unwrap() else {
+            panic!("expected GEOSEARCH");
+        };
+        assert_eq!(query.origin, GeoOrigin::LonLat(15.0, 37.0));
+        assert_eq!(
+            query.shape,
+            GeoShape::Box {
+                width: 400_000.0,
+                height: 200_000.0
+            }
+        );
+        assert_eq!(query.unit, GeoUnit::Kilometers);
+        assert_eq!(query.sort, GeoSort::Desc);
+        assert_eq!(query.count, Some(2));
+        assert!(query.any && query.with_dist && !query.with_coord);
+
+        let frame = cmd_frame(&[
+            bulk("GEOSEARCHSTORE"),
+            bulk("dest"),
+            bulk("k"),
+            bulk("FROMMEMBER"),
+            bulk("a"),
+            bulk("BYRADIUS"),
+            bulk("10"),
+            bulk("m"),
+            bulk("STOREDIST"),
+        ]);
+        assert!(matches!(
+            Command::try_from(frame).unwrap(),
+            Command::GEOSEARCHSTORE { dest, store_dist: true, .. } if dest.as_ref() == b"dest"
+        ));
+    }
+
+    #[test]
+    fn parse_geosearch_errors() {
+        let frame = cmd_frame(&[bulk("GEOSEARCH"), bulk("k"), bulk("FROMMEMBER"), bulk("a")]);
+        assert_eq!(
+            err_text(frame),
+            "ERR wrong number of arguments for 'geosearch' command"
+        );
+
+        let frame = cmd_frame(&[
+            bulk("GEOSEARCH"),
+            bulk("k"),
+            bulk("BYRADIUS"),
+            bulk("1"),
+            bulk("m"),
+            bulk("ASC"),
+            bulk("WITHDIST"),
+        ]);
+        assert_eq!(
+            err_text(frame),
+            "ERR exactly one of FROMMEMBER or FROMLONLAT can be specified for geosearch"
+        );
+
+        let frame = cmd_frame(&[
+            bulk("GEOSEARCH"),
+            bulk("k"),
+            bulk("FROMMEMBER"),
+            bulk("a"),
+            bulk("ASC"),
+            bulk("WITHDIST"),
+            bulk("WITHHASH"),
+        ]);
+        assert_eq!(
+            err_text(frame),
+            "ERR exactly one of BYRADIUS and BYBOX can be specified for geosearch"
+        );
+
+        let frame = cmd_frame(&[
+            bulk("GEOSEARCH"),
+            bulk("k"),
+            bulk("FROMMEMBER"),
+            bulk("a"),
+            bulk("BYRADIUS"),
+            bulk("1"),
+            bulk("m"),
+            bulk("COUNT"),
+            bulk("0"),
+        ]);
+        assert_eq!(err_text(frame), "ERR COUNT must be > 0");
+
+        let frame = cmd_frame(&[
+            bulk("GEOSEARCHSTORE"),
+            bulk("d"),
+            bulk("k"),
+            bulk("FROMMEMBER"),
+            bulk("a"),
+            bulk("BYRADIUS"),
+            bulk("1"),
+            bulk("m"),
+            bulk("WITHCOORD"),
+        ]);
+        assert_eq!(err_text(frame), "ERR syntax error");
+    }
+
+    #[test]
+    fn parse_bitfield_program() {
+        let frame = cmd_frame(&[
+            bulk("BITFIELD"),
+            bulk("k"),
+            bulk("OVERFLOW"),
+            bulk("SAT"),
+            bulk("INCRBY"),
+            bulk("u8"),
+            bulk("#2"),
+            bulk("10"),
+            bulk("GET"),
+            bulk("i64"),
+            bulk("0"),
+        ]);
+        let Command::BITFIELD { ops, .. } = Command::try_from(frame).unwrap() else {
+            panic!("expected BITFIELD");
+        };
+        assert_eq!(
+            ops,
+            vec![
+                BitfieldOp::Overflow(BitfieldOverflow::Sat),
+                BitfieldOp::IncrBy {
+                    ty: BitfieldType {
+                        signed: false,
+                        bits: 8
+                    },
+                    offset: 16,
+                    incr: 10
+                },
+                BitfieldOp::Get {
+                    ty: BitfieldType {
+                        signed: true,
+                        bits: 64
+                    },
+                    offset: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_bitfield_errors() {
+        let frame = cmd_frame(&[
+            bulk("BITFIELD"),
+            bulk("k"),
+            bulk("GET"),
+            bulk("u64"),
+            bulk("0"),
+        ]);
+        assert!(err_text(frame).starts_with("ERR Invalid bitfield type"));
+
+        let frame = cmd_frame(&[bulk("BITFIELD"), bulk("k"), bulk("OVERFLOW"), bulk("NOPE")]);
+        assert_eq!(err_text(frame), "ERR Invalid OVERFLOW type specified");
+
+        let frame = cmd_frame(&[
+            bulk("BITFIELD_RO"),
+            bulk("k"),
+            bulk("SET"),
+            bulk("u8"),
+            bulk("0"),
+            bulk("1"),
+        ]);
+        assert_eq!(
+            err_text(frame),
+            "ERR BITFIELD_RO only supports the GET subcommand"
+        );
+    }
 }
@@ -0,0 +1,2027 @@
+//! Every command the server runs, declared once: arity, flags, key
+//! positions and docs as reported by COMMAND INFO and COMMAND DOCS, the
+//! parser that builds it and the handler that runs it. Flags, ACL
+//! categories and key positions follow Redis 7. Commands can also be
+//! registered at startup with a handler of their own.
+
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use tokio_util::bytes::Bytes;
+
+use super::{Command, parse::*};
+use crate::{
+    protocol::resp::Frame,
+    service::{
+        clients::ClientHandle,
+        context::ServerContext,
+        handlers::{
+            CommandEffect,
+            multikey::{
+                bitop, del, exists, geosearchstore, lcs, mget, mset, msetnx, pfcount, pfmerge,
+            },
+            nokey::{
+                client_getname, client_id, client_info, client_kill, client_list, client_no_evict,
+                client_pause, client_reply, client_setname, client_unpause, command_count,
+                command_docs, command_getkeys, command_info, command_list, config_get,
+                config_resetstat, config_rewrite, config_set, dbsize, echo, flushdb, help, info,
+                memory_doctor, memory_stats, ping, scan, slowlog_get, slowlog_len, slowlog_reset,
+            },
+            singlekey::{
+                append, bitcount, bitfield, bitpos, decr, decrby, expire, geoadd, geodist, geohash,
+                geopos, geosearch, get, getbit, getdel, getex, getrange, getset, incr, incrby,
+                incrbyfloat, memory_usage, persist, pfadd, pfdebug, pttl, set, setbit, setnx,
+                setrange, strlen, ttl,
+            },
+        },
+    },
+    store::types::SetCondition,
+    utils::{glob::glob_match, time::get_current_millis},
+};
+
+/// Builds a command from the whole call, name included.
+pub type Parser = fn(&[Frame]) -> Result<Command, Frame>;
+
+/// Runs a command its parser built, on behalf of the calling client.
+pub type Handler =
+    for<'a> fn(&'a ServerContext, &'a ClientHandle, &'a Command) -> BoxFuture<'a, CommandEffect>;
+
+/// A [`Handler`] for the command matching `$command`, running `$run` with
+/// the server context and calling client bound to `$ctx` and `$client`.
+macro_rules! handler {
+    ($command:pat, |$ctx:pat_param, $client:pat_param| $run:expr) => {
+        Some(|ctx, client, cmd| {
+            Box::pin(async move {
+                let ($ctx, $client) = (ctx, client);
+                let $command = cmd else {
+                    unreachable!("{cmd:?} dispatched to the wrong handler")
+                };
+                $run.await
+            })
+        })
+    };
+}
+
+/// Legacy key positions: the first and last key argument and the step
+/// between keys. A negative `last` counts back from the end of the
+/// arguments; all zero means the command takes no keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeySpec {
+    pub first: i64,
+    pub last: i64,
+    pub step: i64,
+}
+
+impl KeySpec {
+    pub const NONE: Self = Self::new(0, 0, 0);
+    /// Just the first argument.
+    pub const FIRST: Self = Self::new(1, 1, 1);
+    /// Every argument.
+    pub const ALL: Self = Self::new(1, -1, 1);
+    /// Every other argument, as in `MSET key value [key value ...]`.
+    pub const PAIRS: Self = Self::new(1, -1, 2);
+
+    pub const fn new(first: i64, last: i64, step: i64) -> Self {
+        Self { first, last, step }
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.first == 0
+    }
+
+    /// Indexes of the key arguments in a call with `argc` arguments,
+    /// counting the command name.
+    pub fn positions(&self, argc: usize) -> Vec<usize> {
+        if self.is_none() {
+            return Vec::new();
+        }
+        let last = if self.last < 0 {
+            argc as i64 + self.last
+        } else {
+            self.last
+        };
+        (self.first..=last.min(argc as i64 - 1))
+            .step_by(self.step as usize)
+            .map(|i| i as usize)
+            .collect()
+    }
+}
+
+pub struct CommandSpec {
+    /// Lowercase; subcommands are named `container|sub`.
+    pub name: &'static str,
+    /// Argument count including the name; negative means at least that many.
+    pub arity: i64,
+    pub flags: &'static [&'static str],
+    /// ACL categories, without the leading `@`.
+    pub acl_categories: &'static [&'static str],
+    pub keys: KeySpec,
+    /// Runs once the arity checks out. `None` for containers that need a
+    /// subcommand, and for registered commands, which get their raw
+    /// arguments instead.
+    pub parse: Option<Parser>,
+    /// Runs what `parse` built. Set along with `parse`, except for SHUTDOWN
+    /// and MONITOR, which act on the connection and run there.
+    pub handler: Option<Handler>,
+    pub group: &'static str,
+    pub since: &'static str,
+    pub summary: &'static str,
+    pub subcommands: &'static [CommandSpec],
+}
+
+impl CommandSpec {
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    pub fn subcommand(&self, name: &[u8]) -> Option<&'static CommandSpec> {
+        self.subcommands.iter().find(|sub| {
+            sub.name
+                .split_once('|')
+                .is_some_and(|(_, sub)| sub.as_bytes().eq_ignore_ascii_case(name))
+        })
+    }
+
+    pub fn accepts(&self, argc: usize) -> bool {
+        let argc = argc as i64;
+        if self.arity < 0 {
+            argc >= -self.arity
+        } else {
+            argc == self.arity
+        }
+    }
+
+    /// The COMMAND INFO entry: name, arity, flags, first key, last key, step,
+    /// ACL categories, tips, key specs and subcommands.
+    pub fn info(&self) -> Frame {
+        Frame::Array(vec![
+            bulk(self.name),
+            Frame::Integer(self.arity),
+            Frame::Array(self.flags.iter().map(|f| status(f)).collect()),
+            Frame::Integer(self.keys.first),
+            Frame::Integer(self.keys.last),
+            Frame::Integer(self.keys.step),
+            Frame::Array(
+                self.acl_categories
+                    .iter()
+                    .map(|c| status(&format!("@{c}")))
+                    .collect(),
+            ),
+            Frame::Array(Vec::new()),
+            Frame::Array(self.key_specs()),
+            Frame::Array(self.subcommands.iter().map(Self::info).collect()),
+        ])
+    }
+
+    /// The legacy key positions as a single Redis 7 key spec.
+    fn key_specs(&self) -> Vec<Frame> {
+        if self.keys.is_none() {
+            return Vec::new();
+        }
+        let flags: &[&str] = if self.has_flag("write") {
+            &["RW", "UPDATE"]
+        } else {
+            &["RO", "ACCESS"]
+        };
+        let last = if self.keys.last < 0 {
+            self.keys.last
+        } else {
+            self.keys.last - self.keys.first
+        };
+        vec![Frame::Array(vec![
+            bulk("flags"),
+            Frame::Array(flags.iter().map(|f| status(f)).collect()),
+            bulk("begin_search"),
+            Frame::Array(vec![
+                bulk("type"),
+                bulk("index"),
+                bulk("spec"),
+                Frame::Array(vec![bulk("index"), Frame::Integer(self.keys.first)]),
+            ]),
+            bulk("find_keys"),
+            Frame::Array(vec![
+                bulk("type"),
+                bulk("range"),
+                bulk("spec"),
+                Frame::Array(vec![
+                    bulk("lastkey"),
+                    Frame::Integer(last),
+                    bulk("keystep"),
+                    Frame::Integer(self.keys.step),
+                    bulk("limit"),
+                    Frame::Integer(0),
+                ]),
+            ]),
+        ])]
+    }
+
+    /// The COMMAND DOCS entry, a map flattened for RESP2.
+    pub fn docs(&self) -> Frame {
+        let mut doc = vec![
+            bulk("summary"),
+            bulk(self.summary),
+            bulk("since"),
+            bulk(self.since),
+            bulk("group"),
+            bulk(self.group),
+        ];
+        if !self.subcommands.is_empty() {
+            doc.push(bulk("subcommands"));
+            doc.push(Frame::Array(
+                self.subcommands
+                    .iter()
+                    .flat_map(|sub| [bulk(sub.name), sub.docs()])
+                    .collect(),
+            ));
+        }
+        Frame::Array(doc)
+    }
+
+    /// This command and its subcommands, depth first.
+    pub fn with_subcommands(&'static self) -> impl Iterator<Item = &'static CommandSpec> {
+        std::iter::once(self).chain(self.subcommands)
+    }
+}
+
+/// Filters for `COMMAND LIST FILTERBY`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommandListFilter {
+    /// Commands added by a module; there are none.
+    Module(String),
+    AclCategory(String),
+    Pattern(String),
+}
+
+impl CommandListFilter {
+    pub fn matches(&self, spec: &CommandSpec) -> bool {
+        match self {
+            Self::Module(_) => false,
+            Self::AclCategory(category) => spec
+                .acl_categories
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(category)),
+            Self::Pattern(pattern) => glob_match(pattern.as_bytes(), spec.name.as_bytes()),
+        }
+    }
+}
+
+/// Looks up a top-level command, built in or registered, ignoring case.
+pub fn find(name: &[u8]) -> Option<&'static CommandSpec> {
+    let name = name.to_ascii_lowercase();
+    if let Ok(idx) = COMMANDS.binary_search_by(|spec| spec.name.as_bytes().cmp(&name)) {
+        return Some(&COMMANDS[idx]);
+    }
+    REGISTERED
+        .read()
+        .unwrap()
+        .iter()
+        .find(|registered| registered.spec.name.as_bytes() == name)
+        .map(|registered| registered.spec)
+}
+
+/// Looks up a command, or a subcommand as `container|sub`, ignoring case.
+pub fn lookup(name: &[u8]) -> Option<&'static CommandSpec> {
+    let (command, sub) = match name.iter().position(|&b| b == b'|') {
+        Some(bar) => (&name[..bar], Some(&name[bar + 1..])),
+        None => (name, None),
+    };
+    let spec = find(command)?;
+    match sub {
+        Some(sub) => spec.subcommand(sub),
+        None => Some(spec),
+    }
+}
+
+/// The spec a call runs under: the subcommand's for container commands
+/// given one.
+pub fn resolve(args: &[impl AsRef<[u8]>]) -> Option<&'static CommandSpec> {
+    let spec = find(args.first()?.as_ref())?;
+    match args.get(1) {
+        Some(sub) if !spec.subcommands.is_empty() => spec.subcommand(sub.as_ref()),
+        _ => Some(spec),
+    }
+}
+
+/// Every top-level command, the built-in ones first.
+pub fn commands() -> Vec<&'static CommandSpec> {
+    COMMANDS
+        .iter()
+        .chain(
+            REGISTERED
+                .read()
+                .unwrap()
+                .iter()
+                .map(|registered| registered.spec),
+        )
+        .collect()
+}
+
+/// Runs a command added with [`register`]. `args` is the call as received,
+/// name included, with its arity already checked.
+#[async_trait]
+pub trait CommandHandler: Send + Sync {
+    async fn call(
+        &self,
+        ctx: &ServerContext,
+        client: &ClientHandle,
+        args: &[Bytes],
+    ) -> CommandEffect;
+}
+
+impl fmt::Debug for dyn CommandHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CommandHandler")
+    }
+}
+
+struct Registered {
+    spec: &'static CommandSpec,
+    handler: Arc<dyn CommandHandler>,
+}
+
+static REGISTERED: RwLock<Vec<Registered>> = RwLock::new(Vec::new());
+
+/// Adds a command next to the built-in ones, meant to be called before the
+/// server starts. Its spec drives arity errors, pausing, OOM refusals and
+/// COMMAND output like any other; `spec.parse` and `spec.handler` must be
+/// `None`, as `handler` does its own parsing.
+pub fn register(spec: CommandSpec, handler: Arc<dyn CommandHandler>) -> Result<()> {
+    let name = spec.name;
+    if name.is_empty()
+        || !name
+            .bytes()
+            .all(|b| b.is_ascii_graphic() && !b.is_ascii_uppercase() && b != b'|')
+    {
+        bail!("invalid command name '{name}'");
+    }
+    if spec.arity == 0 {
+        bail!("command '{name}' has an arity of 0");
+    }
+    if !spec.keys.is_none() && (spec.keys.first < 1 || spec.keys.step < 1) {
+        bail!("command '{name}' has invalid key positions");
+    }
+    if spec.parse.is_some() || spec.handler.is_some() || !spec.subcommands.is_empty() {
+        bail!("command '{name}' can't have a parser, handler or subcommands");
+    }
+
+    let mut registered = REGISTERED.write().unwrap();
+    let taken = COMMANDS.iter().any(|builtin| builtin.name == name)
+        || registered.iter().any(|other| other.spec.name == name);
+    if taken {
+        bail!("command '{name}' already exists");
+    }
+    registered.push(Registered {
+        spec: Box::leak(Box::new(spec)),
+        handler,
+    });
+    Ok(())
+}
+
+/// The handler of a registered command.
+pub fn handler(name: &str) -> Option<Arc<dyn CommandHandler>> {
+    REGISTERED
+        .read()
+        .unwrap()
+        .iter()
+        .find(|registered| registered.spec.name == name)
+        .map(|registered| registered.handler.clone())
+}
+
+fn bulk(s: &str) -> Frame {
+    Frame::BulkString(Bytes::copy_from_slice(s.as_bytes()))
+}
+
+fn status(s: &str) -> Frame {
+    Frame::SimpleString(s.to_string())
+}
+
+const CONNECTION: &[&str] = &["slow", "connection"];
+const ADMIN_CONNECTION: &[&str] = &["admin", "slow", "dangerous", "connection"];
+const ADMIN: &[&str] = &["admin", "slow", "dangerous"];
+const CLIENT_FLAGS: &[&str] = &["noscript", "loading", "stale"];
+const ADMIN_FLAGS: &[&str] = &["admin", "noscript", "loading", "stale"];
+
+pub static COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "append",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        acl_categories: &["write", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_append),
+        handler: handler!(Command::APPEND { key, value }, |ctx, _| append(
+            &ctx.store,
+            key.clone(),
+            value.clone()
+        )),
+        group: "string",
+        since: "2.0.0",
+        summary: "Appends a string to the value of a key. Creates the key if it doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "bitcount",
+        arity: -2,
+        flags: &["readonly"],
+        acl_categories: &["read", "bitmap", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_bitcount),
+        handler: handler!(Command::BITCOUNT { key, range }, |ctx, _| bitcount(
+            &ctx.store,
+            key.clone(),
+            *range
+        )),
+        group: "bitmap",
+        since: "2.6.0",
+        summary: "Counts the number of set bits (population counting) in a string.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "bitfield",
+        arity: -2,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "bitmap", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_bitfield),
+        handler: handler!(Command::BITFIELD { key, ops }, |ctx, _| bitfield(
+            &ctx.store,
+            key.clone(),
+            ops
+        )),
+        group: "bitmap",
+        since: "3.2.0",
+        summary: "Performs arbitrary bitfield integer operations on strings.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "bitfield_ro",
+        arity: -2,
+        flags: &["readonly", "fast"],
+        acl_categories: &["read", "bitmap", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_bitfield_ro),
+        handler: handler!(Command::BITFIELD_RO { key, ops }, |ctx, _| bitfield(
+            &ctx.store,
+            key.clone(),
+            ops
+        )),
+        group: "bitmap",
+        since: "6.0.0",
+        summary: "Performs arbitrary read-only bitfield integer operations on strings.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "bitop",
+        arity: -4,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "bitmap", "slow"],
+        keys: KeySpec::new(2, -1, 1),
+        parse: Some(parse_bitop),
+        handler: handler!(Command::BITOP { op, dest, keys }, |ctx, _| bitop(
+            &ctx.store,
+            *op,
+            dest.clone(),
+            keys
+        )),
+        group: "bitmap",
+        since: "2.6.0",
+        summary: "Performs bitwise operations on multiple strings, and stores the result.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "bitpos",
+        arity: -3,
+        flags: &["readonly"],
+        acl_categories: &["read", "bitmap", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_bitpos),
+        handler: handler!(Command::BITPOS { key, bit, range }, |ctx, _| bitpos(
+            &ctx.store,
+            key.clone(),
+            *bit,
+            *range
+        )),
+        group: "bitmap",
+        since: "2.8.7",
+        summary: "Finds the first set (1) or clear (0) bit in a string.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "client",
+        arity: -2,
+        flags: &[],
+        acl_categories: &["slow"],
+        keys: KeySpec::NONE,
+        parse: None,
+        handler: None,
+        group: "connection",
+        since: "2.4.0",
+        summary: "A container for client connection commands.",
+        subcommands: &[
+            CommandSpec {
+                name: "client|getname",
+                arity: 2,
+                flags: CLIENT_FLAGS,
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_client_getname),
+                handler: handler!(Command::CLIENT_GETNAME, |_, client| client_getname(client)),
+                group: "connection",
+                since: "2.6.9",
+                summary: "Returns the name of the connection.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "client|help",
+                arity: 2,
+                flags: &["loading", "stale"],
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_help),
+                handler: handler!(Command::HELP { command }, |_, _| help(command)),
+                group: "connection",
+                since: "5.0.0",
+                summary: "Returns helpful text about the different subcommands.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "client|id",
+                arity: 2,
+                flags: CLIENT_FLAGS,
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_client_id),
+                handler: handler!(Command::CLIENT_ID, |_, client| client_id(client)),
+                group: "connection",
+                since: "5.0.0",
+                summary: "Returns the unique client ID of the connection.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "client|info",
+                arity: 2,
+                flags: CLIENT_FLAGS,
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_client_info),
+                handler: handler!(Command::CLIENT_INFO, |_, client| client_info(client)),
+                group: "connection",
+                since: "6.2.0",
+                summary: "Returns information about the connection.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "client|kill",
+                arity: -3,
+                flags: ADMIN_FLAGS,
+                acl_categories: ADMIN_CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_client_kill),
+                handler: handler!(Command::CLIENT_KILL { filter }, |ctx, client| client_kill(
+                    &ctx.clients,
+                    client,
+                    filter
+                )),
+                group: "connection",
+                since: "2.4.0",
+                summary: "Terminates open connections.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "client|list",
+                arity: -2,
+                flags: ADMIN_FLAGS,
+                acl_categories: ADMIN_CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_client_list),
+                handler: handler!(Command::CLIENT_LIST { kind, ids }, |ctx, _| client_list(
+                    &ctx.clients,
+                    *kind,
+                    ids
+                )),
+                group: "connection",
+                since: "2.4.0",
+                summary: "Lists open connections.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "client|no-evict",
+                arity: 3,
+                flags: ADMIN_FLAGS,
+                acl_categories: ADMIN_CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_client_no_evict),
+                handler: handler!(
+                    Command::CLIENT_NO_EVICT { on },
+                    |_, client| client_no_evict(client, *on)
+                ),
+                group: "connection",
+                since: "7.0.0",
+                summary: "Sets the client eviction mode of the connection.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "client|pause",
+                arity: -3,
+                flags: ADMIN_FLAGS,
+                acl_categories: ADMIN_CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_client_pause),
+                handler: handler!(Command::CLIENT_PAUSE { timeout_ms, mode }, |ctx, _| {
+                    client_pause(&ctx.clients, *timeout_ms, *mode)
+                }),
+                group: "connection",
+                since: "3.0.0",
+                summary: "Suspends commands processing.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "client|reply",
+                arity: 3,
+                flags: CLIENT_FLAGS,
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_client_reply),
+                handler: handler!(Command::CLIENT_REPLY { mode }, |_, client| client_reply(
+                    client, *mode
+                )),
+                group: "connection",
+                since: "3.2.0",
+                summary: "Instructs the server whether to reply to commands.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "client|setname",
+                arity: 3,
+                flags: CLIENT_FLAGS,
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_client_setname),
+                handler: handler!(
+                    Command::CLIENT_SETNAME { name },
+                    |_, client| client_setname(client, name.clone())
+                ),
+                group: "connection",
+                since: "2.6.9",
+                summary: "Sets the connection name.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "client|unpause",
+                arity: 2,
+                flags: ADMIN_FLAGS,
+                acl_categories: ADMIN_CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_client_unpause),
+                handler: handler!(Command::CLIENT_UNPAUSE, |ctx, _| client_unpause(
+                    &ctx.clients
+                )),
+                group: "connection",
+                since: "6.2.0",
+                summary: "Resumes processing commands from paused clients.",
+                subcommands: &[],
+            },
+        ],
+    },
+    CommandSpec {
+        name: "command",
+        arity: -1,
+        flags: &["loading", "stale"],
+        acl_categories: CONNECTION,
+        keys: KeySpec::NONE,
+        parse: Some(parse_command_info),
+        handler: handler!(Command::COMMAND_INFO { names }, |_, _| command_info(names)),
+        group: "server",
+        since: "2.8.13",
+        summary: "Returns detailed information about all commands.",
+        subcommands: &[
+            CommandSpec {
+                name: "command|count",
+                arity: 2,
+                flags: &["loading", "stale"],
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_command_count),
+                handler: handler!(Command::COMMAND_COUNT, |_, _| command_count()),
+                group: "server",
+                since: "2.8.13",
+                summary: "Returns a count of commands.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "command|docs",
+                arity: -2,
+                flags: &["loading", "stale"],
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_command_docs),
+                handler: handler!(Command::COMMAND_DOCS { names }, |_, _| command_docs(names)),
+                group: "server",
+                since: "7.0.0",
+                summary: "Returns documentary information about one, multiple or all commands.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "command|getkeys",
+                arity: -3,
+                flags: &["loading", "stale"],
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_command_getkeys),
+                handler: handler!(Command::COMMAND_GETKEYS { args }, |_, _| command_getkeys(
+                    args
+                )),
+                group: "server",
+                since: "2.8.13",
+                summary: "Extracts the key names from an arbitrary command.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "command|help",
+                arity: 2,
+                flags: &["loading", "stale"],
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_help),
+                handler: handler!(Command::HELP { command }, |_, _| help(command)),
+                group: "server",
+                since: "5.0.0",
+                summary: "Returns helpful text about the different subcommands.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "command|info",
+                arity: -2,
+                flags: &["loading", "stale"],
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_command_info),
+                handler: handler!(Command::COMMAND_INFO { names }, |_, _| command_info(names)),
+                group: "server",
+                since: "2.8.13",
+                summary: "Returns information about one, multiple or all commands.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "command|list",
+                arity: -2,
+                flags: &["loading", "stale"],
+                acl_categories: CONNECTION,
+                keys: KeySpec::NONE,
+                parse: Some(parse_command_list),
+                handler: handler!(Command::COMMAND_LIST { filter }, |_, _| command_list(
+                    filter.as_ref()
+                )),
+                group: "server",
+                since: "7.0.0",
+                summary: "Returns a list of command names.",
+                subcommands: &[],
+            },
+        ],
+    },
+    CommandSpec {
+        name: "config",
+        arity: -2,
+        flags: &[],
+        acl_categories: &["slow"],
+        keys: KeySpec::NONE,
+        parse: None,
+        handler: None,
+        group: "server",
+        since: "2.0.0",
+        summary: "A container for server configuration commands.",
+        subcommands: &[
+            CommandSpec {
+                name: "config|get",
+                arity: -3,
+                flags: ADMIN_FLAGS,
+                acl_categories: ADMIN,
+                keys: KeySpec::NONE,
+                parse: Some(parse_config_get),
+                handler: handler!(Command::CONFIG_GET { patterns }, |ctx, _| config_get(
+                    &ctx.config,
+                    patterns
+                )),
+                group: "server",
+                since: "2.0.0",
+                summary: "Returns the effective values of configuration parameters.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "config|help",
+                arity: 2,
+                flags: &["loading", "stale"],
+                acl_categories: &["slow"],
+                keys: KeySpec::NONE,
+                parse: Some(parse_help),
+                handler: handler!(Command::HELP { command }, |_, _| help(command)),
+                group: "server",
+                since: "5.0.0",
+                summary: "Returns helpful text about the different subcommands.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "config|resetstat",
+                arity: 2,
+                flags: ADMIN_FLAGS,
+                acl_categories: ADMIN,
+                keys: KeySpec::NONE,
+                parse: Some(parse_config_resetstat),
+                handler: handler!(Command::CONFIG_RESETSTAT, |ctx, _| config_resetstat(
+                    &ctx.store,
+                    &ctx.clients,
+                    &ctx.command_stats
+                )),
+                group: "server",
+                since: "2.0.0",
+                summary: "Resets the server's statistics.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "config|rewrite",
+                arity: 2,
+                flags: ADMIN_FLAGS,
+                acl_categories: ADMIN,
+                keys: KeySpec::NONE,
+                parse: Some(parse_config_rewrite),
+                handler: handler!(Command::CONFIG_REWRITE, |ctx, _| config_rewrite(
+                    &ctx.config
+                )),
+                group: "server",
+                since: "2.8.0",
+                summary: "Persists the effective configuration to file.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "config|set",
+                arity: -4,
+                flags: ADMIN_FLAGS,
+                acl_categories: ADMIN,
+                keys: KeySpec::NONE,
+                parse: Some(parse_config_set),
+                handler: handler!(Command::CONFIG_SET { pairs }, |ctx, _| config_set(
+                    &ctx.store,
+                    &ctx.config,
+                    &ctx.aof,
+                    &ctx.slowlog,
+                    pairs
+                )),
+                group: "server",
+                since: "2.0.0",
+                summary: "Sets configuration parameters in-flight.",
+                subcommands: &[],
+            },
+        ],
+    },
+    CommandSpec {
+        name: "dbsize",
+        arity: 1,
+        flags: &["readonly", "fast"],
+        acl_categories: &["keyspace", "read", "fast"],
+        keys: KeySpec::NONE,
+        parse: Some(parse_dbsize),
+        handler: handler!(Command::DBSIZE, |ctx, _| dbsize(&ctx.store)),
+        group: "server",
+        since: "1.0.0",
+        summary: "Returns the number of keys in the database.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "decr",
+        arity: 2,
+        flags: &["write", "denyoom", "fast"],
+        acl_categories: &["write", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_decr),
+        handler: handler!(Command::DECR { key }, |ctx, _| decr(
+            &ctx.store,
+            key.clone()
+        )),
+        group: "string",
+        since: "1.0.0",
+        summary: "Decrements the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "decrby",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        acl_categories: &["write", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_decrby),
+        handler: handler!(Command::DECRBY { key, delta }, |ctx, _| decrby(
+            &ctx.store,
+            key.clone(),
+            *delta
+        )),
+        group: "string",
+        since: "1.0.0",
+        summary: "Decrements a number from the integer value of a key. Uses 0 as initial value if the key doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "del",
+        arity: -2,
+        flags: &["write"],
+        acl_categories: &["keyspace", "write", "slow"],
+        keys: KeySpec::ALL,
+        parse: Some(parse_del),
+        handler: handler!(Command::DEL { keys }, |ctx, _| del(
+            &ctx.store,
+            keys.clone()
+        )),
+        group: "generic",
+        since: "1.0.0",
+        summary: "Deletes one or more keys.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "echo",
+        arity: 2,
+        flags: &["loading", "stale", "fast"],
+        acl_categories: &["fast", "connection"],
+        keys: KeySpec::NONE,
+        parse: Some(parse_echo),
+        handler: handler!(Command::ECHO { msg }, |_, _| echo(msg.clone())),
+        group: "connection",
+        since: "1.0.0",
+        summary: "Returns the given string.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "exists",
+        arity: -2,
+        flags: &["readonly", "fast"],
+        acl_categories: &["keyspace", "read", "fast"],
+        keys: KeySpec::ALL,
+        parse: Some(parse_exists),
+        handler: handler!(Command::EXISTS { keys }, |ctx, _| exists(
+            &ctx.store,
+            keys.clone()
+        )),
+        group: "generic",
+        since: "1.0.0",
+        summary: "Determines whether one or more keys exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "expire",
+        arity: -3,
+        flags: &["write", "fast"],
+        acl_categories: &["keyspace", "write", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_expire),
+        handler: handler!(Command::EXPIRE { key, ttl }, |ctx, _| expire(
+            &ctx.store,
+            key.clone(),
+            *ttl,
+            get_current_millis()
+        )),
+        group: "generic",
+        since: "1.0.0",
+        summary: "Sets the expiration time of a key in seconds.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "flushdb",
+        arity: -1,
+        flags: &["write"],
+        acl_categories: &["keyspace", "write", "slow", "dangerous"],
+        keys: KeySpec::NONE,
+        parse: Some(parse_flushdb),
+        handler: handler!(Command::FLUSHDB, |ctx, _| flushdb(&ctx.store)),
+        group: "server",
+        since: "1.0.0",
+        summary: "Removes all keys from the current database.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "geoadd",
+        arity: -5,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "geo", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_geoadd),
+        handler: handler!(
+            Command::GEOADD {
+                key,
+                cond,
+                ch,
+                items
+            },
+            |ctx, _| geoadd(&ctx.store, key.clone(), *cond, *ch, items)
+        ),
+        group: "geo",
+        since: "3.2.0",
+        summary: "Adds one or more members to a geospatial index. The key is created if it doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "geodist",
+        arity: -4,
+        flags: &["readonly"],
+        acl_categories: &["read", "geo", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_geodist),
+        handler: handler!(
+            Command::GEODIST {
+                key,
+                member1,
+                member2,
+                unit
+            },
+            |ctx, _| geodist(&ctx.store, key.clone(), member1, member2, *unit)
+        ),
+        group: "geo",
+        since: "3.2.0",
+        summary: "Returns the distance between two members of a geospatial index.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "geohash",
+        arity: -2,
+        flags: &["readonly"],
+        acl_categories: &["read", "geo", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_geohash),
+        handler: handler!(Command::GEOHASH { key, members }, |ctx, _| geohash(
+            &ctx.store,
+            key.clone(),
+            members
+        )),
+        group: "geo",
+        since: "3.2.0",
+        summary: "Returns members from a geospatial index as geohash strings.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "geopos",
+        arity: -2,
+        flags: &["readonly"],
+        acl_categories: &["read", "geo", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_geopos),
+        handler: handler!(Command::GEOPOS { key, members }, |ctx, _| geopos(
+            &ctx.store,
+            key.clone(),
+            members
+        )),
+        group: "geo",
+        since: "3.2.0",
+        summary: "Returns the longitude and latitude of members from a geospatial index.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "geosearch",
+        arity: -7,
+        flags: &["readonly"],
+        acl_categories: &["read", "geo", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_geosearch),
+        handler: handler!(Command::GEOSEARCH { key, query }, |ctx, _| geosearch(
+            &ctx.store,
+            key.clone(),
+            query
+        )),
+        group: "geo",
+        since: "6.2.0",
+        summary: "Queries a geospatial index for members inside an area of a box or a circle.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "geosearchstore",
+        arity: -8,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "geo", "slow"],
+        keys: KeySpec::new(1, 2, 1),
+        parse: Some(parse_geosearchstore),
+        handler: handler!(
+            Command::GEOSEARCHSTORE {
+                dest,
+                key,
+                query,
+                store_dist
+            },
+            |ctx, _| geosearchstore(&ctx.store, dest.clone(), key, query, *store_dist)
+        ),
+        group: "geo",
+        since: "6.2.0",
+        summary: "Queries a geospatial index for members inside an area of a box or a circle, optionally stores the result.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "get",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        acl_categories: &["read", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_get),
+        handler: handler!(Command::GET { key }, |ctx, _| get(&ctx.store, key.clone())),
+        group: "string",
+        since: "1.0.0",
+        summary: "Returns the string value of a key.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "getbit",
+        arity: 3,
+        flags: &["readonly", "fast"],
+        acl_categories: &["read", "bitmap", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_getbit),
+        handler: handler!(Command::GETBIT { key, offset }, |ctx, _| getbit(
+            &ctx.store,
+            key.clone(),
+            *offset
+        )),
+        group: "bitmap",
+        since: "2.2.0",
+        summary: "Returns a bit value by offset.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "getdel",
+        arity: 2,
+        flags: &["write", "fast"],
+        acl_categories: &["write", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_getdel),
+        handler: handler!(Command::GETDEL { key }, |ctx, _| getdel(
+            &ctx.store,
+            key.clone()
+        )),
+        group: "string",
+        since: "6.2.0",
+        summary: "Returns the string value of a key after deleting the key.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "getex",
+        arity: -2,
+        flags: &["write", "fast"],
+        acl_categories: &["write", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_getex),
+        handler: handler!(Command::GETEX { key, exp }, |ctx, _| getex(
+            &ctx.store,
+            key.clone(),
+            exp.clone()
+        )),
+        group: "string",
+        since: "6.2.0",
+        summary: "Returns the string value of a key after setting its expiration time.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "getrange",
+        arity: 4,
+        flags: &["readonly"],
+        acl_categories: &["read", "string", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_getrange),
+        handler: handler!(Command::GETRANGE { key, start, end }, |ctx, _| getrange(
+            &ctx.store,
+            key.clone(),
+            *start,
+            *end
+        )),
+        group: "string",
+        since: "2.4.0",
+        summary: "Returns a substring of the string stored at a key.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "getset",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        acl_categories: &["write", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_getset),
+        handler: handler!(Command::GETSET { key, entry }, |ctx, _| getset(
+            &ctx.store,
+            key.clone(),
+            entry.clone()
+        )),
+        group: "string",
+        since: "1.0.0",
+        summary: "Returns the previous string value of a key after setting it to a new value.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "incr",
+        arity: 2,
+        flags: &["write", "denyoom", "fast"],
+        acl_categories: &["write", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_incr),
+        handler: handler!(Command::INCR { key }, |ctx, _| incr(
+            &ctx.store,
+            key.clone()
+        )),
+        group: "string",
+        since: "1.0.0",
+        summary: "Increments the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "incrby",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        acl_categories: &["write", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_incrby),
+        handler: handler!(Command::INCRBY { key, delta }, |ctx, _| incrby(
+            &ctx.store,
+            key.clone(),
+            *delta
+        )),
+        group: "string",
+        since: "1.0.0",
+        summary: "Increments the integer value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "incrbyfloat",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        acl_categories: &["write", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_incrbyfloat),
+        handler: handler!(Command::INCRBYFLOAT { key, incr }, |ctx, _| incrbyfloat(
+            &ctx.store,
+            key.clone(),
+            *incr
+        )),
+        group: "string",
+        since: "2.6.0",
+        summary: "Increment the floating point value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "info",
+        arity: -1,
+        flags: &["loading", "stale"],
+        acl_categories: &["slow", "dangerous"],
+        keys: KeySpec::NONE,
+        parse: Some(parse_info),
+        handler: handler!(Command::INFO { sections }, |ctx, _| info(
+            &ctx.store,
+            &ctx.config,
+            &ctx.aof,
+            &ctx.clients,
+            &ctx.command_stats,
+            sections
+        )),
+        group: "server",
+        since: "1.0.0",
+        summary: "Returns information and statistics about the server.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "lcs",
+        arity: -3,
+        flags: &["readonly"],
+        acl_categories: &["read", "string", "slow"],
+        keys: KeySpec::new(1, 2, 1),
+        parse: Some(parse_lcs),
+        handler: handler!(
+            Command::LCS {
+                key1,
+                key2,
+                len,
+                idx,
+                min_match_len,
+                with_match_len
+            },
+            |ctx, _| lcs(
+                &ctx.store,
+                key1,
+                key2,
+                *len,
+                *idx,
+                *min_match_len,
+                *with_match_len
+            )
+        ),
+        group: "string",
+        since: "7.0.0",
+        summary: "Finds the longest common substring.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "memory",
+        arity: -2,
+        flags: &[],
+        acl_categories: &["slow"],
+        keys: KeySpec::NONE,
+        parse: None,
+        handler: None,
+        group: "server",
+        since: "4.0.0",
+        summary: "A container for memory diagnostics commands.",
+        subcommands: &[
+            CommandSpec {
+                name: "memory|doctor",
+                arity: 2,
+                flags: &[],
+                acl_categories: &["slow"],
+                keys: KeySpec::NONE,
+                parse: Some(parse_memory_doctor),
+                handler: handler!(Command::MEMORY_DOCTOR, |ctx, _| memory_doctor(
+                    &ctx.store,
+                    &ctx.config
+                )),
+                group: "server",
+                since: "4.0.0",
+                summary: "Outputs a memory problems report.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "memory|help",
+                arity: 2,
+                flags: &["loading", "stale"],
+                acl_categories: &["slow"],
+                keys: KeySpec::NONE,
+                parse: Some(parse_help),
+                handler: handler!(Command::HELP { command }, |_, _| help(command)),
+                group: "server",
+                since: "4.0.0",
+                summary: "Returns helpful text about the different subcommands.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "memory|stats",
+                arity: 2,
+                flags: &[],
+                acl_categories: &["slow"],
+                keys: KeySpec::NONE,
+                parse: Some(parse_memory_stats),
+                handler: handler!(Command::MEMORY_STATS, |ctx, _| memory_stats(&ctx.store)),
+                group: "server",
+                since: "4.0.0",
+                summary: "Returns details about memory usage.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "memory|usage",
+                arity: -3,
+                flags: &["readonly"],
+                acl_categories: &["read", "slow"],
+                keys: KeySpec::new(2, 2, 1),
+                parse: Some(parse_memory_usage),
+                handler: handler!(Command::MEMORY_USAGE { key, samples: _ }, |ctx, _| {
+                    memory_usage(&ctx.store, key.clone())
+                }),
+                group: "server",
+                since: "4.0.0",
+                summary: "Estimates the memory usage of a key.",
+                subcommands: &[],
+            },
+        ],
+    },
+    CommandSpec {
+        name: "mget",
+        arity: -2,
+        flags: &["readonly", "fast"],
+        acl_categories: &["read", "string", "fast"],
+        keys: KeySpec::ALL,
+        parse: Some(parse_mget),
+        handler: handler!(Command::MGET { keys }, |ctx, _| mget(
+            &ctx.store,
+            keys.clone()
+        )),
+        group: "string",
+        since: "1.0.0",
+        summary: "Atomically returns the string values of one or more keys.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "monitor",
+        arity: 1,
+        flags: ADMIN_FLAGS,
+        acl_categories: ADMIN,
+        keys: KeySpec::NONE,
+        parse: Some(parse_monitor),
+        handler: None,
+        group: "server",
+        since: "1.0.0",
+        summary: "Listens for all requests received by the server in real-time.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "mset",
+        arity: -3,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "string", "slow"],
+        keys: KeySpec::PAIRS,
+        parse: Some(parse_mset),
+        handler: handler!(Command::MSET { items }, |ctx, _| mset(
+            &ctx.store,
+            items.clone()
+        )),
+        group: "string",
+        since: "1.0.1",
+        summary: "Atomically creates or modifies the string values of one or more keys.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "msetnx",
+        arity: -3,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "string", "slow"],
+        keys: KeySpec::PAIRS,
+        parse: Some(parse_msetnx),
+        handler: handler!(Command::MSETNX { items }, |ctx, _| msetnx(
+            &ctx.store,
+            items.clone()
+        )),
+        group: "string",
+        since: "1.0.1",
+        summary: "Atomically modifies the string values of one or more keys only when all keys don't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "persist",
+        arity: 2,
+        flags: &["write", "fast"],
+        acl_categories: &["keyspace", "write", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_persist),
+        handler: handler!(Command::PERSIST { key }, |ctx, _| persist(
+            &ctx.store,
+            key.clone()
+        )),
+        group: "generic",
+        since: "2.2.0",
+        summary: "Removes the expiration time of a key.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "pexpire",
+        arity: -3,
+        flags: &["write", "fast"],
+        acl_categories: &["keyspace", "write", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_pexpire),
+        handler: handler!(Command::PEXPIRE { key, ttl }, |ctx, _| expire(
+            &ctx.store,
+            key.clone(),
+            *ttl,
+            get_current_millis()
+        )),
+        group: "generic",
+        since: "2.6.0",
+        summary: "Sets the expiration time of a key in milliseconds.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "pfadd",
+        arity: -2,
+        flags: &["write", "denyoom", "fast"],
+        acl_categories: &["write", "hyperloglog", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_pfadd),
+        handler: handler!(Command::PFADD { key, elements }, |ctx, _| pfadd(
+            &ctx.store,
+            key.clone(),
+            elements
+        )),
+        group: "hyperloglog",
+        since: "2.8.9",
+        summary: "Adds elements to a HyperLogLog key. Creates the key if it doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "pfcount",
+        arity: -2,
+        flags: &["readonly", "may_replicate"],
+        acl_categories: &["read", "hyperloglog", "slow"],
+        keys: KeySpec::ALL,
+        parse: Some(parse_pfcount),
+        handler: handler!(Command::PFCOUNT { keys }, |ctx, _| pfcount(
+            &ctx.store, keys
+        )),
+        group: "hyperloglog",
+        since: "2.8.9",
+        summary: "Returns the approximated cardinality of the set(s) observed by the HyperLogLog key(s).",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "pfdebug",
+        arity: 3,
+        flags: &["write", "denyoom", "admin"],
+        acl_categories: &["write", "hyperloglog", "admin", "slow", "dangerous"],
+        keys: KeySpec::new(2, 2, 1),
+        parse: Some(parse_pfdebug),
+        handler: handler!(Command::PFDEBUG { sub, key }, |ctx, _| pfdebug(
+            &ctx.store,
+            *sub,
+            key.clone()
+        )),
+        group: "hyperloglog",
+        since: "2.8.9",
+        summary: "Internal commands for debugging HyperLogLog values.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "pfmerge",
+        arity: -2,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "hyperloglog", "slow"],
+        keys: KeySpec::ALL,
+        parse: Some(parse_pfmerge),
+        handler: handler!(Command::PFMERGE { dest, keys }, |ctx, _| pfmerge(
+            &ctx.store,
+            dest.clone(),
+            keys
+        )),
+        group: "hyperloglog",
+        since: "2.8.9",
+        summary: "Merges one or more HyperLogLog values into a single key.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "ping",
+        arity: -1,
+        flags: &["fast"],
+        acl_categories: &["fast", "connection"],
+        keys: KeySpec::NONE,
+        parse: Some(parse_ping),
+        handler: handler!(Command::PING, |_, _| ping()),
+        group: "connection",
+        since: "1.0.0",
+        summary: "Returns the server's liveliness response.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "psetex",
+        arity: 4,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "string", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_psetex),
+        handler: handler!(Command::PSETEX { key, entry }, |ctx, _| set(
+            &ctx.store,
+            key.clone(),
+            entry.clone(),
+            SetCondition::Always,
+            false
+        )),
+        group: "string",
+        since: "2.6.0",
+        summary: "Sets both string value and expiration time in milliseconds of a key. The key is created if it doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "pttl",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        acl_categories: &["keyspace", "read", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_pttl),
+        handler: handler!(Command::PTTL { key }, |ctx, _| pttl(
+            &ctx.store,
+            key.clone(),
+            get_current_millis()
+        )),
+        group: "generic",
+        since: "2.6.0",
+        summary: "Returns the expiration time in milliseconds of a key.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "scan",
+        arity: -2,
+        flags: &["readonly"],
+        acl_categories: &["keyspace", "read", "slow"],
+        keys: KeySpec::NONE,
+        parse: Some(parse_scan),
+        handler: handler!(
+            Command::SCAN {
+                cursor,
+                pattern,
+                count
+            },
+            |ctx, _| scan(&ctx.store, *cursor, pattern.as_ref(), *count)
+        ),
+        group: "generic",
+        since: "2.8.0",
+        summary: "Iterates over the key names in the database.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "set",
+        arity: -3,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "string", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_set),
+        handler: handler!(
+            Command::SET {
+                key,
+                entry,
+                cond,
+                get
+            },
+            |ctx, _| set(&ctx.store, key.clone(), entry.clone(), *cond, *get)
+        ),
+        group: "string",
+        since: "1.0.0",
+        summary: "Sets the string value of a key, ignoring its type. The key is created if it doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "setbit",
+        arity: 4,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "bitmap", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_setbit),
+        handler: handler!(Command::SETBIT { key, offset, bit }, |ctx, _| setbit(
+            &ctx.store,
+            key.clone(),
+            *offset,
+            *bit
+        )),
+        group: "bitmap",
+        since: "2.2.0",
+        summary: "Sets or clears the bit at offset of the string value. Creates the key if it doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "setex",
+        arity: 4,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "string", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_setex),
+        handler: handler!(Command::SETEX { key, entry }, |ctx, _| set(
+            &ctx.store,
+            key.clone(),
+            entry.clone(),
+            SetCondition::Always,
+            false
+        )),
+        group: "string",
+        since: "2.0.0",
+        summary: "Sets the string value and expiration time of a key. Creates the key if it doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "setnx",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        acl_categories: &["write", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_setnx),
+        handler: handler!(Command::SETNX { key, entry }, |ctx, _| setnx(
+            &ctx.store,
+            key.clone(),
+            entry.clone()
+        )),
+        group: "string",
+        since: "1.0.0",
+        summary: "Set the string value of a key only when the key doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "setrange",
+        arity: 4,
+        flags: &["write", "denyoom"],
+        acl_categories: &["write", "string", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_setrange),
+        handler: handler!(Command::SETRANGE { key, offset, value }, |ctx, _| setrange(
+            &ctx.store,
+            key.clone(),
+            *offset,
+            value.clone()
+        )),
+        group: "string",
+        since: "2.2.0",
+        summary: "Overwrites a part of a string value with another by an offset. Creates the key if it doesn't exist.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "shutdown",
+        arity: -1,
+        flags: &[
+            "admin",
+            "noscript",
+            "loading",
+            "stale",
+            "no_multi",
+            "allow_busy",
+        ],
+        acl_categories: ADMIN,
+        keys: KeySpec::NONE,
+        parse: Some(parse_shutdown),
+        handler: None,
+        group: "server",
+        since: "1.0.0",
+        summary: "Synchronously saves the database(s) to disk and shuts down the Redis server.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "slowlog",
+        arity: -2,
+        flags: &[],
+        acl_categories: &["slow"],
+        keys: KeySpec::NONE,
+        parse: None,
+        handler: None,
+        group: "server",
+        since: "2.2.12",
+        summary: "A container for slow log commands.",
+        subcommands: &[
+            CommandSpec {
+                name: "slowlog|get",
+                arity: -2,
+                flags: &["admin", "loading", "stale"],
+                acl_categories: ADMIN,
+                keys: KeySpec::NONE,
+                parse: Some(parse_slowlog_get),
+                handler: handler!(Command::SLOWLOG_GET { count }, |ctx, _| slowlog_get(
+                    &ctx.slowlog,
+                    *count
+                )),
+                group: "server",
+                since: "2.2.12",
+                summary: "Returns the slow log's entries.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "slowlog|help",
+                arity: 2,
+                flags: &["loading", "stale"],
+                acl_categories: &["slow"],
+                keys: KeySpec::NONE,
+                parse: Some(parse_help),
+                handler: handler!(Command::HELP { command }, |_, _| help(command)),
+                group: "server",
+                since: "6.2.0",
+                summary: "Returns helpful text about the different subcommands.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "slowlog|len",
+                arity: 2,
+                flags: &["admin", "loading", "stale"],
+                acl_categories: ADMIN,
+                keys: KeySpec::NONE,
+                parse: Some(parse_slowlog_len),
+                handler: handler!(Command::SLOWLOG_LEN, |ctx, _| slowlog_len(&ctx.slowlog)),
+                group: "server",
+                since: "2.2.12",
+                summary: "Returns the number of entries in the slow log.",
+                subcommands: &[],
+            },
+            CommandSpec {
+                name: "slowlog|reset",
+                arity: 2,
+                flags: &["admin", "loading", "stale"],
+                acl_categories: ADMIN,
+                keys: KeySpec::NONE,
+                parse: Some(parse_slowlog_reset),
+                handler: handler!(Command::SLOWLOG_RESET, |ctx, _| slowlog_reset(&ctx.slowlog)),
+                group: "server",
+                since: "2.2.12",
+                summary: "Clears all entries from the slow log.",
+                subcommands: &[],
+            },
+        ],
+    },
+    CommandSpec {
+        name: "strlen",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        acl_categories: &["read", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_strlen),
+        handler: handler!(Command::STRLEN { key }, |ctx, _| strlen(
+            &ctx.store,
+            key.clone()
+        )),
+        group: "string",
+        since: "2.2.0",
+        summary: "Returns the length of a string value.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "substr",
+        arity: 4,
+        flags: &["readonly"],
+        acl_categories: &["read", "string", "slow"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_getrange),
+        handler: handler!(Command::GETRANGE { key, start, end }, |ctx, _| getrange(
+            &ctx.store,
+            key.clone(),
+            *start,
+            *end
+        )),
+        group: "string",
+        since: "1.0.0",
+        summary: "Returns a substring from a string value.",
+        subcommands: &[],
+    },
+    CommandSpec {
+        name: "ttl",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        acl_categories: &["keyspace", "read", "fast"],
+        keys: KeySpec::FIRST,
+        parse: Some(parse_ttl),
+        handler: handler!(Command::TTL { key }, |ctx, _| ttl(
+            &ctx.store,
+            key.clone(),
+            get_current_millis()
+        )),
+        group: "generic",
+        since: "1.0.0",
+        summary: "Returns the expiration time in seconds of a key.",
+        subcommands: &[],
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::command::Command;
+
+    /// One valid call of every command and subcommand, and its keys.
+    const CALLS: &[(&str, &[&str])] = &[
+        ("APPEND k v", &["k"]),
+        ("BITCOUNT k", &["k"]),
+        ("BITFIELD k GET u8 0", &["k"]),
+        ("BITFIELD_RO k GET u8 0", &["k"]),
+        ("BITOP AND d a b", &["d", "a", "b"]),
+        ("BITPOS k 1", &["k"]),
+        ("CLIENT GETNAME", &[]),
+        ("CLIENT HELP", &[]),
+        ("CLIENT ID", &[]),
+        ("CLIENT INFO", &[]),
+        ("CLIENT KILL ID 1", &[]),
+        ("CLIENT LIST", &[]),
+        ("CLIENT NO-EVICT on", &[]),
+        ("CLIENT PAUSE 10", &[]),
+        ("CLIENT REPLY on", &[]),
+        ("CLIENT SETNAME n", &[]),
+        ("CLIENT UNPAUSE", &[]),
+        ("COMMAND COUNT", &[]),
+        ("COMMAND DOCS get", &[]),
+        ("COMMAND GETKEYS GET k", &[]),
+        ("COMMAND HELP", &[]),
+        ("COMMAND INFO", &[]),
+        ("COMMAND LIST", &[]),
+        ("CONFIG GET *", &[]),
+        ("CONFIG HELP", &[]),
+        ("CONFIG RESETSTAT", &[]),
+        ("CONFIG REWRITE", &[]),
+        ("CONFIG SET maxmemory 0", &[]),
+        ("DBSIZE", &[]),
+        ("DECR k", &["k"]),
+        ("DECRBY k 1", &["k"]),
+        ("DEL a b", &["a", "b"]),
+        ("ECHO x", &[]),
+        ("EXISTS a b", &["a", "b"]),
+        ("EXPIRE k 10", &["k"]),
+        ("FLUSHDB", &[]),
+        ("GEOADD k 13.36 38.11 p", &["k"]),
+        ("GEODIST k a b", &["k"]),
+        ("GEOHASH k p", &["k"]),
+        ("GEOPOS k p", &["k"]),
+        ("GEOSEARCH k FROMLONLAT 15 37 BYRADIUS 200 km", &["k"]),
+        (
+            "GEOSEARCHSTORE d k FROMLONLAT 15 37 BYRADIUS 200 km",
+            &["d", "k"],
+        ),
+        ("GET k", &["k"]),
+        ("GETBIT k 1", &["k"]),
+        ("GETDEL k", &["k"]),
+        ("GETEX k", &["k"]),
+        ("GETRANGE k 0 1", &["k"]),
+        ("GETSET k v", &["k"]),
+        ("INCR k", &["k"]),
+        ("INCRBY k 1", &["k"]),
+        ("INCRBYFLOAT k 1.5", &["k"]),
+        ("INFO", &[]),
+        ("LCS a b", &["a", "b"]),
+        ("MEMORY DOCTOR", &[]),
+        ("MEMORY HELP", &[]),
+        ("MEMORY STATS", &[]),
+        ("MEMORY USAGE k", &["k"]),
+        ("MGET a b", &["a", "b"]),
+        ("MONITOR", &[]),
+        ("MSET a 1 b 2", &["a", "b"]),
+        ("MSETNX a 1 b 2", &["a", "b"]),
+        ("PERSIST k", &["k"]),
+        ("PEXPIRE k 10", &["k"]),
+        ("PFADD k a", &["k"]),
+        ("PFCOUNT a b", &["a", "b"]),
+        ("PFDEBUG GETREG k", &["k"]),
+        ("PFMERGE d a", &["d", "a"]),
+        ("PING", &[]),
+        ("PSETEX k 100 v", &["k"]),
+        ("PTTL k", &["k"]),
+        ("SCAN 0", &[]),
+        ("SET k v", &["k"]),
+        ("SETBIT k 1 1", &["k"]),
+        ("SETEX k 10 v", &["k"]),
+        ("SETNX k v", &["k"]),
+        ("SETRANGE k 0 v", &["k"]),
+        ("SHUTDOWN", &[]),
+        ("SLOWLOG GET", &[]),
+        ("SLOWLOG HELP", &[]),
+        ("SLOWLOG LEN", &[]),
+        ("SLOWLOG RESET", &[]),
+        ("STRLEN k", &["k"]),
+        ("SUBSTR k 0 1", &["k"]),
+        ("TTL k", &["k"]),
+    ];
+
+    #[test]
+    fn every_command_parses_and_finds_its_keys() {
+        let mut covered = Vec::new();
+        for (call, keys) in CALLS {
+            let args: Vec<Bytes> = call
+                .split(' ')
+                .map(|arg| Bytes::copy_from_slice(arg.as_bytes()))
+                .collect();
+            let spec = resolve(&args).unwrap_or_else(|| panic!("{call}: not in the table"));
+            assert!(spec.accepts(args.len()), "{call}: arity {}", spec.arity);
+            let frame = Frame::Array(args.iter().cloned().map(Frame::BulkString).collect());
+            if let Err(err) = Command::try_from(frame) {
+                panic!("{call}: {err:?}");
+            }
+
+            let found: Vec<_> = spec
+                .keys
+                .positions(args.len())
+                .into_iter()
+                .map(|i| args[i].clone())
+                .collect();
+            assert_eq!(found, *keys, "{call}: keys");
+            covered.push(spec.name);
+        }
+
+        for spec in COMMANDS.iter().flat_map(CommandSpec::with_subcommands) {
+            if spec.subcommands.is_empty() {
+                assert!(
+                    covered.contains(&spec.name),
+                    "{} has no sample call",
+                    spec.name
+                );
+                assert!(spec.parse.is_some(), "{} has no parser", spec.name);
+                assert_eq!(
+                    spec.handler.is_some(),
+                    !matches!(spec.name, "monitor" | "shutdown"),
+                    "{} handler",
+                    spec.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn names_are_sorted_lowercase_and_resolvable() {
+        for pair in COMMANDS.windows(2) {
+            assert!(pair[0].name < pair[1].name, "{} out of order", pair[1].name);
+        }
+        for spec in COMMANDS.iter().flat_map(CommandSpec::with_subcommands) {
+            assert_eq!(spec.name, spec.name.to_ascii_lowercase());
+            assert!(std::ptr::eq(
+                lookup(spec.name.to_ascii_uppercase().as_bytes()).unwrap(),
+                spec
+            ));
+        }
+        assert!(lookup(b"config|nope").is_none());
+        assert_eq!(resolve(&["CONFIG", "get", "*"]).unwrap().name, "config|get");
+        assert_eq!(resolve(&["get", "k"]).unwrap().name, "get");
+        assert_eq!(resolve(&["config"]).unwrap().name, "config");
+        assert!(resolve(&["config", "nope"]).is_none());
+        assert!(resolve(&["config|get", "*"]).is_none());
+    }
+
+    #[test]
+    fn key_positions_follow_the_spec() {
+        assert_eq!(KeySpec::FIRST.positions(3), vec![1]);
+        assert_eq!(KeySpec::ALL.positions(4), vec![1, 2, 3]);
+        assert_eq!(KeySpec::PAIRS.positions(5), vec![1, 3]);
+        assert_eq!(KeySpec::new(2, -1, 1).positions(5), vec![2, 3, 4]);
+        assert!(KeySpec::NONE.positions(3).is_empty());
+    }
+
+    #[test]
+    fn arity_is_exact_or_a_minimum() {
+        let get = lookup(b"get").unwrap();
+        assert!(get.accepts(2) && !get.accepts(3));
+        let del = lookup(b"del").unwrap();
+        assert!(!del.accepts(1) && del.accepts(2) && del.accepts(9));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::protocol::resp::Frame;
+use crate::{protocol::resp::Frame, telemetry};
 use tokio_util::{
     bytes::{Buf, Bytes, BytesMut},
     codec::{Decoder, Encoder},
@@ -20,12 +20,18 @@ impl Decoder for RespCodec {
     type Error = anyhow::Error;
 
     fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let span = telemetry::span("resp.decode");
         match Frame::parse(buf) {
             Ok(Some((frame, consumed))) => {
+                drop(span.with_attr("bytes", consumed));
                 buf.advance(consumed);
                 Ok(Some(frame))
             }
-            Ok(None) => Ok(None),
+            Ok(None) => {
+                // Not a whole frame yet; the call that completes it is traced.
+                span.discard();
+                Ok(None)
+            }
             Err(e) => Err(e),
         }
     }
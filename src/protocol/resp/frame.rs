@@ -44,6 +44,20 @@ impl Frame {
         }
     }
 
+    /// The bulk-string arguments of a command frame, for logging.
+    pub fn args(&self) -> Vec<Bytes> {
+        match self {
+            Frame::Array(items) => items
+                .iter()
+                .filter_map(|item| match item {
+                    Frame::BulkString(b) => Some(b.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
     fn parse_raw_buffer<'a>(buf: &'a [u8], prefix: &'a str) -> nom::IResult<&'a [u8], &'a [u8]> {
         preceded(tag(prefix), terminated(take_until("\r\n"), line_ending)).parse(buf)
     }
@@ -245,6 +259,19 @@ mod tests {
         assert_eq!(items.len(), 2);
     }
 
+    #[test]
+    fn args_of_command_frame() {
+        let frame = Frame::Array(vec![
+            Frame::BulkString(Bytes::from_static(b"GET")),
+            Frame::BulkString(Bytes::from_static(b"k")),
+        ]);
+        assert_eq!(
+            frame.args(),
+            vec![Bytes::from_static(b"GET"), Bytes::from_static(b"k")]
+        );
+        assert!(Frame::Integer(1).args().is_empty());
+    }
+
     #[test]
     fn parse_null_array() {
         let (frame, consumed) = Frame::parse(b"*-1\r\n").unwrap().unwrap();
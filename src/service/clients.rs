@@ -0,0 +1,479 @@
+//! Registry of connected clients, backing the CLIENT command family and
+//! `maxclients`.
+
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicBool, AtomicU8, AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::{sync::watch, time::Instant};
+use tokio_util::{bytes::Bytes, sync::CancellationToken};
+
+use crate::{protocol::command::table, utils::time::get_current_millis};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PauseMode {
+    All,
+    Write,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplyMode {
+    On,
+    Off,
+    Skip,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClientType {
+    Normal,
+    Master,
+    Replica,
+    PubSub,
+}
+
+/// Filters for CLIENT KILL. `legacy` is the old `CLIENT KILL addr:port` form,
+/// which replies OK or an error instead of a count.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClientKillFilter {
+    pub id: Option<u64>,
+    pub addr: Option<String>,
+    pub laddr: Option<String>,
+    pub user: Option<String>,
+    pub skip_me: bool,
+    pub legacy: bool,
+}
+
+// Reply states: SKIP_THIS suppresses the CLIENT REPLY SKIP reply itself and
+// arms SKIP_NEXT for the following command.
+const REPLY_ON: u8 = 0;
+const REPLY_OFF: u8 = 1;
+const REPLY_SKIP_THIS: u8 = 2;
+const REPLY_SKIP_NEXT: u8 = 3;
+
+pub struct ClientHandle {
+    pub id: u64,
+    pub addr: SocketAddr,
+    pub laddr: SocketAddr,
+    created_ms: u64,
+    last_interaction_ms: AtomicU64,
+    name: Mutex<Option<Bytes>>,
+    last_cmd: Mutex<String>,
+    qbuf: AtomicUsize,
+    obl: AtomicUsize,
+    no_evict: AtomicBool,
+    monitor: AtomicBool,
+    reply: AtomicU8,
+    killed: CancellationToken,
+}
+
+impl ClientHandle {
+    fn new(id: u64, addr: SocketAddr, laddr: SocketAddr) -> Self {
+        let now = get_current_millis();
+        Self {
+            id,
+            addr,
+            laddr,
+            created_ms: now,
+            last_interaction_ms: AtomicU64::new(now),
+            name: Mutex::new(None),
+            last_cmd: Mutex::new(String::from("NULL")),
+            qbuf: AtomicUsize::new(0),
+            obl: AtomicUsize::new(0),
+            no_evict: AtomicBool::new(false),
+            monitor: AtomicBool::new(false),
+            reply: AtomicU8::new(REPLY_ON),
+            killed: CancellationToken::new(),
+        }
+    }
+
+    pub fn name(&self) -> Option<Bytes> {
+        self.name.lock().expect("client name lock").clone()
+    }
+
+    pub fn set_name(&self, name: Option<Bytes>) {
+        *self.name.lock().expect("client name lock") = name;
+    }
+
+    pub fn user(&self) -> &str {
+        "default"
+    }
+
+    /// Records that the command `args` is about to be processed.
+    pub fn touch(&self, args: &[Bytes]) {
+        self.last_interaction_ms
+            .store(get_current_millis(), Ordering::Relaxed);
+        *self.last_cmd.lock().expect("client cmd lock") = command_label(args);
+    }
+
+    pub fn set_buffers(&self, qbuf: usize, obl: usize) {
+        self.qbuf.store(qbuf, Ordering::Relaxed);
+        self.obl.store(obl, Ordering::Relaxed);
+    }
+
+    pub fn set_no_evict(&self, on: bool) {
+        self.no_evict.store(on, Ordering::Relaxed);
+    }
+
+    pub fn set_monitor(&self) {
+        self.monitor.store(true, Ordering::Relaxed);
+    }
+
+    pub fn set_reply_mode(&self, mode: ReplyMode) {
+        let state = match mode {
+            ReplyMode::On => REPLY_ON,
+            ReplyMode::Off => REPLY_OFF,
+            ReplyMode::Skip => REPLY_SKIP_THIS,
+        };
+        self.reply.store(state, Ordering::Relaxed);
+    }
+
+    /// Whether the reply to the command just executed should be sent.
+    /// Advances CLIENT REPLY SKIP by one command.
+    pub fn should_reply(&self) -> bool {
+        match self.reply.load(Ordering::Relaxed) {
+            REPLY_ON => true,
+            REPLY_SKIP_THIS => {
+                self.reply.store(REPLY_SKIP_NEXT, Ordering::Relaxed);
+                false
+            }
+            REPLY_SKIP_NEXT => {
+                self.reply.store(REPLY_ON, Ordering::Relaxed);
+                false
+            }
+            _ => false,
+        }
+    }
+
+    pub fn kill(&self) {
+        self.killed.cancel();
+    }
+
+    pub async fn killed(&self) {
+        self.killed.cancelled().await
+    }
+
+    /// One line of CLIENT LIST / CLIENT INFO output.
+    pub fn info_line(&self) -> String {
+        let now = get_current_millis();
+        let name = self.name().unwrap_or_default();
+        let mut flags = String::new();
+        if self.monitor.load(Ordering::Relaxed) {
+            flags.push('O');
+        }
+        if self.no_evict.load(Ordering::Relaxed) {
+            flags.push('e');
+        }
+        if flags.is_empty() {
+            flags.push('N');
+        }
+        format!(
+            "id={} addr={} laddr={} name={} age={} idle={} flags={} db=0 sub=0 psub=0 multi=-1 qbuf={} obl={} cmd={} user={} resp=2",
+            self.id,
+            self.addr,
+            self.laddr,
+            String::from_utf8_lossy(&name),
+            now.saturating_sub(self.created_ms) / 1000,
+            now.saturating_sub(self.last_interaction_ms.load(Ordering::Relaxed)) / 1000,
+            flags,
+            self.qbuf.load(Ordering::Relaxed),
+            self.obl.load(Ordering::Relaxed),
+            self.last_cmd.lock().expect("client cmd lock"),
+            self.user(),
+        )
+    }
+
+    fn matches(&self, filter: &ClientKillFilter, me: u64) -> bool {
+        !(filter.skip_me && self.id == me)
+            && filter.id.is_none_or(|id| id == self.id)
+            && filter
+                .addr
+                .as_ref()
+                .is_none_or(|addr| *addr == self.addr.to_string())
+            && filter
+                .laddr
+                .as_ref()
+                .is_none_or(|laddr| *laddr == self.laddr.to_string())
+            && filter.user.as_ref().is_none_or(|user| user == self.user())
+    }
+}
+
+/// Lowercase command name, with the subcommand for container commands.
+pub fn command_label(args: &[Bytes]) -> String {
+    let arg = |i: usize| {
+        args.get(i)
+            .map(|b| String::from_utf8_lossy(b).to_ascii_lowercase())
+    };
+    match (arg(0), arg(1)) {
+        (Some(cmd), Some(sub))
+            if table::lookup(cmd.as_bytes()).is_some_and(|spec| !spec.subcommands.is_empty()) =>
+        {
+            format!("{cmd}|{sub}")
+        }
+        (Some(cmd), _) => cmd,
+        (None, _) => String::from("NULL"),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Pause {
+    until: Instant,
+    mode: PauseMode,
+}
+
+/// Bytes read from and written to every client connection.
+#[derive(Default)]
+pub struct NetTraffic {
+    pub input: AtomicU64,
+    pub output: AtomicU64,
+}
+
+pub struct ClientRegistry {
+    next_id: AtomicU64,
+    total_connections: AtomicU64,
+    rejected_connections: AtomicU64,
+    traffic: Arc<NetTraffic>,
+    clients: RwLock<BTreeMap<u64, Arc<ClientHandle>>>,
+    pause: watch::Sender<Option<Pause>>,
+}
+
+impl Default for ClientRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientRegistry {
+    pub fn new() -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            total_connections: AtomicU64::new(0),
+            rejected_connections: AtomicU64::new(0),
+            traffic: Arc::default(),
+            clients: RwLock::new(BTreeMap::new()),
+            pause: watch::Sender::new(None),
+        }
+    }
+
+    /// Adds a connection, or returns `None` when `maxclients` are connected.
+    pub fn register(
+        &self,
+        addr: SocketAddr,
+        laddr: SocketAddr,
+        maxclients: usize,
+    ) -> Option<Arc<ClientHandle>> {
+        let mut clients = self.clients.write().expect("client registry lock");
+        if clients.len() >= maxclients {
+            self.rejected_connections.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        self.total_connections.fetch_add(1, Ordering::Relaxed);
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let client = Arc::new(ClientHandle::new(id, addr, laddr));
+        clients.insert(id, client.clone());
+        Some(client)
+    }
+
+    pub fn unregister(&self, id: u64) {
+        self.clients
+            .write()
+            .expect("client registry lock")
+            .remove(&id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.read().expect("client registry lock").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Connections accepted since startup.
+    pub fn total_connections(&self) -> u64 {
+        self.total_connections.load(Ordering::Relaxed)
+    }
+
+    /// Connections refused because of `maxclients`.
+    pub fn rejected_connections(&self) -> u64 {
+        self.rejected_connections.load(Ordering::Relaxed)
+    }
+
+    /// Counters shared with every connection's stream.
+    pub fn traffic(&self) -> Arc<NetTraffic> {
+        self.traffic.clone()
+    }
+
+    pub fn net_input_bytes(&self) -> u64 {
+        self.traffic.input.load(Ordering::Relaxed)
+    }
+
+    pub fn net_output_bytes(&self) -> u64 {
+        self.traffic.output.load(Ordering::Relaxed)
+    }
+
+    /// Zeroes the connection counters for CONFIG RESETSTAT.
+    pub fn reset_stats(&self) {
+        self.total_connections.store(0, Ordering::Relaxed);
+        self.rejected_connections.store(0, Ordering::Relaxed);
+        self.traffic.input.store(0, Ordering::Relaxed);
+        self.traffic.output.store(0, Ordering::Relaxed);
+    }
+
+    /// Connected clients ordered by id.
+    pub fn list(&self) -> Vec<Arc<ClientHandle>> {
+        self.clients
+            .read()
+            .expect("client registry lock")
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Kills every client matching `filter`, returning how many were killed.
+    pub fn kill(&self, filter: &ClientKillFilter, me: u64) -> usize {
+        let victims: Vec<_> = self
+            .list()
+            .into_iter()
+            .filter(|client| client.matches(filter, me))
+            .collect();
+        for client in &victims {
+            client.kill();
+        }
+        victims.len()
+    }
+
+    pub fn pause(&self, timeout: Duration, mode: PauseMode) {
+        self.pause.send_replace(Some(Pause {
+            until: Instant::now() + timeout,
+            mode,
+        }));
+    }
+
+    pub fn unpause(&self) {
+        self.pause.send_replace(None);
+    }
+
+    /// Waits out an active CLIENT PAUSE that applies to this command.
+    pub async fn wait_if_paused(&self, is_write: bool) {
+        let mut rx = self.pause.subscribe();
+        loop {
+            let pause = *rx.borrow_and_update();
+            let Some(pause) = pause else {
+                return;
+            };
+            if Instant::now() >= pause.until || (pause.mode == PauseMode::Write && !is_write) {
+                return;
+            }
+            tokio::select! {
+                _ = tokio::time::sleep_until(pause.until) => {}
+                _ = rx.changed() => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn register_enforces_maxclients_and_assigns_ids() {
+        let registry = ClientRegistry::new();
+        let a = registry.register(addr(1), addr(6379), 2).unwrap();
+        let b = registry.register(addr(2), addr(6379), 2).unwrap();
+        assert!(registry.register(addr(3), addr(6379), 2).is_none());
+        assert!(b.id > a.id);
+
+        registry.unregister(a.id);
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.total_connections(), 2);
+        assert_eq!(registry.rejected_connections(), 1);
+        assert!(registry.register(addr(3), addr(6379), 2).is_some());
+    }
+
+    #[test]
+    fn info_line_reports_name_flags_and_last_command() {
+        let registry = ClientRegistry::new();
+        let client = registry.register(addr(5000), addr(6379), 10).unwrap();
+        client.set_name(Some(Bytes::from_static(b"worker")));
+        client.set_no_evict(true);
+        client.touch(&[Bytes::from_static(b"CLIENT"), Bytes::from_static(b"List")]);
+        let line = client.info_line();
+        assert!(line.starts_with(&format!(
+            "id={} addr=127.0.0.1:5000 laddr=127.0.0.1:6379 name=worker age=0 idle=0 flags=e db=0",
+            client.id
+        )));
+        assert!(line.contains(" cmd=client|list user=default"));
+    }
+
+    #[test]
+    fn kill_filters_and_skipme() {
+        let registry = ClientRegistry::new();
+        let me = registry.register(addr(1), addr(6379), 10).unwrap();
+        let other = registry.register(addr(2), addr(6379), 10).unwrap();
+
+        let by_user = ClientKillFilter {
+            user: Some("default".into()),
+            skip_me: true,
+            ..Default::default()
+        };
+        assert_eq!(registry.kill(&by_user, me.id), 1);
+        assert!(other.killed.is_cancelled());
+        assert!(!me.killed.is_cancelled());
+
+        let by_addr = ClientKillFilter {
+            addr: Some("127.0.0.1:1".into()),
+            ..Default::default()
+        };
+        assert_eq!(registry.kill(&by_addr, other.id), 1);
+        assert!(me.killed.is_cancelled());
+    }
+
+    #[test]
+    fn reply_skip_suppresses_two_replies() {
+        let registry = ClientRegistry::new();
+        let client = registry.register(addr(1), addr(6379), 10).unwrap();
+        client.set_reply_mode(ReplyMode::Skip);
+        assert!(!client.should_reply());
+        assert!(!client.should_reply());
+        assert!(client.should_reply());
+
+        client.set_reply_mode(ReplyMode::Off);
+        assert!(!client.should_reply());
+        assert!(!client.should_reply());
+    }
+
+    #[tokio::test]
+    async fn pause_blocks_until_timeout_or_unpause() {
+        let registry = Arc::new(ClientRegistry::new());
+        registry.pause(Duration::from_millis(50), PauseMode::Write);
+
+        let start = Instant::now();
+        registry.wait_if_paused(false).await;
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        registry.wait_if_paused(true).await;
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        registry.pause(Duration::from_secs(60), PauseMode::All);
+        let waiter = {
+            let registry = registry.clone();
+            tokio::spawn(async move { registry.wait_if_paused(false).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiter.is_finished());
+        registry.unpause();
+        waiter.await.unwrap();
+        assert!(start.elapsed() < Duration::from_secs(60));
+    }
+}
@@ -1,31 +1,47 @@
 use crate::{
     config::AppConfig,
-    protocol::{command::Command, resp::Frame},
-    service::handlers::{
-        CommandEffect,
-        multikey::{del, exists, mget, mset},
-        nokey::{config_get, config_rewrite, config_set, dbsize, echo, flushdb, info, ping},
-        singlekey::{
-            append, decr, expire, get, getdel, getset, incr, persist, pttl, set, setnx, strlen, ttl,
+    protocol::{
+        command::{
+            Command,
+            table::{self, CommandSpec},
         },
+        resp::Frame,
+    },
+    service::{
+        clients::{ClientHandle, ClientRegistry, command_label},
+        handlers::CommandEffect,
+        monitor::Monitor,
+        shutdown::{SaveMode, Shutdown, ShutdownOptions},
+        slowlog::SlowLog,
+        stats::CommandStats,
     },
     store::{
         memory::MemoryStore,
         persistence::{
             AofEngine,
             aof::{Aof, NoopAof},
+            live::LiveAof,
+            record::Record,
         },
     },
-    utils::time::get_current_millis,
+    telemetry,
+};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
 };
-use std::sync::Arc;
 use tokio::sync::RwLock;
-use tokio_util::sync::CancellationToken;
+use tokio_util::{bytes::Bytes, sync::CancellationToken};
 
 pub struct ServerContext {
     pub store: MemoryStore,
     pub config: Arc<RwLock<AppConfig>>,
-    pub aof: Arc<dyn Aof>,
+    pub aof: LiveAof,
+    pub clients: ClientRegistry,
+    pub slowlog: SlowLog,
+    pub monitor: Monitor,
+    pub command_stats: CommandStats,
+    pub shutdown: Shutdown,
     pub cancel: CancellationToken,
 }
 
@@ -38,16 +54,113 @@ impl ServerContext {
         } else {
             Arc::new(NoopAof)
         };
+        let slowlog = SlowLog::new(config.slowlog_log_slower_than, config.slowlog_max_len);
         Ok(Arc::new(Self {
             store,
             config: Arc::new(RwLock::new(config)),
-            aof,
+            aof: LiveAof::new(aof),
+            clients: ClientRegistry::new(),
+            slowlog,
+            monitor: Monitor::new(),
+            command_stats: CommandStats::new(),
+            shutdown: Shutdown::new(),
             cancel: CancellationToken::new(),
         }))
     }
 
-    pub async fn execute(&self, cmd: Command) -> Frame {
-        let effect = self.dispatch(&cmd).await;
+    /// Runs `cmd` on behalf of `client`; `args` is the raw command as
+    /// received, which picks its table entry and is kept for the slow log
+    /// and monitors.
+    pub async fn execute(&self, client: &ClientHandle, args: &[Bytes], cmd: Command) -> Frame {
+        let Some(spec) = table::resolve(args) else {
+            return Frame::Error("ERR unknown command".into());
+        };
+        // CLIENT commands stay available so a paused server can be unpaused.
+        if !spec.name.starts_with("client|") {
+            self.clients.wait_if_paused(spec.has_flag("write")).await;
+        }
+        let Some(_in_flight) = self.shutdown.admit().await else {
+            return Frame::Error("ERR Server is shutting down".into());
+        };
+
+        self.monitor.feed(0, client.addr, args);
+        let name = command_label(args);
+        if spec.has_flag("denyoom") && !self.make_room().await {
+            self.command_stats.reject(&name);
+            return Frame::Error("OOM command not allowed when used memory > 'maxmemory'.".into());
+        }
+
+        let start = Instant::now();
+        let frame = self.run(client, spec, cmd).await;
+        let elapsed = start.elapsed();
+        self.command_stats
+            .record(&name, elapsed, matches!(frame, Frame::Error(_)));
+        self.slowlog.record(elapsed, args, client);
+        frame
+    }
+
+    /// Shuts the server down gracefully: holds new commands, waits up to
+    /// `shutdown-timeout` for running ones, then saves per `options` and
+    /// cancels every task. On abort, or a failed save without FORCE, the
+    /// server keeps running and the error is returned.
+    pub async fn stop(&self, options: ShutdownOptions) -> Result<(), String> {
+        if !self.shutdown.begin() {
+            return Err("ERR Shutdown already in progress.".into());
+        }
+        let config = self.config.read().await.clone();
+        if !options.now
+            && !self
+                .shutdown
+                .drain(Duration::from_secs(config.shutdown_timeout))
+                .await
+        {
+            log::warn!("Shutdown aborted");
+            return Err("ERR Errors trying to SHUTDOWN. Check logs.".into());
+        }
+
+        let saved = match options.save {
+            SaveMode::Default => self.aof.fsync().await,
+            SaveMode::Save => {
+                self.aof
+                    .save(
+                        config.aof_path.clone(),
+                        config.fsync_mode,
+                        &self.store,
+                        config.append_only,
+                    )
+                    .await
+            }
+            SaveMode::NoSave => Ok(()),
+        };
+        if let Err(err) = saved {
+            log::error!("Error saving before shutdown: {err:?}");
+            if !options.force {
+                self.shutdown.abort();
+                return Err("ERR Errors trying to SHUTDOWN. Check logs.".into());
+            }
+        }
+
+        log::info!("Shutting down...");
+        self.shutdown.finish();
+        self.cancel.cancel();
+        Ok(())
+    }
+
+    async fn run(&self, client: &ClientHandle, spec: &CommandSpec, cmd: Command) -> Frame {
+        // Keeps the AOF from being swapped between the write and its record.
+        let aof = if spec.has_flag("write") {
+            Some(self.aof.current().await)
+        } else {
+            None
+        };
+        let span = telemetry::span("command.dispatch");
+        let effect = span
+            .scope(MemoryStore::count_lookups(
+                cmd.counts_lookups(spec),
+                self.dispatch(client, spec, &cmd),
+            ))
+            .await;
+        drop(span);
         match effect {
             CommandEffect::Read(frame) => {
                 if !matches!(frame, Frame::Error(_)) {
@@ -58,8 +171,12 @@ impl ServerContext {
             CommandEffect::Write(frame, record) => {
                 if !matches!(frame, Frame::Error(_)) {
                     self.store.increment_commands();
-                    if let Err(err) = self.aof.append(record).await {
-                        eprintln!("AOF write error: {err:?}");
+                    let appended = match &aof {
+                        Some(aof) => aof.append(record).await,
+                        None => self.aof.append(record).await,
+                    };
+                    if let Err(err) = appended {
+                        log::error!("AOF write error: {err:?}");
                     }
                 }
                 frame
@@ -67,45 +184,41 @@ impl ServerContext {
         }
     }
 
-    async fn dispatch(&self, cmd: &Command) -> CommandEffect {
-        let store = &self.store;
-        let config = &self.config;
-        let aof = &self.aof;
-        match cmd {
-            Command::PING => ping().await,
-            Command::CONFIG_GET { pattern } => config_get(config, pattern.clone()).await,
-            Command::CONFIG_SET { key, value } => {
-                config_set(config, aof, key.clone(), value.clone()).await
-            }
-            Command::CONFIG_REWRITE => config_rewrite(config).await,
-            Command::ECHO { msg } => echo(msg.clone()).await,
-            Command::DBSIZE => dbsize(store).await,
-            Command::FLUSHDB => flushdb(store).await,
-            Command::INFO => info(store).await,
-            Command::GET { key } => get(store, key.clone()).await,
-            Command::SET { key, entry } => set(store, key.clone(), entry.clone()).await,
-            Command::GETDEL { key } => getdel(store, key.clone()).await,
-            Command::GETSET { key, entry } => getset(store, key.clone(), entry.clone()).await,
-            Command::SETNX { key, entry } => setnx(store, key.clone(), entry.clone()).await,
-            Command::INCR { key } => incr(store, key.clone()).await,
-            Command::DECR { key } => decr(store, key.clone()).await,
-            Command::STRLEN { key } => strlen(store, key.clone()).await,
-            Command::APPEND { key, value } => append(store, key.clone(), value.clone()).await,
-            Command::TTL { key } => ttl(store, key.clone(), get_current_millis()).await,
-            Command::PTTL { key } => pttl(store, key.clone(), get_current_millis()).await,
-            Command::PERSIST { key } => persist(store, key.clone()).await,
-            Command::EXPIRE { key, ttl } => {
-                expire(store, key.clone(), *ttl, get_current_millis()).await
-            }
-            Command::PEXPIRE { key, ttl } => {
-                expire(store, key.clone(), *ttl, get_current_millis()).await
-            }
-            Command::DEL { keys } => del(store, keys.clone()).await,
-            Command::EXISTS { keys } => exists(store, keys.clone()).await,
-            Command::MGET { keys } => mget(store, keys.clone()).await,
-            Command::MSET { items } => mset(store, items.clone()).await,
-            #[allow(unreachable_patterns)]
-            Command::SHUTDOWN => unreachable!(),
+    /// Evicts keys per `maxmemory-policy` until usage is under `maxmemory`,
+    /// logging each evicted key as a delete. Returns false if it cannot.
+    async fn make_room(&self) -> bool {
+        let (maxmemory, policy) = {
+            let config = self.config.read().await;
+            (config.maxmemory, config.maxmemory_policy)
+        };
+        let eviction = self.store.evict(maxmemory, policy).await;
+        if !eviction.keys.is_empty()
+            && let Err(err) = self
+                .aof
+                .append(Record::Del {
+                    keys: eviction.keys,
+                })
+                .await
+        {
+            log::error!("AOF write error: {err:?}");
+        }
+        eviction.fits
+    }
+
+    /// Runs `cmd` through the handler in its table entry; registered
+    /// commands carry their own.
+    async fn dispatch(
+        &self,
+        client: &ClientHandle,
+        spec: &CommandSpec,
+        cmd: &Command,
+    ) -> CommandEffect {
+        if let Command::REGISTERED { handler, args } = cmd {
+            return handler.call(self, client, args).await;
         }
+        let handler = spec
+            .handler
+            .unwrap_or_else(|| unreachable!("{} runs on the connection", spec.name));
+        handler(self, client, cmd).await
     }
 }
@@ -1,9 +1,6 @@
 use crate::{
     protocol::resp::Frame,
-    store::{
-        persistence::record::Record,
-        types::{Entry, Expiry},
-    },
+    store::{persistence::record::Record, types::Entry},
 };
 use tokio_util::bytes::Bytes;
 
@@ -18,18 +15,7 @@ pub enum CommandEffect {
 
 impl CommandEffect {
     pub fn from_set(frame: Frame, key: Bytes, entry: Entry) -> Self {
-        let exp_ms = match entry.exp {
-            Expiry::At(ms) => Some(ms),
-            Expiry::None | Expiry::Keep => None,
-        };
-        Self::Write(
-            frame,
-            Record::Set {
-                key,
-                value: entry.value,
-                exp_ms,
-            },
-        )
+        Self::Write(frame, Record::from_entry(key, entry))
     }
 }
 
@@ -48,7 +34,7 @@ mod tests {
 
     pub fn entry(value: &[u8], exp: Expiry) -> Entry {
         Entry {
-            value: Bytes::from(value.to_vec()),
+            value: Bytes::from(value.to_vec()).into(),
             exp,
         }
     }
@@ -1,4 +1,15 @@
-use crate::{protocol::resp::Frame, service::handlers::CommandEffect, store::traits::Store};
+use crate::{
+    protocol::resp::Frame,
+    service::handlers::CommandEffect,
+    store::{
+        ops::{
+            self, bitmap,
+            geo::{self, GeoQuery},
+            hyperloglog,
+        },
+        traits::Store,
+    },
+};
 use tokio_util::bytes::Bytes;
 
 pub async fn del(store: &impl Store, keys: Vec<Bytes>) -> CommandEffect {
@@ -19,7 +30,10 @@ pub async fn mget(store: &impl Store, keys: Vec<Bytes>) -> CommandEffect {
         .await
         .iter()
         .map(|e| match e {
-            Some(entry) => Frame::BulkString(entry.value.clone()),
+            Some(entry) => match entry.value.as_string() {
+                Ok(value) => Frame::BulkString(value.clone()),
+                Err(_) => Frame::NullBulkString,
+            },
             None => Frame::NullBulkString,
         })
         .collect();
@@ -34,6 +48,118 @@ pub async fn mset(store: &impl Store, items: Vec<(Bytes, Bytes)>) -> CommandEffe
     )
 }
 
+pub async fn msetnx(store: &impl Store, items: Vec<(Bytes, Bytes)>) -> CommandEffect {
+    if !ops::msetnx(store, &items).await {
+        return CommandEffect::Read(Frame::Integer(0));
+    }
+    CommandEffect::Write(
+        Frame::Integer(1),
+        crate::store::persistence::record::Record::MSet { items },
+    )
+}
+
+pub async fn bitop(
+    store: &impl Store,
+    op: bitmap::BitOp,
+    dest: Bytes,
+    keys: &[Bytes],
+) -> CommandEffect {
+    match bitmap::bitop(store, op, dest.clone(), keys).await {
+        Ok(Some(resolved)) => {
+            CommandEffect::from_set(Frame::Integer(resolved.value.len() as i64), dest, resolved)
+        }
+        Ok(None) => CommandEffect::Write(
+            Frame::Integer(0),
+            crate::store::persistence::record::Record::Del { keys: vec![dest] },
+        ),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn pfcount(store: &impl Store, keys: &[Bytes]) -> CommandEffect {
+    match hyperloglog::pfcount(store, keys).await {
+        Ok(count) => CommandEffect::Read(Frame::Integer(count as i64)),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn pfmerge(store: &impl Store, dest: Bytes, keys: &[Bytes]) -> CommandEffect {
+    match hyperloglog::pfmerge(store, dest.clone(), keys).await {
+        Ok(resolved) => CommandEffect::from_set(Frame::SimpleString("OK".into()), dest, resolved),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn geosearchstore(
+    store: &impl Store,
+    dest: Bytes,
+    key: &Bytes,
+    query: &GeoQuery,
+    store_dist: bool,
+) -> CommandEffect {
+    match geo::geosearchstore(store, dest.clone(), key, query, store_dist).await {
+        Ok((count, Some(resolved))) => {
+            CommandEffect::from_set(Frame::Integer(count as i64), dest, resolved)
+        }
+        Ok((_, None)) => CommandEffect::Write(
+            Frame::Integer(0),
+            crate::store::persistence::record::Record::Del { keys: vec![dest] },
+        ),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn lcs(
+    store: &impl Store,
+    key1: &Bytes,
+    key2: &Bytes,
+    len: bool,
+    idx: bool,
+    min_match_len: usize,
+    with_match_len: bool,
+) -> CommandEffect {
+    let lcs = match ops::lcs(store, key1, key2).await {
+        Ok(lcs) => lcs,
+        Err(msg) => return CommandEffect::Read(Frame::Error(msg.into())),
+    };
+
+    if len {
+        return CommandEffect::Read(Frame::Integer(lcs.sequence.len() as i64));
+    }
+    if !idx {
+        return CommandEffect::Read(Frame::BulkString(lcs.sequence));
+    }
+
+    let matches = lcs
+        .matches
+        .iter()
+        .filter(|m| m.len >= min_match_len)
+        .map(|m| {
+            let mut item = vec![
+                Frame::Array(vec![
+                    Frame::Integer(m.a.0 as i64),
+                    Frame::Integer(m.a.1 as i64),
+                ]),
+                Frame::Array(vec![
+                    Frame::Integer(m.b.0 as i64),
+                    Frame::Integer(m.b.1 as i64),
+                ]),
+            ];
+            if with_match_len {
+                item.push(Frame::Integer(m.len as i64));
+            }
+            Frame::Array(item)
+        })
+        .collect();
+
+    CommandEffect::Read(Frame::Array(vec![
+        Frame::BulkString("matches".into()),
+        Frame::Array(matches),
+        Frame::BulkString("len".into()),
+        Frame::Integer(lcs.sequence.len() as i64),
+    ]))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,4 +245,97 @@ mod tests {
             Bytes::from_static(b"2")
         );
     }
+
+    #[tokio::test]
+    async fn msetnx_sets_when_all_missing() {
+        let store = MemoryStore::new();
+        let items = vec![
+            (Bytes::from_static(b"a"), Bytes::from_static(b"1")),
+            (Bytes::from_static(b"b"), Bytes::from_static(b"2")),
+        ];
+        let (frame, record) = write_frame(msetnx(&store, items.clone()).await);
+        assert_eq!(frame, Frame::Integer(1));
+        assert!(matches!(record, Record::MSet { items: ri } if ri == items));
+    }
+
+    #[tokio::test]
+    async fn msetnx_returns_zero_when_any_exists() {
+        let store = MemoryStore::new();
+        store
+            .set(Bytes::from_static(b"b"), entry(b"x", Expiry::None))
+            .await;
+        let items = vec![
+            (Bytes::from_static(b"a"), Bytes::from_static(b"1")),
+            (Bytes::from_static(b"b"), Bytes::from_static(b"2")),
+        ];
+        let frame = read_frame(msetnx(&store, items).await);
+        assert_eq!(frame, Frame::Integer(0));
+        assert!(store.get(&Bytes::from_static(b"a")).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn lcs_modes() {
+        let store = MemoryStore::new();
+        let (a, b) = (Bytes::from_static(b"a"), Bytes::from_static(b"b"));
+        store.set(a.clone(), entry(b"ohmytext", Expiry::None)).await;
+        store
+            .set(b.clone(), entry(b"mynewtext", Expiry::None))
+            .await;
+
+        let frame = read_frame(lcs(&store, &a, &b, false, false, 0, false).await);
+        assert_eq!(frame, Frame::BulkString("mytext".into()));
+
+        let frame = read_frame(lcs(&store, &a, &b, true, false, 0, false).await);
+        assert_eq!(frame, Frame::Integer(6));
+
+        let frame = read_frame(lcs(&store, &a, &b, false, true, 4, true).await);
+        assert_eq!(
+            frame,
+            Frame::Array(vec![
+                Frame::BulkString("matches".into()),
+                Frame::Array(vec![Frame::Array(vec![
+                    Frame::Array(vec![Frame::Integer(4), Frame::Integer(7)]),
+                    Frame::Array(vec![Frame::Integer(5), Frame::Integer(8)]),
+                    Frame::Integer(4),
+                ])]),
+                Frame::BulkString("len".into()),
+                Frame::Integer(6),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn bitop_returns_dest_length() {
+        let store = MemoryStore::new();
+        store
+            .set(Bytes::from_static(b"a"), entry(b"abc", Expiry::None))
+            .await;
+        let (frame, record) = write_frame(
+            bitop(
+                &store,
+                bitmap::BitOp::Not,
+                Bytes::from_static(b"d"),
+                &[Bytes::from_static(b"a")],
+            )
+            .await,
+        );
+        assert_eq!(frame, Frame::Integer(3));
+        assert!(matches!(record, Record::Set { key, .. } if key == Bytes::from_static(b"d")));
+    }
+
+    #[tokio::test]
+    async fn bitop_on_missing_sources_deletes_dest() {
+        let store = MemoryStore::new();
+        let (frame, record) = write_frame(
+            bitop(
+                &store,
+                bitmap::BitOp::And,
+                Bytes::from_static(b"d"),
+                &[Bytes::from_static(b"x"), Bytes::from_static(b"y")],
+            )
+            .await,
+        );
+        assert_eq!(frame, Frame::Integer(0));
+        assert!(matches!(record, Record::Del { keys } if keys == vec![Bytes::from_static(b"d")]));
+    }
 }
@@ -1,10 +1,33 @@
 use crate::{
-    config::AppConfig,
-    protocol::resp::Frame,
-    service::handlers::CommandEffect,
-    store::{memory::MemoryStore, persistence::aof::Aof, traits::Store},
+    config::{
+        AppConfig,
+        registry::{self, Hook, Param},
+    },
+    logging,
+    protocol::{
+        command::{
+            Command,
+            table::{self, CommandListFilter, CommandSpec},
+        },
+        resp::Frame,
+    },
+    service::{
+        clients::{
+            ClientHandle, ClientKillFilter, ClientRegistry, ClientType, PauseMode, ReplyMode,
+        },
+        handlers::CommandEffect,
+        slowlog::SlowLog,
+        stats::CommandStats,
+    },
+    store::{
+        memory::{MemoryStats, MemoryStore},
+        persistence::live::LiveAof,
+        traits::Store,
+    },
+    telemetry,
+    utils::{alloc, glob::glob_match, time::get_current_millis},
 };
-use std::sync::Arc;
+use std::{fmt::Write, sync::Arc, time::Duration};
 use tokio::sync::RwLock;
 use tokio_util::bytes::Bytes;
 
@@ -20,6 +43,19 @@ pub async fn dbsize(store: &impl Store) -> CommandEffect {
     CommandEffect::Read(Frame::Integer(store.len().await as i64))
 }
 
+pub async fn scan(
+    store: &MemoryStore,
+    cursor: u64,
+    pattern: Option<&Bytes>,
+    count: usize,
+) -> CommandEffect {
+    let (next, keys) = store.scan(cursor, count, pattern.map(|p| p.as_ref())).await;
+    CommandEffect::Read(Frame::Array(vec![
+        Frame::BulkString(next.to_string().into()),
+        Frame::Array(keys.into_iter().map(Frame::BulkString).collect()),
+    ]))
+}
+
 pub async fn flushdb(store: &impl Store) -> CommandEffect {
     store.clear().await;
     CommandEffect::Write(
@@ -28,101 +64,306 @@ pub async fn flushdb(store: &impl Store) -> CommandEffect {
     )
 }
 
-pub async fn info(store: &MemoryStore) -> CommandEffect {
-    let key_count = store.len().await as i64;
-    let used_memory = store.used_memory().await;
-    let uptime_seconds = store.uptime_seconds();
-    let total_commands = store.total_commands();
-
-    let info = format!(
-        "yars_version:{}\r\ndb_keys:{}\r\nused_memory:{}\r\nuptime_seconds:{}\r\ntotal_commands:{}\r\n",
-        env!("CARGO_PKG_VERSION"),
-        key_count,
-        used_memory,
-        uptime_seconds,
-        total_commands
-    );
-    CommandEffect::Read(Frame::BulkString(info.into()))
-}
-
-pub async fn config_get(config: &Arc<RwLock<AppConfig>>, pattern: Bytes) -> CommandEffect {
-    let Some(pattern) = std::str::from_utf8(&pattern)
-        .ok()
-        .map(|s| s.to_ascii_lowercase())
-    else {
-        return CommandEffect::Read(Frame::Error("ERR pattern is not valid UTF-8".into()));
-    };
+/// The Redis release whose commands and replies yars follows, reported as
+/// `redis_version` for clients that check it before using newer commands.
+const REDIS_VERSION: &str = "7.2.0";
 
+/// INFO sections in output order, with their header and whether a plain
+/// `INFO` includes them.
+const INFO_SECTIONS: &[(&str, &str, bool)] = &[
+    ("server", "Server", true),
+    ("clients", "Clients", true),
+    ("memory", "Memory", true),
+    ("persistence", "Persistence", true),
+    ("stats", "Stats", true),
+    ("replication", "Replication", true),
+    ("commandstats", "Commandstats", false),
+    ("keyspace", "Keyspace", true),
+];
+
+pub async fn info(
+    store: &MemoryStore,
+    config: &Arc<RwLock<AppConfig>>,
+    aof: &LiveAof,
+    clients: &ClientRegistry,
+    command_stats: &CommandStats,
+    sections: &[String],
+) -> CommandEffect {
     let config = config.read().await;
-    let mut values = Vec::new();
+    let wanted = |name: &str, default: bool| {
+        (sections.is_empty() && default)
+            || sections.iter().any(|s| {
+                s == name || s == "all" || s == "everything" || (s == "default" && default)
+            })
+    };
 
-    if pattern == "*" || pattern == "appendonly" {
-        values.push(Frame::BulkString("appendonly".into()));
-        values.push(Frame::BulkString(config.append_only.to_string().into()));
+    let mut out = String::new();
+    for &(name, title, default) in INFO_SECTIONS {
+        if !wanted(name, default) {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push_str("\r\n");
+        }
+        let _ = write!(out, "# {title}\r\n");
+        match name {
+            "server" => {
+                let uptime = store.uptime_seconds();
+                let _ = write!(
+                    out,
+                    "redis_version:{}\r\nyars_version:{}\r\nredis_mode:standalone\r\nos:{} {}\r\narch_bits:{}\r\nprocess_id:{}\r\ntcp_port:{}\r\nserver_time_usec:{}\r\nuptime_in_seconds:{}\r\nuptime_in_days:{}\r\nconfig_file:{}\r\n",
+                    REDIS_VERSION,
+                    env!("CARGO_PKG_VERSION"),
+                    std::env::consts::OS,
+                    std::env::consts::ARCH,
+                    usize::BITS,
+                    std::process::id(),
+                    config.port,
+                    get_current_millis() * 1000,
+                    uptime,
+                    uptime / 86400,
+                    config.config_path.display(),
+                );
+            }
+            "clients" => {
+                let _ = write!(
+                    out,
+                    "connected_clients:{}\r\nmaxclients:{}\r\nblocked_clients:0\r\n",
+                    clients.len(),
+                    config.maxclients,
+                );
+            }
+            "memory" => {
+                let used = store.used_memory().await;
+                let peak = store.peak_memory();
+                let _ = write!(
+                    out,
+                    "used_memory:{used}\r\nused_memory_human:{}\r\nused_memory_peak:{peak}\r\nused_memory_peak_human:{}\r\nmaxmemory:{}\r\nmaxmemory_human:{}\r\nmaxmemory_policy:{}\r\n",
+                    bytes_to_human(used as u64),
+                    bytes_to_human(peak as u64),
+                    config.maxmemory,
+                    bytes_to_human(config.maxmemory),
+                    config.maxmemory_policy.as_str(),
+                );
+                if let Some(allocated) = alloc::allocated_bytes() {
+                    let _ = write!(out, "allocator_allocated:{allocated}\r\n");
+                }
+            }
+            "persistence" => {
+                let status = aof.status().await;
+                let _ = write!(
+                    out,
+                    "loading:0\r\naof_enabled:{}\r\naof_rewrite_in_progress:0\r\naof_rewrite_scheduled:0\r\naof_last_rewrite_time_sec:-1\r\naof_current_rewrite_time_sec:-1\r\naof_last_bgrewrite_status:ok\r\naof_rewrites:0\r\naof_last_write_status:{}\r\n",
+                    u8::from(status.is_some()),
+                    if status.as_ref().is_none_or(|s| s.last_write_ok) {
+                        "ok"
+                    } else {
+                        "err"
+                    },
+                );
+                if let Some(status) = status {
+                    let _ = write!(
+                        out,
+                        "aof_current_size:{}\r\naof_base_size:{}\r\n",
+                        status.current_size, status.base_size,
+                    );
+                }
+            }
+            "stats" => {
+                let _ = write!(
+                    out,
+                    "total_connections_received:{}\r\ntotal_commands_processed:{}\r\ntotal_net_input_bytes:{}\r\ntotal_net_output_bytes:{}\r\nrejected_connections:{}\r\nexpired_keys:{}\r\nevicted_keys:{}\r\nkeyspace_hits:{}\r\nkeyspace_misses:{}\r\n",
+                    clients.total_connections(),
+                    store.total_commands(),
+                    clients.net_input_bytes(),
+                    clients.net_output_bytes(),
+                    clients.rejected_connections(),
+                    store.expired_keys(),
+                    store.evicted_keys(),
+                    store.keyspace_hits(),
+                    store.keyspace_misses(),
+                );
+            }
+            "replication" => out.push_str("role:master\r\nconnected_slaves:0\r\n"),
+            "commandstats" => {
+                for stat in command_stats.snapshot() {
+                    let per_call = if stat.calls == 0 {
+                        0.0
+                    } else {
+                        stat.usec as f64 / stat.calls as f64
+                    };
+                    let _ = write!(
+                        out,
+                        "cmdstat_{}:calls={},usec={},usec_per_call={per_call:.2},rejected_calls={},failed_calls={}\r\n",
+                        stat.name, stat.calls, stat.usec, stat.rejected_calls, stat.failed_calls,
+                    );
+                }
+            }
+            "keyspace" => {
+                let keys = store.len().await as u64;
+                if keys > 0 {
+                    let expires = store.volatile_keys().min(keys);
+                    let avg_ttl = store.avg_ttl().await;
+                    let _ = write!(
+                        out,
+                        "db0:keys={keys},expires={expires},avg_ttl={avg_ttl}\r\n"
+                    );
+                }
+            }
+            _ => unreachable!("unknown INFO section {name}"),
+        }
     }
-    if pattern == "*" || pattern == "appendfilename" {
-        values.push(Frame::BulkString("appendfilename".into()));
-        values.push(Frame::BulkString(
-            config.aof_path.to_string_lossy().into_owned().into(),
-        ));
+    CommandEffect::Read(Frame::BulkString(out.into()))
+}
+
+/// Formats a byte count the way INFO's `*_human` fields do, e.g. `1.50M`.
+fn bytes_to_human(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
     }
-    if pattern == "*" || pattern == "appendfsync" {
-        values.push(Frame::BulkString("appendfsync".into()));
-        values.push(Frame::BulkString(config.fsync_mode.as_str().into()));
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
     }
+    format!("{value:.2}{}", UNITS[unit])
+}
 
+pub async fn config_get(config: &Arc<RwLock<AppConfig>>, patterns: &[Bytes]) -> CommandEffect {
+    let patterns: Vec<Vec<u8>> = patterns.iter().map(|p| p.to_ascii_lowercase()).collect();
+    let config = config.read().await;
+    let mut values = Vec::new();
+    for param in registry::PARAMS {
+        if patterns
+            .iter()
+            .any(|pattern| glob_match(pattern, param.name.as_bytes()))
+        {
+            values.push(Frame::BulkString(param.name.into()));
+            values.push(Frame::BulkString(param.get(&config).into()));
+        }
+    }
     CommandEffect::Read(Frame::Array(values))
 }
 
+/// Applies every pair or none; see [`apply_config`].
 pub async fn config_set(
+    store: &MemoryStore,
     config: &Arc<RwLock<AppConfig>>,
-    aof: &Arc<dyn Aof>,
-    key: Bytes,
-    value: Bytes,
+    aof: &LiveAof,
+    slowlog: &SlowLog,
+    pairs: &[(Bytes, Bytes)],
 ) -> CommandEffect {
-    let Some(key) = std::str::from_utf8(&key)
-        .ok()
-        .map(|s| s.to_ascii_lowercase())
-    else {
-        return CommandEffect::Read(Frame::Error("ERR key is not valid UTF-8".into()));
+    let failed = |name: &str, reason: &dyn std::fmt::Display| {
+        CommandEffect::Read(Frame::Error(format!(
+            "ERR CONFIG SET failed (possibly related to argument '{name}') - {reason}"
+        )))
     };
 
-    let Some(value) = std::str::from_utf8(&value).ok() else {
-        return CommandEffect::Read(Frame::Error("ERR value is not a valid string".into()));
-    };
+    let mut changes: Vec<(&'static Param, &str)> = Vec::with_capacity(pairs.len());
+    for (name, value) in pairs {
+        let name = String::from_utf8_lossy(name);
+        let Some(param) = registry::lookup(&name) else {
+            return CommandEffect::Read(Frame::Error(format!(
+                "ERR Unknown option or number of arguments for CONFIG SET - '{name}'"
+            )));
+        };
+        if !param.mutable {
+            return failed(param.name, &"can't set immutable config");
+        }
+        if changes.iter().any(|(seen, _)| std::ptr::eq(*seen, param)) {
+            return failed(param.name, &"duplicate parameter");
+        }
+        let Ok(value) = std::str::from_utf8(value) else {
+            return failed(param.name, &"value is not a valid string");
+        };
+        changes.push((param, value));
+    }
 
-    match &key[..] {
-        "appendfsync" => {
-            let mut config = config.write().await;
-            match config.set_fsync_mode(value) {
-                Ok(()) => {
-                    aof.set_fsync_mode(config.fsync_mode);
-                    CommandEffect::Read(Frame::SimpleString("OK".to_string()))
-                }
-                Err(e) => CommandEffect::Read(Frame::Error(format!("ERR {e}"))),
+    match apply_config(store, config, aof, slowlog, &changes).await {
+        Ok(()) => CommandEffect::Read(Frame::SimpleString("OK".into())),
+        Err((name, e)) => failed(name, &e),
+    }
+}
+
+/// Sets `changes` on the running server as one unit. Values are validated on
+/// a copy of the config and each hook runs once; if one fails, those already
+/// run are undone and the parameter that caused it is returned.
+pub async fn apply_config(
+    store: &MemoryStore,
+    config: &Arc<RwLock<AppConfig>>,
+    aof: &LiveAof,
+    slowlog: &SlowLog,
+    changes: &[(&'static Param, &str)],
+) -> Result<(), (&'static str, anyhow::Error)> {
+    let mut config = config.write().await;
+    let mut next = config.clone();
+    for (param, value) in changes {
+        param.set(&mut next, value).map_err(|e| (param.name, e))?;
+    }
+
+    let mut hooks: Vec<(Hook, &'static str)> = Vec::new();
+    for (param, _) in changes {
+        if let Some(hook) = param.hook
+            && !hooks.iter().any(|(seen, _)| *seen == hook)
+        {
+            hooks.push((hook, param.name));
+        }
+    }
+    for (i, (hook, name)) in hooks.iter().enumerate() {
+        if let Err(e) = apply_hook(*hook, &config, &next, store, aof, slowlog).await {
+            for (done, _) in hooks[..i].iter().rev() {
+                let _ = apply_hook(*done, &next, &config, store, aof, slowlog).await;
             }
+            return Err((name, e));
         }
-        "appendonly" => {
-            let mut config = config.write().await;
-            match value.parse::<bool>() {
-                Ok(v) => {
-                    config.append_only = v;
-                    CommandEffect::Read(Frame::SimpleString("OK".into()))
+    }
+
+    *config = next;
+    Ok(())
+}
+
+/// Brings the running server from `from` to `to` for the parameters behind
+/// `hook`.
+async fn apply_hook(
+    hook: Hook,
+    from: &AppConfig,
+    to: &AppConfig,
+    store: &MemoryStore,
+    aof: &LiveAof,
+    slowlog: &SlowLog,
+) -> anyhow::Result<()> {
+    match hook {
+        Hook::Persistence => {
+            if !to.append_only {
+                if from.append_only {
+                    aof.stop().await;
                 }
-                Err(e) => CommandEffect::Read(Frame::Error(format!("ERR {e}"))),
+            } else if !from.append_only || to.aof_path != from.aof_path {
+                aof.start(to.aof_path.clone(), to.fsync_mode, store).await?;
             }
         }
-        "appendfilename" => {
-            let mut config = config.write().await;
-            if value.is_empty() {
-                CommandEffect::Read(Frame::Error("ERR empty filename".into()))
-            } else {
-                config.aof_path = config.data_dir.join(value);
-                CommandEffect::Read(Frame::SimpleString("OK".into()))
-            }
+        Hook::Fsync => aof.set_fsync_mode(to.fsync_mode).await,
+        Hook::SlowLog => {
+            slowlog.set_slower_than(to.slowlog_log_slower_than);
+            slowlog.set_max_len(to.slowlog_max_len);
         }
-        _ => CommandEffect::Read(Frame::Error("ERR unknown configuration option".into())),
+        Hook::Logging => logging::configure(to)?,
+        Hook::Tracing => telemetry::configure(to)?,
     }
+    Ok(())
+}
+
+pub async fn config_resetstat(
+    store: &MemoryStore,
+    clients: &ClientRegistry,
+    command_stats: &CommandStats,
+) -> CommandEffect {
+    store.reset_stats();
+    clients.reset_stats();
+    command_stats.reset();
+    CommandEffect::Read(Frame::SimpleString("OK".into()))
 }
 
 pub async fn config_rewrite(config: &Arc<RwLock<AppConfig>>) -> CommandEffect {
@@ -133,9 +374,296 @@ pub async fn config_rewrite(config: &Arc<RwLock<AppConfig>>) -> CommandEffect {
     }
 }
 
+pub async fn memory_stats(store: &MemoryStore) -> CommandEffect {
+    let stats = store.memory_stats().await;
+    let overhead = stats.used.saturating_sub(stats.dataset);
+    let mut fields = vec![
+        ("peak.allocated", Frame::Integer(stats.peak as i64)),
+        ("total.allocated", Frame::Integer(stats.used as i64)),
+        ("overhead.total", Frame::Integer(overhead as i64)),
+        (
+            "overhead.hashtable.main",
+            Frame::Integer(stats.hashtable as i64),
+        ),
+        ("keys.count", Frame::Integer(stats.keys as i64)),
+        (
+            "keys.bytes-per-key",
+            Frame::Integer(stats.used.checked_div(stats.keys).unwrap_or(0) as i64),
+        ),
+        ("dataset.bytes", Frame::Integer(stats.dataset as i64)),
+        (
+            "dataset.percentage",
+            Frame::BulkString(format!("{:.2}", percentage(stats.dataset, stats.used)).into()),
+        ),
+        (
+            "peak.percentage",
+            Frame::BulkString(format!("{:.2}", percentage(stats.used, stats.peak)).into()),
+        ),
+    ];
+    if let (Some(allocated), Some(peak)) = (alloc::allocated_bytes(), alloc::peak_allocated_bytes())
+    {
+        fields.push(("allocator.allocated", Frame::Integer(allocated as i64)));
+        fields.push(("allocator.peak", Frame::Integer(peak as i64)));
+    }
+
+    let frames = fields
+        .into_iter()
+        .flat_map(|(name, value)| [Frame::BulkString(name.into()), value])
+        .collect();
+    CommandEffect::Read(Frame::Array(frames))
+}
+
+pub async fn memory_doctor(store: &MemoryStore, config: &Arc<RwLock<AppConfig>>) -> CommandEffect {
+    let stats = store.memory_stats().await;
+    let maxmemory = config.read().await.maxmemory;
+    CommandEffect::Read(Frame::BulkString(memory_report(&stats, maxmemory).into()))
+}
+
+/// Below this much data the ratios the doctor looks at are meaningless.
+const DOCTOR_MIN_MEMORY: usize = 5 * 1024 * 1024;
+
+fn memory_report(stats: &MemoryStats, maxmemory: u64) -> String {
+    if stats.used < DOCTOR_MIN_MEMORY {
+        return "This instance is empty or is using very little memory, \
+                so there is nothing to diagnose yet."
+            .into();
+    }
+
+    let mut issues = Vec::new();
+    if stats.peak * 2 > stats.used * 3 {
+        issues.push(
+            " * Peak memory: In the past this instance used more than 150% the memory \
+             that is currently using. Freed memory may not have been returned to the \
+             operating system yet.",
+        );
+    }
+    if stats.used.saturating_sub(stats.dataset) > stats.dataset {
+        issues.push(
+            " * High per-key overhead: Bookkeeping costs more than the keys and values \
+             themselves. Many small keys could be packed into fewer larger values.",
+        );
+    }
+    if maxmemory > 0 && stats.used as u64 * 10 > maxmemory * 9 {
+        issues.push(
+            " * Close to maxmemory: Used memory is above 90% of 'maxmemory'; writes \
+             will soon trigger evictions or be rejected.",
+        );
+    }
+
+    if issues.is_empty() {
+        "No memory issues detected in this instance.".into()
+    } else {
+        format!(
+            "Some memory issues were detected:\n\n{}\n",
+            issues.join("\n\n")
+        )
+    }
+}
+
+fn percentage(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+pub async fn client_list(
+    clients: &ClientRegistry,
+    kind: Option<ClientType>,
+    ids: &[u64],
+) -> CommandEffect {
+    let mut out = String::new();
+    if kind.is_none_or(|kind| kind == ClientType::Normal) {
+        for client in clients.list() {
+            if ids.is_empty() || ids.contains(&client.id) {
+                out.push_str(&client.info_line());
+                out.push('\n');
+            }
+        }
+    }
+    CommandEffect::Read(Frame::BulkString(out.into()))
+}
+
+pub async fn client_info(client: &ClientHandle) -> CommandEffect {
+    CommandEffect::Read(Frame::BulkString(
+        format!("{}\n", client.info_line()).into(),
+    ))
+}
+
+pub async fn client_id(client: &ClientHandle) -> CommandEffect {
+    CommandEffect::Read(Frame::Integer(client.id as i64))
+}
+
+pub async fn client_setname(client: &ClientHandle, name: Bytes) -> CommandEffect {
+    client.set_name((!name.is_empty()).then_some(name));
+    CommandEffect::Read(Frame::SimpleString("OK".into()))
+}
+
+pub async fn client_getname(client: &ClientHandle) -> CommandEffect {
+    CommandEffect::Read(match client.name() {
+        Some(name) => Frame::BulkString(name),
+        None => Frame::NullBulkString,
+    })
+}
+
+pub async fn client_kill(
+    clients: &ClientRegistry,
+    client: &ClientHandle,
+    filter: &ClientKillFilter,
+) -> CommandEffect {
+    let killed = clients.kill(filter, client.id);
+    CommandEffect::Read(match (filter.legacy, killed) {
+        (true, 0) => Frame::Error("ERR No such client".into()),
+        (true, _) => Frame::SimpleString("OK".into()),
+        (false, n) => Frame::Integer(n as i64),
+    })
+}
+
+pub async fn client_pause(
+    clients: &ClientRegistry,
+    timeout_ms: u64,
+    mode: PauseMode,
+) -> CommandEffect {
+    clients.pause(Duration::from_millis(timeout_ms), mode);
+    CommandEffect::Read(Frame::SimpleString("OK".into()))
+}
+
+pub async fn client_unpause(clients: &ClientRegistry) -> CommandEffect {
+    clients.unpause();
+    CommandEffect::Read(Frame::SimpleString("OK".into()))
+}
+
+pub async fn client_no_evict(client: &ClientHandle, on: bool) -> CommandEffect {
+    client.set_no_evict(on);
+    CommandEffect::Read(Frame::SimpleString("OK".into()))
+}
+
+/// The OK is only delivered for `ON`; the session drops it otherwise.
+pub async fn client_reply(client: &ClientHandle, mode: ReplyMode) -> CommandEffect {
+    client.set_reply_mode(mode);
+    CommandEffect::Read(Frame::SimpleString("OK".into()))
+}
+
+/// COMMAND and COMMAND INFO; unknown names get a null entry.
+pub async fn command_info(names: &[Bytes]) -> CommandEffect {
+    let entries = if names.is_empty() {
+        table::commands()
+            .into_iter()
+            .map(CommandSpec::info)
+            .collect()
+    } else {
+        names
+            .iter()
+            .map(|name| table::lookup(name).map_or(Frame::NullBulkString, CommandSpec::info))
+            .collect()
+    };
+    CommandEffect::Read(Frame::Array(entries))
+}
+
+/// Each subcommand of `command` with its summary, in the layout of Redis's
+/// HELP replies.
+pub async fn help(command: &str) -> CommandEffect {
+    let Some(spec) = table::find(command.as_bytes()) else {
+        return CommandEffect::Read(Frame::Error("ERR unknown command".into()));
+    };
+    let mut lines = vec![format!(
+        "{} <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+        spec.name.to_ascii_uppercase()
+    )];
+    for sub in spec.subcommands {
+        let (_, name) = sub.name.split_once('|').unwrap_or(("", sub.name));
+        lines.push(name.to_ascii_uppercase());
+        lines.push(format!("    {}", sub.summary));
+    }
+    CommandEffect::Read(Frame::Array(
+        lines.into_iter().map(Frame::SimpleString).collect(),
+    ))
+}
+
+pub async fn command_count() -> CommandEffect {
+    CommandEffect::Read(Frame::Integer(table::commands().len() as i64))
+}
+
+/// Name and docs pairs; unknown names are left out.
+pub async fn command_docs(names: &[Bytes]) -> CommandEffect {
+    let specs: Vec<&CommandSpec> = if names.is_empty() {
+        table::commands()
+    } else {
+        names
+            .iter()
+            .filter_map(|name| table::lookup(name))
+            .collect()
+    };
+    CommandEffect::Read(Frame::Array(
+        specs
+            .into_iter()
+            .flat_map(|spec| [Frame::BulkString(spec.name.into()), spec.docs()])
+            .collect(),
+    ))
+}
+
+/// Names of every command and subcommand, optionally filtered.
+pub async fn command_list(filter: Option<&CommandListFilter>) -> CommandEffect {
+    CommandEffect::Read(Frame::Array(
+        table::commands()
+            .into_iter()
+            .flat_map(CommandSpec::with_subcommands)
+            .filter(|spec| filter.is_none_or(|filter| filter.matches(spec)))
+            .map(|spec| Frame::BulkString(spec.name.into()))
+            .collect(),
+    ))
+}
+
+/// The keys `args` would touch, at the positions its table entry gives once
+/// it parses.
+pub async fn command_getkeys(args: &[Bytes]) -> CommandEffect {
+    let error = |msg: &str| CommandEffect::Read(Frame::Error(msg.into()));
+    let Some(spec) = table::resolve(args) else {
+        return error("ERR Invalid command specified");
+    };
+    if !spec.accepts(args.len()) {
+        return error("ERR Invalid number of arguments specified for command");
+    }
+    let frame = Frame::Array(args.iter().cloned().map(Frame::BulkString).collect());
+    if let Err(err) = Command::try_from(frame) {
+        return CommandEffect::Read(err);
+    }
+    let positions = spec.keys.positions(args.len());
+    if positions.is_empty() {
+        return error("ERR The command has no key arguments");
+    }
+    CommandEffect::Read(Frame::Array(
+        positions
+            .into_iter()
+            .map(|i| Frame::BulkString(args[i].clone()))
+            .collect(),
+    ))
+}
+
+pub async fn slowlog_get(slowlog: &SlowLog, count: Option<i64>) -> CommandEffect {
+    let count = match count {
+        None => 10,
+        Some(-1) => usize::MAX,
+        Some(n) => n as usize,
+    };
+    let entries = slowlog.get(count).iter().map(|e| e.to_frame()).collect();
+    CommandEffect::Read(Frame::Array(entries))
+}
+
+pub async fn slowlog_len(slowlog: &SlowLog) -> CommandEffect {
+    CommandEffect::Read(Frame::Integer(slowlog.len() as i64))
+}
+
+pub async fn slowlog_reset(slowlog: &SlowLog) -> CommandEffect {
+    slowlog.reset();
+    CommandEffect::Read(Frame::SimpleString("OK".into()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{LogFormat, LogLevel, TraceExporter};
     use crate::service::handlers::tests::{read_frame, write_frame};
     use crate::store::persistence::record::Record;
     use crate::store::{
@@ -145,11 +673,27 @@ mod tests {
 
     fn make_config() -> Arc<RwLock<AppConfig>> {
         Arc::new(RwLock::new(AppConfig {
+            bind: "127.0.0.1".into(),
+            port: 6379,
             append_only: true,
             aof_path: std::path::PathBuf::from("/tmp/test.aof"),
             fsync_mode: crate::config::FsyncMode::EverySec,
             config_path: std::path::PathBuf::from("/tmp/test.toml"),
             data_dir: std::path::PathBuf::from("/tmp"),
+            maxmemory: 0,
+            maxmemory_policy: crate::config::EvictionPolicy::NoEviction,
+            maxclients: 10000,
+            slowlog_log_slower_than: 10000,
+            slowlog_max_len: 128,
+            watch_config: false,
+            shutdown_timeout: 10,
+            log_level: LogLevel::Notice,
+            log_file: None,
+            log_format: LogFormat::Text,
+            metrics_port: 0,
+            trace_exporter: TraceExporter::None,
+            trace_file: std::path::PathBuf::from("traces.jsonl"),
+            trace_endpoint: "http://127.0.0.1:4318/v1/traces".into(),
         }))
     }
 
@@ -172,7 +716,7 @@ mod tests {
             .set(
                 Bytes::from_static(b"k"),
                 Entry {
-                    value: Bytes::from_static(b"v"),
+                    value: Bytes::from_static(b"v").into(),
                     exp: Expiry::None,
                 },
             )
@@ -188,7 +732,7 @@ mod tests {
             .set(
                 Bytes::from_static(b"k"),
                 Entry {
-                    value: Bytes::from_static(b"v"),
+                    value: Bytes::from_static(b"v").into(),
                     exp: Expiry::None,
                 },
             )
@@ -199,67 +743,186 @@ mod tests {
         assert!(store.is_empty().await);
     }
 
-    #[tokio::test]
-    async fn info_contains_expected_fields() {
-        let store = MemoryStore::new();
-        let frame = read_frame(info(&store).await);
+    async fn info_text(store: &MemoryStore, sections: &[&str]) -> String {
+        let aof = LiveAof::disabled();
+        let sections: Vec<String> = sections.iter().map(|s| s.to_string()).collect();
+        let frame = read_frame(
+            info(
+                store,
+                &make_config(),
+                &aof,
+                &ClientRegistry::new(),
+                &CommandStats::new(),
+                &sections,
+            )
+            .await,
+        );
         let Frame::BulkString(data) = frame else {
             panic!("expected bulk string")
         };
-        let info_str = std::str::from_utf8(&data).unwrap();
-        assert!(info_str.contains("yars_version"));
-        assert!(info_str.contains("db_keys"));
-        assert!(info_str.contains("used_memory"));
-        assert!(info_str.contains("uptime_seconds"));
-        assert!(info_str.contains("total_commands"));
+        String::from_utf8(data.to_vec()).unwrap()
     }
 
     #[tokio::test]
-    async fn config_get_star_returns_all() {
-        let config = make_config();
-        let frame = read_frame(config_get(&config, Bytes::from_static(b"*")).await);
-        let Frame::Array(items) = frame else {
+    async fn info_default_sections() {
+        let store = MemoryStore::new();
+        store
+            .set(
+                Bytes::from_static(b"k"),
+                Entry {
+                    value: Bytes::from_static(b"v").into(),
+                    exp: Expiry::None,
+                },
+            )
+            .await;
+        let info = info_text(&store, &[]).await;
+        assert!(info.starts_with("# Server\r\nredis_version:7.2.0\r\nyars_version:"));
+        for header in [
+            "# Clients",
+            "# Memory",
+            "# Persistence",
+            "# Stats",
+            "# Keyspace",
+        ] {
+            assert!(info.contains(&format!("\r\n\r\n{header}\r\n")), "{header}");
+        }
+        assert!(!info.contains("# Commandstats"));
+        assert!(info.contains("aof_enabled:0\r\n"));
+        assert!(info.contains("db0:keys=1,expires=0,avg_ttl=0\r\n"));
+    }
+
+    #[tokio::test]
+    async fn info_keyspace_reports_average_ttl() {
+        let store = MemoryStore::new();
+        let now = get_current_millis();
+        for (key, ttl) in [(&b"a"[..], 100_000), (b"b", 300_000)] {
+            store
+                .set(
+                    Bytes::copy_from_slice(key),
+                    Entry {
+                        value: Bytes::from_static(b"v").into(),
+                        exp: Expiry::At(now + ttl),
+                    },
+                )
+                .await;
+        }
+        store
+            .set(
+                Bytes::from_static(b"c"),
+                Entry {
+                    value: Bytes::from_static(b"v").into(),
+                    exp: Expiry::None,
+                },
+            )
+            .await;
+
+        let info = info_text(&store, &["keyspace"]).await;
+        let avg_ttl: u64 = info
+            .split("avg_ttl=")
+            .nth(1)
+            .and_then(|rest| rest.trim_end().parse().ok())
+            .unwrap();
+        assert!(info.contains("db0:keys=3,expires=2,"));
+        assert!((199_000..=200_000).contains(&avg_ttl), "{avg_ttl}");
+    }
+
+    #[tokio::test]
+    async fn info_selects_sections() {
+        let store = MemoryStore::new();
+        let info = info_text(&store, &["memory"]).await;
+        assert!(info.starts_with("# Memory\r\nused_memory:"));
+        assert!(!info.contains("# Server"));
+
+        // An empty keyspace section still has its header.
+        let info = info_text(&store, &["keyspace", "stats"]).await;
+        assert!(info.starts_with("# Stats\r\n"));
+        assert!(info.ends_with("# Keyspace\r\n"));
+
+        let info = info_text(&store, &["everything"]).await;
+        assert!(info.contains("# Commandstats\r\n"));
+        assert!(info_text(&store, &["nosuchsection"]).await.is_empty());
+    }
+
+    #[test]
+    fn bytes_to_human_units() {
+        assert_eq!(bytes_to_human(1023), "1023B");
+        assert_eq!(bytes_to_human(1536), "1.50K");
+        assert_eq!(bytes_to_human(3 * 1024 * 1024), "3.00M");
+    }
+
+    async fn get_config(config: &Arc<RwLock<AppConfig>>, patterns: &[&str]) -> Vec<Frame> {
+        let patterns: Vec<Bytes> = patterns
+            .iter()
+            .map(|p| Bytes::from(p.to_string()))
+            .collect();
+        let Frame::Array(items) = read_frame(config_get(config, &patterns).await) else {
             panic!("expected array")
         };
-        assert!(items.len() >= 6);
+        items
+    }
+
+    async fn set_config(config: &Arc<RwLock<AppConfig>>, pairs: &[(&str, &str)]) -> Frame {
+        let pairs: Vec<(Bytes, Bytes)> = pairs
+            .iter()
+            .map(|(k, v)| (Bytes::from(k.to_string()), Bytes::from(v.to_string())))
+            .collect();
+        read_frame(
+            config_set(
+                &MemoryStore::new(),
+                config,
+                &LiveAof::disabled(),
+                &SlowLog::new(10000, 128),
+                &pairs,
+            )
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn config_get_star_returns_all() {
+        let config = make_config();
+        let items = get_config(&config, &["*"]).await;
+        assert_eq!(items.len(), 2 * registry::PARAMS.len());
     }
 
     #[tokio::test]
     async fn config_get_specific_key() {
         let config = make_config();
-        let frame = read_frame(config_get(&config, Bytes::from_static(b"appendonly")).await);
-        let Frame::Array(items) = frame else {
-            panic!("expected array")
-        };
+        let items = get_config(&config, &["APPENDONLY"]).await;
         assert_eq!(items.len(), 2);
         assert_eq!(items[0], Frame::BulkString("appendonly".into()));
-        assert_eq!(items[1], Frame::BulkString("true".into()));
+        assert_eq!(items[1], Frame::BulkString("yes".into()));
+    }
+
+    #[tokio::test]
+    async fn config_get_globs_and_multiple_patterns() {
+        let config = make_config();
+        let names: Vec<Frame> = get_config(&config, &["maxmemory*", "port", "max*"])
+            .await
+            .into_iter()
+            .step_by(2)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                Frame::BulkString("port".into()),
+                Frame::BulkString("maxmemory".into()),
+                Frame::BulkString("maxmemory-policy".into()),
+                Frame::BulkString("maxclients".into()),
+            ]
+        );
     }
 
     #[tokio::test]
     async fn config_get_unknown_returns_empty() {
         let config = make_config();
-        let frame = read_frame(config_get(&config, Bytes::from_static(b"unknown")).await);
-        let Frame::Array(items) = frame else {
-            panic!("expected array")
-        };
-        assert!(items.is_empty());
+        assert!(get_config(&config, &["unknown"]).await.is_empty());
     }
 
     #[tokio::test]
     async fn config_set_fsync_mode_ok() {
         let config = make_config();
-        let aof: Arc<dyn crate::store::persistence::aof::Aof> =
-            Arc::new(crate::store::persistence::aof::NoopAof);
-        let frame = read_frame(
-            config_set(
-                &config,
-                &aof,
-                Bytes::from_static(b"appendfsync"),
-                Bytes::from_static(b"no"),
-            )
-            .await,
-        );
+        let frame = set_config(&config, &[("appendfsync", "no")]).await;
         assert_eq!(frame, Frame::SimpleString("OK".into()));
         let cfg = config.read().await;
         assert!(matches!(cfg.fsync_mode, crate::config::FsyncMode::No));
@@ -268,17 +931,7 @@ mod tests {
     #[tokio::test]
     async fn config_set_appendonly_ok() {
         let config = make_config();
-        let aof: Arc<dyn crate::store::persistence::aof::Aof> =
-            Arc::new(crate::store::persistence::aof::NoopAof);
-        let frame = read_frame(
-            config_set(
-                &config,
-                &aof,
-                Bytes::from_static(b"appendonly"),
-                Bytes::from_static(b"false"),
-            )
-            .await,
-        );
+        let frame = set_config(&config, &[("appendonly", "false")]).await;
         assert_eq!(frame, Frame::SimpleString("OK".into()));
         assert!(!config.read().await.append_only);
     }
@@ -286,17 +939,124 @@ mod tests {
     #[tokio::test]
     async fn config_set_unknown_returns_error() {
         let config = make_config();
-        let aof: Arc<dyn crate::store::persistence::aof::Aof> =
-            Arc::new(crate::store::persistence::aof::NoopAof);
-        let frame = read_frame(
-            config_set(
-                &config,
-                &aof,
-                Bytes::from_static(b"unknown"),
-                Bytes::from_static(b"v"),
+        let frame = set_config(&config, &[("unknown", "v")]).await;
+        assert!(matches!(frame, Frame::Error(_)));
+    }
+
+    #[tokio::test]
+    async fn config_set_multiple_pairs_is_atomic() {
+        let config = make_config();
+        let frame = set_config(&config, &[("maxclients", "5"), ("maxmemory", "1mb")]).await;
+        assert_eq!(frame, Frame::SimpleString("OK".into()));
+        assert_eq!(config.read().await.maxclients, 5);
+        assert_eq!(config.read().await.maxmemory, 1 << 20);
+
+        let frame = set_config(&config, &[("maxclients", "7"), ("maxmemory", "lots")]).await;
+        let Frame::Error(msg) = frame else {
+            panic!("expected error");
+        };
+        assert!(msg.contains("'maxmemory'"));
+        assert_eq!(config.read().await.maxclients, 5);
+
+        for param in ["port", "logfile", "trace-file"] {
+            let frame = set_config(&config, &[(param, "/tmp/x")]).await;
+            assert!(matches!(frame, Frame::Error(msg) if msg.contains("immutable")));
+        }
+        let frame = set_config(&config, &[("maxclients", "1"), ("MAXCLIENTS", "2")]).await;
+        assert!(matches!(frame, Frame::Error(msg) if msg.contains("duplicate")));
+        assert_eq!(config.read().await.maxclients, 5);
+    }
+
+    #[tokio::test]
+    async fn config_resetstat_clears_counters() {
+        let store = MemoryStore::new();
+        let clients = ClientRegistry::new();
+        let stats = CommandStats::new();
+        store.increment_commands();
+        stats.record("get", Duration::ZERO, false);
+        let frame = read_frame(config_resetstat(&store, &clients, &stats).await);
+        assert_eq!(frame, Frame::SimpleString("OK".into()));
+        assert_eq!(store.total_commands(), 0);
+        assert!(stats.snapshot().is_empty());
+    }
+
+    #[tokio::test]
+    async fn help_lists_every_subcommand() {
+        let Frame::Array(lines) = read_frame(help("slowlog").await) else {
+            panic!("expected array");
+        };
+        let lines: Vec<String> = lines
+            .into_iter()
+            .map(|line| match line {
+                Frame::SimpleString(line) => line,
+                other => panic!("expected status, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            lines[0],
+            "SLOWLOG <subcommand> [<arg> [value] [opt] ...]. Subcommands are:"
+        );
+        for name in ["GET", "HELP", "LEN", "RESET"] {
+            assert!(lines.iter().any(|line| line == name), "{name}");
+        }
+        assert_eq!(lines[1..].len(), 8);
+        assert!(lines[2].starts_with("    "));
+    }
+
+    #[tokio::test]
+    async fn memory_stats_reports_dataset_and_overhead() {
+        let store = MemoryStore::new();
+        store
+            .set(
+                Bytes::from_static(b"k"),
+                Entry {
+                    value: Bytes::from_static(b"v").into(),
+                    exp: Expiry::None,
+                },
             )
-            .await,
+            .await;
+        let Frame::Array(fields) = read_frame(memory_stats(&store).await) else {
+            panic!("expected array");
+        };
+        let field = |name: &str| {
+            fields
+                .chunks(2)
+                .find(|pair| pair[0] == Frame::BulkString(name.to_string().into()))
+                .map(|pair| pair[1].clone())
+        };
+        let used = crate::store::memory::entry_memory(1, 1) as i64;
+        assert_eq!(field("keys.count"), Some(Frame::Integer(1)));
+        assert_eq!(field("dataset.bytes"), Some(Frame::Integer(2)));
+        assert_eq!(field("total.allocated"), Some(Frame::Integer(used)));
+        assert_eq!(field("overhead.total"), Some(Frame::Integer(used - 2)));
+        assert_eq!(field("allocator.allocated"), None);
+    }
+
+    #[test]
+    fn memory_report_flags_issues() {
+        let small = MemoryStats::default();
+        assert!(memory_report(&small, 0).contains("very little memory"));
+
+        let healthy = MemoryStats {
+            keys: 1000,
+            used: 8 << 20,
+            peak: 8 << 20,
+            dataset: 6 << 20,
+            hashtable: 1 << 20,
+        };
+        assert_eq!(
+            memory_report(&healthy, 0),
+            "No memory issues detected in this instance."
         );
-        assert!(matches!(frame, Frame::Error(_)));
+
+        let troubled = MemoryStats {
+            peak: 16 << 20,
+            dataset: 1 << 20,
+            ..healthy
+        };
+        let report = memory_report(&troubled, 8 << 20);
+        assert!(report.contains("Peak memory"));
+        assert!(report.contains("High per-key overhead"));
+        assert!(report.contains("Close to maxmemory"));
     }
 }
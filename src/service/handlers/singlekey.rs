@@ -2,39 +2,94 @@ use crate::{
     protocol::resp::Frame,
     service::handlers::CommandEffect,
     store::{
-        ops,
+        memory::MemoryStore,
+        ops::{
+            self,
+            bitmap::{self, BitRange, BitfieldOp},
+            geo::{self, GeoQuery, GeoUnit},
+            hyperloglog::{self, PfDebugReply, PfDebugSub},
+        },
         traits::Store,
-        types::{Entry, Expiry},
+        types::{Entry, Expiry, SetCondition},
     },
 };
 use tokio_util::bytes::Bytes;
 
 pub async fn get(store: &impl Store, key: Bytes) -> CommandEffect {
-    match store.get(&key).await {
-        Some(entry) => CommandEffect::Read(Frame::BulkString(entry.value)),
+    match ops::get_string(store, &key).await {
+        Ok(Some(value)) => CommandEffect::Read(Frame::BulkString(value)),
+        Ok(None) => CommandEffect::Read(Frame::NullBulkString),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+/// The payload of an entry a string command read or wrote, which the op has
+/// already checked is a string.
+fn string_of(entry: &Entry) -> Bytes {
+    entry.value.as_string().cloned().unwrap_or_default()
+}
+
+/// Strings and sorted sets both keep their size up to date, so `SAMPLES` is
+/// accepted for compatibility but has nothing to sample.
+pub async fn memory_usage(store: &MemoryStore, key: Bytes) -> CommandEffect {
+    match store.memory_usage(&key).await {
+        Some(bytes) => CommandEffect::Read(Frame::Integer(bytes as i64)),
         None => CommandEffect::Read(Frame::NullBulkString),
     }
 }
 
-pub async fn set(store: &impl Store, key: Bytes, entry: Entry) -> CommandEffect {
-    let resolved = store.set(key.clone(), entry).await;
-    CommandEffect::from_set(Frame::SimpleString("OK".into()), key, resolved)
+pub async fn set(
+    store: &impl Store,
+    key: Bytes,
+    entry: Entry,
+    cond: SetCondition,
+    get: bool,
+) -> CommandEffect {
+    let (existing, resolved) = match ops::set(store, key.clone(), entry, cond, get).await {
+        Ok(outcome) => outcome,
+        Err(msg) => return CommandEffect::Read(Frame::Error(msg.into())),
+    };
+    let frame = match (get, existing, &resolved) {
+        (true, Some(e), _) => Frame::BulkString(string_of(&e)),
+        (true, None, _) | (false, _, None) => Frame::NullBulkString,
+        (false, _, Some(_)) => Frame::SimpleString("OK".into()),
+    };
+    match resolved {
+        Some(resolved) => CommandEffect::from_set(frame, key, resolved),
+        None => CommandEffect::Read(frame),
+    }
 }
 
 pub async fn getdel(store: &impl Store, key: Bytes) -> CommandEffect {
     match ops::getdel(store, key.clone()).await {
-        Some(entry) => CommandEffect::Write(
-            Frame::BulkString(entry.value),
+        Ok(Some(entry)) => CommandEffect::Write(
+            Frame::BulkString(string_of(&entry)),
             crate::store::persistence::record::Record::Del { keys: vec![key] },
         ),
-        None => CommandEffect::Read(Frame::NullBulkString),
+        Ok(None) => CommandEffect::Read(Frame::NullBulkString),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn getex(store: &impl Store, key: Bytes, exp: Expiry) -> CommandEffect {
+    let touches_ttl = !matches!(exp, Expiry::Keep);
+    match ops::getex(store, key.clone(), exp).await {
+        Ok(Some(resolved)) if touches_ttl => {
+            CommandEffect::from_set(Frame::BulkString(string_of(&resolved)), key, resolved)
+        }
+        Ok(Some(entry)) => CommandEffect::Read(Frame::BulkString(string_of(&entry))),
+        Ok(None) => CommandEffect::Read(Frame::NullBulkString),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
     }
 }
 
 pub async fn getset(store: &impl Store, key: Bytes, entry: Entry) -> CommandEffect {
-    let (existing, resolved) = ops::getset(store, key.clone(), entry).await;
+    let (existing, resolved) = match ops::getset(store, key.clone(), entry).await {
+        Ok(outcome) => outcome,
+        Err(msg) => return CommandEffect::Read(Frame::Error(msg.into())),
+    };
     let frame = match existing {
-        Some(e) => Frame::BulkString(e.value),
+        Some(e) => Frame::BulkString(string_of(&e)),
         None => Frame::NullBulkString,
     };
     CommandEffect::from_set(frame, key, resolved)
@@ -50,7 +105,7 @@ pub async fn setnx(store: &impl Store, key: Bytes, entry: Entry) -> CommandEffec
 pub async fn incr(store: &impl Store, key: Bytes) -> CommandEffect {
     match ops::incr(store, key.clone()).await {
         Ok(resolved) => {
-            let value = std::str::from_utf8(&resolved.value)
+            let value = std::str::from_utf8(&string_of(&resolved))
                 .ok()
                 .and_then(|s| s.parse::<i64>().ok())
                 .unwrap();
@@ -63,7 +118,20 @@ pub async fn incr(store: &impl Store, key: Bytes) -> CommandEffect {
 pub async fn decr(store: &impl Store, key: Bytes) -> CommandEffect {
     match ops::decr(store, key.clone()).await {
         Ok(resolved) => {
-            let value = std::str::from_utf8(&resolved.value)
+            let value = std::str::from_utf8(&string_of(&resolved))
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok())
+                .unwrap();
+            CommandEffect::from_set(Frame::Integer(value), key, resolved)
+        }
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn incrby(store: &impl Store, key: Bytes, delta: i64) -> CommandEffect {
+    match ops::incr_by(store, key.clone(), delta).await {
+        Ok(resolved) => {
+            let value = std::str::from_utf8(&string_of(&resolved))
                 .ok()
                 .and_then(|s| s.parse::<i64>().ok())
                 .unwrap();
@@ -73,13 +141,241 @@ pub async fn decr(store: &impl Store, key: Bytes) -> CommandEffect {
     }
 }
 
+pub async fn decrby(store: &impl Store, key: Bytes, delta: i64) -> CommandEffect {
+    match delta.checked_neg() {
+        Some(delta) => incrby(store, key, delta).await,
+        None => CommandEffect::Read(Frame::Error("ERR decrement would overflow".into())),
+    }
+}
+
+pub async fn incrbyfloat(store: &impl Store, key: Bytes, incr: f64) -> CommandEffect {
+    match ops::incr_by_float(store, key.clone(), incr).await {
+        Ok(resolved) => {
+            CommandEffect::from_set(Frame::BulkString(string_of(&resolved)), key, resolved)
+        }
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn getrange(store: &impl Store, key: Bytes, start: i64, end: i64) -> CommandEffect {
+    match ops::getrange(store, key, start, end).await {
+        Ok(value) => CommandEffect::Read(Frame::BulkString(value)),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn setrange(
+    store: &impl Store,
+    key: Bytes,
+    offset: usize,
+    value: Bytes,
+) -> CommandEffect {
+    match ops::setrange(store, key.clone(), offset, value).await {
+        Ok(Some(resolved)) => {
+            CommandEffect::from_set(Frame::Integer(resolved.value.len() as i64), key, resolved)
+        }
+        Ok(None) => strlen(store, key).await,
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn setbit(store: &impl Store, key: Bytes, offset: usize, bit: bool) -> CommandEffect {
+    match bitmap::setbit(store, key.clone(), offset, bit).await {
+        Ok((previous, resolved)) => {
+            CommandEffect::from_set(Frame::Integer(previous as i64), key, resolved)
+        }
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn getbit(store: &impl Store, key: Bytes, offset: usize) -> CommandEffect {
+    match bitmap::getbit(store, key, offset).await {
+        Ok(bit) => CommandEffect::Read(Frame::Integer(bit as i64)),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn bitcount(store: &impl Store, key: Bytes, range: Option<BitRange>) -> CommandEffect {
+    match bitmap::bitcount(store, key, range).await {
+        Ok(count) => CommandEffect::Read(Frame::Integer(count)),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn bitpos(
+    store: &impl Store,
+    key: Bytes,
+    bit: bool,
+    range: Option<BitRange>,
+) -> CommandEffect {
+    match bitmap::bitpos(store, key, bit, range).await {
+        Ok(pos) => CommandEffect::Read(Frame::Integer(pos)),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn bitfield(store: &impl Store, key: Bytes, ops: &[BitfieldOp]) -> CommandEffect {
+    let (replies, resolved) = match bitmap::bitfield(store, key.clone(), ops).await {
+        Ok(outcome) => outcome,
+        Err(msg) => return CommandEffect::Read(Frame::Error(msg.into())),
+    };
+    let frame = Frame::Array(
+        replies
+            .into_iter()
+            .map(|reply| match reply {
+                Some(value) => Frame::Integer(value),
+                None => Frame::NullBulkString,
+            })
+            .collect(),
+    );
+    match resolved {
+        Some(resolved) => CommandEffect::from_set(frame, key, resolved),
+        None => CommandEffect::Read(frame),
+    }
+}
+
+pub async fn pfadd(store: &impl Store, key: Bytes, elements: &[Bytes]) -> CommandEffect {
+    match hyperloglog::pfadd(store, key.clone(), elements).await {
+        Ok(Some(resolved)) => CommandEffect::from_set(Frame::Integer(1), key, resolved),
+        Ok(None) => CommandEffect::Read(Frame::Integer(0)),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn pfdebug(store: &impl Store, sub: PfDebugSub, key: Bytes) -> CommandEffect {
+    match hyperloglog::pfdebug(store, sub, key.clone()).await {
+        Ok(PfDebugReply::Registers(registers)) => CommandEffect::Read(Frame::Array(
+            registers
+                .into_iter()
+                .map(|value| Frame::Integer(value as i64))
+                .collect(),
+        )),
+        Ok(PfDebugReply::Decoded(ops)) => CommandEffect::Read(Frame::SimpleString(ops)),
+        Ok(PfDebugReply::Encoding(encoding)) => {
+            CommandEffect::Read(Frame::SimpleString(encoding.as_str().into()))
+        }
+        Ok(PfDebugReply::ToDense(Some(resolved))) => {
+            CommandEffect::from_set(Frame::Integer(1), key, resolved)
+        }
+        Ok(PfDebugReply::ToDense(None)) => CommandEffect::Read(Frame::Integer(0)),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn geoadd(
+    store: &impl Store,
+    key: Bytes,
+    cond: SetCondition,
+    ch: bool,
+    items: &[(f64, f64, Bytes)],
+) -> CommandEffect {
+    match geo::geoadd(store, key.clone(), cond, ch, items).await {
+        Ok((count, Some(resolved))) => {
+            CommandEffect::from_set(Frame::Integer(count), key, resolved)
+        }
+        Ok((count, None)) => CommandEffect::Read(Frame::Integer(count)),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn geopos(store: &impl Store, key: Bytes, members: &[Bytes]) -> CommandEffect {
+    match geo::geopos(store, &key, members).await {
+        Ok(positions) => CommandEffect::Read(Frame::Array(
+            positions
+                .into_iter()
+                .map(|pos| match pos {
+                    Some((lon, lat)) => coord_frame(lon, lat),
+                    None => Frame::NullArray,
+                })
+                .collect(),
+        )),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn geodist(
+    store: &impl Store,
+    key: Bytes,
+    member1: &Bytes,
+    member2: &Bytes,
+    unit: GeoUnit,
+) -> CommandEffect {
+    match geo::geodist(store, &key, member1, member2).await {
+        Ok(Some(dist)) => CommandEffect::Read(dist_frame(dist, unit)),
+        Ok(None) => CommandEffect::Read(Frame::NullBulkString),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn geohash(store: &impl Store, key: Bytes, members: &[Bytes]) -> CommandEffect {
+    match geo::geohash(store, &key, members).await {
+        Ok(hashes) => CommandEffect::Read(Frame::Array(
+            hashes
+                .into_iter()
+                .map(|hash| match hash {
+                    Some(hash) => Frame::BulkString(hash.into()),
+                    None => Frame::NullBulkString,
+                })
+                .collect(),
+        )),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
+}
+
+pub async fn geosearch(store: &impl Store, key: Bytes, query: &GeoQuery) -> CommandEffect {
+    let matches = match geo::geosearch(store, &key, query).await {
+        Ok(matches) => matches,
+        Err(msg) => return CommandEffect::Read(Frame::Error(msg.into())),
+    };
+    let plain = !(query.with_dist || query.with_hash || query.with_coord);
+    CommandEffect::Read(Frame::Array(
+        matches
+            .into_iter()
+            .map(|m| {
+                if plain {
+                    return Frame::BulkString(m.member);
+                }
+                let mut item = vec![Frame::BulkString(m.member)];
+                if query.with_dist {
+                    item.push(dist_frame(m.dist, query.unit));
+                }
+                if query.with_hash {
+                    item.push(Frame::Integer(m.hash as i64));
+                }
+                if query.with_coord {
+                    item.push(coord_frame(m.lon, m.lat));
+                }
+                Frame::Array(item)
+            })
+            .collect(),
+    ))
+}
+
+fn coord_frame(lon: f64, lat: f64) -> Frame {
+    Frame::Array(vec![
+        Frame::BulkString(lon.to_string().into()),
+        Frame::BulkString(lat.to_string().into()),
+    ])
+}
+
+fn dist_frame(meters: f64, unit: GeoUnit) -> Frame {
+    Frame::BulkString(format!("{:.4}", meters / unit.meters()).into())
+}
+
 pub async fn strlen(store: &impl Store, key: Bytes) -> CommandEffect {
-    CommandEffect::Read(Frame::Integer(ops::strlen(store, key).await))
+    match ops::strlen(store, key).await {
+        Ok(len) => CommandEffect::Read(Frame::Integer(len)),
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
 }
 
 pub async fn append(store: &impl Store, key: Bytes, value: Bytes) -> CommandEffect {
-    let resolved = ops::append(store, key.clone(), value).await;
-    CommandEffect::from_set(Frame::Integer(resolved.value.len() as i64), key, resolved)
+    match ops::append(store, key.clone(), value).await {
+        Ok(resolved) => {
+            CommandEffect::from_set(Frame::Integer(resolved.value.len() as i64), key, resolved)
+        }
+        Err(msg) => CommandEffect::Read(Frame::Error(msg.into())),
+    }
 }
 
 pub async fn ttl(store: &impl Store, key: Bytes, now: u64) -> CommandEffect {
@@ -122,7 +418,6 @@ pub async fn expire(store: &impl Store, key: Bytes, ttl_ms: u64, now: u64) -> Co
 mod tests {
     use super::*;
     use crate::service::handlers::tests::{entry, read_frame, write_frame};
-    use crate::store::memory::MemoryStore;
     use crate::store::persistence::record::Record;
 
     #[tokio::test]
@@ -145,14 +440,110 @@ mod tests {
     #[tokio::test]
     async fn set_returns_ok_and_mutation() {
         let store = MemoryStore::new();
-        let (frame, record) =
-            write_frame(set(&store, Bytes::from_static(b"k"), entry(b"v", Expiry::None)).await);
+        let (frame, record) = write_frame(
+            set(
+                &store,
+                Bytes::from_static(b"k"),
+                entry(b"v", Expiry::None),
+                SetCondition::Always,
+                false,
+            )
+            .await,
+        );
         assert_eq!(frame, Frame::SimpleString("OK".into()));
         assert!(
             matches!(record, Record::Set { key, value, .. } if key == Bytes::from_static(b"k") && value == Bytes::from_static(b"v"))
         );
     }
 
+    #[tokio::test]
+    async fn set_nx_on_existing_returns_nil_without_write() {
+        let store = MemoryStore::new();
+        store
+            .set(Bytes::from_static(b"k"), entry(b"old", Expiry::None))
+            .await;
+        let frame = read_frame(
+            set(
+                &store,
+                Bytes::from_static(b"k"),
+                entry(b"new", Expiry::None),
+                SetCondition::IfMissing,
+                false,
+            )
+            .await,
+        );
+        assert_eq!(frame, Frame::NullBulkString);
+    }
+
+    #[tokio::test]
+    async fn set_get_returns_previous_value() {
+        let store = MemoryStore::new();
+        store
+            .set(Bytes::from_static(b"k"), entry(b"old", Expiry::None))
+            .await;
+        let (frame, record) = write_frame(
+            set(
+                &store,
+                Bytes::from_static(b"k"),
+                entry(b"new", Expiry::None),
+                SetCondition::Always,
+                true,
+            )
+            .await,
+        );
+        assert_eq!(frame, Frame::BulkString("old".into()));
+        assert!(matches!(record, Record::Set { value, .. } if value == Bytes::from_static(b"new")));
+    }
+
+    #[tokio::test]
+    async fn set_nx_get_on_existing_returns_old_value_without_write() {
+        let store = MemoryStore::new();
+        store
+            .set(Bytes::from_static(b"k"), entry(b"old", Expiry::None))
+            .await;
+        let frame = read_frame(
+            set(
+                &store,
+                Bytes::from_static(b"k"),
+                entry(b"new", Expiry::None),
+                SetCondition::IfMissing,
+                true,
+            )
+            .await,
+        );
+        assert_eq!(frame, Frame::BulkString("old".into()));
+    }
+
+    #[tokio::test]
+    async fn getex_without_options_is_read() {
+        let store = MemoryStore::new();
+        store
+            .set(Bytes::from_static(b"k"), entry(b"v", Expiry::None))
+            .await;
+        let frame = read_frame(getex(&store, Bytes::from_static(b"k"), Expiry::Keep).await);
+        assert_eq!(frame, Frame::BulkString("v".into()));
+    }
+
+    #[tokio::test]
+    async fn getex_with_expiry_records_set() {
+        let store = MemoryStore::new();
+        let far_future = crate::utils::time::get_current_millis() + 1_000_000;
+        store
+            .set(Bytes::from_static(b"k"), entry(b"v", Expiry::None))
+            .await;
+        let (frame, record) =
+            write_frame(getex(&store, Bytes::from_static(b"k"), Expiry::At(far_future)).await);
+        assert_eq!(frame, Frame::BulkString("v".into()));
+        assert!(matches!(record, Record::Set { exp_ms: Some(t), .. } if t == far_future));
+    }
+
+    #[tokio::test]
+    async fn getex_missing() {
+        let store = MemoryStore::new();
+        let frame = read_frame(getex(&store, Bytes::from_static(b"k"), Expiry::None).await);
+        assert_eq!(frame, Frame::NullBulkString);
+    }
+
     #[tokio::test]
     async fn getdel_existing() {
         let store = MemoryStore::new();
@@ -265,6 +656,129 @@ mod tests {
         assert!(matches!(record, Record::Set { key, .. } if key == Bytes::from_static(b"k")));
     }
 
+    #[tokio::test]
+    async fn incrby_and_decrby() {
+        let store = MemoryStore::new();
+        let (frame, _) = write_frame(incrby(&store, Bytes::from_static(b"k"), 10).await);
+        assert_eq!(frame, Frame::Integer(10));
+        let (frame, record) = write_frame(decrby(&store, Bytes::from_static(b"k"), 3).await);
+        assert_eq!(frame, Frame::Integer(7));
+        assert!(matches!(record, Record::Set { value, .. } if value == Bytes::from_static(b"7")));
+    }
+
+    #[tokio::test]
+    async fn decrby_min_overflows() {
+        let store = MemoryStore::new();
+        let frame = read_frame(decrby(&store, Bytes::from_static(b"k"), i64::MIN).await);
+        assert_eq!(frame, Frame::Error("ERR decrement would overflow".into()));
+    }
+
+    #[tokio::test]
+    async fn incrbyfloat_records_resulting_value() {
+        let store = MemoryStore::new();
+        store
+            .set(Bytes::from_static(b"k"), entry(b"10.5", Expiry::None))
+            .await;
+        let (frame, record) = write_frame(incrbyfloat(&store, Bytes::from_static(b"k"), 0.1).await);
+        assert_eq!(frame, Frame::BulkString("10.6".into()));
+        assert!(
+            matches!(record, Record::Set { value, .. } if value == Bytes::from_static(b"10.6"))
+        );
+    }
+
+    #[tokio::test]
+    async fn getrange_reads_slice() {
+        let store = MemoryStore::new();
+        store
+            .set(
+                Bytes::from_static(b"k"),
+                entry(b"Hello World", Expiry::None),
+            )
+            .await;
+        let frame = read_frame(getrange(&store, Bytes::from_static(b"k"), -5, -1).await);
+        assert_eq!(frame, Frame::BulkString("World".into()));
+    }
+
+    #[tokio::test]
+    async fn setrange_returns_new_length() {
+        let store = MemoryStore::new();
+        store
+            .set(
+                Bytes::from_static(b"k"),
+                entry(b"Hello World", Expiry::None),
+            )
+            .await;
+        let (frame, record) = write_frame(
+            setrange(
+                &store,
+                Bytes::from_static(b"k"),
+                6,
+                Bytes::from_static(b"Redis"),
+            )
+            .await,
+        );
+        assert_eq!(frame, Frame::Integer(11));
+        assert!(
+            matches!(record, Record::Set { value, .. } if value == Bytes::from_static(b"Hello Redis"))
+        );
+
+        let frame = read_frame(setrange(&store, Bytes::from_static(b"k"), 100, Bytes::new()).await);
+        assert_eq!(frame, Frame::Integer(11));
+    }
+
+    #[tokio::test]
+    async fn setbit_returns_previous_bit() {
+        let store = MemoryStore::new();
+        let (frame, record) = write_frame(setbit(&store, Bytes::from_static(b"k"), 7, true).await);
+        assert_eq!(frame, Frame::Integer(0));
+        assert!(matches!(record, Record::Set { value, .. } if value == Bytes::from_static(&[1])));
+        let (frame, _) = write_frame(setbit(&store, Bytes::from_static(b"k"), 7, false).await);
+        assert_eq!(frame, Frame::Integer(1));
+    }
+
+    #[tokio::test]
+    async fn getbit_reads_bit() {
+        let store = MemoryStore::new();
+        store
+            .set(Bytes::from_static(b"k"), entry(&[0x40], Expiry::None))
+            .await;
+        let frame = read_frame(getbit(&store, Bytes::from_static(b"k"), 1).await);
+        assert_eq!(frame, Frame::Integer(1));
+    }
+
+    #[tokio::test]
+    async fn bitfield_read_only_program_is_read() {
+        let store = MemoryStore::new();
+        let ops = [BitfieldOp::Get {
+            ty: bitmap::BitfieldType {
+                signed: false,
+                bits: 4,
+            },
+            offset: 0,
+        }];
+        let frame = read_frame(bitfield(&store, Bytes::from_static(b"k"), &ops).await);
+        assert_eq!(frame, Frame::Array(vec![Frame::Integer(0)]));
+    }
+
+    #[tokio::test]
+    async fn bitfield_fail_overflow_replies_nil() {
+        let store = MemoryStore::new();
+        let ty = bitmap::BitfieldType {
+            signed: false,
+            bits: 2,
+        };
+        let ops = [
+            BitfieldOp::Overflow(bitmap::BitfieldOverflow::Fail),
+            BitfieldOp::IncrBy {
+                ty,
+                offset: 0,
+                incr: 4,
+            },
+        ];
+        let (frame, _) = write_frame(bitfield(&store, Bytes::from_static(b"k"), &ops).await);
+        assert_eq!(frame, Frame::Array(vec![Frame::NullBulkString]));
+    }
+
     #[tokio::test]
     async fn strlen_existing() {
         let store = MemoryStore::new();
@@ -415,4 +929,17 @@ mod tests {
         let frame = read_frame(expire(&store, Bytes::from_static(b"k"), 5000, now).await);
         assert_eq!(frame, Frame::Integer(0));
     }
+
+    #[tokio::test]
+    async fn memory_usage_existing_and_missing() {
+        let store = MemoryStore::new();
+        store
+            .set(Bytes::from_static(b"key"), entry(b"value", Expiry::None))
+            .await;
+        let frame = read_frame(memory_usage(&store, Bytes::from_static(b"key")).await);
+        let expected = crate::store::memory::entry_memory(3, 5) as i64;
+        assert_eq!(frame, Frame::Integer(expected));
+        let frame = read_frame(memory_usage(&store, Bytes::from_static(b"nope")).await);
+        assert_eq!(frame, Frame::NullBulkString);
+    }
 }
@@ -0,0 +1,266 @@
+//! Server statistics in the Prometheus text exposition format (0.0.4).
+
+use std::fmt::{Display, Write};
+
+use crate::{
+    service::context::ServerContext, store::traits::Store, utils::histogram::HistogramSnapshot,
+};
+
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[derive(Default)]
+struct Exposition {
+    out: String,
+}
+
+impl Exposition {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = write!(
+            self.out,
+            "# HELP yars_{name} {help}\n# TYPE yars_{name} {kind}\n"
+        );
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        let _ = write!(self.out, "yars_{name}");
+        if !labels.is_empty() {
+            self.out.push('{');
+            for (i, (key, value)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.out.push(',');
+                }
+                let _ = write!(self.out, "{key}=\"{}\"", escape(value));
+            }
+            self.out.push('}');
+        }
+        let _ = writeln!(self.out, " {value}");
+    }
+
+    fn single(&mut self, name: &str, kind: &str, help: &str, value: impl Display) {
+        self.family(name, kind, help);
+        self.sample(name, &[], value);
+    }
+
+    fn histogram(&mut self, name: &str, labels: &[(&str, &str)], snapshot: &HistogramSnapshot) {
+        let bucket = format!("{name}_bucket");
+        for (bound, count) in &snapshot.buckets {
+            let le = bound.to_string();
+            self.sample(&bucket, &[labels, &[("le", &le)]].concat(), count);
+        }
+        self.sample(
+            &bucket,
+            &[labels, &[("le", "+Inf")]].concat(),
+            snapshot.count,
+        );
+        self.sample(&format!("{name}_sum"), labels, snapshot.sum_seconds);
+        self.sample(&format!("{name}_count"), labels, snapshot.count);
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+pub async fn render(ctx: &ServerContext) -> String {
+    let store = &ctx.store;
+    let clients = &ctx.clients;
+    let maxmemory = ctx.config.read().await.maxmemory;
+    let aof = ctx.aof.status().await;
+    let commands = ctx.command_stats.snapshot();
+    let latencies = ctx.command_stats.latencies();
+    let keys = store.len().await as u64;
+
+    let mut m = Exposition::default();
+    m.single(
+        "uptime_seconds",
+        "gauge",
+        "Seconds since the server started.",
+        store.uptime_seconds(),
+    );
+    m.single(
+        "commands_processed_total",
+        "counter",
+        "Commands processed successfully.",
+        store.total_commands(),
+    );
+
+    m.family("command_calls_total", "counter", "Calls per command.");
+    for stat in &commands {
+        m.sample("command_calls_total", &[("cmd", &stat.name)], stat.calls);
+    }
+    m.family(
+        "command_failed_calls_total",
+        "counter",
+        "Calls per command that replied with an error.",
+    );
+    for stat in &commands {
+        m.sample(
+            "command_failed_calls_total",
+            &[("cmd", &stat.name)],
+            stat.failed_calls,
+        );
+    }
+    m.family(
+        "command_rejected_calls_total",
+        "counter",
+        "Calls per command refused before running.",
+    );
+    for stat in &commands {
+        m.sample(
+            "command_rejected_calls_total",
+            &[("cmd", &stat.name)],
+            stat.rejected_calls,
+        );
+    }
+    m.family(
+        "command_duration_seconds",
+        "histogram",
+        "Time spent running each command.",
+    );
+    for (name, latency) in &latencies {
+        m.histogram("command_duration_seconds", &[("cmd", name)], latency);
+    }
+
+    m.single(
+        "connected_clients",
+        "gauge",
+        "Client connections currently open.",
+        clients.len(),
+    );
+    m.single(
+        "connections_received_total",
+        "counter",
+        "Client connections accepted.",
+        clients.total_connections(),
+    );
+    m.single(
+        "rejected_connections_total",
+        "counter",
+        "Client connections refused because of maxclients.",
+        clients.rejected_connections(),
+    );
+    m.single(
+        "net_input_bytes_total",
+        "counter",
+        "Bytes read from clients.",
+        clients.net_input_bytes(),
+    );
+    m.single(
+        "net_output_bytes_total",
+        "counter",
+        "Bytes written to clients.",
+        clients.net_output_bytes(),
+    );
+
+    m.single("keys", "gauge", "Keys in the keyspace.", keys);
+    m.single(
+        "expiring_keys",
+        "gauge",
+        "Keys with a TTL.",
+        store.volatile_keys().min(keys),
+    );
+    m.single(
+        "expired_keys_total",
+        "counter",
+        "Keys removed because their TTL passed.",
+        store.expired_keys(),
+    );
+    m.single(
+        "evicted_keys_total",
+        "counter",
+        "Keys evicted to stay under maxmemory.",
+        store.evicted_keys(),
+    );
+    m.single(
+        "keyspace_hits_total",
+        "counter",
+        "Key lookups that found a key.",
+        store.keyspace_hits(),
+    );
+    m.single(
+        "keyspace_misses_total",
+        "counter",
+        "Key lookups that found nothing.",
+        store.keyspace_misses(),
+    );
+    m.single(
+        "memory_used_bytes",
+        "gauge",
+        "Memory used by the keyspace.",
+        store.used_memory().await,
+    );
+    m.single(
+        "memory_max_bytes",
+        "gauge",
+        "The maxmemory limit; 0 means none.",
+        maxmemory,
+    );
+
+    m.single(
+        "aof_enabled",
+        "gauge",
+        "Whether the append-only file is on.",
+        u8::from(aof.is_some()),
+    );
+    if let Some(aof) = aof {
+        m.single(
+            "aof_size_bytes",
+            "gauge",
+            "Size of the append-only file.",
+            aof.current_size,
+        );
+        m.single(
+            "aof_write_errors_total",
+            "counter",
+            "Failed writes to the append-only file since it was opened.",
+            aof.write_errors,
+        );
+        m.family(
+            "aof_fsync_duration_seconds",
+            "histogram",
+            "Time spent in fsync on the append-only file since it was opened.",
+        );
+        m.histogram("aof_fsync_duration_seconds", &[], &aof.fsync_latency);
+    }
+    m.out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::utils::histogram::LatencyHistogram;
+
+    #[test]
+    fn samples_escape_labels() {
+        let mut m = Exposition::default();
+        m.single("up", "gauge", "Always one.", 1);
+        m.sample("calls", &[("cmd", "a\"b\\c\nd")], 2);
+        assert_eq!(
+            m.out,
+            "# HELP yars_up Always one.\n# TYPE yars_up gauge\nyars_up 1\n\
+             yars_calls{cmd=\"a\\\"b\\\\c\\nd\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn histograms_end_with_inf_sum_and_count() {
+        let latency = LatencyHistogram::new();
+        latency.observe(Duration::from_micros(40));
+        latency.observe(Duration::from_secs(20));
+        let mut m = Exposition::default();
+        m.histogram("lat", &[("cmd", "get")], &latency.snapshot());
+
+        let lines: Vec<_> = m.out.lines().collect();
+        assert_eq!(lines[0], "yars_lat_bucket{cmd=\"get\",le=\"0.00001\"} 0");
+        assert_eq!(lines[2], "yars_lat_bucket{cmd=\"get\",le=\"0.00005\"} 1");
+        assert!(lines.contains(&"yars_lat_bucket{cmd=\"get\",le=\"10\"} 1"));
+        assert!(lines.contains(&"yars_lat_bucket{cmd=\"get\",le=\"+Inf\"} 2"));
+        assert_eq!(lines[lines.len() - 2], "yars_lat_sum{cmd=\"get\"} 20.00004");
+        assert_eq!(lines[lines.len() - 1], "yars_lat_count{cmd=\"get\"} 2");
+    }
+}
@@ -1,2 +1,9 @@
+pub mod clients;
 pub mod context;
 pub mod handlers;
+pub mod metrics;
+pub mod monitor;
+pub mod reload;
+pub mod shutdown;
+pub mod slowlog;
+pub mod stats;
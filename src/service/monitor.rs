@@ -0,0 +1,148 @@
+//! Fan-out of executed commands to clients in MONITOR mode.
+
+use std::{
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tokio::sync::broadcast;
+use tokio_util::bytes::Bytes;
+
+/// Lines buffered per monitor before a slow one starts missing them.
+const MONITOR_BACKLOG: usize = 4096;
+
+pub struct Monitor {
+    tx: broadcast::Sender<String>,
+    watchers: Arc<AtomicUsize>,
+}
+
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Monitor {
+    pub fn new() -> Self {
+        Self {
+            tx: broadcast::Sender::new(MONITOR_BACKLOG),
+            watchers: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn subscribe(&self) -> MonitorSubscription {
+        self.watchers.fetch_add(1, Ordering::Relaxed);
+        MonitorSubscription {
+            rx: self.tx.subscribe(),
+            watchers: self.watchers.clone(),
+        }
+    }
+
+    /// Publishes a command to every monitor. Costs a single atomic load when
+    /// nobody is watching.
+    pub fn feed(&self, db: usize, addr: SocketAddr, args: &[Bytes]) {
+        if self.watchers.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let _ = self.tx.send(format_line(SystemTime::now(), db, addr, args));
+    }
+}
+
+pub struct MonitorSubscription {
+    rx: broadcast::Receiver<String>,
+    watchers: Arc<AtomicUsize>,
+}
+
+impl MonitorSubscription {
+    /// Next line to forward, skipping any this monitor fell behind on.
+    pub async fn recv(&mut self) -> Option<String> {
+        loop {
+            match self.rx.recv().await {
+                Ok(line) => return Some(line),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for MonitorSubscription {
+    fn drop(&mut self) {
+        self.watchers.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// `<secs>.<micros> [<db> <addr>] "arg" ...`, as printed by Redis.
+fn format_line(now: SystemTime, db: usize, addr: SocketAddr, args: &[Bytes]) -> String {
+    let since_epoch = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let mut line = format!(
+        "{}.{:06} [{db} {addr}]",
+        since_epoch.as_secs(),
+        since_epoch.subsec_micros()
+    );
+    for arg in args {
+        line.push(' ');
+        push_quoted(&mut line, arg);
+    }
+    line
+}
+
+fn push_quoted(out: &mut String, bytes: &[u8]) {
+    out.push('"');
+    for &b in bytes {
+        match b {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x07 => out.push_str("\\a"),
+            0x08 => out.push_str("\\b"),
+            b' '..=b'~' => out.push(b as char),
+            _ => out.push_str(&format!("\\x{b:02x}")),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn addr() -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], 5000))
+    }
+
+    #[test]
+    fn line_quotes_arguments() {
+        let now = UNIX_EPOCH + Duration::from_micros(1_339_518_083_107_412);
+        let args = [
+            Bytes::from_static(b"SET"),
+            Bytes::from_static(b"k"),
+            Bytes::from_static(b"a \"b\"\n\x01"),
+        ];
+        assert_eq!(
+            format_line(now, 0, addr(), &args),
+            r#"1339518083.107412 [0 127.0.0.1:5000] "SET" "k" "a \"b\"\n\x01""#
+        );
+    }
+
+    #[tokio::test]
+    async fn feed_reaches_subscribers_only_while_subscribed() {
+        let monitor = Monitor::new();
+        monitor.feed(0, addr(), &[Bytes::from_static(b"PING")]);
+
+        let mut sub = monitor.subscribe();
+        monitor.feed(0, addr(), &[Bytes::from_static(b"GET")]);
+        assert!(sub.recv().await.unwrap().ends_with(r#"] "GET""#));
+
+        drop(sub);
+        assert_eq!(monitor.watchers.load(Ordering::Relaxed), 0);
+    }
+}
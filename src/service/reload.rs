@@ -0,0 +1,218 @@
+//! Re-reads `yars.toml` while the server runs, on SIGHUP or, with
+//! `watch-config`, whenever the file changes. SIGHUP also reopens the log file.
+
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Result, anyhow};
+use tokio::signal::unix::{SignalKind, signal};
+
+use crate::{
+    config::{
+        AppConfig,
+        registry::{self, Param},
+    },
+    logging,
+    service::{context::ServerContext, handlers::nokey::apply_config},
+};
+
+/// How often the config file is checked for changes when watching.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Parameters that differed between the file and the running server.
+#[derive(Debug, Default, PartialEq)]
+pub struct ReloadReport {
+    pub applied: Vec<&'static str>,
+    /// Changed in the file but immutable, so left as they are.
+    pub restart_required: Vec<&'static str>,
+}
+
+pub struct Reloader {
+    ctx: Arc<ServerContext>,
+    /// Command-line directives, reapplied on top of the file like at startup.
+    overrides: Vec<(String, String)>,
+}
+
+impl Reloader {
+    pub fn new(ctx: Arc<ServerContext>, overrides: Vec<(String, String)>) -> Self {
+        Self { ctx, overrides }
+    }
+
+    /// Resolves the configuration again and applies every mutable parameter
+    /// that changed through the same path as CONFIG SET. Nothing is applied
+    /// if the file is invalid or a change fails.
+    pub async fn reload(&self) -> Result<ReloadReport> {
+        let current = self.ctx.config.read().await.clone();
+        let fresh = AppConfig::resolve(Some(current.config_path.clone()), &self.overrides)?;
+
+        let mut report = ReloadReport::default();
+        let mut values = Vec::new();
+        for param in registry::PARAMS {
+            let value = param.get(&fresh);
+            if value == param.get(&current) {
+                continue;
+            }
+            if param.mutable {
+                report.applied.push(param.name);
+                values.push((param, value));
+            } else {
+                report.restart_required.push(param.name);
+            }
+        }
+
+        let changes: Vec<(&'static Param, &str)> =
+            values.iter().map(|(p, v)| (*p, v.as_str())).collect();
+        apply_config(
+            &self.ctx.store,
+            &self.ctx.config,
+            &self.ctx.aof,
+            &self.ctx.slowlog,
+            &changes,
+        )
+        .await
+        .map_err(|(name, e)| anyhow!("{name}: {e}"))?;
+        Ok(report)
+    }
+
+    /// Reloads on SIGHUP, and on file changes while `watch-config` is set,
+    /// until the server shuts down. SIGHUP also reopens the log file, so it
+    /// can be rotated.
+    pub async fn run(self) -> Result<()> {
+        let mut hangup = signal(SignalKind::hangup())?;
+        let mut ticker = tokio::time::interval(WATCH_INTERVAL);
+        let mut modified = self.modified().await;
+        loop {
+            tokio::select! {
+                _ = self.ctx.cancel.cancelled() => return Ok(()),
+                _ = hangup.recv() => {
+                    if let Err(err) = logging::reopen() {
+                        log::error!("Failed to reopen the log file: {err:#}");
+                    }
+                }
+                _ = ticker.tick() => {
+                    if !self.ctx.config.read().await.watch_config {
+                        continue;
+                    }
+                    let now = self.modified().await;
+                    if now == modified {
+                        continue;
+                    }
+                    modified = now;
+                }
+            }
+            self.reload_and_log().await;
+        }
+    }
+
+    async fn modified(&self) -> Option<SystemTime> {
+        let path = self.ctx.config.read().await.config_path.clone();
+        tokio::fs::metadata(path)
+            .await
+            .and_then(|m| m.modified())
+            .ok()
+    }
+
+    async fn reload_and_log(&self) {
+        let path = self.ctx.config.read().await.config_path.clone();
+        match self.reload().await {
+            Ok(report) => {
+                if report.applied.is_empty() {
+                    log::info!("Reloaded {}: no changes applied", path.display());
+                } else {
+                    log::info!(
+                        "Reloaded {}: applied {}",
+                        path.display(),
+                        report.applied.join(", ")
+                    );
+                }
+                for name in report.restart_required {
+                    log::warn!(
+                        "'{name}' changed in {} but needs a restart to take effect",
+                        path.display()
+                    );
+                }
+            }
+            Err(err) => log::error!(
+                "Config reload failed, keeping the running configuration: {}: {err}",
+                path.display()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{EvictionPolicy, FsyncMode, LogFormat, LogLevel, TraceExporter};
+
+    async fn context(dir: &std::path::Path) -> Arc<ServerContext> {
+        ServerContext::new(AppConfig {
+            bind: "127.0.0.1".into(),
+            port: 6379,
+            append_only: false,
+            aof_path: dir.join("data.aof"),
+            fsync_mode: FsyncMode::EverySec,
+            config_path: dir.join("yars.toml"),
+            data_dir: dir.to_path_buf(),
+            maxmemory: 0,
+            maxmemory_policy: EvictionPolicy::NoEviction,
+            maxclients: 10000,
+            slowlog_log_slower_than: 10000,
+            slowlog_max_len: 128,
+            watch_config: false,
+            shutdown_timeout: 10,
+            log_level: LogLevel::Notice,
+            log_file: None,
+            log_format: LogFormat::Text,
+            metrics_port: 0,
+            trace_exporter: TraceExporter::None,
+            trace_file: std::path::PathBuf::from("traces.jsonl"),
+            trace_endpoint: "http://127.0.0.1:4318/v1/traces".into(),
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reload_applies_mutable_changes_and_reports_the_rest() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = context(dir.path()).await;
+        std::fs::write(
+            dir.path().join("yars.toml"),
+            format!(
+                "dir = {:?}\nappend_only = false\nport = 7000\nfsync_mode = \"always\"\nmaxmemory = 100\n",
+                dir.path().display().to_string()
+            ),
+        )
+        .unwrap();
+
+        let reloader = Reloader::new(ctx.clone(), vec![("maxmemory".into(), "200".into())]);
+        let report = reloader.reload().await.unwrap();
+        assert_eq!(report.applied, vec!["appendfsync", "maxmemory"]);
+        assert_eq!(report.restart_required, vec!["port"]);
+
+        let config = ctx.config.read().await;
+        assert_eq!(config.fsync_mode, FsyncMode::Always);
+        assert_eq!(config.maxmemory, 200);
+        assert_eq!(config.port, 6379);
+    }
+
+    #[tokio::test]
+    async fn reload_keeps_config_when_file_is_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = context(dir.path()).await;
+        std::fs::write(
+            dir.path().join("yars.toml"),
+            "maxmemory_policy = \"sometimes\"\n",
+        )
+        .unwrap();
+
+        assert!(Reloader::new(ctx.clone(), vec![]).reload().await.is_err());
+        assert_eq!(
+            ctx.config.read().await.maxmemory_policy,
+            EvictionPolicy::NoEviction
+        );
+    }
+}
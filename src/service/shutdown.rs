@@ -0,0 +1,205 @@
+//! Coordinates a graceful stop, whether asked for by SHUTDOWN or a signal.
+//!
+//! While a shutdown is draining, commands already executing run to completion
+//! and new ones wait; if the shutdown is aborted or fails they go ahead, once
+//! it completes they are refused.
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use tokio::{sync::watch, time::Instant};
+
+/// How often a drain checks whether in-flight commands have finished.
+const DRAIN_POLL: Duration = Duration::from_millis(5);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SaveMode {
+    /// fsync the AOF if it is on.
+    #[default]
+    Default,
+    /// Write a snapshot to the AOF path, even with the AOF off.
+    Save,
+    /// Skip the final fsync.
+    NoSave,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShutdownOptions {
+    pub save: SaveMode,
+    /// Don't wait for in-flight commands.
+    pub now: bool,
+    /// Shut down even if saving fails.
+    pub force: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Running,
+    Draining,
+    Stopped,
+}
+
+pub struct Shutdown {
+    state: watch::Sender<State>,
+    in_flight: AtomicUsize,
+}
+
+/// Marks a command as executing until dropped.
+pub struct InFlight<'a>(&'a Shutdown);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            state: watch::Sender::new(State::Running),
+            in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn in_progress(&self) -> bool {
+        *self.state.borrow() != State::Running
+    }
+
+    /// Lets a command run, waiting while a shutdown drains. `None` once the
+    /// server has stopped.
+    pub async fn admit(&self) -> Option<InFlight<'_>> {
+        let mut state = self.state.subscribe();
+        loop {
+            // Counted before the state is checked, so a drain that starts
+            // in between still waits for this command.
+            self.in_flight.fetch_add(1, Ordering::SeqCst);
+            let guard = InFlight(self);
+            match *state.borrow_and_update() {
+                State::Running => return Some(guard),
+                State::Stopped => return None,
+                State::Draining => drop(guard),
+            }
+            if state.changed().await.is_err() {
+                return None;
+            }
+        }
+    }
+
+    /// Starts draining. Returns false if a shutdown is already under way.
+    pub fn begin(&self) -> bool {
+        self.state.send_if_modified(|state| {
+            if *state != State::Running {
+                return false;
+            }
+            *state = State::Draining;
+            true
+        })
+    }
+
+    /// Waits up to `timeout` for in-flight commands to finish. Returns false
+    /// if the shutdown was aborted meanwhile.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.subscribe();
+        loop {
+            if *state.borrow_and_update() != State::Draining {
+                return false;
+            }
+            let pending = self.in_flight.load(Ordering::SeqCst);
+            if pending == 0 {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                log::warn!("shutdown-timeout reached with {pending} commands still running");
+                return true;
+            }
+            tokio::select! {
+                _ = state.changed() => {}
+                _ = tokio::time::sleep(DRAIN_POLL) => {}
+            }
+        }
+    }
+
+    /// Cancels a draining shutdown. Returns false if there was none.
+    pub fn abort(&self) -> bool {
+        self.state.send_if_modified(|state| {
+            if *state != State::Draining {
+                return false;
+            }
+            *state = State::Running;
+            true
+        })
+    }
+
+    pub fn finish(&self) {
+        self.state.send_replace(State::Stopped);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn drain_waits_for_in_flight_commands() {
+        let shutdown = Arc::new(Shutdown::new());
+        let guard = shutdown.admit().await.unwrap();
+        assert!(shutdown.begin());
+        assert!(!shutdown.begin());
+
+        let draining = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.drain(Duration::from_secs(5)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!draining.is_finished());
+        drop(guard);
+        assert!(draining.await.unwrap());
+
+        shutdown.finish();
+        assert!(shutdown.admit().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn drain_gives_up_after_timeout() {
+        let shutdown = Shutdown::new();
+        let _guard = shutdown.admit().await.unwrap();
+        shutdown.begin();
+        assert!(shutdown.drain(Duration::from_millis(20)).await);
+    }
+
+    #[tokio::test]
+    async fn abort_resumes_held_commands() {
+        let shutdown = Arc::new(Shutdown::new());
+        assert!(!shutdown.abort());
+        let guard = shutdown.admit().await.unwrap();
+        shutdown.begin();
+
+        let held = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.admit().await.is_some() }
+        });
+        let draining = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.drain(Duration::from_secs(5)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!held.is_finished());
+
+        assert!(shutdown.abort());
+        assert!(!draining.await.unwrap());
+        assert!(held.await.unwrap());
+        assert!(!shutdown.in_progress());
+        drop(guard);
+    }
+}
@@ -0,0 +1,205 @@
+//! Bounded log of commands that exceeded `slowlog-log-slower-than`.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex,
+        atomic::{AtomicI64, AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio_util::bytes::Bytes;
+
+use crate::{protocol::resp::Frame, service::clients::ClientHandle};
+
+/// Arguments kept per entry; the last slot notes how many were dropped.
+const MAX_ARGC: usize = 32;
+/// Bytes kept per argument before it is truncated.
+const MAX_ARG_LEN: usize = 128;
+
+#[derive(Clone, Debug)]
+pub struct SlowLogEntry {
+    pub id: u64,
+    /// Unix time in seconds at which the command was logged.
+    pub timestamp: u64,
+    pub duration: Duration,
+    pub args: Vec<Bytes>,
+    pub addr: String,
+    pub name: Bytes,
+}
+
+impl SlowLogEntry {
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Integer(self.id as i64),
+            Frame::Integer(self.timestamp as i64),
+            Frame::Integer(self.duration.as_micros() as i64),
+            Frame::Array(self.args.iter().cloned().map(Frame::BulkString).collect()),
+            Frame::BulkString(self.addr.clone().into()),
+            Frame::BulkString(self.name.clone()),
+        ])
+    }
+}
+
+pub struct SlowLog {
+    /// Threshold in microseconds; negative disables logging, zero logs all.
+    slower_than: AtomicI64,
+    max_len: AtomicUsize,
+    next_id: AtomicU64,
+    entries: Mutex<VecDeque<SlowLogEntry>>,
+}
+
+impl SlowLog {
+    pub fn new(slower_than: i64, max_len: usize) -> Self {
+        Self {
+            slower_than: AtomicI64::new(slower_than),
+            max_len: AtomicUsize::new(max_len),
+            next_id: AtomicU64::new(0),
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn set_slower_than(&self, micros: i64) {
+        self.slower_than.store(micros, Ordering::Relaxed);
+    }
+
+    pub fn set_max_len(&self, max_len: usize) {
+        self.max_len.store(max_len, Ordering::Relaxed);
+        self.entries.lock().expect("slowlog lock").truncate(max_len);
+    }
+
+    /// Logs the command if it ran for at least the configured threshold.
+    pub fn record(&self, duration: Duration, args: &[Bytes], client: &ClientHandle) {
+        let slower_than = self.slower_than.load(Ordering::Relaxed);
+        if slower_than < 0 || duration.as_micros() < slower_than as u128 {
+            return;
+        }
+        let max_len = self.max_len.load(Ordering::Relaxed);
+        if max_len == 0 {
+            return;
+        }
+
+        let entry = SlowLogEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            timestamp: crate::utils::time::get_current_millis() / 1000,
+            duration,
+            args: truncate_args(args),
+            addr: client.addr.to_string(),
+            name: client.name().unwrap_or_default(),
+        };
+        let mut entries = self.entries.lock().expect("slowlog lock");
+        entries.push_front(entry);
+        entries.truncate(max_len);
+    }
+
+    /// Up to `count` entries, newest first.
+    pub fn get(&self, count: usize) -> Vec<SlowLogEntry> {
+        let entries = self.entries.lock().expect("slowlog lock");
+        entries.iter().take(count).cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("slowlog lock").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn reset(&self) {
+        self.entries.lock().expect("slowlog lock").clear();
+    }
+}
+
+fn truncate_args(args: &[Bytes]) -> Vec<Bytes> {
+    let kept = if args.len() > MAX_ARGC {
+        MAX_ARGC - 1
+    } else {
+        args.len()
+    };
+    let mut out: Vec<Bytes> = args[..kept]
+        .iter()
+        .map(|arg| {
+            if arg.len() > MAX_ARG_LEN {
+                let mut short = arg[..MAX_ARG_LEN].to_vec();
+                short.extend_from_slice(
+                    format!("... ({} more bytes)", arg.len() - MAX_ARG_LEN).as_bytes(),
+                );
+                short.into()
+            } else {
+                arg.clone()
+            }
+        })
+        .collect();
+    if kept < args.len() {
+        out.push(format!("... ({} more arguments)", args.len() - kept).into());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::clients::ClientRegistry;
+
+    fn client() -> std::sync::Arc<ClientHandle> {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 5000));
+        ClientRegistry::new().register(addr, addr, 1).unwrap()
+    }
+
+    fn args(parts: &[&str]) -> Vec<Bytes> {
+        parts.iter().map(|p| Bytes::from(p.to_string())).collect()
+    }
+
+    #[test]
+    fn records_only_commands_over_threshold() {
+        let log = SlowLog::new(1000, 10);
+        let client = client();
+        log.record(Duration::from_micros(999), &args(&["GET", "a"]), &client);
+        log.record(Duration::from_micros(1000), &args(&["GET", "b"]), &client);
+        assert_eq!(log.len(), 1);
+
+        let entry = &log.get(10)[0];
+        assert_eq!(entry.id, 0);
+        assert_eq!(entry.args, args(&["GET", "b"]));
+        assert_eq!(entry.addr, "127.0.0.1:5000");
+
+        log.set_slower_than(-1);
+        log.record(Duration::from_secs(1), &args(&["GET", "c"]), &client);
+        assert_eq!(log.len(), 1);
+    }
+
+    #[test]
+    fn keeps_newest_entries_up_to_max_len() {
+        let log = SlowLog::new(0, 2);
+        let client = client();
+        for key in ["a", "b", "c"] {
+            log.record(Duration::ZERO, &args(&["GET", key]), &client);
+        }
+        let ids: Vec<_> = log.get(10).iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![2, 1]);
+        assert_eq!(log.get(1).len(), 1);
+
+        log.set_max_len(1);
+        assert_eq!(log.len(), 1);
+        log.reset();
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn truncates_long_arguments() {
+        let long = "x".repeat(MAX_ARG_LEN + 5);
+        let many: Vec<Bytes> = (0..40).map(|i| Bytes::from(i.to_string())).collect();
+
+        let out = truncate_args(&args(&["SET", "k", &long]));
+        assert_eq!(
+            out[2],
+            Bytes::from(format!("{}... (5 more bytes)", "x".repeat(MAX_ARG_LEN)))
+        );
+
+        let out = truncate_args(&many);
+        assert_eq!(out.len(), MAX_ARGC);
+        assert_eq!(out[MAX_ARGC - 1], Bytes::from("... (9 more arguments)"));
+    }
+}
@@ -0,0 +1,144 @@
+//! Per-command counters reported by `INFO commandstats` and `/metrics`.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use crate::utils::histogram::{HistogramSnapshot, LatencyHistogram};
+
+#[derive(Default)]
+struct CommandStat {
+    calls: AtomicU64,
+    usec: AtomicU64,
+    rejected_calls: AtomicU64,
+    failed_calls: AtomicU64,
+    latency: LatencyHistogram,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandStatSnapshot {
+    pub name: String,
+    pub calls: u64,
+    pub usec: u64,
+    /// Refused before running, e.g. by `maxmemory`.
+    pub rejected_calls: u64,
+    /// Ran but replied with an error.
+    pub failed_calls: u64,
+}
+
+#[derive(Default)]
+pub struct CommandStats {
+    commands: RwLock<HashMap<String, Arc<CommandStat>>>,
+}
+
+impl CommandStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn stat(&self, name: &str) -> Arc<CommandStat> {
+        if let Some(stat) = self.commands.read().expect("command stats lock").get(name) {
+            return stat.clone();
+        }
+        self.commands
+            .write()
+            .expect("command stats lock")
+            .entry(name.to_string())
+            .or_default()
+            .clone()
+    }
+
+    pub fn record(&self, name: &str, duration: Duration, failed: bool) {
+        let stat = self.stat(name);
+        stat.calls.fetch_add(1, Ordering::Relaxed);
+        stat.usec
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+        stat.latency.observe(duration);
+        if failed {
+            stat.failed_calls.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn reject(&self, name: &str) {
+        self.stat(name)
+            .rejected_calls
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Commands seen so far, sorted by name.
+    pub fn snapshot(&self) -> Vec<CommandStatSnapshot> {
+        let commands = self.commands.read().expect("command stats lock");
+        let mut out: Vec<_> = commands
+            .iter()
+            .map(|(name, stat)| CommandStatSnapshot {
+                name: name.clone(),
+                calls: stat.calls.load(Ordering::Relaxed),
+                usec: stat.usec.load(Ordering::Relaxed),
+                rejected_calls: stat.rejected_calls.load(Ordering::Relaxed),
+                failed_calls: stat.failed_calls.load(Ordering::Relaxed),
+            })
+            .collect();
+        out.sort_by(|a, b| a.name.cmp(&b.name));
+        out
+    }
+
+    /// Latency histogram of every command seen so far, sorted by name.
+    pub fn latencies(&self) -> Vec<(String, HistogramSnapshot)> {
+        let commands = self.commands.read().expect("command stats lock");
+        let mut out: Vec<_> = commands
+            .iter()
+            .map(|(name, stat)| (name.clone(), stat.latency.snapshot()))
+            .collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
+
+    pub fn reset(&self) {
+        self.commands.write().expect("command stats lock").clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_calls_failures_and_rejections() {
+        let stats = CommandStats::new();
+        stats.record("get", Duration::from_micros(10), false);
+        stats.record("get", Duration::from_micros(30), true);
+        stats.reject("set");
+
+        assert_eq!(
+            stats.snapshot(),
+            vec![
+                CommandStatSnapshot {
+                    name: "get".into(),
+                    calls: 2,
+                    usec: 40,
+                    rejected_calls: 0,
+                    failed_calls: 1,
+                },
+                CommandStatSnapshot {
+                    name: "set".into(),
+                    calls: 0,
+                    usec: 0,
+                    rejected_calls: 1,
+                    failed_calls: 0,
+                },
+            ]
+        );
+        let latencies = stats.latencies();
+        assert_eq!(latencies[0].0, "get");
+        assert_eq!(latencies[0].1.count, 2);
+        assert_eq!(latencies[1].1.count, 0);
+
+        stats.reset();
+        assert!(stats.snapshot().is_empty());
+    }
+}
@@ -1,24 +1,170 @@
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::{BuildHasher, RandomState};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
 use std::time::Instant;
 
 use async_trait::async_trait;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use tokio_util::bytes::Bytes;
 
 use crate::{
+    config::EvictionPolicy,
     store::{
         traits::Store,
         types::{Entry, Expiry},
     },
-    utils::time::get_current_millis,
+    telemetry::{self, Traced},
+    utils::{
+        glob::glob_match,
+        random::{random_f64, random_u64},
+        time::get_current_millis,
+    },
 };
 
+/// Keys inspected per eviction round; the best candidate among them is evicted.
+const EVICTION_SAMPLES: usize = 5;
+const TTL_SAMPLES: usize = 64;
+const LFU_INIT_VAL: u8 = 5;
+const LFU_LOG_FACTOR: f64 = 10.0;
+const LFU_DECAY_MS: u64 = 60_000;
+
+tokio::task_local! {
+    /// Set to false while running a command whose lookups only serve a
+    /// write, which Redis leaves out of keyspace hits/misses.
+    static COUNT_LOOKUPS: bool;
+}
+
+/// Per-key access metadata used to rank eviction candidates. Atomic so that
+/// reads can record an access while only holding the map's read lock.
+struct Access {
+    last_access: AtomicU64,
+    lfu_counter: AtomicU8,
+}
+
+impl Access {
+    fn new(now: u64) -> Self {
+        Self {
+            last_access: AtomicU64::new(now),
+            lfu_counter: AtomicU8::new(LFU_INIT_VAL),
+        }
+    }
+
+    fn idle_ms(&self, now: u64) -> u64 {
+        now.saturating_sub(self.last_access.load(Ordering::Relaxed))
+    }
+
+    /// Logarithmic access counter, decayed by one per idle minute.
+    fn frequency(&self, now: u64) -> u8 {
+        let periods = self.idle_ms(now) / LFU_DECAY_MS;
+        let counter = self.lfu_counter.load(Ordering::Relaxed);
+        counter.saturating_sub(periods.min(u8::MAX as u64) as u8)
+    }
+
+    fn touch(&self, now: u64) {
+        let mut counter = self.frequency(now);
+        if counter < u8::MAX {
+            let base = counter.saturating_sub(LFU_INIT_VAL) as f64;
+            if random_f64() < 1.0 / (base * LFU_LOG_FACTOR + 1.0) {
+                counter += 1;
+            }
+        }
+        self.lfu_counter.store(counter, Ordering::Relaxed);
+        self.last_access.store(now, Ordering::Relaxed);
+    }
+}
+
+struct Slot {
+    entry: Entry,
+    access: Access,
+}
+
+/// Size of one hash table bucket plus its control byte.
+const BUCKET_SIZE: usize = size_of::<(Bytes, Slot)>() + 1;
+
+/// Heap header `Bytes` allocates once a buffer is shared (pointer, capacity
+/// and reference count).
+const SHARED_BYTES_HEADER: usize = 3 * size_of::<usize>();
+
+/// Estimated fixed cost of a key beyond its raw key and value bytes.
+pub const ENTRY_OVERHEAD: usize = BUCKET_SIZE + 2 * SHARED_BYTES_HEADER;
+
+pub fn entry_memory(key_len: usize, value_len: usize) -> usize {
+    key_len + value_len + ENTRY_OVERHEAD
+}
+
+impl Slot {
+    fn memory(&self, key: &Bytes) -> usize {
+        entry_memory(key.len(), self.entry.value.len())
+    }
+
+    fn has_ttl(&self) -> bool {
+        matches!(self.entry.exp, Expiry::At(_))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryStats {
+    pub keys: usize,
+    pub used: usize,
+    pub peak: usize,
+    /// Raw key and value bytes, without any overhead.
+    pub dataset: usize,
+    /// Bytes reserved by the hash table itself, including spare capacity.
+    pub hashtable: usize,
+}
+
+type Shard = HashMap<Bytes, Slot>;
+
+/// Takes `lock` for reading, tracing the wait for it and how long it is held.
+async fn read_lock(lock: &RwLock<Shard>) -> Traced<RwLockReadGuard<'_, Shard>> {
+    let wait = telemetry::span("store.lock.wait").with_attr("mode", "read");
+    let guard = lock.read().await;
+    drop(wait);
+    Traced::new(
+        guard,
+        telemetry::span("store.lock.hold").with_attr("mode", "read"),
+    )
+}
+
+async fn write_lock(lock: &RwLock<Shard>) -> Traced<RwLockWriteGuard<'_, Shard>> {
+    let wait = telemetry::span("store.lock.wait").with_attr("mode", "write");
+    let guard = lock.write().await;
+    drop(wait);
+    Traced::new(
+        guard,
+        telemetry::span("store.lock.hold").with_attr("mode", "write"),
+    )
+}
+
+/// Shards used by [`MemoryStore::new`]; enough that unrelated keys rarely
+/// contend while keeping whole-keyspace scans cheap.
+pub const DEFAULT_SHARDS: usize = 64;
+
+/// The keyspace is split into independently locked shards selected by key
+/// hash. Operations touching several keys lock every shard involved in
+/// ascending shard order, so they stay atomic without risking deadlock.
 pub struct MemoryStore {
-    map: RwLock<HashMap<Bytes, Entry>>,
+    shards: Box<[RwLock<Shard>]>,
+    hasher: RandomState,
     start_time: Instant,
     commands_processed: AtomicU64,
     total_memory: AtomicU64,
+    peak_memory: AtomicU64,
+    evicted_keys: AtomicU64,
+    expired_keys: AtomicU64,
+    keyspace_hits: AtomicU64,
+    keyspace_misses: AtomicU64,
+    /// Keys with a TTL set, including ones expired but not yet removed.
+    volatile_keys: AtomicU64,
+}
+
+/// Result of an eviction pass: the keys removed, and whether usage is now
+/// within the limit.
+#[derive(Debug, Default)]
+pub struct Eviction {
+    pub keys: Vec<Bytes>,
+    pub fits: bool,
 }
 
 impl Default for MemoryStore {
@@ -29,14 +175,67 @@ impl Default for MemoryStore {
 
 impl MemoryStore {
     pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARDS)
+    }
+
+    /// Creates a store with `count` shards, rounded up to a power of two.
+    /// A single shard behaves like one globally locked map.
+    pub fn with_shards(count: usize) -> Self {
+        let count = count.max(1).next_power_of_two();
         Self {
-            map: RwLock::new(HashMap::new()),
+            shards: (0..count).map(|_| RwLock::new(HashMap::new())).collect(),
+            hasher: RandomState::new(),
             start_time: Instant::now(),
             commands_processed: AtomicU64::new(0),
             total_memory: AtomicU64::new(0),
+            peak_memory: AtomicU64::new(0),
+            evicted_keys: AtomicU64::new(0),
+            expired_keys: AtomicU64::new(0),
+            keyspace_hits: AtomicU64::new(0),
+            keyspace_misses: AtomicU64::new(0),
+            volatile_keys: AtomicU64::new(0),
         }
     }
 
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    fn shard_index(&self, key: &[u8]) -> usize {
+        self.hasher.hash_one(key) as usize & (self.shards.len() - 1)
+    }
+
+    fn shard(&self, key: &[u8]) -> &RwLock<Shard> {
+        &self.shards[self.shard_index(key)]
+    }
+
+    /// Shard indexes touched by `keys`, in the order their locks must be taken.
+    fn lock_order<'a>(&self, keys: impl Iterator<Item = &'a Bytes>) -> BTreeSet<usize> {
+        keys.map(|key| self.shard_index(key)).collect()
+    }
+
+    async fn read_shards(
+        &self,
+        order: BTreeSet<usize>,
+    ) -> BTreeMap<usize, Traced<RwLockReadGuard<'_, Shard>>> {
+        let mut guards = BTreeMap::new();
+        for idx in order {
+            guards.insert(idx, read_lock(&self.shards[idx]).await);
+        }
+        guards
+    }
+
+    async fn write_shards(
+        &self,
+        order: BTreeSet<usize>,
+    ) -> BTreeMap<usize, Traced<RwLockWriteGuard<'_, Shard>>> {
+        let mut guards = BTreeMap::new();
+        for idx in order {
+            guards.insert(idx, write_lock(&self.shards[idx]).await);
+        }
+        guards
+    }
+
     pub fn increment_commands(&self) {
         self.commands_processed.fetch_add(1, Ordering::Relaxed);
     }
@@ -52,116 +251,552 @@ impl MemoryStore {
     pub async fn used_memory(&self) -> usize {
         self.total_memory.load(Ordering::Relaxed) as usize
     }
+
+    pub fn peak_memory(&self) -> usize {
+        self.peak_memory.load(Ordering::Relaxed) as usize
+    }
+
+    fn adjust_memory(&self, delta: i64) {
+        let total = self
+            .total_memory
+            .fetch_add(delta as u64, Ordering::Relaxed)
+            .wrapping_add(delta as u64);
+        self.peak_memory.fetch_max(total, Ordering::Relaxed);
+    }
+
+    /// Estimated bytes used by `key`, without counting as an access.
+    pub async fn memory_usage(&self, key: &Bytes) -> Option<usize> {
+        let now = get_current_millis();
+        let shard = read_lock(self.shard(key)).await;
+        shard
+            .get(key)
+            .filter(|slot| !slot.entry.is_expired(now))
+            .map(|slot| slot.memory(key))
+    }
+
+    pub async fn memory_stats(&self) -> MemoryStats {
+        let mut stats = MemoryStats {
+            used: self.total_memory.load(Ordering::Relaxed) as usize,
+            peak: self.peak_memory.load(Ordering::Relaxed) as usize,
+            ..MemoryStats::default()
+        };
+        for shard in &self.shards {
+            let shard = read_lock(shard).await;
+            stats.keys += shard.len();
+            stats.dataset += shard
+                .iter()
+                .map(|(key, slot)| key.len() + slot.entry.value.len())
+                .sum::<usize>();
+            stats.hashtable += shard.capacity() * BUCKET_SIZE;
+        }
+        stats
+    }
+
+    /// Cursor-based iteration for SCAN. The cursor is the next shard to
+    /// visit; whole shards are returned until at least `count` keys have been
+    /// collected, so every key present for the full scan is seen exactly once.
+    pub async fn scan(
+        &self,
+        cursor: u64,
+        count: usize,
+        pattern: Option<&[u8]>,
+    ) -> (u64, Vec<Bytes>) {
+        let now = get_current_millis();
+        let mut shard_idx = cursor as usize;
+        let mut keys = Vec::new();
+        while shard_idx < self.shards.len() && keys.len() < count {
+            let shard = read_lock(&self.shards[shard_idx]).await;
+            keys.extend(
+                shard
+                    .iter()
+                    .filter(|(_, slot)| !slot.entry.is_expired(now))
+                    .filter(|(key, _)| pattern.is_none_or(|p| glob_match(p, key)))
+                    .map(|(key, _)| key.clone()),
+            );
+            shard_idx += 1;
+        }
+        let next = if shard_idx >= self.shards.len() {
+            0
+        } else {
+            shard_idx as u64
+        };
+        (next, keys)
+    }
+
+    /// Every live key with its entry, one shard at a time.
+    pub async fn snapshot(&self) -> Vec<(Bytes, Entry)> {
+        let now = get_current_millis();
+        let mut out = Vec::new();
+        for shard in &self.shards {
+            let shard = read_lock(shard).await;
+            out.extend(
+                shard
+                    .iter()
+                    .filter(|(_, slot)| !slot.entry.is_expired(now))
+                    .map(|(key, slot)| (key.clone(), slot.entry.clone())),
+            );
+        }
+        out
+    }
+
+    pub fn evicted_keys(&self) -> u64 {
+        self.evicted_keys.load(Ordering::Relaxed)
+    }
+
+    pub fn expired_keys(&self) -> u64 {
+        self.expired_keys.load(Ordering::Relaxed)
+    }
+
+    /// Successful and failed key lookups by reads.
+    pub fn keyspace_hits(&self) -> u64 {
+        self.keyspace_hits.load(Ordering::Relaxed)
+    }
+
+    pub fn keyspace_misses(&self) -> u64 {
+        self.keyspace_misses.load(Ordering::Relaxed)
+    }
+
+    pub fn volatile_keys(&self) -> u64 {
+        self.volatile_keys.load(Ordering::Relaxed)
+    }
+
+    /// Mean remaining TTL in milliseconds over a sample of keys with an
+    /// expiry, walking shards from a random starting point; 0 when there are
+    /// none.
+    pub async fn avg_ttl(&self) -> u64 {
+        if self.volatile_keys() == 0 {
+            return 0;
+        }
+        let now = get_current_millis();
+        let first = (random_u64() % self.shards.len() as u64) as usize;
+        let mut ttls = Vec::with_capacity(TTL_SAMPLES);
+        for offset in 0..self.shards.len() {
+            let shard = read_lock(&self.shards[(first + offset) % self.shards.len()]).await;
+            let sampled = shard
+                .values()
+                .filter_map(|slot| match slot.entry.exp {
+                    Expiry::At(at) if at >= now => Some(at - now),
+                    _ => None,
+                })
+                .take(TTL_SAMPLES - ttls.len());
+            ttls.extend(sampled);
+            if ttls.len() == TTL_SAMPLES {
+                break;
+            }
+        }
+        match ttls.len() as u64 {
+            0 => 0,
+            n => ttls.iter().sum::<u64>() / n,
+        }
+    }
+
+    /// Zeroes the counters reported by `INFO stats` for CONFIG RESETSTAT and
+    /// restarts peak tracking from current usage.
+    pub fn reset_stats(&self) {
+        for counter in [
+            &self.commands_processed,
+            &self.evicted_keys,
+            &self.expired_keys,
+            &self.keyspace_hits,
+            &self.keyspace_misses,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.peak_memory
+            .store(self.total_memory.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    /// Runs `fut` with keyspace hit/miss accounting switched on or off.
+    pub async fn count_lookups<F: Future>(enabled: bool, fut: F) -> F::Output {
+        COUNT_LOOKUPS.scope(enabled, fut).await
+    }
+
+    fn record_lookup(&self, hit: bool) {
+        if !COUNT_LOOKUPS.try_with(|enabled| *enabled).unwrap_or(true) {
+            return;
+        }
+        let counter = if hit {
+            &self.keyspace_hits
+        } else {
+            &self.keyspace_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Accounts for `slot` leaving the keyspace for any reason.
+    fn forget(&self, slot: &Slot) {
+        if slot.has_ttl() {
+            self.volatile_keys.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Evicts keys chosen by `policy` until usage is at or below `maxmemory`
+    /// (0 means unlimited).
+    pub async fn evict(&self, maxmemory: u64, policy: EvictionPolicy) -> Eviction {
+        let over = || maxmemory != 0 && self.total_memory.load(Ordering::Relaxed) > maxmemory;
+        if !over() {
+            return Eviction {
+                keys: Vec::new(),
+                fits: true,
+            };
+        }
+        if policy == EvictionPolicy::NoEviction {
+            return Eviction::default();
+        }
+
+        let mut keys = Vec::new();
+        while over() {
+            let now = get_current_millis();
+            let Some(key) = self.pick_victim(policy, now).await else {
+                return Eviction { keys, fits: false };
+            };
+            let Some(slot) = write_lock(self.shard(&key)).await.remove(&key) else {
+                continue;
+            };
+            self.forget(&slot);
+            self.adjust_memory(-(slot.memory(&key) as i64));
+            self.evicted_keys.fetch_add(1, Ordering::Relaxed);
+            keys.push(key);
+        }
+        Eviction { keys, fits: true }
+    }
+
+    /// Samples a few keys, walking shards from a random starting point, and
+    /// returns the best candidate for `policy`, approximating a true
+    /// LRU/LFU/TTL ordering.
+    async fn pick_victim(&self, policy: EvictionPolicy, now: u64) -> Option<Bytes> {
+        let first = (random_u64() % self.shards.len() as u64) as usize;
+        let mut candidates = Vec::with_capacity(EVICTION_SAMPLES);
+        for offset in 0..self.shards.len() {
+            let shard = read_lock(&self.shards[(first + offset) % self.shards.len()]).await;
+            if shard.is_empty() {
+                continue;
+            }
+            let start = (random_u64() % shard.len() as u64) as usize;
+            let sampled = shard
+                .iter()
+                .chain(shard.iter())
+                .skip(start)
+                .take(shard.len())
+                .filter(|(_, slot)| {
+                    !policy.is_volatile() || matches!(slot.entry.exp, Expiry::At(_))
+                })
+                .take(EVICTION_SAMPLES - candidates.len())
+                .map(|(key, slot)| (key.clone(), Self::victim_rank(slot, policy, now)));
+            candidates.extend(sampled);
+            if candidates.len() == EVICTION_SAMPLES {
+                break;
+            }
+        }
+        candidates
+            .into_iter()
+            .min_by_key(|(_, rank)| *rank)
+            .map(|(key, _)| key)
+    }
+
+    /// Plain-value writes for MSET and MSETNX, with every involved shard
+    /// already locked.
+    fn insert_items(
+        &self,
+        shards: &mut BTreeMap<usize, Traced<RwLockWriteGuard<'_, Shard>>>,
+        items: &[(Bytes, Bytes)],
+        now: u64,
+    ) {
+        let mut added_memory: i64 = 0;
+        for (key, value) in items {
+            let shard = shards
+                .get_mut(&self.shard_index(key))
+                .expect("shard locked");
+            let current = shard.remove(key);
+            let old_memory = current.as_ref().map_or(0, |slot| slot.memory(key));
+            if let Some(slot) = &current {
+                self.forget(slot);
+            }
+            added_memory += entry_memory(key.len(), value.len()) as i64 - old_memory as i64;
+
+            let access = match current {
+                Some(slot) => {
+                    slot.access.touch(now);
+                    slot.access
+                }
+                None => Access::new(now),
+            };
+            shard.insert(
+                key.clone(),
+                Slot {
+                    entry: Entry {
+                        value: value.clone().into(),
+                        exp: Expiry::None,
+                    },
+                    access,
+                },
+            );
+        }
+        self.adjust_memory(added_memory);
+    }
+
+    /// The body of `update`, with `key`'s shard already locked.
+    fn update_slot<F, R>(&self, shard: &mut Shard, key: &Bytes, now: u64, f: F) -> R
+    where
+        F: FnOnce(&mut Option<Entry>) -> R,
+    {
+        let current = shard.remove(key);
+        let old_memory = current.as_ref().map_or(0, |slot| slot.memory(key));
+        if let Some(slot) = &current {
+            self.forget(slot);
+            if slot.entry.is_expired(now) {
+                self.expired_keys.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let current = current.filter(|slot| !slot.entry.is_expired(now));
+        self.record_lookup(current.is_some());
+
+        let (mut entry, access) = match current {
+            Some(slot) => {
+                slot.access.touch(now);
+                (Some(slot.entry), Some(slot.access))
+            }
+            None => (None, None),
+        };
+        let result = f(&mut entry);
+
+        let mut new_memory = 0;
+        if let Some(entry) = entry {
+            let slot = Slot {
+                entry,
+                access: access.unwrap_or_else(|| Access::new(now)),
+            };
+            if slot.has_ttl() {
+                self.volatile_keys.fetch_add(1, Ordering::Relaxed);
+            }
+            new_memory = slot.memory(key);
+            shard.insert(key.clone(), slot);
+        }
+        self.adjust_memory(new_memory as i64 - old_memory as i64);
+        result
+    }
+
+    /// Lower ranks are evicted first.
+    fn victim_rank(slot: &Slot, policy: EvictionPolicy, now: u64) -> u64 {
+        match policy {
+            EvictionPolicy::NoEviction
+            | EvictionPolicy::AllKeysRandom
+            | EvictionPolicy::VolatileRandom => 0,
+            EvictionPolicy::AllKeysLru | EvictionPolicy::VolatileLru => {
+                u64::MAX - slot.access.idle_ms(now)
+            }
+            EvictionPolicy::AllKeysLfu | EvictionPolicy::VolatileLfu => {
+                slot.access.frequency(now) as u64
+            }
+            EvictionPolicy::VolatileTtl => match slot.entry.exp {
+                Expiry::At(at) => at,
+                Expiry::None | Expiry::Keep => u64::MAX,
+            },
+        }
+    }
 }
 
 #[async_trait]
 impl Store for MemoryStore {
     async fn set(&self, key: Bytes, mut entry: Entry) -> Entry {
-        let mut map = self.map.write().await;
-        let old_memory = map
-            .get(&key)
-            .filter(|current| !current.is_expired(get_current_millis()))
-            .map(|e| key.len() + e.value.len())
-            .unwrap_or(0);
-
-        let new_memory = key.len() + entry.value.len();
-        self.total_memory.fetch_add(
-            (new_memory as i64 - old_memory as i64) as u64,
-            Ordering::Relaxed,
-        );
-
-        let existing_exp = map
-            .get(&key)
-            .filter(|current| !current.is_expired(get_current_millis()))
-            .map(|current| &current.exp);
+        let now = get_current_millis();
+        let mut shard = write_lock(self.shard(&key)).await;
+        let current = shard.remove(&key);
+        let old_memory = current.as_ref().map_or(0, |slot| slot.memory(&key));
+        if let Some(slot) = &current {
+            self.forget(slot);
+            if slot.entry.is_expired(now) {
+                self.expired_keys.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let current = current.filter(|slot| !slot.entry.is_expired(now));
+        self.adjust_memory(entry_memory(key.len(), entry.value.len()) as i64 - old_memory as i64);
 
-        entry.exp = match &entry.exp {
-            Expiry::Keep => existing_exp.cloned().unwrap_or(Expiry::None),
-            _ => entry.exp.clone(),
+        if let Expiry::Keep = entry.exp {
+            entry.exp = current
+                .as_ref()
+                .map_or(Expiry::None, |slot| slot.entry.exp.clone());
+        }
+        let access = match current {
+            Some(slot) => {
+                slot.access.touch(now);
+                slot.access
+            }
+            None => Access::new(now),
+        };
+        let slot = Slot {
+            entry: entry.clone(),
+            access,
         };
-        map.insert(key, entry.clone());
+        if slot.has_ttl() {
+            self.volatile_keys.fetch_add(1, Ordering::Relaxed);
+        }
+        shard.insert(key, slot);
         entry
     }
 
     async fn get(&self, key: &Bytes) -> Option<Entry> {
         let now = get_current_millis();
+        let lock = self.shard(key);
         {
-            let map = self.map.read().await;
-            match map.get(key) {
-                Some(entry) if !entry.is_expired(now) => return Some(entry.clone()),
-                None => return None,
+            let shard = read_lock(lock).await;
+            match shard.get(key) {
+                Some(slot) if !slot.entry.is_expired(now) => {
+                    slot.access.touch(now);
+                    self.record_lookup(true);
+                    return Some(slot.entry.clone());
+                }
+                None => {
+                    self.record_lookup(false);
+                    return None;
+                }
                 _ => {}
             }
         }
 
-        self.map.write().await.remove(key);
+        self.record_lookup(false);
+        let mut shard = write_lock(lock).await;
+        if shard
+            .get(key)
+            .is_some_and(|slot| slot.entry.is_expired(now))
+            && let Some(slot) = shard.remove(key)
+        {
+            self.forget(&slot);
+            self.expired_keys.fetch_add(1, Ordering::Relaxed);
+            self.adjust_memory(-(slot.memory(key) as i64));
+        }
         None
     }
 
+    async fn update<F, R>(&self, key: &Bytes, f: F) -> R
+    where
+        F: FnOnce(&mut Option<Entry>) -> R + Send,
+        R: Send,
+    {
+        let mut shard = write_lock(self.shard(key)).await;
+        self.update_slot(&mut shard, key, get_current_millis(), f)
+    }
+
+    async fn update_from<F, R>(&self, keys: &[Bytes], dest: &Bytes, f: F) -> R
+    where
+        F: FnOnce(&[Option<Entry>], &mut Option<Entry>) -> R + Send,
+        R: Send,
+    {
+        let now = get_current_millis();
+        let mut shards = self
+            .write_shards(self.lock_order(keys.iter().chain([dest])))
+            .await;
+        let sources: Vec<_> = keys
+            .iter()
+            .map(|key| {
+                let slot = shards[&self.shard_index(key)]
+                    .get(key)
+                    .filter(|slot| !slot.entry.is_expired(now));
+                self.record_lookup(slot.is_some());
+                let slot = slot?;
+                slot.access.touch(now);
+                Some(slot.entry.clone())
+            })
+            .collect();
+        let shard = shards
+            .get_mut(&self.shard_index(dest))
+            .expect("shard locked");
+        self.update_slot(shard, dest, now, |entry| f(&sources, entry))
+    }
+
     async fn del(&self, keys: &[Bytes]) -> i64 {
-        let mut map = self.map.write().await;
+        let mut shards = self.write_shards(self.lock_order(keys.iter())).await;
         let mut freed_memory: usize = 0;
         let mut deleted_count = 0;
         for key in keys {
-            if let Some(entry) = map.remove(key) {
-                freed_memory += key.len() + entry.value.len();
+            let shard = shards
+                .get_mut(&self.shard_index(key))
+                .expect("shard locked");
+            if let Some(slot) = shard.remove(key) {
+                self.forget(&slot);
+                freed_memory += slot.memory(key);
                 deleted_count += 1;
             }
         }
-        self.total_memory
-            .fetch_sub(freed_memory as u64, Ordering::Relaxed);
+        self.adjust_memory(-(freed_memory as i64));
         deleted_count
     }
 
     async fn exists(&self, keys: &[Bytes]) -> i64 {
-        self.map
-            .read()
-            .await
-            .iter()
-            .filter(|(k, _)| keys.contains(k))
-            .filter(|(_, v)| !v.is_expired(get_current_millis()))
+        let now = get_current_millis();
+        let shards = self.read_shards(self.lock_order(keys.iter())).await;
+        keys.iter()
+            .filter(|key| {
+                shards[&self.shard_index(key)]
+                    .get(*key)
+                    .is_some_and(|slot| !slot.entry.is_expired(now))
+            })
             .count() as i64
     }
 
     async fn mget(&self, keys: &[Bytes]) -> Vec<Option<Entry>> {
         let now = get_current_millis();
-        let map = self.map.read().await;
+        let shards = self.read_shards(self.lock_order(keys.iter())).await;
         keys.iter()
-            .map(|k| map.get(k).filter(|v| !v.is_expired(now)).cloned())
+            .map(|k| {
+                let slot = shards[&self.shard_index(k)]
+                    .get(k)
+                    .filter(|v| !v.entry.is_expired(now));
+                self.record_lookup(slot.is_some());
+                let slot = slot?;
+                slot.access.touch(now);
+                Some(slot.entry.clone())
+            })
             .collect()
     }
 
     async fn mset(&self, items: &[(Bytes, Bytes)]) {
-        let mut map = self.map.write().await;
-        let mut added_memory: usize = 0;
-        for (key, value) in items {
-            let old_memory = map.get(key).map(|e| key.len() + e.value.len()).unwrap_or(0);
-            let new_memory = key.len() + value.len();
-            added_memory += new_memory - old_memory;
+        let mut shards = self
+            .write_shards(self.lock_order(items.iter().map(|(key, _)| key)))
+            .await;
+        self.insert_items(&mut shards, items, get_current_millis());
+    }
 
-            map.insert(
-                key.clone(),
-                Entry {
-                    value: value.clone(),
-                    exp: Expiry::None,
-                },
-            );
+    async fn msetnx(&self, items: &[(Bytes, Bytes)]) -> bool {
+        let now = get_current_millis();
+        let mut shards = self
+            .write_shards(self.lock_order(items.iter().map(|(key, _)| key)))
+            .await;
+        let exists = items.iter().any(|(key, _)| {
+            shards[&self.shard_index(key)]
+                .get(key)
+                .is_some_and(|slot| !slot.entry.is_expired(now))
+        });
+        if !exists {
+            self.insert_items(&mut shards, items, now);
         }
-        self.total_memory
-            .fetch_add(added_memory as u64, Ordering::Relaxed);
+        !exists
     }
 
     async fn len(&self) -> usize {
-        self.map.read().await.len()
+        let mut len = 0;
+        for shard in &self.shards {
+            len += read_lock(shard).await.len();
+        }
+        len
     }
 
     async fn clear(&self) {
-        self.map.write().await.clear();
+        let mut shards = self.write_shards((0..self.shards.len()).collect()).await;
+        for shard in shards.values_mut() {
+            shard.clear();
+        }
         self.total_memory.store(0, Ordering::Relaxed);
+        self.volatile_keys.store(0, Ordering::Relaxed);
     }
 
     async fn is_empty(&self) -> bool {
-        self.map.read().await.is_empty()
+        for shard in &self.shards {
+            if !read_lock(shard).await.is_empty() {
+                return false;
+            }
+        }
+        true
     }
 }
 
@@ -171,7 +806,7 @@ mod tests {
 
     fn entry(value: &[u8], exp: Expiry) -> Entry {
         Entry {
-            value: Bytes::from(value.to_vec()),
+            value: Bytes::from(value.to_vec()).into(),
             exp,
         }
     }
@@ -202,6 +837,33 @@ mod tests {
         assert_eq!(store.len().await, 0);
     }
 
+    #[tokio::test]
+    async fn keyspace_counters_track_lookups_and_ttls() {
+        let store = MemoryStore::new();
+        let (a, b) = (Bytes::from_static(b"a"), Bytes::from_static(b"b"));
+        let later = get_current_millis() + 60_000;
+        store.set(a.clone(), entry(b"v", Expiry::At(later))).await;
+        store.set(b.clone(), entry(b"v", Expiry::At(0))).await;
+        assert_eq!(store.volatile_keys(), 2);
+
+        assert!(store.get(&a).await.is_some());
+        assert!(store.get(&b).await.is_none());
+        store.mget(&[a.clone(), Bytes::from_static(b"c")]).await;
+        assert_eq!(store.keyspace_hits(), 2);
+        assert_eq!(store.keyspace_misses(), 2);
+        assert_eq!(store.expired_keys(), 1);
+        assert_eq!(store.volatile_keys(), 1);
+
+        MemoryStore::count_lookups(false, store.get(&a)).await;
+        assert_eq!(store.keyspace_hits(), 2);
+
+        store.set(a.clone(), entry(b"v", Expiry::None)).await;
+        assert_eq!(store.volatile_keys(), 0);
+        store.set(a.clone(), entry(b"v", Expiry::At(later))).await;
+        store.del(&[a]).await;
+        assert_eq!(store.volatile_keys(), 0);
+    }
+
     #[tokio::test]
     async fn get_non_expired_returns_some() {
         let store = MemoryStore::new();
@@ -326,6 +988,25 @@ mod tests {
         assert_eq!(store.used_memory().await, 0);
     }
 
+    #[tokio::test]
+    async fn reset_stats_clears_counters() {
+        let store = MemoryStore::new();
+        store.increment_commands();
+        store.get(&Bytes::from_static(b"missing")).await;
+        store
+            .set(Bytes::from_static(b"k"), entry(b"0123456789", Expiry::None))
+            .await;
+        store
+            .set(Bytes::from_static(b"k"), entry(b"v", Expiry::None))
+            .await;
+
+        store.reset_stats();
+        assert_eq!(store.total_commands(), 0);
+        assert_eq!(store.keyspace_misses(), 0);
+        assert_eq!(store.peak_memory(), entry_memory(1, 1));
+        assert_eq!(store.len().await, 1);
+    }
+
     #[tokio::test]
     async fn total_commands_starts_at_zero() {
         let store = MemoryStore::new();
@@ -369,4 +1050,268 @@ mod tests {
         let resolved = store.set(k.clone(), entry(b"v", Expiry::Keep)).await;
         assert!(matches!(resolved.exp, Expiry::None));
     }
+
+    async fn fill(store: &MemoryStore, keys: &[&'static [u8]], exp: Expiry) {
+        for key in keys {
+            store
+                .set(Bytes::from_static(key), entry(b"0123456789", exp.clone()))
+                .await;
+        }
+    }
+
+    #[tokio::test]
+    async fn evict_is_noop_under_limit_or_unlimited() {
+        let store = MemoryStore::new();
+        fill(&store, &[b"a", b"b"], Expiry::None).await;
+        let eviction = store.evict(0, EvictionPolicy::AllKeysLru).await;
+        assert!(eviction.fits && eviction.keys.is_empty());
+        let eviction = store.evict(1000, EvictionPolicy::NoEviction).await;
+        assert!(eviction.fits && eviction.keys.is_empty());
+    }
+
+    #[tokio::test]
+    async fn noeviction_reports_oom() {
+        let store = MemoryStore::new();
+        fill(&store, &[b"a", b"b"], Expiry::None).await;
+        let eviction = store.evict(1, EvictionPolicy::NoEviction).await;
+        assert!(!eviction.fits);
+        assert_eq!(store.len().await, 2);
+    }
+
+    #[tokio::test]
+    async fn allkeys_eviction_frees_until_under_limit() {
+        let store = MemoryStore::new();
+        fill(&store, &[b"a", b"b", b"c", b"d"], Expiry::None).await;
+        let size = entry_memory(1, 10);
+        let eviction = store
+            .evict((2 * size + 1) as u64, EvictionPolicy::AllKeysRandom)
+            .await;
+        assert!(eviction.fits);
+        assert_eq!(eviction.keys.len(), 2);
+        assert_eq!(store.len().await, 2);
+        assert_eq!(store.evicted_keys(), 2);
+        assert_eq!(store.used_memory().await, 2 * size);
+    }
+
+    #[tokio::test]
+    async fn lru_prefers_least_recently_used() {
+        let store = MemoryStore::new();
+        fill(&store, &[b"old", b"new"], Expiry::None).await;
+        {
+            let shard = store.shard(b"old").read().await;
+            shard[&Bytes::from_static(b"old")]
+                .access
+                .last_access
+                .store(0, Ordering::Relaxed);
+        }
+        let limit = entry_memory(3, 10) as u64;
+        let eviction = store.evict(limit, EvictionPolicy::AllKeysLru).await;
+        assert_eq!(eviction.keys, vec![Bytes::from_static(b"old")]);
+    }
+
+    #[tokio::test]
+    async fn lfu_prefers_least_frequently_used() {
+        let store = MemoryStore::new();
+        fill(&store, &[b"hot", b"cold"], Expiry::None).await;
+        {
+            let shard = store.shard(b"hot").read().await;
+            shard[&Bytes::from_static(b"hot")]
+                .access
+                .lfu_counter
+                .store(100, Ordering::Relaxed);
+        }
+        let limit = entry_memory(4, 10) as u64;
+        let eviction = store.evict(limit, EvictionPolicy::AllKeysLfu).await;
+        assert_eq!(eviction.keys, vec![Bytes::from_static(b"cold")]);
+    }
+
+    #[tokio::test]
+    async fn volatile_policies_only_evict_keys_with_ttl() {
+        let store = MemoryStore::new();
+        let far_future = get_current_millis() + 1_000_000;
+        fill(&store, &[b"keep"], Expiry::None).await;
+        fill(&store, &[b"soon"], Expiry::At(far_future)).await;
+        fill(&store, &[b"late"], Expiry::At(far_future + 1)).await;
+
+        let limit = 2 * entry_memory(4, 10) as u64;
+        let eviction = store.evict(limit, EvictionPolicy::VolatileTtl).await;
+        assert_eq!(eviction.keys, vec![Bytes::from_static(b"soon")]);
+
+        let eviction = store.evict(1, EvictionPolicy::VolatileLru).await;
+        assert!(!eviction.fits);
+        assert_eq!(eviction.keys, vec![Bytes::from_static(b"late")]);
+        assert!(store.get(&Bytes::from_static(b"keep")).await.is_some());
+    }
+
+    #[test]
+    fn lfu_counter_decays_with_idle_time() {
+        let access = Access::new(0);
+        access.lfu_counter.store(10, Ordering::Relaxed);
+        assert_eq!(access.frequency(0), 10);
+        assert_eq!(access.frequency(3 * LFU_DECAY_MS), 7);
+        assert_eq!(access.frequency(u64::MAX), 0);
+    }
+
+    #[tokio::test]
+    async fn memory_includes_per_entry_overhead() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"key");
+        store.set(k.clone(), entry(b"value", Expiry::None)).await;
+        assert_eq!(store.used_memory().await, entry_memory(3, 5));
+        assert_eq!(store.memory_usage(&k).await, Some(3 + 5 + ENTRY_OVERHEAD));
+        assert_eq!(store.memory_usage(&Bytes::from_static(b"nope")).await, None);
+    }
+
+    #[tokio::test]
+    async fn overwriting_expired_key_releases_its_memory() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store.set(k.clone(), entry(b"old", Expiry::At(0))).await;
+        store.set(k.clone(), entry(b"new", Expiry::None)).await;
+        assert_eq!(store.used_memory().await, entry_memory(1, 3));
+
+        store.set(k.clone(), entry(b"old", Expiry::At(0))).await;
+        store.mset(&[(k.clone(), Bytes::from_static(b"v"))]).await;
+        assert_eq!(store.used_memory().await, entry_memory(1, 1));
+    }
+
+    #[tokio::test]
+    async fn lazy_expiry_on_get_releases_memory() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store.set(k.clone(), entry(b"v", Expiry::At(0))).await;
+        assert!(store.get(&k).await.is_none());
+        assert_eq!(store.used_memory().await, 0);
+    }
+
+    #[tokio::test]
+    async fn mset_shrinking_value_does_not_underflow() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store
+            .mset(&[(k.clone(), Bytes::from_static(b"long value"))])
+            .await;
+        store.mset(&[(k.clone(), Bytes::from_static(b"v"))]).await;
+        assert_eq!(store.used_memory().await, entry_memory(1, 1));
+    }
+
+    #[tokio::test]
+    async fn stats_track_peak_and_dataset() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store
+            .set(k.clone(), entry(b"0123456789", Expiry::None))
+            .await;
+        store.del(&[k]).await;
+        store
+            .set(Bytes::from_static(b"x"), entry(b"y", Expiry::None))
+            .await;
+
+        let stats = store.memory_stats().await;
+        assert_eq!(stats.keys, 1);
+        assert_eq!(stats.dataset, 2);
+        assert_eq!(stats.used, entry_memory(1, 1));
+        assert_eq!(stats.peak, entry_memory(1, 10));
+        assert!(stats.hashtable >= BUCKET_SIZE);
+    }
+
+    #[test]
+    fn shard_count_rounds_up_to_power_of_two() {
+        assert_eq!(MemoryStore::with_shards(0).shard_count(), 1);
+        assert_eq!(MemoryStore::with_shards(5).shard_count(), 8);
+        assert_eq!(MemoryStore::new().shard_count(), DEFAULT_SHARDS);
+    }
+
+    #[tokio::test]
+    async fn multi_key_ops_span_shards() {
+        let store = MemoryStore::with_shards(4);
+        let items: Vec<_> = (0..64)
+            .map(|i| (Bytes::from(format!("key:{i}")), Bytes::from(format!("{i}"))))
+            .collect();
+        store.mset(&items).await;
+        assert_eq!(store.len().await, 64);
+
+        let keys: Vec<_> = items.iter().map(|(key, _)| key.clone()).collect();
+        let values = store.mget(&keys).await;
+        assert!(
+            values
+                .iter()
+                .zip(&items)
+                .all(|(got, (_, want))| got.as_ref().is_some_and(|e| e.value == *want))
+        );
+        assert_eq!(store.exists(&keys).await, 64);
+        assert_eq!(store.del(&keys[..32]).await, 32);
+        assert_eq!(store.len().await, 32);
+        assert_eq!(
+            store.used_memory().await,
+            items[32..]
+                .iter()
+                .map(|(k, v)| entry_memory(k.len(), v.len()))
+                .sum::<usize>()
+        );
+    }
+
+    #[tokio::test]
+    async fn exists_counts_repeated_keys() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store.set(k.clone(), entry(b"v", Expiry::None)).await;
+        assert_eq!(store.exists(&[k.clone(), k]).await, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_overlapping_msets_do_not_deadlock() {
+        let store = std::sync::Arc::new(MemoryStore::with_shards(8));
+        let keys: Vec<_> = (0..16).map(|i| Bytes::from(format!("k{i}"))).collect();
+        let mut tasks = Vec::new();
+        for t in 0..8 {
+            let store = store.clone();
+            let keys = keys.clone();
+            tasks.push(tokio::spawn(async move {
+                let mut items: Vec<_> = keys
+                    .iter()
+                    .map(|k| (k.clone(), Bytes::from_static(b"v")))
+                    .collect();
+                if t % 2 == 1 {
+                    items.reverse();
+                }
+                for _ in 0..200 {
+                    store.mset(&items).await;
+                    store.del(&keys[..4]).await;
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(store.len().await, 12);
+    }
+
+    #[tokio::test]
+    async fn scan_visits_every_key_once() {
+        let store = MemoryStore::with_shards(8);
+        let items: Vec<_> = (0..50)
+            .map(|i| (Bytes::from(format!("key:{i}")), Bytes::from_static(b"v")))
+            .collect();
+        store.mset(&items).await;
+        store
+            .set(Bytes::from_static(b"other"), entry(b"v", Expiry::None))
+            .await;
+
+        let mut seen = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (next, keys) = store.scan(cursor, 5, Some(b"key:*")).await;
+            seen.extend(keys);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        seen.sort();
+        let mut expected: Vec<_> = items.into_iter().map(|(k, _)| k).collect();
+        expected.sort();
+        assert_eq!(seen, expected);
+        assert_eq!(store.scan(99, 10, None).await, (0, Vec::new()));
+    }
 }
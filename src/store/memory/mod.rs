@@ -1,3 +1,5 @@
 mod engine;
 
-pub use engine::MemoryStore;
+pub use engine::{
+    DEFAULT_SHARDS, ENTRY_OVERHEAD, Eviction, MemoryStats, MemoryStore, entry_memory,
+};
@@ -0,0 +1,731 @@
+use tokio_util::bytes::Bytes;
+
+use crate::store::{
+    ops::{MAX_STRING_LEN, get_string, rewrite_string},
+    traits::Store,
+    types::{Entry, Expiry},
+};
+
+/// Highest addressable bit, so a bitmap never grows past `MAX_STRING_LEN`.
+pub const MAX_BIT_OFFSET: usize = MAX_STRING_LEN * 8 - 1;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BitUnit {
+    #[default]
+    Byte,
+    Bit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BitRange {
+    pub start: i64,
+    pub end: Option<i64>,
+    pub unit: BitUnit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitOp {
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BitfieldType {
+    pub signed: bool,
+    pub bits: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BitfieldOverflow {
+    #[default]
+    Wrap,
+    Sat,
+    Fail,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitfieldOp {
+    Get {
+        ty: BitfieldType,
+        offset: usize,
+    },
+    Set {
+        ty: BitfieldType,
+        offset: usize,
+        value: i64,
+    },
+    IncrBy {
+        ty: BitfieldType,
+        offset: usize,
+        incr: i64,
+    },
+    Overflow(BitfieldOverflow),
+}
+
+pub async fn setbit(
+    store: &impl Store,
+    key: Bytes,
+    offset: usize,
+    bit: bool,
+) -> Result<(bool, Entry), &'static str> {
+    store
+        .update(&key, |slot| {
+            let mut previous = false;
+            let resolved = rewrite_string(slot, |current| {
+                let mut buf = current.map(|value| value.to_vec()).unwrap_or_default();
+                let byte = offset / 8;
+                if buf.len() <= byte {
+                    buf.resize(byte + 1, 0);
+                }
+
+                let mask = 0x80u8 >> (offset % 8);
+                previous = buf[byte] & mask != 0;
+                if bit {
+                    buf[byte] |= mask;
+                } else {
+                    buf[byte] &= !mask;
+                }
+                Ok(buf.into())
+            })?;
+            Ok((previous, resolved))
+        })
+        .await
+}
+
+pub async fn getbit(store: &impl Store, key: Bytes, offset: usize) -> Result<bool, &'static str> {
+    Ok(get_string(store, &key)
+        .await?
+        .is_some_and(|value| bit_at(&value, offset)))
+}
+
+pub async fn bitcount(
+    store: &impl Store,
+    key: Bytes,
+    range: Option<BitRange>,
+) -> Result<i64, &'static str> {
+    let Some(value) = get_string(store, &key).await? else {
+        return Ok(0);
+    };
+
+    let (start, end) = match range {
+        None if value.is_empty() => return Ok(0),
+        None => (0, value.len() * 8 - 1),
+        Some(range) => match bit_span(value.len(), range) {
+            Some(span) => span,
+            None => return Ok(0),
+        },
+    };
+    Ok(count_ones(&value, start, end) as i64)
+}
+
+pub async fn bitpos(
+    store: &impl Store,
+    key: Bytes,
+    bit: bool,
+    range: Option<BitRange>,
+) -> Result<i64, &'static str> {
+    let Some(value) = get_string(store, &key).await? else {
+        return Ok(if bit { -1 } else { 0 });
+    };
+    Ok(find_bit(&value, bit, range))
+}
+
+fn find_bit(value: &[u8], bit: bool, range: Option<BitRange>) -> i64 {
+    let end_given = range.is_some_and(|r| r.end.is_some());
+    let span = match range {
+        None if value.is_empty() => None,
+        None => Some((0, value.len() * 8 - 1)),
+        Some(range) => bit_span(value.len(), range),
+    };
+    let Some((start, end)) = span else {
+        return -1;
+    };
+
+    let skip = if bit { 0x00 } else { 0xFF };
+    let mut pos = start;
+    while pos <= end {
+        if pos % 8 == 0 && pos + 7 <= end && value[pos / 8] == skip {
+            pos += 8;
+            continue;
+        }
+        if bit_at(value, pos) == bit {
+            return pos as i64;
+        }
+        pos += 1;
+    }
+
+    // Clear bits past the end of the string count as zero-padding, unless the
+    // caller pinned the range with an explicit end.
+    if !bit && !end_given {
+        return (end + 1) as i64;
+    }
+    -1
+}
+
+/// Computes `op` across `keys` and stores it at `dest`, all under one lock so
+/// `dest` can safely be among the sources. Returns `None` when the result is
+/// empty, in which case `dest` is deleted instead.
+pub async fn bitop(
+    store: &impl Store,
+    op: BitOp,
+    dest: Bytes,
+    keys: &[Bytes],
+) -> Result<Option<Entry>, &'static str> {
+    store
+        .update_from(keys, &dest, |sources, dest| {
+            let sources = sources
+                .iter()
+                .map(|entry| match entry {
+                    Some(entry) => entry.value.as_string().map(|value| &value[..]),
+                    None => Ok(&[][..]),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let len = sources.iter().map(|src| src.len()).max().unwrap_or(0);
+            if len == 0 {
+                *dest = None;
+                return Ok(None);
+            }
+
+            let byte_at = |src: &[u8], i: usize| src.get(i).copied().unwrap_or(0);
+            let result: Vec<u8> = (0..len)
+                .map(|i| {
+                    let mut bytes = sources.iter().map(|src| byte_at(src, i));
+                    let first = bytes.next().unwrap_or(0);
+                    match op {
+                        BitOp::And => bytes.fold(first, |acc, b| acc & b),
+                        BitOp::Or => bytes.fold(first, |acc, b| acc | b),
+                        BitOp::Xor => bytes.fold(first, |acc, b| acc ^ b),
+                        BitOp::Not => !first,
+                    }
+                })
+                .collect();
+
+            let entry = Entry {
+                value: Bytes::from(result).into(),
+                exp: Expiry::None,
+            };
+            *dest = Some(entry.clone());
+            Ok(Some(entry))
+        })
+        .await
+}
+
+/// Runs a `BITFIELD` program. Each non-`OVERFLOW` op yields a reply value,
+/// `None` meaning a `FAIL` overflow. The resolved entry is returned only when
+/// the program contained a write.
+pub async fn bitfield(
+    store: &impl Store,
+    key: Bytes,
+    ops: &[BitfieldOp],
+) -> Result<(Vec<Option<i64>>, Option<Entry>), &'static str> {
+    let writes = ops
+        .iter()
+        .any(|op| matches!(op, BitfieldOp::Set { .. } | BitfieldOp::IncrBy { .. }));
+    if !writes {
+        let value = get_string(store, &key).await?;
+        let mut buf = value.unwrap_or_default().to_vec();
+        return Ok((run_bitfield(&mut buf, ops), None));
+    }
+
+    let needed = ops
+        .iter()
+        .filter_map(|op| match op {
+            BitfieldOp::Set { ty, offset, .. } | BitfieldOp::IncrBy { ty, offset, .. } => {
+                Some((offset + ty.bits as usize).div_ceil(8))
+            }
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    store
+        .update(&key, |slot| {
+            let mut replies = Vec::new();
+            let resolved = rewrite_string(slot, |current| {
+                let mut buf = current.map(|value| value.to_vec()).unwrap_or_default();
+                if buf.len() < needed {
+                    buf.resize(needed, 0);
+                }
+                replies = run_bitfield(&mut buf, ops);
+                Ok(buf.into())
+            })?;
+            Ok((replies, Some(resolved)))
+        })
+        .await
+}
+
+fn run_bitfield(buf: &mut [u8], ops: &[BitfieldOp]) -> Vec<Option<i64>> {
+    let mut overflow = BitfieldOverflow::default();
+    let mut replies = Vec::new();
+    for op in ops {
+        match *op {
+            BitfieldOp::Overflow(mode) => overflow = mode,
+            BitfieldOp::Get { ty, offset } => replies.push(Some(read_field(buf, offset, ty))),
+            BitfieldOp::Set { ty, offset, value } => {
+                let old = read_field(buf, offset, ty);
+                let target = if ty.signed {
+                    value as i128
+                } else {
+                    value as u64 as i128
+                };
+                match clamp_field(target, ty, overflow) {
+                    Some(new) => {
+                        write_field(buf, offset, ty, new);
+                        replies.push(Some(old));
+                    }
+                    None => replies.push(None),
+                }
+            }
+            BitfieldOp::IncrBy { ty, offset, incr } => {
+                let old = read_field(buf, offset, ty);
+                match clamp_field(old as i128 + incr as i128, ty, overflow) {
+                    Some(new) => {
+                        write_field(buf, offset, ty, new);
+                        replies.push(Some(new));
+                    }
+                    None => replies.push(None),
+                }
+            }
+        }
+    }
+    replies
+}
+
+fn bit_at(buf: &[u8], offset: usize) -> bool {
+    buf.get(offset / 8)
+        .is_some_and(|byte| byte & (0x80 >> (offset % 8)) != 0)
+}
+
+/// Resolves a (possibly negative) `BYTE`/`BIT` range against a string of
+/// `len` bytes into an inclusive span of bit positions.
+fn bit_span(len: usize, range: BitRange) -> Option<(usize, usize)> {
+    let total = match range.unit {
+        BitUnit::Byte => len as i64,
+        BitUnit::Bit => len as i64 * 8,
+    };
+    let mut start = range.start;
+    let mut end = range.end.unwrap_or(total - 1);
+    if start < 0 && end < 0 && start > end {
+        return None;
+    }
+    if start < 0 {
+        start = (total + start).max(0);
+    }
+    if end < 0 {
+        end = (total + end).max(0);
+    }
+    end = end.min(total - 1);
+    if total == 0 || start > end {
+        return None;
+    }
+
+    let (start, end) = (start as usize, end as usize);
+    match range.unit {
+        BitUnit::Byte => Some((start * 8, end * 8 + 7)),
+        BitUnit::Bit => Some((start, end)),
+    }
+}
+
+fn count_ones(buf: &[u8], start: usize, end: usize) -> u32 {
+    let (first, last) = (start / 8, end / 8);
+    let head = 0xFFu8 >> (start % 8);
+    let tail = 0xFFu8 << (7 - end % 8);
+    if first == last {
+        return (buf[first] & head & tail).count_ones();
+    }
+    (buf[first] & head).count_ones()
+        + buf[first + 1..last]
+            .iter()
+            .map(|b| b.count_ones())
+            .sum::<u32>()
+        + (buf[last] & tail).count_ones()
+}
+
+fn read_field(buf: &[u8], offset: usize, ty: BitfieldType) -> i64 {
+    let mut raw = 0u64;
+    for i in 0..ty.bits as usize {
+        raw = (raw << 1) | bit_at(buf, offset + i) as u64;
+    }
+    if ty.signed && ty.bits < 64 && raw & (1 << (ty.bits - 1)) != 0 {
+        raw |= u64::MAX << ty.bits;
+    }
+    raw as i64
+}
+
+fn write_field(buf: &mut [u8], offset: usize, ty: BitfieldType, value: i64) {
+    let raw = value as u64;
+    for i in 0..ty.bits as usize {
+        let pos = offset + i;
+        let mask = 0x80u8 >> (pos % 8);
+        if raw >> (ty.bits as usize - 1 - i) & 1 == 1 {
+            buf[pos / 8] |= mask;
+        } else {
+            buf[pos / 8] &= !mask;
+        }
+    }
+}
+
+/// Applies the overflow policy to a value that should land in a field of
+/// type `ty`. `None` means the write must be skipped (`FAIL`).
+fn clamp_field(value: i128, ty: BitfieldType, overflow: BitfieldOverflow) -> Option<i64> {
+    let (min, max) = if ty.signed {
+        (-(1i128 << (ty.bits - 1)), (1i128 << (ty.bits - 1)) - 1)
+    } else {
+        (0, (1i128 << ty.bits) - 1)
+    };
+    if (min..=max).contains(&value) {
+        return Some(value as i64);
+    }
+
+    match overflow {
+        BitfieldOverflow::Fail => None,
+        BitfieldOverflow::Sat => Some(value.clamp(min, max) as i64),
+        BitfieldOverflow::Wrap => Some(((value - min).rem_euclid(1i128 << ty.bits) + min) as i64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    };
+
+    use super::*;
+    use crate::store::memory::MemoryStore;
+
+    fn entry(value: &[u8]) -> Entry {
+        Entry {
+            value: Bytes::from(value.to_vec()).into(),
+            exp: Expiry::None,
+        }
+    }
+
+    fn range(start: i64, end: i64, unit: BitUnit) -> Option<BitRange> {
+        Some(BitRange {
+            start,
+            end: Some(end),
+            unit,
+        })
+    }
+
+    fn int(signed: bool, bits: u32) -> BitfieldType {
+        BitfieldType { signed, bits }
+    }
+
+    #[tokio::test]
+    async fn setbit_grows_and_returns_previous() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        let (previous, resolved) = setbit(&store, k.clone(), 7, true).await.unwrap();
+        assert!(!previous);
+        assert_eq!(resolved.value, Bytes::from_static(&[0x01]));
+
+        let (previous, resolved) = setbit(&store, k.clone(), 7, false).await.unwrap();
+        assert!(previous);
+        assert_eq!(resolved.value, Bytes::from_static(&[0x00]));
+
+        let (_, resolved) = setbit(&store, k, 17, true).await.unwrap();
+        assert_eq!(resolved.value, Bytes::from_static(&[0x00, 0x00, 0x40]));
+    }
+
+    #[tokio::test]
+    async fn getbit_out_of_range_is_zero() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store.set(k.clone(), entry(&[0x80])).await;
+        assert!(getbit(&store, k.clone(), 0).await.unwrap());
+        assert!(!getbit(&store, k.clone(), 1).await.unwrap());
+        assert!(!getbit(&store, k, 100).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn bitcount_byte_and_bit_ranges() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store.set(k.clone(), entry(b"foobar")).await;
+        assert_eq!(bitcount(&store, k.clone(), None).await.unwrap(), 26);
+        assert_eq!(
+            bitcount(&store, k.clone(), range(0, 0, BitUnit::Byte))
+                .await
+                .unwrap(),
+            4
+        );
+        assert_eq!(
+            bitcount(&store, k.clone(), range(1, 1, BitUnit::Byte))
+                .await
+                .unwrap(),
+            6
+        );
+        assert_eq!(
+            bitcount(&store, k.clone(), range(5, 30, BitUnit::Bit))
+                .await
+                .unwrap(),
+            17
+        );
+        assert_eq!(
+            bitcount(&store, k.clone(), range(-2, -1, BitUnit::Byte))
+                .await
+                .unwrap(),
+            7
+        );
+        assert_eq!(
+            bitcount(&store, k, range(3, 1, BitUnit::Byte))
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn bitpos_finds_set_and_clear_bits() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store.set(k.clone(), entry(&[0xff, 0xf0, 0x00])).await;
+        assert_eq!(bitpos(&store, k.clone(), false, None).await.unwrap(), 12);
+
+        store.set(k.clone(), entry(&[0x00, 0xff, 0xf0])).await;
+        assert_eq!(bitpos(&store, k.clone(), true, None).await.unwrap(), 8);
+        let from_byte_two = Some(BitRange {
+            start: 2,
+            end: None,
+            unit: BitUnit::Byte,
+        });
+        assert_eq!(
+            bitpos(&store, k.clone(), true, from_byte_two)
+                .await
+                .unwrap(),
+            16
+        );
+        assert_eq!(
+            bitpos(&store, k.clone(), true, range(7, 15, BitUnit::Bit))
+                .await
+                .unwrap(),
+            8
+        );
+
+        store.set(k.clone(), entry(&[0xff, 0xff])).await;
+        assert_eq!(bitpos(&store, k.clone(), false, None).await.unwrap(), 16);
+        assert_eq!(
+            bitpos(&store, k.clone(), false, range(0, -1, BitUnit::Byte))
+                .await
+                .unwrap(),
+            -1
+        );
+
+        let missing = Bytes::from_static(b"missing");
+        assert_eq!(
+            bitpos(&store, missing.clone(), false, None).await.unwrap(),
+            0
+        );
+        assert_eq!(bitpos(&store, missing, true, None).await.unwrap(), -1);
+    }
+
+    #[tokio::test]
+    async fn bitop_pads_shorter_sources() {
+        let store = MemoryStore::new();
+        let (a, b, dest) = (
+            Bytes::from_static(b"a"),
+            Bytes::from_static(b"b"),
+            Bytes::from_static(b"dest"),
+        );
+        store.set(a.clone(), entry(&[0xf0, 0xff])).await;
+        store.set(b.clone(), entry(&[0x3c])).await;
+        let keys = [a.clone(), b.clone()];
+
+        let and = bitop(&store, BitOp::And, dest.clone(), &keys)
+            .await
+            .unwrap();
+        assert_eq!(and.unwrap().value, Bytes::from_static(&[0x30, 0x00]));
+        let or = bitop(&store, BitOp::Or, dest.clone(), &keys).await.unwrap();
+        assert_eq!(or.unwrap().value, Bytes::from_static(&[0xfc, 0xff]));
+        let xor = bitop(&store, BitOp::Xor, dest.clone(), &keys)
+            .await
+            .unwrap();
+        assert_eq!(xor.unwrap().value, Bytes::from_static(&[0xcc, 0xff]));
+        let not = bitop(&store, BitOp::Not, dest.clone(), &[b]).await.unwrap();
+        assert_eq!(not.unwrap().value, Bytes::from_static(&[0xc3]));
+    }
+
+    #[tokio::test]
+    async fn bitop_on_empty_sources_deletes_dest() {
+        let store = MemoryStore::new();
+        let dest = Bytes::from_static(b"dest");
+        store.set(dest.clone(), entry(b"x")).await;
+        let result = bitop(
+            &store,
+            BitOp::Or,
+            dest.clone(),
+            &[Bytes::from_static(b"none")],
+        )
+        .await
+        .unwrap();
+        assert!(result.is_none());
+        assert!(store.get(&dest).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn bitfield_get_on_missing_key_does_not_write() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        let (replies, resolved) = bitfield(
+            &store,
+            k.clone(),
+            &[BitfieldOp::Get {
+                ty: int(false, 8),
+                offset: 0,
+            }],
+        )
+        .await
+        .unwrap();
+        assert_eq!(replies, vec![Some(0)]);
+        assert!(resolved.is_none());
+        assert!(store.get(&k).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn bitfield_set_get_and_signed_reads() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        let (replies, resolved) = bitfield(
+            &store,
+            k,
+            &[
+                BitfieldOp::Set {
+                    ty: int(false, 8),
+                    offset: 0,
+                    value: 255,
+                },
+                BitfieldOp::Get {
+                    ty: int(false, 8),
+                    offset: 0,
+                },
+                BitfieldOp::Get {
+                    ty: int(true, 8),
+                    offset: 0,
+                },
+                BitfieldOp::Get {
+                    ty: int(false, 4),
+                    offset: 4,
+                },
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(replies, vec![Some(0), Some(255), Some(-1), Some(15)]);
+        assert_eq!(resolved.unwrap().value, Bytes::from_static(&[0xff]));
+    }
+
+    #[tokio::test]
+    async fn bitfield_overflow_modes() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        let ty = int(false, 2);
+        let (replies, _) = bitfield(
+            &store,
+            k.clone(),
+            &[
+                BitfieldOp::IncrBy {
+                    ty,
+                    offset: 0,
+                    incr: 5,
+                },
+                BitfieldOp::Overflow(BitfieldOverflow::Sat),
+                BitfieldOp::IncrBy {
+                    ty,
+                    offset: 0,
+                    incr: 10,
+                },
+                BitfieldOp::Overflow(BitfieldOverflow::Fail),
+                BitfieldOp::IncrBy {
+                    ty,
+                    offset: 0,
+                    incr: 1,
+                },
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(replies, vec![Some(1), Some(3), None]);
+
+        let ty = int(true, 8);
+        let (replies, _) = bitfield(
+            &store,
+            k,
+            &[
+                BitfieldOp::Set {
+                    ty,
+                    offset: 8,
+                    value: 127,
+                },
+                BitfieldOp::IncrBy {
+                    ty,
+                    offset: 8,
+                    incr: 1,
+                },
+                BitfieldOp::Overflow(BitfieldOverflow::Sat),
+                BitfieldOp::IncrBy {
+                    ty,
+                    offset: 8,
+                    incr: -200,
+                },
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(replies, vec![Some(0), Some(-128), Some(-128)]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn bitop_into_a_source_keeps_concurrent_setbits() {
+        const BITS: usize = 4096;
+        let store = Arc::new(MemoryStore::new());
+        let dest = Bytes::from_static(b"dest");
+        let src = Bytes::from_static(b"src");
+        store.set(src.clone(), entry(&[0x01])).await;
+
+        let done = Arc::new(AtomicBool::new(false));
+        let merges = tokio::spawn({
+            let (store, done) = (store.clone(), done.clone());
+            let keys = [dest.clone(), src.clone()];
+            async move {
+                while !done.load(Ordering::Relaxed) {
+                    bitop(&*store, BitOp::Or, keys[0].clone(), &keys)
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+        let setters: Vec<_> = (0..4)
+            .map(|first| {
+                let (store, dest) = (store.clone(), dest.clone());
+                tokio::spawn(async move {
+                    for offset in (8 + first..BITS).step_by(4) {
+                        setbit(&*store, dest.clone(), offset, true).await.unwrap();
+                        tokio::task::yield_now().await;
+                    }
+                })
+            })
+            .collect();
+        for setter in setters {
+            setter.await.unwrap();
+        }
+        done.store(true, Ordering::Relaxed);
+        merges.await.unwrap();
+
+        let value = get_string(&*store, &dest).await.unwrap().unwrap();
+        assert_eq!(value[0], 0x01);
+        assert!(value[1..BITS / 8].iter().all(|&byte| byte == 0xff));
+    }
+
+    #[test]
+    fn clamp_field_wraps_i64() {
+        let ty = int(true, 64);
+        assert_eq!(
+            clamp_field(i64::MAX as i128 + 1, ty, BitfieldOverflow::Wrap),
+            Some(i64::MIN)
+        );
+    }
+}
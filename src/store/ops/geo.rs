@@ -0,0 +1,689 @@
+//! Geospatial indexes: members of a sorted set scored by their 52-bit
+//! interleaved geohash, using the same projection and distance formula as
+//! Redis.
+
+use std::{f64::consts::FRAC_PI_2, sync::Arc};
+
+use tokio_util::bytes::Bytes;
+
+use crate::store::{
+    ops::zset::{self, SortedSet, ZAddOutcome},
+    traits::Store,
+    types::{Entry, Expiry, SetCondition, Value, WRONG_TYPE},
+};
+
+const STEP: u32 = 26;
+pub const LON_MIN: f64 = -180.0;
+pub const LON_MAX: f64 = 180.0;
+pub const LAT_MIN: f64 = -85.051_128_78;
+pub const LAT_MAX: f64 = 85.051_128_78;
+const EARTH_RADIUS_M: f64 = 6_372_797.560_856;
+const BASE32: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+const MISSING_MEMBER: &str = "ERR could not decode requested zset member";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GeoUnit {
+    #[default]
+    Meters,
+    Kilometers,
+    Feet,
+    Miles,
+}
+
+impl GeoUnit {
+    pub fn parse(raw: &[u8]) -> Option<Self> {
+        match raw.to_ascii_lowercase().as_slice() {
+            b"m" => Some(Self::Meters),
+            b"km" => Some(Self::Kilometers),
+            b"ft" => Some(Self::Feet),
+            b"mi" => Some(Self::Miles),
+            _ => None,
+        }
+    }
+
+    pub fn meters(self) -> f64 {
+        match self {
+            Self::Meters => 1.0,
+            Self::Kilometers => 1000.0,
+            Self::Feet => 0.3048,
+            Self::Miles => 1609.34,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GeoOrigin {
+    Member(Bytes),
+    LonLat(f64, f64),
+}
+
+/// Search area, with all dimensions already converted to meters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GeoShape {
+    Radius(f64),
+    Box { width: f64, height: f64 },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GeoSort {
+    #[default]
+    None,
+    Asc,
+    Desc,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoQuery {
+    pub origin: GeoOrigin,
+    pub shape: GeoShape,
+    pub unit: GeoUnit,
+    pub sort: GeoSort,
+    pub count: Option<usize>,
+    pub any: bool,
+    pub with_coord: bool,
+    pub with_dist: bool,
+    pub with_hash: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoMatch {
+    pub member: Bytes,
+    pub dist: f64,
+    pub hash: u64,
+    pub lon: f64,
+    pub lat: f64,
+}
+
+pub fn valid_coords(lon: f64, lat: f64) -> bool {
+    (LON_MIN..=LON_MAX).contains(&lon) && (LAT_MIN..=LAT_MAX).contains(&lat)
+}
+
+fn interleave(lat: u32, lon: u32) -> u64 {
+    (0..32).fold(0, |bits, i| {
+        bits | ((lat as u64 >> i) & 1) << (2 * i) | ((lon as u64 >> i) & 1) << (2 * i + 1)
+    })
+}
+
+fn deinterleave(bits: u64) -> (u32, u32) {
+    (0..32).fold((0, 0), |(lat, lon), i| {
+        (
+            lat | (((bits >> (2 * i)) & 1) as u32) << i,
+            lon | (((bits >> (2 * i + 1)) & 1) as u32) << i,
+        )
+    })
+}
+
+fn encode_in(lon: f64, lat: f64, lat_min: f64, lat_max: f64) -> u64 {
+    let cells = (1u64 << STEP) as f64;
+    let lat_offset = (lat - lat_min) / (lat_max - lat_min) * cells;
+    let lon_offset = (lon - LON_MIN) / (LON_MAX - LON_MIN) * cells;
+    interleave(lat_offset as u32, lon_offset as u32)
+}
+
+/// Encodes a coordinate into the 52-bit geohash used as the member's score.
+pub fn encode(lon: f64, lat: f64) -> u64 {
+    encode_in(lon, lat, LAT_MIN, LAT_MAX)
+}
+
+/// Decodes a geohash score to the centre of its cell.
+pub fn decode(bits: u64) -> (f64, f64) {
+    let (lat_cell, lon_cell) = deinterleave(bits);
+    let cells = (1u64 << STEP) as f64;
+    let lat_lo = LAT_MIN + lat_cell as f64 / cells * (LAT_MAX - LAT_MIN);
+    let lat_hi = LAT_MIN + (lat_cell as f64 + 1.0) / cells * (LAT_MAX - LAT_MIN);
+    let lon_lo = LON_MIN + lon_cell as f64 / cells * (LON_MAX - LON_MIN);
+    let lon_hi = LON_MIN + (lon_cell as f64 + 1.0) / cells * (LON_MAX - LON_MIN);
+    (
+        ((lon_lo + lon_hi) / 2.0).clamp(LON_MIN, LON_MAX),
+        ((lat_lo + lat_hi) / 2.0).clamp(LAT_MIN, LAT_MAX),
+    )
+}
+
+/// Standard 11-character base32 geohash, computed over the full ±90 latitude
+/// range rather than the Mercator-limited one used for scores.
+pub fn hash_string(lon: f64, lat: f64) -> String {
+    let bits = encode_in(lon, lat, -90.0, 90.0);
+    (0..11)
+        .map(|i| {
+            let idx = if i == 10 {
+                0
+            } else {
+                (bits >> (52 - (i + 1) * 5)) & 0x1f
+            };
+            BASE32[idx as usize] as char
+        })
+        .collect()
+}
+
+fn lat_distance(lat1: f64, lat2: f64) -> f64 {
+    EARTH_RADIUS_M * (lat2.to_radians() - lat1.to_radians()).abs()
+}
+
+/// Haversine distance in meters.
+pub fn distance(lon1: f64, lat1: f64, lon2: f64, lat2: f64) -> f64 {
+    let v = ((lon2.to_radians() - lon1.to_radians()) / 2.0).sin();
+    if v == 0.0 {
+        return lat_distance(lat1, lat2);
+    }
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let u = ((lat2 - lat1) / 2.0).sin();
+    let a = u * u + lat1.cos() * lat2.cos() * v * v;
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+/// Distance from the origin if the point falls inside `shape`.
+fn distance_within(shape: GeoShape, origin: (f64, f64), point: (f64, f64)) -> Option<f64> {
+    let ((olon, olat), (lon, lat)) = (origin, point);
+    match shape {
+        GeoShape::Radius(radius) => {
+            let dist = distance(olon, olat, lon, lat);
+            (dist <= radius).then_some(dist)
+        }
+        GeoShape::Box { width, height } => {
+            if lat_distance(lat, olat) > height / 2.0 || distance(lon, lat, olon, lat) > width / 2.0
+            {
+                return None;
+            }
+            Some(distance(olon, olat, lon, lat))
+        }
+    }
+}
+
+/// Longitude and latitude half-extents, in degrees, of a box around the
+/// origin holding every point `shape` can match. The longitude one is `None`
+/// when the shape reaches a pole and so spans every longitude.
+fn bounding_deltas(shape: GeoShape, (_, lat): (f64, f64)) -> (Option<f64>, f64) {
+    match shape {
+        GeoShape::Radius(radius) => {
+            let reach = radius / EARTH_RADIUS_M;
+            let ratio = reach.sin() / lat.to_radians().cos();
+            (
+                (ratio < 1.0).then(|| ratio.asin().to_degrees()),
+                reach.to_degrees(),
+            )
+        }
+        // Widths are measured between points on the same latitude, which
+        // spreads them over the most longitude nearest the pole.
+        GeoShape::Box { width, height } => {
+            let lat_delta = height / 2.0 / EARTH_RADIUS_M;
+            let widest = (lat.abs().to_radians() + lat_delta).min(FRAC_PI_2);
+            let ratio = (width / 4.0 / EARTH_RADIUS_M).sin() / widest.cos();
+            (
+                (ratio < 1.0).then(|| 2.0 * ratio.asin().to_degrees()),
+                lat_delta.to_degrees(),
+            )
+        }
+    }
+}
+
+/// Score ranges covering `shape` around `origin`: the cell holding the origin
+/// and its eight neighbours, at the finest step where together they contain
+/// the shape's bounding box. Ranges are sorted and half-open.
+fn search_ranges(shape: GeoShape, origin: (f64, f64)) -> Vec<(u64, u64)> {
+    let (lon_delta, lat_delta) = bounding_deltas(shape, origin);
+    let (lat_cell, lon_cell) = deinterleave(encode(origin.0, origin.1));
+    // Cells per side and the origin's cell when the grid is `step` bits deep.
+    let grid = |step: u32| {
+        let shift = STEP - step;
+        (
+            1i64 << step,
+            (lat_cell >> shift) as i64,
+            (lon_cell >> shift) as i64,
+        )
+    };
+    let step = (1..=STEP)
+        .rev()
+        .find(|&step| {
+            let (cells, lat_cell, lon_cell) = grid(step);
+            let lat_size = (LAT_MAX - LAT_MIN) / cells as f64;
+            let lon_size = (LON_MAX - LON_MIN) / cells as f64;
+            let lat_covered = (lat_cell == 0
+                || LAT_MIN + (lat_cell - 1) as f64 * lat_size <= origin.1 - lat_delta)
+                && (lat_cell == cells - 1
+                    || LAT_MIN + (lat_cell + 2) as f64 * lat_size >= origin.1 + lat_delta);
+            let lon_covered = cells <= 3
+                || lon_delta.is_some_and(|delta| {
+                    LON_MIN + (lon_cell - 1) as f64 * lon_size <= origin.0 - delta
+                        && LON_MIN + (lon_cell + 2) as f64 * lon_size >= origin.0 + delta
+                });
+            lat_covered && lon_covered
+        })
+        .unwrap_or(1);
+
+    let (cells, lat_cell, lon_cell) = grid(step);
+    let shift = 2 * (STEP - step);
+    let mut hashes: Vec<u64> = (-1..=1)
+        .flat_map(|dlat| (-1..=1).map(move |dlon| (lat_cell + dlat, lon_cell + dlon)))
+        .filter(|&(lat, _)| (0..cells).contains(&lat))
+        .map(|(lat, lon)| interleave(lat as u32, lon.rem_euclid(cells) as u32))
+        .collect();
+    hashes.sort_unstable();
+    hashes.dedup();
+
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    for hash in hashes {
+        let (min, max) = (hash << shift, (hash + 1) << shift);
+        match ranges.last_mut() {
+            Some(last) if last.1 == min => last.1 = max,
+            _ => ranges.push((min, max)),
+        }
+    }
+    ranges
+}
+
+/// Adds or updates members, returning how many were added (or also changed,
+/// with `ch`) and the stored entry when anything was written.
+pub async fn geoadd(
+    store: &impl Store,
+    key: Bytes,
+    cond: SetCondition,
+    ch: bool,
+    items: &[(f64, f64, Bytes)],
+) -> Result<(i64, Option<Entry>), &'static str> {
+    store
+        .update(&key, |slot| {
+            let entry = slot.get_or_insert_with(|| Entry {
+                value: Value::ZSet(Arc::default()),
+                exp: Expiry::None,
+            });
+            let Value::ZSet(set) = &mut entry.value else {
+                return Err(WRONG_TYPE);
+            };
+            let set = Arc::make_mut(set);
+
+            let (mut added, mut updated) = (0, 0);
+            for (lon, lat, member) in items {
+                let exists = set.score(member).is_some();
+                let allowed = match cond {
+                    SetCondition::Always => true,
+                    SetCondition::IfMissing => !exists,
+                    SetCondition::IfExists => exists,
+                };
+                if !allowed {
+                    continue;
+                }
+                match set.insert(member.clone(), encode(*lon, *lat) as f64) {
+                    ZAddOutcome::Added => added += 1,
+                    ZAddOutcome::Updated => updated += 1,
+                    ZAddOutcome::Unchanged => {}
+                }
+            }
+
+            let reply = if ch { added + updated } else { added };
+            if added + updated == 0 {
+                if set.is_empty() {
+                    *slot = None;
+                }
+                return Ok((reply, None));
+            }
+            Ok((reply, Some(entry.clone())))
+        })
+        .await
+}
+
+pub async fn geopos(
+    store: &impl Store,
+    key: &Bytes,
+    members: &[Bytes],
+) -> Result<Vec<Option<(f64, f64)>>, &'static str> {
+    let set = zset::load(store, key).await?;
+    Ok(members
+        .iter()
+        .map(|member| {
+            let score = set.as_ref()?.score(member)?;
+            Some(decode(score as u64))
+        })
+        .collect())
+}
+
+/// Distance between two members in meters, if both exist.
+pub async fn geodist(
+    store: &impl Store,
+    key: &Bytes,
+    member1: &Bytes,
+    member2: &Bytes,
+) -> Result<Option<f64>, &'static str> {
+    let Some(set) = zset::load(store, key).await? else {
+        return Ok(None);
+    };
+    let (Some(a), Some(b)) = (set.score(member1), set.score(member2)) else {
+        return Ok(None);
+    };
+    let ((lon1, lat1), (lon2, lat2)) = (decode(a as u64), decode(b as u64));
+    Ok(Some(distance(lon1, lat1, lon2, lat2)))
+}
+
+pub async fn geohash(
+    store: &impl Store,
+    key: &Bytes,
+    members: &[Bytes],
+) -> Result<Vec<Option<String>>, &'static str> {
+    let set = zset::load(store, key).await?;
+    Ok(members
+        .iter()
+        .map(|member| {
+            let score = set.as_ref()?.score(member)?;
+            let (lon, lat) = decode(score as u64);
+            Some(hash_string(lon, lat))
+        })
+        .collect())
+}
+
+pub async fn geosearch(
+    store: &impl Store,
+    key: &Bytes,
+    query: &GeoQuery,
+) -> Result<Vec<GeoMatch>, &'static str> {
+    match zset::load(store, key).await? {
+        Some(set) => search(&set, query),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn search(set: &SortedSet, query: &GeoQuery) -> Result<Vec<GeoMatch>, &'static str> {
+    let origin = match &query.origin {
+        GeoOrigin::Member(member) => decode(set.score(member).ok_or(MISSING_MEMBER)? as u64),
+        GeoOrigin::LonLat(lon, lat) => (*lon, *lat),
+    };
+
+    let mut matches = Vec::new();
+    let candidates = search_ranges(query.shape, origin)
+        .into_iter()
+        .flat_map(|(min, max)| set.range(min as f64, max as f64));
+    for (member, score) in candidates {
+        let hash = score as u64;
+        let (lon, lat) = decode(hash);
+        if let Some(dist) = distance_within(query.shape, origin, (lon, lat)) {
+            matches.push(GeoMatch {
+                member: member.clone(),
+                dist,
+                hash,
+                lon,
+                lat,
+            });
+            if query.any && query.count == Some(matches.len()) {
+                break;
+            }
+        }
+    }
+
+    // A COUNT without ANY implies ascending order, so the nearest are kept.
+    let sort = match query.sort {
+        GeoSort::None if query.count.is_some() && !query.any => GeoSort::Asc,
+        sort => sort,
+    };
+    match sort {
+        GeoSort::None => {}
+        GeoSort::Asc => matches.sort_by(|a, b| a.dist.total_cmp(&b.dist)),
+        GeoSort::Desc => matches.sort_by(|a, b| b.dist.total_cmp(&a.dist)),
+    }
+    if let Some(count) = query.count {
+        matches.truncate(count);
+    }
+    Ok(matches)
+}
+
+/// Stores search results in `dest` as a new geo set (or distances, with
+/// `store_dist`), searching and writing under one lock. Returns the number
+/// stored and the stored entry, which is `None` when nothing matched and
+/// `dest` was removed.
+pub async fn geosearchstore(
+    store: &impl Store,
+    dest: Bytes,
+    key: &Bytes,
+    query: &GeoQuery,
+    store_dist: bool,
+) -> Result<(usize, Option<Entry>), &'static str> {
+    store
+        .update_from(std::slice::from_ref(key), &dest, |sources, dest| {
+            let matches = match &sources[0] {
+                Some(entry) => search(entry.value.as_zset()?, query)?,
+                None => Vec::new(),
+            };
+            if matches.is_empty() {
+                *dest = None;
+                return Ok((0, None));
+            }
+
+            let mut set = SortedSet::new();
+            for m in matches {
+                let score = if store_dist {
+                    m.dist / query.unit.meters()
+                } else {
+                    m.hash as f64
+                };
+                set.insert(m.member, score);
+            }
+            let stored = set.len();
+            let entry = Entry {
+                value: Value::ZSet(Arc::new(set)),
+                exp: Expiry::None,
+            };
+            *dest = Some(entry.clone());
+            Ok((stored, Some(entry)))
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::memory::MemoryStore;
+
+    fn sicily() -> Vec<(f64, f64, Bytes)> {
+        vec![
+            (13.361389, 38.115556, Bytes::from_static(b"Palermo")),
+            (15.087269, 37.502669, Bytes::from_static(b"Catania")),
+        ]
+    }
+
+    fn query(origin: GeoOrigin, shape: GeoShape) -> GeoQuery {
+        GeoQuery {
+            origin,
+            shape,
+            unit: GeoUnit::Kilometers,
+            sort: GeoSort::None,
+            count: None,
+            any: false,
+            with_coord: false,
+            with_dist: false,
+            with_hash: false,
+        }
+    }
+
+    #[test]
+    fn encode_matches_redis_scores() {
+        assert_eq!(encode(13.361389, 38.115556), 3_479_099_956_230_698);
+        assert_eq!(encode(15.087269, 37.502669), 3_479_447_370_796_909);
+    }
+
+    #[test]
+    fn decode_is_close_to_input() {
+        let (lon, lat) = decode(encode(13.361389, 38.115556));
+        assert!((lon - 13.361389).abs() < 1e-5);
+        assert!((lat - 38.115556).abs() < 1e-5);
+    }
+
+    #[test]
+    fn hash_string_matches_redis() {
+        let (lon, lat) = decode(encode(13.361389, 38.115556));
+        assert_eq!(hash_string(lon, lat), "sqc8b49rny0");
+        let (lon, lat) = decode(encode(15.087269, 37.502669));
+        assert_eq!(hash_string(lon, lat), "sqdtr74hyu0");
+    }
+
+    #[test]
+    fn distance_between_palermo_and_catania() {
+        let (lon1, lat1) = decode(encode(13.361389, 38.115556));
+        let (lon2, lat2) = decode(encode(15.087269, 37.502669));
+        let km = distance(lon1, lat1, lon2, lat2) / 1000.0;
+        assert_eq!(format!("{km:.4}"), "166.2742");
+    }
+
+    #[test]
+    fn rejects_out_of_range_coordinates() {
+        assert!(valid_coords(180.0, 85.0));
+        assert!(!valid_coords(181.0, 0.0));
+        assert!(!valid_coords(0.0, 86.0));
+    }
+
+    #[tokio::test]
+    async fn geoadd_respects_conditions_and_ch() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"Sicily");
+        let (added, _) = geoadd(&store, k.clone(), SetCondition::Always, false, &sicily())
+            .await
+            .unwrap();
+        assert_eq!(added, 2);
+
+        let moved = vec![(13.0, 38.0, Bytes::from_static(b"Palermo"))];
+        let (added, stored) = geoadd(&store, k.clone(), SetCondition::IfMissing, true, &moved)
+            .await
+            .unwrap();
+        assert_eq!(added, 0);
+        assert!(stored.is_none());
+
+        let (changed, _) = geoadd(&store, k.clone(), SetCondition::IfExists, true, &moved)
+            .await
+            .unwrap();
+        assert_eq!(changed, 1);
+    }
+
+    #[tokio::test]
+    async fn geosearch_by_radius_and_box() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"Sicily");
+        geoadd(&store, k.clone(), SetCondition::Always, false, &sicily())
+            .await
+            .unwrap();
+
+        let mut q = query(GeoOrigin::LonLat(15.0, 37.0), GeoShape::Radius(200_000.0));
+        q.sort = GeoSort::Asc;
+        let found = geosearch(&store, &k, &q).await.unwrap();
+        let names: Vec<_> = found.iter().map(|m| m.member.clone()).collect();
+        assert_eq!(names, vec!["Catania", "Palermo"]);
+        assert_eq!(format!("{:.4}", found[0].dist / 1000.0), "56.4413");
+
+        q.shape = GeoShape::Radius(100_000.0);
+        assert_eq!(geosearch(&store, &k, &q).await.unwrap().len(), 1);
+
+        let q = query(
+            GeoOrigin::Member(Bytes::from_static(b"Palermo")),
+            GeoShape::Box {
+                width: 400_000.0,
+                height: 400_000.0,
+            },
+        );
+        assert_eq!(geosearch(&store, &k, &q).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn geosearch_count_keeps_nearest() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"Sicily");
+        geoadd(&store, k.clone(), SetCondition::Always, false, &sicily())
+            .await
+            .unwrap();
+
+        let mut q = query(GeoOrigin::LonLat(15.0, 37.0), GeoShape::Radius(500_000.0));
+        q.count = Some(1);
+        let found = geosearch(&store, &k, &q).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].member, "Catania");
+
+        q.origin = GeoOrigin::Member(Bytes::from_static(b"Rome"));
+        assert_eq!(geosearch(&store, &k, &q).await.unwrap_err(), MISSING_MEMBER);
+    }
+
+    #[tokio::test]
+    async fn geosearchstore_writes_and_clears_dest() {
+        let store = MemoryStore::new();
+        let (k, dest) = (Bytes::from_static(b"Sicily"), Bytes::from_static(b"near"));
+        geoadd(&store, k.clone(), SetCondition::Always, false, &sicily())
+            .await
+            .unwrap();
+
+        let q = query(GeoOrigin::LonLat(15.0, 37.0), GeoShape::Radius(100_000.0));
+        let (count, stored) = geosearchstore(&store, dest.clone(), &k, &q, true)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        let stored = stored.unwrap();
+        let set = stored.value.as_zset().unwrap();
+        assert_eq!(set.len(), 1);
+        assert!((set.score(b"Catania").unwrap() - 56.4413).abs() < 1e-3);
+
+        let q = query(GeoOrigin::LonLat(0.0, 0.0), GeoShape::Radius(1.0));
+        assert!(
+            geosearchstore(&store, dest.clone(), &k, &q, false)
+                .await
+                .unwrap()
+                .1
+                .is_none()
+        );
+        assert!(store.get(&dest).await.is_none());
+
+        let q = query(GeoOrigin::LonLat(15.0, 37.0), GeoShape::Radius(100_000.0));
+        geosearchstore(&store, k.clone(), &k, &q, false)
+            .await
+            .unwrap();
+        let set = zset::load(&store, &k).await.unwrap().unwrap();
+        assert_eq!(set.iter().map(|(m, _)| m).collect::<Vec<_>>(), ["Catania"]);
+    }
+
+    #[test]
+    fn search_ranges_cover_every_match() {
+        // A fixed LCG keeps the points reproducible without a rand dependency.
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = |lo: f64, hi: f64| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            lo + (seed >> 11) as f64 / (1u64 << 53) as f64 * (hi - lo)
+        };
+        let points: Vec<u64> = (0..2000)
+            .map(|_| encode(next(LON_MIN, LON_MAX), next(LAT_MIN, LAT_MAX)))
+            .collect();
+        let origins = [
+            (15.0, 37.0),
+            (179.9, 0.0),
+            (-179.9, -60.0),
+            (0.0, 84.9),
+            (-70.0, -85.0),
+        ];
+        let shapes = [
+            GeoShape::Radius(1_000.0),
+            GeoShape::Radius(500_000.0),
+            GeoShape::Radius(3_000_000.0),
+            GeoShape::Box {
+                width: 800_000.0,
+                height: 200_000.0,
+            },
+            GeoShape::Box {
+                width: 4_000_000.0,
+                height: 6_000_000.0,
+            },
+        ];
+        for origin in origins {
+            for shape in shapes {
+                let ranges = search_ranges(shape, origin);
+                for &hash in &points {
+                    if distance_within(shape, origin, decode(hash)).is_some() {
+                        assert!(
+                            ranges.iter().any(|&(min, max)| (min..max).contains(&hash)),
+                            "{shape:?} around {origin:?} misses {:?}",
+                            decode(hash)
+                        );
+                    }
+                }
+            }
+        }
+
+        let span: u64 = search_ranges(GeoShape::Radius(100_000.0), (15.0, 37.0))
+            .iter()
+            .map(|(min, max)| max - min)
+            .sum();
+        assert!(span < 1 << 42, "scanned {span} scores");
+    }
+}
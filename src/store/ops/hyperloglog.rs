@@ -0,0 +1,718 @@
+//! HyperLogLog sketches stored as plain string values, using the same
+//! header, register layout, sparse opcodes and hash function as Redis so
+//! values can be moved between the two servers with `GET`/`SET`.
+
+use tokio_util::bytes::Bytes;
+
+use crate::store::{
+    ops::{get_string, rewrite_string, slot_string},
+    traits::Store,
+    types::Entry,
+};
+
+const MAGIC: &[u8] = b"HYLL";
+const HEADER_LEN: usize = 16;
+const P: u32 = 14;
+const Q: u32 = 64 - P;
+const REGISTERS: usize = 1 << P;
+const REGISTER_BITS: usize = 6;
+const REGISTER_MAX: u8 = (1 << REGISTER_BITS) - 1;
+const DENSE_LEN: usize = HEADER_LEN + (REGISTERS * REGISTER_BITS).div_ceil(8);
+const ENCODING_DENSE: u8 = 0;
+const ENCODING_SPARSE: u8 = 1;
+const CACHE_INVALID: u8 = 0x80;
+const SPARSE_MAX_BYTES: usize = 3000;
+const SPARSE_VAL_MAX_VALUE: u8 = 32;
+const SPARSE_VAL_MAX_LEN: usize = 4;
+const SPARSE_ZERO_MAX_LEN: usize = 64;
+const SPARSE_XZERO_MAX_LEN: usize = 16384;
+const ALPHA_INF: f64 = 0.721_347_520_444_481_7;
+const HASH_SEED: u64 = 0xadc8_3b19;
+
+pub const WRONG_TYPE: &str = "WRONGTYPE Key is not a valid HyperLogLog string value.";
+pub const CORRUPTED: &str = "INVALIDOBJ Corrupted HLL object detected";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HllEncoding {
+    Sparse,
+    Dense,
+}
+
+impl HllEncoding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sparse => "sparse",
+            Self::Dense => "dense",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PfDebugSub {
+    GetReg,
+    Decode,
+    Encoding,
+    ToDense,
+}
+
+#[derive(Debug)]
+pub enum PfDebugReply {
+    Registers(Vec<u8>),
+    Decoded(String),
+    Encoding(HllEncoding),
+    ToDense(Option<Entry>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Hll {
+    encoding: HllEncoding,
+    registers: Vec<u8>,
+    cached: Option<u64>,
+}
+
+impl Default for Hll {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hll {
+    pub fn new() -> Self {
+        Self {
+            encoding: HllEncoding::Sparse,
+            registers: vec![0; REGISTERS],
+            cached: Some(0),
+        }
+    }
+
+    pub fn decode(raw: &[u8]) -> Result<Self, &'static str> {
+        if raw.len() < HEADER_LEN || &raw[..MAGIC.len()] != MAGIC {
+            return Err(WRONG_TYPE);
+        }
+
+        let card = &raw[8..HEADER_LEN];
+        let cached = (card[7] & CACHE_INVALID == 0)
+            .then(|| u64::from_le_bytes(card.try_into().expect("8-byte cardinality")));
+
+        let (encoding, registers) = match raw[4] {
+            ENCODING_DENSE if raw.len() == DENSE_LEN => {
+                let body = &raw[HEADER_LEN..];
+                let registers = (0..REGISTERS).map(|i| dense_get(body, i)).collect();
+                (HllEncoding::Dense, registers)
+            }
+            ENCODING_SPARSE => (HllEncoding::Sparse, sparse_decode(&raw[HEADER_LEN..])?),
+            _ => return Err(WRONG_TYPE),
+        };
+
+        Ok(Self {
+            encoding,
+            registers,
+            cached,
+        })
+    }
+
+    /// Serialises the sketch, staying sparse while every register fits a
+    /// `VAL` opcode and the payload is under the sparse size limit.
+    pub fn encode(&self) -> Bytes {
+        let sparse = match self.encoding {
+            HllEncoding::Sparse => {
+                sparse_encode(&self.registers).filter(|ops| ops.len() <= SPARSE_MAX_BYTES)
+            }
+            HllEncoding::Dense => None,
+        };
+
+        let mut out = Vec::with_capacity(DENSE_LEN);
+        out.extend_from_slice(MAGIC);
+        out.push(match sparse {
+            Some(_) => ENCODING_SPARSE,
+            None => ENCODING_DENSE,
+        });
+        out.extend_from_slice(&[0; 3]);
+        match self.cached {
+            Some(card) => out.extend_from_slice(&card.to_le_bytes()),
+            None => out.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, CACHE_INVALID]),
+        }
+
+        match sparse {
+            Some(ops) => out.extend_from_slice(&ops),
+            None => {
+                out.resize(DENSE_LEN, 0);
+                for (i, value) in self.registers.iter().enumerate() {
+                    dense_set(&mut out[HEADER_LEN..], i, *value);
+                }
+            }
+        }
+        out.into()
+    }
+
+    pub fn encoding(&self) -> HllEncoding {
+        self.encoding
+    }
+
+    pub fn registers(&self) -> &[u8] {
+        &self.registers
+    }
+
+    /// Adds an element, returning whether any register changed.
+    pub fn add(&mut self, element: &[u8]) -> bool {
+        let (index, count) = pattern_len(element);
+        if self.registers[index] >= count {
+            return false;
+        }
+        self.registers[index] = count;
+        if count > SPARSE_VAL_MAX_VALUE {
+            self.encoding = HllEncoding::Dense;
+        }
+        self.cached = None;
+        true
+    }
+
+    pub fn merge(&mut self, other: &Hll) {
+        for (mine, theirs) in self.registers.iter_mut().zip(&other.registers) {
+            *mine = (*mine).max(*theirs);
+        }
+        if other.encoding == HllEncoding::Dense {
+            self.encoding = HllEncoding::Dense;
+        }
+        self.cached = None;
+    }
+
+    pub fn to_dense(&mut self) -> bool {
+        let converted = self.encoding == HllEncoding::Sparse;
+        self.encoding = HllEncoding::Dense;
+        converted
+    }
+
+    /// Returns the cached cardinality if valid, otherwise estimates it.
+    pub fn count(&self) -> u64 {
+        self.cached.unwrap_or_else(|| estimate(&self.registers))
+    }
+}
+
+/// Ertl's improved raw estimator, as used by Redis since 5.0.
+fn estimate(registers: &[u8]) -> u64 {
+    let mut histogram = [0u32; 64];
+    for value in registers {
+        histogram[*value as usize] += 1;
+    }
+
+    let m = REGISTERS as f64;
+    let mut z = m * tau((m - histogram[Q as usize + 1] as f64) / m);
+    for j in (1..=Q as usize).rev() {
+        z += histogram[j] as f64;
+        z *= 0.5;
+    }
+    z += m * sigma(histogram[0] as f64 / m);
+    (ALPHA_INF * m * m / z).round() as u64
+}
+
+fn tau(mut x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+    let mut y = 1.0;
+    let mut z = 1.0 - x;
+    loop {
+        x = x.sqrt();
+        let previous = z;
+        y *= 0.5;
+        z -= (1.0 - x).powi(2) * y;
+        if previous == z {
+            return z / 3.0;
+        }
+    }
+}
+
+fn sigma(mut x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+    let mut y = 1.0;
+    let mut z = x;
+    loop {
+        x *= x;
+        let previous = z;
+        z += x * y;
+        y += y;
+        if previous == z {
+            return z;
+        }
+    }
+}
+
+/// Maps an element to its register index and the length of the run of
+/// zeroes (plus one) in the remaining hash bits.
+fn pattern_len(element: &[u8]) -> (usize, u8) {
+    let hash = murmur_hash64a(element, HASH_SEED);
+    let index = (hash & (REGISTERS as u64 - 1)) as usize;
+    let rest = (hash >> P) | (1 << Q);
+    (index, rest.trailing_zeros() as u8 + 1)
+}
+
+fn murmur_hash64a(data: &[u8], seed: u64) -> u64 {
+    const M: u64 = 0xc6a4_a793_5bd1_e995;
+    const R: u32 = 47;
+
+    let mut h = seed ^ (data.len() as u64).wrapping_mul(M);
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let mut k = u64::from_le_bytes(chunk.try_into().expect("8-byte chunk"));
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (i, byte) in tail.iter().enumerate() {
+            h ^= (*byte as u64) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+    h
+}
+
+fn dense_get(body: &[u8], index: usize) -> u8 {
+    let bit = index * REGISTER_BITS;
+    let (byte, shift) = (bit / 8, bit % 8);
+    let low = body[byte] >> shift;
+    let high = body
+        .get(byte + 1)
+        .map_or(0, |b| b.checked_shl(8 - shift as u32).unwrap_or(0));
+    (low | high) & REGISTER_MAX
+}
+
+fn dense_set(body: &mut [u8], index: usize, value: u8) {
+    let bit = index * REGISTER_BITS;
+    let (byte, shift) = (bit / 8, bit % 8);
+    body[byte] &= !(REGISTER_MAX << shift);
+    body[byte] |= value << shift;
+    if let Some(next) = body.get_mut(byte + 1) {
+        let spill = 8 - shift as u32;
+        *next &= !REGISTER_MAX.checked_shr(spill).unwrap_or(0);
+        *next |= value.checked_shr(spill).unwrap_or(0);
+    }
+}
+
+fn sparse_decode(ops: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut registers = vec![0; REGISTERS];
+    let mut index = 0;
+    let mut pos = 0;
+    while pos < ops.len() {
+        let op = ops[pos];
+        match op & 0xc0 {
+            0x00 => {
+                index += (op & 0x3f) as usize + 1;
+                pos += 1;
+            }
+            0x40 => {
+                let low = *ops.get(pos + 1).ok_or(CORRUPTED)?;
+                index += (((op & 0x3f) as usize) << 8 | low as usize) + 1;
+                pos += 2;
+            }
+            _ => {
+                let value = ((op >> 2) & 0x1f) + 1;
+                let run = (op & 0x03) as usize + 1;
+                registers
+                    .get_mut(index..index + run)
+                    .ok_or(CORRUPTED)?
+                    .fill(value);
+                index += run;
+                pos += 1;
+            }
+        }
+        if index > REGISTERS {
+            return Err(CORRUPTED);
+        }
+    }
+    if index != REGISTERS {
+        return Err(CORRUPTED);
+    }
+    Ok(registers)
+}
+
+/// Run-length encodes registers into sparse opcodes, or `None` if some
+/// register is too large for a `VAL` opcode.
+fn sparse_encode(registers: &[u8]) -> Option<Vec<u8>> {
+    let mut ops = Vec::new();
+    let mut i = 0;
+    while i < registers.len() {
+        let value = registers[i];
+        let run = registers[i..].iter().take_while(|v| **v == value).count();
+        let mut left = run;
+        if value == 0 {
+            while left > 0 {
+                if left > SPARSE_ZERO_MAX_LEN {
+                    let n = left.min(SPARSE_XZERO_MAX_LEN) - 1;
+                    ops.push(0x40 | (n >> 8) as u8);
+                    ops.push((n & 0xff) as u8);
+                    left -= n + 1;
+                } else {
+                    ops.push((left - 1) as u8);
+                    left = 0;
+                }
+            }
+        } else {
+            if value > SPARSE_VAL_MAX_VALUE {
+                return None;
+            }
+            while left > 0 {
+                let n = left.min(SPARSE_VAL_MAX_LEN);
+                ops.push(0x80 | ((value - 1) << 2) | (n - 1) as u8);
+                left -= n;
+            }
+        }
+        i += run;
+    }
+    Some(ops)
+}
+
+/// Renders sparse opcodes the way `PFDEBUG DECODE` does in Redis.
+fn sparse_describe(ops: &[u8]) -> String {
+    let mut parts = Vec::new();
+    let mut pos = 0;
+    while pos < ops.len() {
+        let op = ops[pos];
+        match op & 0xc0 {
+            0x00 => {
+                parts.push(format!("z:{}", (op & 0x3f) + 1));
+                pos += 1;
+            }
+            0x40 => {
+                let low = ops.get(pos + 1).copied().unwrap_or(0) as usize;
+                parts.push(format!("Z:{}", (((op & 0x3f) as usize) << 8 | low) + 1));
+                pos += 2;
+            }
+            _ => {
+                parts.push(format!("v:{},{}", ((op >> 2) & 0x1f) + 1, (op & 0x03) + 1));
+                pos += 1;
+            }
+        }
+    }
+    parts.join(" ")
+}
+
+async fn load(store: &impl Store, key: &Bytes) -> Result<Option<(Bytes, Hll)>, &'static str> {
+    match get_string(store, key).await? {
+        Some(raw) => {
+            let hll = Hll::decode(&raw)?;
+            Ok(Some((raw, hll)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Adds `elements` to the sketch at `key`, creating it if needed. Returns the
+/// stored entry when the sketch changed.
+pub async fn pfadd(
+    store: &impl Store,
+    key: Bytes,
+    elements: &[Bytes],
+) -> Result<Option<Entry>, &'static str> {
+    store
+        .update(&key, |slot| {
+            let (mut hll, mut changed) = match slot_string(slot)? {
+                Some(raw) => (Hll::decode(raw)?, false),
+                None => (Hll::new(), true),
+            };
+
+            for element in elements {
+                changed |= hll.add(element);
+            }
+            if !changed {
+                return Ok(None);
+            }
+            rewrite_string(slot, |_| Ok(hll.encode())).map(Some)
+        })
+        .await
+}
+
+/// Estimates the cardinality of the union of `keys`. A single key's cached
+/// estimate is used when valid, but never written back: PFCOUNT is read-only.
+pub async fn pfcount(store: &impl Store, keys: &[Bytes]) -> Result<u64, &'static str> {
+    if let [key] = keys {
+        return Ok(load(store, key).await?.map_or(0, |(_, hll)| hll.count()));
+    }
+
+    let mut union = Hll::new();
+    union.cached = None;
+    for key in keys {
+        if let Some((_, hll)) = load(store, key).await? {
+            union.merge(&hll);
+        }
+    }
+    Ok(union.count())
+}
+
+/// Merges `sources` into `dest`, which keeps its TTL if it already existed.
+pub async fn pfmerge(
+    store: &impl Store,
+    dest: Bytes,
+    sources: &[Bytes],
+) -> Result<Entry, &'static str> {
+    let mut union = Hll::new();
+    for key in sources {
+        if let Some((_, hll)) = load(store, key).await? {
+            union.merge(&hll);
+        }
+    }
+
+    store
+        .update(&dest, |slot| {
+            rewrite_string(slot, |current| {
+                let mut merged = match current {
+                    Some(raw) => Hll::decode(raw)?,
+                    None => Hll::new(),
+                };
+                merged.merge(&union);
+                merged.cached = None;
+                Ok(merged.encode())
+            })
+        })
+        .await
+}
+
+pub async fn pfdebug(
+    store: &impl Store,
+    sub: PfDebugSub,
+    key: Bytes,
+) -> Result<PfDebugReply, &'static str> {
+    let Some((raw, hll)) = load(store, &key).await? else {
+        return Err("ERR The specified key does not exist");
+    };
+
+    match sub {
+        PfDebugSub::GetReg => Ok(PfDebugReply::Registers(hll.registers)),
+        PfDebugSub::Encoding => Ok(PfDebugReply::Encoding(hll.encoding)),
+        PfDebugSub::Decode => match hll.encoding {
+            HllEncoding::Sparse => Ok(PfDebugReply::Decoded(sparse_describe(&raw[HEADER_LEN..]))),
+            HllEncoding::Dense => Err("ERR HLL encoding is not sparse"),
+        },
+        PfDebugSub::ToDense => {
+            store
+                .update(&key, |slot| {
+                    let Some(raw) = slot_string(slot)? else {
+                        return Ok(PfDebugReply::ToDense(None));
+                    };
+                    let mut hll = Hll::decode(raw)?;
+                    if !hll.to_dense() {
+                        return Ok(PfDebugReply::ToDense(None));
+                    }
+                    let resolved = rewrite_string(slot, |_| Ok(hll.encode()))?;
+                    Ok(PfDebugReply::ToDense(Some(resolved)))
+                })
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{memory::MemoryStore, types::Expiry};
+
+    fn elements(range: std::ops::Range<usize>) -> Vec<Bytes> {
+        range.map(|i| Bytes::from(format!("element:{i}"))).collect()
+    }
+
+    #[test]
+    fn murmur_matches_reference_vectors() {
+        assert_eq!(murmur_hash64a(b"", HASH_SEED), 0xd8df_ea65_85bc_9732);
+        assert_eq!(murmur_hash64a(b"hello", 0), 0x1e68_d17c_457b_f117);
+        assert_eq!(
+            murmur_hash64a(b"element:123456", HASH_SEED),
+            0x1505_ea11_9dc5_3772
+        );
+    }
+
+    #[test]
+    fn empty_sketch_matches_redis_bytes() {
+        let raw = Hll::new().encode();
+        assert_eq!(&raw[..4], b"HYLL");
+        assert_eq!(raw[4], ENCODING_SPARSE);
+        assert_eq!(&raw[HEADER_LEN..], &[0x7f, 0xff]);
+        assert_eq!(Hll::decode(&raw).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn dense_registers_round_trip() {
+        let mut body = vec![0u8; DENSE_LEN - HEADER_LEN];
+        for i in 0..REGISTERS {
+            dense_set(&mut body, i, (i % 64) as u8);
+        }
+        for i in 0..REGISTERS {
+            assert_eq!(dense_get(&body, i), (i % 64) as u8);
+        }
+    }
+
+    #[test]
+    fn sparse_encoding_round_trips() {
+        let mut registers = vec![0u8; REGISTERS];
+        registers[0] = 3;
+        registers[1] = 3;
+        registers[100] = 32;
+        registers[REGISTERS - 1] = 1;
+        let ops = sparse_encode(&registers).unwrap();
+        assert_eq!(sparse_decode(&ops).unwrap(), registers);
+
+        registers[5] = 33;
+        assert!(sparse_encode(&registers).is_none());
+    }
+
+    #[test]
+    fn sparse_decode_rejects_corruption() {
+        assert_eq!(sparse_decode(&[0x00]), Err(CORRUPTED));
+        assert_eq!(sparse_decode(&[0x7f, 0xff, 0x80]), Err(CORRUPTED));
+        assert_eq!(sparse_decode(&[0x7f]), Err(CORRUPTED));
+    }
+
+    #[test]
+    fn decode_rejects_non_hll_strings() {
+        assert_eq!(Hll::decode(b"hello").unwrap_err(), WRONG_TYPE);
+        let mut raw = Hll::new().encode().to_vec();
+        raw[4] = ENCODING_DENSE;
+        assert_eq!(Hll::decode(&raw).unwrap_err(), WRONG_TYPE);
+    }
+
+    #[test]
+    fn estimate_is_close_for_large_sets() {
+        let mut hll = Hll::new();
+        for element in elements(0..100_000) {
+            hll.add(&element);
+        }
+        let raw = hll.encode();
+        assert_eq!(raw[4], ENCODING_DENSE);
+        let count = Hll::decode(&raw).unwrap().count() as f64;
+        assert!((count - 100_000.0).abs() / 100_000.0 < 0.02, "{count}");
+    }
+
+    #[test]
+    fn small_sets_stay_sparse_and_exactish() {
+        let mut hll = Hll::new();
+        for element in elements(0..10) {
+            hll.add(&element);
+        }
+        let raw = hll.encode();
+        assert_eq!(raw[4], ENCODING_SPARSE);
+        assert_eq!(Hll::decode(&raw).unwrap().count(), 10);
+    }
+
+    #[tokio::test]
+    async fn pfadd_reports_changes() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"hll");
+        assert!(
+            pfadd(&store, k.clone(), &elements(0..3))
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            pfadd(&store, k.clone(), &elements(0..3))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(pfadd(&store, k.clone(), &[]).await.unwrap().is_none());
+        assert!(
+            pfadd(&store, Bytes::from_static(b"empty"), &[])
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn pfcount_does_not_write_back() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"hll");
+        pfadd(&store, k.clone(), &elements(0..5)).await.unwrap();
+        let before = store.get(&k).await.unwrap().value;
+        assert_ne!(before.as_string().unwrap()[15] & CACHE_INVALID, 0);
+
+        assert_eq!(pfcount(&store, std::slice::from_ref(&k)).await.unwrap(), 5);
+        assert_eq!(store.get(&k).await.unwrap().value, before);
+    }
+
+    #[tokio::test]
+    async fn pfcount_and_pfmerge_union() {
+        let store = MemoryStore::new();
+        let (a, b, dest) = (
+            Bytes::from_static(b"a"),
+            Bytes::from_static(b"b"),
+            Bytes::from_static(b"dest"),
+        );
+        pfadd(&store, a.clone(), &elements(0..6)).await.unwrap();
+        pfadd(&store, b.clone(), &elements(3..9)).await.unwrap();
+
+        let union = pfcount(&store, &[a.clone(), b.clone(), Bytes::from_static(b"x")])
+            .await
+            .unwrap();
+        assert_eq!(union, 9);
+
+        pfmerge(&store, dest.clone(), &[a, b]).await.unwrap();
+        assert_eq!(pfcount(&store, &[dest]).await.unwrap(), 9);
+    }
+
+    #[tokio::test]
+    async fn pfcount_on_plain_string_is_wrongtype() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store
+            .set(
+                k.clone(),
+                Entry {
+                    value: Bytes::from_static(b"plain").into(),
+                    exp: Expiry::None,
+                },
+            )
+            .await;
+        assert_eq!(
+            pfcount(&store, std::slice::from_ref(&k)).await.unwrap_err(),
+            WRONG_TYPE
+        );
+        assert_eq!(pfadd(&store, k, &[]).await.unwrap_err(), WRONG_TYPE);
+    }
+
+    #[tokio::test]
+    async fn pfdebug_subcommands() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"hll");
+        pfadd(&store, k.clone(), &elements(0..1)).await.unwrap();
+
+        let reply = pfdebug(&store, PfDebugSub::Encoding, k.clone())
+            .await
+            .unwrap();
+        assert!(matches!(reply, PfDebugReply::Encoding(HllEncoding::Sparse)));
+
+        let reply = pfdebug(&store, PfDebugSub::Decode, k.clone())
+            .await
+            .unwrap();
+        assert!(matches!(reply, PfDebugReply::Decoded(s) if s.contains("v:")));
+
+        let reply = pfdebug(&store, PfDebugSub::ToDense, k.clone())
+            .await
+            .unwrap();
+        assert!(matches!(reply, PfDebugReply::ToDense(Some(_))));
+        let reply = pfdebug(&store, PfDebugSub::ToDense, k.clone())
+            .await
+            .unwrap();
+        assert!(matches!(reply, PfDebugReply::ToDense(None)));
+
+        let reply = pfdebug(&store, PfDebugSub::GetReg, k.clone())
+            .await
+            .unwrap();
+        assert!(
+            matches!(reply, PfDebugReply::Registers(r) if r.len() == REGISTERS && r.iter().filter(|v| **v > 0).count() == 1)
+        );
+
+        let err = pfdebug(&store, PfDebugSub::Decode, k).await.unwrap_err();
+        assert_eq!(err, "ERR HLL encoding is not sparse");
+    }
+}
@@ -1,10 +1,53 @@
+pub mod bitmap;
+pub mod geo;
+pub mod hyperloglog;
+pub mod zset;
+
 use tokio_util::bytes::Bytes;
 
 use crate::store::{
     traits::Store,
-    types::{Entry, Expiry},
+    types::{Entry, Expiry, SetCondition, Value},
 };
 
+/// Largest string a write may produce, matching Redis' default `proto-max-bulk-len`.
+pub const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
+
+/// The string at `key`, or WRONGTYPE if it holds another kind of value.
+pub async fn get_string(store: &impl Store, key: &Bytes) -> Result<Option<Bytes>, &'static str> {
+    match store.get(key).await {
+        Some(entry) => entry.value.as_string().cloned().map(Some),
+        None => Ok(None),
+    }
+}
+
+/// The string held in `slot`, or WRONGTYPE if it holds another kind of value.
+fn slot_string(slot: &Option<Entry>) -> Result<Option<&Bytes>, &'static str> {
+    slot.as_ref()
+        .map(|entry| entry.value.as_string())
+        .transpose()
+}
+
+/// Replaces the string in `slot` with what `f` makes of it (`None` when the
+/// key is missing), keeping any TTL. WRONGTYPE if the key holds another kind
+/// of value, in which case `f` is not called.
+fn rewrite_string(
+    slot: &mut Option<Entry>,
+    f: impl FnOnce(Option<&Bytes>) -> Result<Bytes, &'static str>,
+) -> Result<Entry, &'static str> {
+    let value = Value::String(f(slot_string(slot)?)?);
+    match slot {
+        Some(entry) => entry.value = value,
+        None => {
+            *slot = Some(Entry {
+                value,
+                exp: Expiry::None,
+            })
+        }
+    }
+    Ok(slot.clone().expect("slot was just filled"))
+}
+
 pub async fn incr(store: &impl Store, key: Bytes) -> Result<Entry, &'static str> {
     incr_by(store, key, 1).await
 }
@@ -14,87 +57,326 @@ pub async fn decr(store: &impl Store, key: Bytes) -> Result<Entry, &'static str>
 }
 
 pub async fn incr_by(store: &impl Store, key: Bytes, delta: i64) -> Result<Entry, &'static str> {
-    let mut entry = match store.get(&key).await {
-        Some(entry) => entry,
-        None => Entry {
-            value: b"0".to_vec().into(),
-            exp: Expiry::None,
-        },
-    };
+    store
+        .update(&key, |slot| {
+            rewrite_string(slot, |current| {
+                let current = match current {
+                    Some(value) => std::str::from_utf8(value)
+                        .ok()
+                        .and_then(|s| s.parse::<i64>().ok()),
+                    None => Some(0),
+                };
+                let value = current
+                    .and_then(|current| current.checked_add(delta))
+                    .ok_or("ERR value is not an integer or out of range")?;
+                Ok(value.to_string().into())
+            })
+        })
+        .await
+}
 
-    let current = std::str::from_utf8(&entry.value)
-        .ok()
-        .and_then(|s| s.parse::<i64>().ok())
-        .ok_or("ERR value is not an integer or out of range")?;
-    current
-        .checked_add(delta)
-        .ok_or("ERR value is not an integer or out of range")?;
-    entry.value = current.checked_add(delta).unwrap().to_string().into();
-    let resolved = store.set(key, entry).await;
-    Ok(resolved)
+pub async fn incr_by_float(
+    store: &impl Store,
+    key: Bytes,
+    incr: f64,
+) -> Result<Entry, &'static str> {
+    store
+        .update(&key, |slot| {
+            rewrite_string(slot, |current| {
+                let current = match current {
+                    Some(value) => std::str::from_utf8(value)
+                        .ok()
+                        .and_then(|s| s.parse::<f64>().ok())
+                        .filter(|f| f.is_finite())
+                        .ok_or("ERR value is not a valid float")?,
+                    None => 0.0,
+                };
+                let result = current + incr;
+                if !result.is_finite() {
+                    return Err("ERR increment would produce NaN or Infinity");
+                }
+                Ok(format_float(result).into())
+            })
+        })
+        .await
 }
 
-pub async fn strlen(store: &impl Store, key: Bytes) -> i64 {
-    match store.get(&key).await {
-        Some(entry) => entry.value.len() as i64,
-        None => 0,
+/// Formats a float the way Redis does for `INCRBYFLOAT`: the shortest
+/// representation that round-trips, without exponent or trailing zeros.
+pub fn format_float(value: f64) -> String {
+    if value == 0.0 {
+        return String::from("0");
     }
+    value.to_string()
 }
 
-pub async fn append(store: &impl Store, key: Bytes, value: Bytes) -> Entry {
-    if let Some(mut entry) = store.get(&key).await {
-        let combined = [entry.value, value].concat();
-        entry.value = Bytes::copy_from_slice(&combined);
-        return store.set(key, entry).await;
-    }
+pub async fn getrange(
+    store: &impl Store,
+    key: Bytes,
+    start: i64,
+    end: i64,
+) -> Result<Bytes, &'static str> {
+    let Some(value) = get_string(store, &key).await? else {
+        return Ok(Bytes::new());
+    };
 
-    let entry = Entry {
-        value,
-        exp: Expiry::None,
+    let len = value.len() as i64;
+    if start < 0 && end < 0 && start > end {
+        return Ok(Bytes::new());
+    }
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let end = if end < 0 {
+        (len + end).max(0)
+    } else {
+        end.min(len - 1)
     };
-    store.set(key, entry).await
+    if len == 0 || start > end {
+        return Ok(Bytes::new());
+    }
+    Ok(value.slice(start as usize..=end as usize))
 }
 
-pub async fn getdel(store: &impl Store, key: Bytes) -> Option<Entry> {
-    let entry = store.get(&key).await;
-    if entry.is_some() {
-        store.del(&[key]).await;
+/// Overwrites part of the string at `offset`, zero-padding any gap. Returns
+/// `None` when `value` is empty, in which case nothing is written.
+pub async fn setrange(
+    store: &impl Store,
+    key: Bytes,
+    offset: usize,
+    value: Bytes,
+) -> Result<Option<Entry>, &'static str> {
+    if value.is_empty() {
+        return Ok(None);
     }
-    entry
+    let end = offset
+        .checked_add(value.len())
+        .filter(|end| *end <= MAX_STRING_LEN)
+        .ok_or("ERR string exceeds maximum allowed size (proto-max-bulk-len)")?;
+
+    store
+        .update(&key, |slot| {
+            rewrite_string(slot, |current| {
+                let mut buf = current.map(|value| value.to_vec()).unwrap_or_default();
+                if buf.len() < end {
+                    buf.resize(end, 0);
+                }
+                buf[offset..end].copy_from_slice(&value);
+                Ok(buf.into())
+            })
+        })
+        .await
+        .map(Some)
 }
 
-pub async fn getset(store: &impl Store, key: Bytes, entry: Entry) -> (Option<Entry>, Entry) {
-    let existing = store.get(&key).await;
-    let resolved = store.set(key, entry).await;
-    (existing, resolved)
+#[derive(Debug, PartialEq)]
+pub struct LcsMatch {
+    pub a: (usize, usize),
+    pub b: (usize, usize),
+    pub len: usize,
 }
 
-pub async fn setnx(store: &impl Store, key: Bytes, entry: Entry) -> Option<Entry> {
-    if store.get(&key).await.is_none() {
-        let resolved = store.set(key, entry).await;
-        return Some(resolved);
+pub struct Lcs {
+    pub sequence: Bytes,
+    pub matches: Vec<LcsMatch>,
+}
+
+pub async fn lcs(store: &impl Store, key1: &Bytes, key2: &Bytes) -> Result<Lcs, &'static str> {
+    let a = get_string(store, key1).await?.unwrap_or_default();
+    let b = get_string(store, key2).await?.unwrap_or_default();
+    longest_common_subsequence(&a, &b)
+}
+
+/// Dynamic-programming LCS that also reports the matching ranges, walking the
+/// table backwards the same way Redis does so `LCS ... IDX` output lines up.
+fn longest_common_subsequence(a: &[u8], b: &[u8]) -> Result<Lcs, &'static str> {
+    let cols = b.len() + 1;
+    let cells = (a.len() + 1)
+        .checked_mul(cols)
+        .filter(|cells| *cells < u32::MAX as usize / size_of::<u32>())
+        .ok_or("ERR Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len")?;
+
+    let mut dp = vec![0u32; cells];
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            dp[i * cols + j] = if a[i - 1] == b[j - 1] {
+                dp[(i - 1) * cols + j - 1] + 1
+            } else {
+                dp[(i - 1) * cols + j].max(dp[i * cols + j - 1])
+            };
+        }
+    }
+
+    let total = dp[a.len() * cols + b.len()] as usize;
+    let mut sequence = vec![0u8; total];
+    let mut matches = Vec::new();
+    let (mut i, mut j, mut idx) = (a.len(), b.len(), total);
+    // (a_start, a_end, b_start, b_end) of the range currently being extended.
+    let mut range: Option<(usize, usize, usize, usize)> = None;
+
+    while i > 0 && j > 0 {
+        let mut emit = false;
+        if a[i - 1] == b[j - 1] {
+            sequence[idx - 1] = a[i - 1];
+            match &mut range {
+                None => range = Some((i - 1, i - 1, j - 1, j - 1)),
+                Some(r) if r.0 == i && r.2 == j => {
+                    r.0 -= 1;
+                    r.2 -= 1;
+                }
+                Some(_) => emit = true,
+            }
+            if let Some(r) = range
+                && (r.0 == 0 || r.2 == 0)
+            {
+                emit = true;
+            }
+            idx -= 1;
+            i -= 1;
+            j -= 1;
+        } else {
+            if dp[(i - 1) * cols + j] > dp[i * cols + j - 1] {
+                i -= 1;
+            } else {
+                j -= 1;
+            }
+            emit = range.is_some();
+        }
+
+        if emit && let Some((a_start, a_end, b_start, b_end)) = range.take() {
+            matches.push(LcsMatch {
+                a: (a_start, a_end),
+                b: (b_start, b_end),
+                len: a_end - a_start + 1,
+            });
+        }
     }
-    None
+
+    Ok(Lcs {
+        sequence: sequence.into(),
+        matches,
+    })
+}
+
+pub async fn strlen(store: &impl Store, key: Bytes) -> Result<i64, &'static str> {
+    Ok(get_string(store, &key)
+        .await?
+        .map_or(0, |value| value.len() as i64))
+}
+
+pub async fn append(store: &impl Store, key: Bytes, value: Bytes) -> Result<Entry, &'static str> {
+    store
+        .update(&key, |slot| {
+            rewrite_string(slot, |current| match current {
+                Some(current) => Ok([&current[..], &value[..]].concat().into()),
+                None => Ok(value.clone()),
+            })
+        })
+        .await
+}
+
+pub async fn getdel(store: &impl Store, key: Bytes) -> Result<Option<Entry>, &'static str> {
+    store
+        .update(&key, |slot| {
+            slot_string(slot)?;
+            Ok(slot.take())
+        })
+        .await
+}
+
+pub async fn getset(
+    store: &impl Store,
+    key: Bytes,
+    entry: Entry,
+) -> Result<(Option<Entry>, Entry), &'static str> {
+    let (existing, resolved) = set(store, key, entry, SetCondition::Always, true).await?;
+    Ok((existing, resolved.expect("unconditional set")))
+}
+
+/// Sets `key` if `cond` allows it, returning the previous entry and the
+/// stored one. With `get` the previous value must be a string, otherwise
+/// nothing is written.
+pub async fn set(
+    store: &impl Store,
+    key: Bytes,
+    entry: Entry,
+    cond: SetCondition,
+    get: bool,
+) -> Result<(Option<Entry>, Option<Entry>), &'static str> {
+    store
+        .update(&key, |slot| {
+            if get {
+                slot_string(slot)?;
+            }
+            let allowed = match cond {
+                SetCondition::Always => true,
+                SetCondition::IfMissing => slot.is_none(),
+                SetCondition::IfExists => slot.is_some(),
+            };
+            if !allowed {
+                return Ok((slot.clone(), None));
+            }
+            let mut entry = entry;
+            if let Expiry::Keep = entry.exp {
+                entry.exp = slot.as_ref().map_or(Expiry::None, |e| e.exp.clone());
+            }
+            Ok((slot.replace(entry.clone()), Some(entry)))
+        })
+        .await
+}
+
+pub async fn getex(
+    store: &impl Store,
+    key: Bytes,
+    exp: Expiry,
+) -> Result<Option<Entry>, &'static str> {
+    store
+        .update(&key, |slot| {
+            let Some(entry) = slot.as_mut() else {
+                return Ok(None);
+            };
+            entry.value.as_string()?;
+            if !matches!(exp, Expiry::Keep) {
+                entry.exp = exp;
+            }
+            Ok(Some(entry.clone()))
+        })
+        .await
+}
+
+pub async fn msetnx(store: &impl Store, items: &[(Bytes, Bytes)]) -> bool {
+    store.msetnx(items).await
+}
+
+pub async fn setnx(store: &impl Store, key: Bytes, entry: Entry) -> Option<Entry> {
+    let (_, resolved) = set(store, key, entry, SetCondition::IfMissing, false)
+        .await
+        .expect("only SET ... GET checks the type");
+    resolved
 }
 
 pub async fn persist(store: &impl Store, key: Bytes) -> Option<Entry> {
-    if let Some(mut entry) = store.get(&key).await
-        && let Expiry::At(_) = entry.exp
-    {
-        entry.exp = Expiry::None;
-        let resolved = store.set(key, entry).await;
-        return Some(resolved);
-    }
-    None
+    store
+        .update(&key, |slot| {
+            let entry = slot
+                .as_mut()
+                .filter(|entry| matches!(entry.exp, Expiry::At(_)))?;
+            entry.exp = Expiry::None;
+            Some(entry.clone())
+        })
+        .await
 }
 
 pub async fn pexpire(store: &impl Store, key: Bytes, ttl: u64, now: u64) -> Option<Entry> {
-    if let Some(mut entry) = store.get(&key).await {
-        entry.exp = Expiry::At(now.saturating_add(ttl));
-        let resolved = store.set(key, entry).await;
-        return Some(resolved);
-    }
-    None
+    store
+        .update(&key, |slot| {
+            let entry = slot.as_mut()?;
+            entry.exp = Expiry::At(now.saturating_add(ttl));
+            Some(entry.clone())
+        })
+        .await
 }
 
 #[cfg(test)]
@@ -104,7 +386,7 @@ mod tests {
 
     fn entry(value: &[u8], exp: Expiry) -> Entry {
         Entry {
-            value: Bytes::from(value.to_vec()),
+            value: Bytes::from(value.to_vec()).into(),
             exp,
         }
     }
@@ -174,25 +456,182 @@ mod tests {
         assert!(err.contains("integer"));
     }
 
+    #[tokio::test]
+    async fn incr_by_float_formats_like_redis() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store.set(k.clone(), entry(b"10.50", Expiry::None)).await;
+        let resolved = incr_by_float(&store, k.clone(), 0.1).await.unwrap();
+        assert_eq!(resolved.value, Bytes::from_static(b"10.6"));
+
+        let resolved = incr_by_float(&store, k.clone(), -5.6).await.unwrap();
+        assert_eq!(resolved.value, Bytes::from_static(b"5"));
+
+        store.set(k.clone(), entry(b"5.0e3", Expiry::None)).await;
+        let resolved = incr_by_float(&store, k, 2.0e2).await.unwrap();
+        assert_eq!(resolved.value, Bytes::from_static(b"5200"));
+    }
+
+    #[tokio::test]
+    async fn incr_by_float_errors() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store.set(k.clone(), entry(b"abc", Expiry::None)).await;
+        let err = incr_by_float(&store, k.clone(), 1.0).await.unwrap_err();
+        assert_eq!(err, "ERR value is not a valid float");
+
+        store.set(k.clone(), entry(b"1", Expiry::None)).await;
+        let err = incr_by_float(&store, k, f64::INFINITY).await.unwrap_err();
+        assert_eq!(err, "ERR increment would produce NaN or Infinity");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_increments_are_not_lost() {
+        let store = std::sync::Arc::new(MemoryStore::new());
+        let (ints, floats) = (Bytes::from_static(b"ints"), Bytes::from_static(b"floats"));
+        let mut tasks = Vec::new();
+        for _ in 0..16 {
+            let (store, ints, floats) = (store.clone(), ints.clone(), floats.clone());
+            tasks.push(tokio::spawn(async move {
+                for _ in 0..500 {
+                    incr(&*store, ints.clone()).await.unwrap();
+                    incr_by_float(&*store, floats.clone(), 1.0).await.unwrap();
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(store.get(&ints).await.unwrap().value, "8000");
+        assert_eq!(store.get(&floats).await.unwrap().value, "8000");
+    }
+
+    #[tokio::test]
+    async fn getrange_handles_negative_indexes() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        store
+            .set(k.clone(), entry(b"This is a string", Expiry::None))
+            .await;
+        assert_eq!(getrange(&store, k.clone(), 0, 3).await.unwrap(), "This");
+        assert_eq!(getrange(&store, k.clone(), -3, -1).await.unwrap(), "ing");
+        assert_eq!(
+            getrange(&store, k.clone(), 0, -1).await.unwrap(),
+            "This is a string"
+        );
+        assert_eq!(
+            getrange(&store, k.clone(), 10, 100).await.unwrap(),
+            "string"
+        );
+        assert_eq!(getrange(&store, k.clone(), -1, -5).await.unwrap(), "");
+        assert_eq!(getrange(&store, k, 5, 3).await.unwrap(), "");
+        assert_eq!(
+            getrange(&store, Bytes::from_static(b"missing"), 0, -1)
+                .await
+                .unwrap(),
+            ""
+        );
+    }
+
+    #[tokio::test]
+    async fn setrange_zero_pads_new_key() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        let resolved = setrange(&store, k, 3, Bytes::from_static(b"ab"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved.value, Bytes::from_static(b"\0\0\0ab"));
+    }
+
+    #[tokio::test]
+    async fn setrange_overwrites_and_keeps_expiry() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        let far_future = crate::utils::time::get_current_millis() + 1_000_000;
+        store
+            .set(k.clone(), entry(b"Hello World", Expiry::At(far_future)))
+            .await;
+        let resolved = setrange(&store, k, 6, Bytes::from_static(b"Redis"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved.value, Bytes::from_static(b"Hello Redis"));
+        assert!(matches!(resolved.exp, Expiry::At(t) if t == far_future));
+    }
+
+    #[tokio::test]
+    async fn setrange_empty_value_and_size_limit() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        assert!(
+            setrange(&store, k.clone(), 10, Bytes::new())
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(store.get(&k).await.is_none());
+
+        let err = setrange(&store, k, MAX_STRING_LEN, Bytes::from_static(b"x"))
+            .await
+            .unwrap_err();
+        assert!(err.contains("maximum allowed size"));
+    }
+
+    #[test]
+    fn lcs_matches_redis_example() {
+        let lcs = longest_common_subsequence(b"ohmytext", b"mynewtext").unwrap();
+        assert_eq!(lcs.sequence, Bytes::from_static(b"mytext"));
+        assert_eq!(
+            lcs.matches,
+            vec![
+                LcsMatch {
+                    a: (4, 7),
+                    b: (5, 8),
+                    len: 4
+                },
+                LcsMatch {
+                    a: (2, 3),
+                    b: (0, 1),
+                    len: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lcs_of_empty_inputs() {
+        let lcs = longest_common_subsequence(b"", b"abc").unwrap();
+        assert!(lcs.sequence.is_empty());
+        assert!(lcs.matches.is_empty());
+    }
+
     #[tokio::test]
     async fn strlen_existing() {
         let store = MemoryStore::new();
         let k = Bytes::from_static(b"k");
         store.set(k.clone(), entry(b"hello", Expiry::None)).await;
-        assert_eq!(strlen(&store, k).await, 5);
+        assert_eq!(strlen(&store, k).await.unwrap(), 5);
     }
 
     #[tokio::test]
     async fn strlen_missing() {
         let store = MemoryStore::new();
-        assert_eq!(strlen(&store, Bytes::from_static(b"missing")).await, 0);
+        assert_eq!(
+            strlen(&store, Bytes::from_static(b"missing"))
+                .await
+                .unwrap(),
+            0
+        );
     }
 
     #[tokio::test]
     async fn append_new_key() {
         let store = MemoryStore::new();
         let k = Bytes::from_static(b"k");
-        let resolved = append(&store, k.clone(), Bytes::from_static(b"abc")).await;
+        let resolved = append(&store, k.clone(), Bytes::from_static(b"abc"))
+            .await
+            .unwrap();
         assert_eq!(resolved.value, Bytes::from_static(b"abc"));
     }
 
@@ -201,7 +640,9 @@ mod tests {
         let store = MemoryStore::new();
         let k = Bytes::from_static(b"k");
         store.set(k.clone(), entry(b"hello", Expiry::None)).await;
-        let resolved = append(&store, k, Bytes::from_static(b" world")).await;
+        let resolved = append(&store, k, Bytes::from_static(b" world"))
+            .await
+            .unwrap();
         assert_eq!(resolved.value, Bytes::from_static(b"hello world"));
     }
 
@@ -210,7 +651,7 @@ mod tests {
         let store = MemoryStore::new();
         let k = Bytes::from_static(b"k");
         store.set(k.clone(), entry(b"v", Expiry::None)).await;
-        let got = getdel(&store, k.clone()).await.unwrap();
+        let got = getdel(&store, k.clone()).await.unwrap().unwrap();
         assert_eq!(got.value, Bytes::from_static(b"v"));
         assert!(store.get(&k).await.is_none());
     }
@@ -221,6 +662,7 @@ mod tests {
         assert!(
             getdel(&store, Bytes::from_static(b"missing"))
                 .await
+                .unwrap()
                 .is_none()
         );
     }
@@ -230,7 +672,9 @@ mod tests {
         let store = MemoryStore::new();
         let k = Bytes::from_static(b"k");
         store.set(k.clone(), entry(b"old", Expiry::None)).await;
-        let (existing, resolved) = getset(&store, k.clone(), entry(b"new", Expiry::None)).await;
+        let (existing, resolved) = getset(&store, k.clone(), entry(b"new", Expiry::None))
+            .await
+            .unwrap();
         assert_eq!(existing.unwrap().value, Bytes::from_static(b"old"));
         assert_eq!(resolved.value, Bytes::from_static(b"new"));
     }
@@ -239,7 +683,9 @@ mod tests {
     async fn getset_missing() {
         let store = MemoryStore::new();
         let k = Bytes::from_static(b"k");
-        let (existing, resolved) = getset(&store, k.clone(), entry(b"new", Expiry::None)).await;
+        let (existing, resolved) = getset(&store, k.clone(), entry(b"new", Expiry::None))
+            .await
+            .unwrap();
         assert!(existing.is_none());
         assert_eq!(resolved.value, Bytes::from_static(b"new"));
     }
@@ -262,6 +708,134 @@ mod tests {
         assert!(resolved.is_none());
     }
 
+    #[tokio::test]
+    async fn set_if_missing_only_sets_new_keys() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        let (existing, resolved) = set(
+            &store,
+            k.clone(),
+            entry(b"v1", Expiry::None),
+            SetCondition::IfMissing,
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(existing.is_none());
+        assert!(resolved.is_some());
+
+        let (existing, resolved) = set(
+            &store,
+            k.clone(),
+            entry(b"v2", Expiry::None),
+            SetCondition::IfMissing,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(existing.unwrap().value, Bytes::from_static(b"v1"));
+        assert!(resolved.is_none());
+        assert_eq!(
+            store.get(&k).await.unwrap().value,
+            Bytes::from_static(b"v1")
+        );
+    }
+
+    #[tokio::test]
+    async fn set_if_exists_skips_missing_keys() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        let (existing, resolved) = set(
+            &store,
+            k.clone(),
+            entry(b"v", Expiry::None),
+            SetCondition::IfExists,
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(existing.is_none());
+        assert!(resolved.is_none());
+        assert!(store.get(&k).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn getex_updates_expiry() {
+        let store = MemoryStore::new();
+        let k = Bytes::from_static(b"k");
+        let far_future = crate::utils::time::get_current_millis() + 1_000_000;
+        store.set(k.clone(), entry(b"v", Expiry::None)).await;
+
+        let resolved = getex(&store, k.clone(), Expiry::At(far_future))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(resolved.exp, Expiry::At(t) if t == far_future));
+
+        let resolved = getex(&store, k.clone(), Expiry::Keep)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(resolved.exp, Expiry::At(t) if t == far_future));
+
+        let resolved = getex(&store, k, Expiry::None).await.unwrap().unwrap();
+        assert!(matches!(resolved.exp, Expiry::None));
+    }
+
+    #[tokio::test]
+    async fn msetnx_is_all_or_nothing() {
+        let store = MemoryStore::new();
+        let items = vec![
+            (Bytes::from_static(b"a"), Bytes::from_static(b"1")),
+            (Bytes::from_static(b"b"), Bytes::from_static(b"2")),
+        ];
+        assert!(msetnx(&store, &items).await);
+
+        let items = vec![
+            (Bytes::from_static(b"b"), Bytes::from_static(b"3")),
+            (Bytes::from_static(b"c"), Bytes::from_static(b"4")),
+        ];
+        assert!(!msetnx(&store, &items).await);
+        assert!(store.get(&Bytes::from_static(b"c")).await.is_none());
+        assert_eq!(
+            store.get(&Bytes::from_static(b"b")).await.unwrap().value,
+            Bytes::from_static(b"2")
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_conditional_sets_have_one_winner() {
+        let store = std::sync::Arc::new(MemoryStore::new());
+        let mut tasks = Vec::new();
+        for i in 0..16 {
+            let store = store.clone();
+            tasks.push(tokio::spawn(async move {
+                let k = Bytes::from(format!("k{}", i % 4));
+                let nx = set(
+                    &*store,
+                    Bytes::from_static(b"k"),
+                    entry(b"v", Expiry::None),
+                    SetCondition::IfMissing,
+                    false,
+                )
+                .await
+                .unwrap();
+                let items = [
+                    (k, Bytes::from_static(b"v")),
+                    (Bytes::from_static(b"m"), Bytes::new()),
+                ];
+                (nx.1.is_some(), msetnx(&*store, &items).await)
+            }));
+        }
+        let (mut nx_wins, mut msetnx_wins) = (0, 0);
+        for task in tasks {
+            let (nx, msetnx) = task.await.unwrap();
+            nx_wins += nx as i32;
+            msetnx_wins += msetnx as i32;
+        }
+        assert_eq!((nx_wins, msetnx_wins), (1, 1));
+    }
+
     #[tokio::test]
     async fn persist_removes_expiry() {
         let store = MemoryStore::new();
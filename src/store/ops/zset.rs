@@ -0,0 +1,214 @@
+//! Sorted sets, held decoded in the keyspace and serialised only for the AOF.
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use tokio_util::bytes::{BufMut, Bytes, BytesMut};
+
+use crate::store::traits::Store;
+
+const CORRUPTED: &str = "ERR corrupted sorted set value";
+
+/// Encoded size of a member besides its bytes: the score and the length.
+const MEMBER_HEADER: usize = 12;
+
+#[derive(Clone, Copy, Debug)]
+struct Score(f64);
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZAddOutcome {
+    Added,
+    Updated,
+    Unchanged,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SortedSet {
+    scores: HashMap<Bytes, f64>,
+    ordered: BTreeSet<(Score, Bytes)>,
+    /// Size of the encoded form, kept up to date for memory accounting.
+    memory: usize,
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn decode(mut rest: &[u8]) -> Result<Self, &'static str> {
+        let mut set = Self::new();
+        while !rest.is_empty() {
+            if rest.len() < MEMBER_HEADER {
+                return Err(CORRUPTED);
+            }
+            let score = f64::from_le_bytes(rest[..8].try_into().expect("8-byte score"));
+            let len = u32::from_le_bytes(rest[8..12].try_into().expect("4-byte length")) as usize;
+            let member = rest
+                .get(MEMBER_HEADER..MEMBER_HEADER + len)
+                .ok_or(CORRUPTED)?;
+            set.insert(Bytes::copy_from_slice(member), score);
+            rest = &rest[MEMBER_HEADER + len..];
+        }
+        Ok(set)
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut out = BytesMut::with_capacity(self.memory);
+        for (member, score) in self.iter() {
+            out.put_f64_le(score);
+            out.put_u32_le(member.len() as u32);
+            out.put_slice(member);
+        }
+        out.freeze()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+
+    pub fn score(&self, member: &[u8]) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    pub fn insert(&mut self, member: Bytes, score: f64) -> ZAddOutcome {
+        match self.scores.insert(member.clone(), score) {
+            Some(old) if old == score => ZAddOutcome::Unchanged,
+            Some(old) => {
+                self.ordered.remove(&(Score(old), member.clone()));
+                self.ordered.insert((Score(score), member));
+                ZAddOutcome::Updated
+            }
+            None => {
+                self.memory += MEMBER_HEADER + member.len();
+                self.ordered.insert((Score(score), member));
+                ZAddOutcome::Added
+            }
+        }
+    }
+
+    /// Members in ascending score order, ties broken by member bytes.
+    pub fn iter(&self) -> impl Iterator<Item = (&Bytes, f64)> {
+        self.ordered.iter().map(|(score, member)| (member, score.0))
+    }
+
+    /// Members scored within `min..max`, in ascending order.
+    pub fn range(&self, min: f64, max: f64) -> impl Iterator<Item = (&Bytes, f64)> {
+        self.ordered
+            .range((Score(min), Bytes::new())..(Score(max), Bytes::new()))
+            .map(|(score, member)| (member, score.0))
+    }
+}
+
+/// The sorted set at `key`, or WRONGTYPE if it holds another kind of value.
+pub async fn load(store: &impl Store, key: &Bytes) -> Result<Option<Arc<SortedSet>>, &'static str> {
+    match store.get(key).await {
+        Some(entry) => entry.value.as_zset().cloned().map(Some),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_reports_outcome_and_keeps_order() {
+        let mut set = SortedSet::new();
+        assert_eq!(
+            set.insert(Bytes::from_static(b"b"), 2.0),
+            ZAddOutcome::Added
+        );
+        assert_eq!(
+            set.insert(Bytes::from_static(b"a"), 3.0),
+            ZAddOutcome::Added
+        );
+        assert_eq!(
+            set.insert(Bytes::from_static(b"a"), 1.0),
+            ZAddOutcome::Updated
+        );
+        assert_eq!(
+            set.insert(Bytes::from_static(b"a"), 1.0),
+            ZAddOutcome::Unchanged
+        );
+
+        let members: Vec<_> = set.iter().map(|(m, s)| (m.clone(), s)).collect();
+        assert_eq!(
+            members,
+            vec![
+                (Bytes::from_static(b"a"), 1.0),
+                (Bytes::from_static(b"b"), 2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let mut set = SortedSet::new();
+        set.insert(Bytes::from_static(b"x"), -1.5);
+        set.insert(Bytes::from_static(b""), 0.0);
+        let decoded = SortedSet::decode(&set.encode()).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded.score(b"x"), Some(-1.5));
+        assert_eq!(decoded.score(b""), Some(0.0));
+    }
+
+    #[test]
+    fn decode_rejects_truncated_input() {
+        assert_eq!(SortedSet::decode(b"hello").unwrap_err(), CORRUPTED);
+        let encoded = Bytes::from_static(b"x");
+        let mut set = SortedSet::new();
+        set.insert(encoded, 1.0);
+        let raw = set.encode();
+        assert_eq!(
+            SortedSet::decode(&raw[..raw.len() - 1]).unwrap_err(),
+            CORRUPTED
+        );
+    }
+
+    #[test]
+    fn memory_tracks_encoded_size_and_range_is_half_open() {
+        let mut set = SortedSet::new();
+        for (member, score) in [(&b"a"[..], 1.0), (b"bb", 2.0), (b"c", 3.0)] {
+            set.insert(Bytes::copy_from_slice(member), score);
+        }
+        set.insert(Bytes::from_static(b"a"), 2.0);
+        assert_eq!(set.memory(), set.encode().len());
+
+        let members: Vec<_> = set.range(2.0, 3.0).map(|(m, _)| m.clone()).collect();
+        assert_eq!(
+            members,
+            vec![Bytes::from_static(b"a"), Bytes::from_static(b"bb")]
+        );
+    }
+}
@@ -4,13 +4,34 @@ use async_trait::async_trait;
 use crate::{
     config::FsyncMode,
     store::{persistence::record::Record, traits::Store},
+    utils::histogram::HistogramSnapshot,
 };
 
+/// State of the append-only file as reported by `INFO persistence` and
+/// `/metrics`. Counters start over whenever a new file is opened.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AofStatus {
+    pub current_size: u64,
+    /// Size when the file was opened.
+    pub base_size: u64,
+    pub last_write_ok: bool,
+    pub write_errors: u64,
+    pub fsync_latency: HistogramSnapshot,
+}
+
 #[async_trait]
 pub trait Aof: Send + Sync + 'static {
     async fn append(&self, record: Record) -> Result<()>;
     async fn replay_into(&self, store: &dyn Store) -> Result<()>;
     fn set_fsync_mode(&self, _mode: FsyncMode) {}
+    /// `None` when persistence is disabled.
+    fn status(&self) -> Option<AofStatus> {
+        None
+    }
+    /// Forces everything appended so far to disk.
+    async fn fsync(&self) -> Result<()> {
+        Ok(())
+    }
     async fn shutdown(&self) {}
 }
 
@@ -46,7 +46,7 @@ fn encode_payload(record: Record, out: &mut BytesMut) -> Result<()> {
     out.put_u8(RecordTag::from(&record) as u8);
 
     match record {
-        Record::Set { key, value, exp_ms } => {
+        Record::Set { key, value, exp_ms } | Record::ZSet { key, value, exp_ms } => {
             put_bytes(out, &key);
             put_bytes(out, &value);
             put_opt_u64(out, exp_ms);
@@ -76,11 +76,14 @@ fn decode_payload(mut input: &[u8]) -> Result<Option<Record>> {
     let tag = RecordTag::try_from(input.get_u8())?;
 
     let record = match tag {
-        RecordTag::Set => {
+        RecordTag::Set | RecordTag::ZSet => {
             let key = get_bytes(&mut input)?;
             let value = get_bytes(&mut input)?;
             let exp_ms = get_opt_u64(&mut input)?;
-            Record::Set { key, value, exp_ms }
+            match tag {
+                RecordTag::ZSet => Record::ZSet { key, value, exp_ms },
+                _ => Record::Set { key, value, exp_ms },
+            }
         }
         RecordTag::Del => {
             let count = input.get_u32() as usize;
@@ -182,6 +185,16 @@ mod tests {
                 assert_eq!(items, di);
             }
             (Record::FlushDb, Record::FlushDb) => {}
+            (
+                Record::ZSet { key, value, exp_ms },
+                Record::ZSet {
+                    key: dk,
+                    value: dv,
+                    exp_ms: de,
+                },
+            ) => {
+                assert_eq!((key, value, exp_ms), (dk, dv, de));
+            }
             _ => panic!("record type mismatch"),
         }
     }
@@ -204,6 +217,15 @@ mod tests {
         });
     }
 
+    #[test]
+    fn round_trip_zset() {
+        round_trip(Record::ZSet {
+            key: Bytes::from_static(b"places"),
+            value: Bytes::from_static(b"encoded"),
+            exp_ms: Some(42),
+        });
+    }
+
     #[test]
     fn round_trip_del_single_key() {
         round_trip(Record::Del {
@@ -0,0 +1,263 @@
+//! On-disk AOF header and the format versions this build can read.
+//!
+//! The header is the crate name followed by three bytes. yars 0.1.x wrote its
+//! own crate version there; current files write [`FORMAT_MARKER`] and a
+//! big-endian `u16` format version instead, so upgrading the crate no longer
+//! invalidates existing data files.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use tokio_util::{
+    bytes::BytesMut,
+    codec::{Decoder, Encoder},
+};
+
+use crate::store::{
+    ops::zset::SortedSet,
+    persistence::{codec::RecordCodec, record::Record},
+};
+
+pub const MAGIC: &[u8] = env!("CARGO_PKG_NAME").as_bytes();
+/// Never a crate major version, so it tells versioned headers from legacy ones.
+const FORMAT_MARKER: u8 = 0xFF;
+pub const FORMAT_VERSION: u16 = 3;
+pub const HEADER_LEN: usize = MAGIC.len() + 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Read and appended to as is.
+    Current,
+    /// Readable, but rewritten into [`FORMAT_VERSION`] before it is opened.
+    Migrate,
+}
+
+/// Every format version this build understands; anything else is refused.
+const COMPATIBILITY: &[(u16, Compatibility)] = &[
+    // Header holds the crate version (yars 0.1.x); records are unchanged.
+    (1, Compatibility::Migrate),
+    // Sorted sets were `Set` records holding `LEGACY_ZSET_PREFIX` and the
+    // encoded set, indistinguishable from a string with the same bytes.
+    (2, Compatibility::Migrate),
+    (3, Compatibility::Current),
+];
+
+const LEGACY_ZSET_PREFIX: &[u8] = b"\xffZSET";
+
+pub fn header() -> [u8; HEADER_LEN] {
+    let mut out = [0u8; HEADER_LEN];
+    out[..MAGIC.len()].copy_from_slice(MAGIC);
+    out[MAGIC.len()] = FORMAT_MARKER;
+    out[MAGIC.len() + 1..].copy_from_slice(&FORMAT_VERSION.to_be_bytes());
+    out
+}
+
+/// Returns the format version recorded in the header of `raw`.
+pub fn read_header(raw: &[u8]) -> Result<u16> {
+    if raw.len() < HEADER_LEN {
+        return Err(anyhow!("invalid AOF: file too small for header"));
+    }
+    if &raw[..MAGIC.len()] != MAGIC {
+        return Err(anyhow!("invalid AOF: bad magic"));
+    }
+    if raw[MAGIC.len()] != FORMAT_MARKER {
+        return Ok(1);
+    }
+    Ok(u16::from_be_bytes([
+        raw[MAGIC.len() + 1],
+        raw[MAGIC.len() + 2],
+    ]))
+}
+
+pub fn compatibility(version: u16) -> Result<Compatibility> {
+    match COMPATIBILITY.iter().find(|(v, _)| *v == version) {
+        Some((_, compat)) => Ok(*compat),
+        None if version > FORMAT_VERSION => Err(anyhow!(
+            "AOF format version {version} is newer than this build supports ({FORMAT_VERSION})"
+        )),
+        None => Err(anyhow!("unsupported AOF format version: {version}")),
+    }
+}
+
+/// Where the original of a migrated file is kept, e.g. `appendonly.aof.v1.bak`.
+pub fn backup_path(path: &Path, version: u16) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{version}.bak"));
+    path.with_file_name(name)
+}
+
+/// Rewrites an AOF in an older readable format into [`FORMAT_VERSION`],
+/// copying the original aside first. Returns the backup path if it migrated.
+/// A crash mid-way leaves either the original or the migrated file in place.
+pub async fn migrate(path: &Path) -> Result<Option<PathBuf>> {
+    let raw = match tokio::fs::read(path).await {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    if raw.is_empty() {
+        return Ok(None);
+    }
+
+    let version = read_header(&raw)?;
+    if compatibility(version)? == Compatibility::Current {
+        return Ok(None);
+    }
+
+    let mut codec = RecordCodec;
+    let mut src = BytesMut::from(&raw[HEADER_LEN..]);
+    let mut out = BytesMut::from(&header()[..]);
+    while let Some(record) = codec.decode(&mut src)? {
+        codec.encode(upgrade(version, record), &mut out)?;
+    }
+    if !src.is_empty() {
+        log::warn!(
+            "truncated AOF tail dropped during migration ({} bytes)",
+            src.len()
+        );
+    }
+
+    let backup = backup_path(path, version);
+    tokio::fs::copy(path, &backup).await?;
+
+    super::replace_file(path, &out).await?;
+
+    Ok(Some(backup))
+}
+
+/// Rewrites a record read from a `version` file into its current form.
+fn upgrade(version: u16, record: Record) -> Record {
+    match record {
+        Record::Set { key, value, exp_ms }
+            if version == 2
+                && value
+                    .strip_prefix(LEGACY_ZSET_PREFIX)
+                    .is_some_and(|set| SortedSet::decode(set).is_ok()) =>
+        {
+            Record::ZSet {
+                key,
+                value: value.slice(LEGACY_ZSET_PREFIX.len()..),
+                exp_ms,
+            }
+        }
+        record => record,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_util::bytes::Bytes;
+
+    use super::*;
+
+    fn legacy_file(records: Vec<Record>) -> Vec<u8> {
+        let mut raw = BytesMut::from(MAGIC);
+        raw.extend_from_slice(&[0, 1, 0]);
+        for record in records {
+            RecordCodec.encode(record, &mut raw).unwrap();
+        }
+        raw.to_vec()
+    }
+
+    #[test]
+    fn header_round_trips_format_version() {
+        assert_eq!(read_header(&header()).unwrap(), FORMAT_VERSION);
+        assert_eq!(
+            compatibility(FORMAT_VERSION).unwrap(),
+            Compatibility::Current
+        );
+    }
+
+    #[test]
+    fn crate_version_headers_are_format_one() {
+        let raw = legacy_file(vec![]);
+        assert_eq!(read_header(&raw).unwrap(), 1);
+        assert_eq!(compatibility(1).unwrap(), Compatibility::Migrate);
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let mut raw = header();
+        raw[MAGIC.len() + 1..].copy_from_slice(&99u16.to_be_bytes());
+        let err = compatibility(read_header(&raw).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("newer than this build"));
+        assert!(compatibility(0).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        assert!(read_header(b"short").is_err());
+        let mut raw = header();
+        raw[0] = b'x';
+        assert!(read_header(&raw).is_err());
+    }
+
+    #[tokio::test]
+    async fn migrate_rewrites_legacy_file_and_keeps_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("appendonly.aof");
+        let legacy = legacy_file(vec![Record::Set {
+            key: Bytes::from_static(b"k"),
+            value: Bytes::from_static(b"v"),
+            exp_ms: None,
+        }]);
+        std::fs::write(&path, &legacy).unwrap();
+
+        let backup = migrate(&path).await.unwrap().unwrap();
+        assert_eq!(backup, dir.path().join("appendonly.aof.v1.bak"));
+        assert_eq!(std::fs::read(&backup).unwrap(), legacy);
+
+        let migrated = std::fs::read(&path).unwrap();
+        assert_eq!(read_header(&migrated).unwrap(), FORMAT_VERSION);
+        assert_eq!(&migrated[HEADER_LEN..], &legacy[HEADER_LEN..]);
+
+        assert_eq!(migrate(&path).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn migrate_turns_format_two_sorted_sets_into_zset_records() {
+        let mut set = SortedSet::new();
+        set.insert(Bytes::from_static(b"m"), 1.0);
+        let legacy_set = [LEGACY_ZSET_PREFIX, &set.encode()].concat();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("appendonly.aof");
+        let mut raw = BytesMut::from(MAGIC);
+        raw.extend_from_slice(&[FORMAT_MARKER, 0, 2]);
+        for value in [legacy_set, b"\xffZSETjunk".to_vec(), b"plain".to_vec()] {
+            let record = Record::Set {
+                key: Bytes::from_static(b"k"),
+                value: value.into(),
+                exp_ms: None,
+            };
+            RecordCodec.encode(record, &mut raw).unwrap();
+        }
+        std::fs::write(&path, &raw).unwrap();
+
+        assert_eq!(
+            migrate(&path).await.unwrap(),
+            Some(dir.path().join("appendonly.aof.v2.bak"))
+        );
+        let mut src = BytesMut::from(&std::fs::read(&path).unwrap()[HEADER_LEN..]);
+        let mut records = Vec::new();
+        while let Some(record) = RecordCodec.decode(&mut src).unwrap() {
+            records.push(record);
+        }
+        assert!(matches!(&records[0], Record::ZSet { value, .. } if *value == set.encode()));
+        assert!(
+            matches!(&records[1], Record::Set { value, .. } if value[..] == b"\xffZSETjunk"[..])
+        );
+        assert!(matches!(&records[2], Record::Set { value, .. } if value == "plain"));
+    }
+
+    #[tokio::test]
+    async fn migrate_ignores_missing_and_current_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("appendonly.aof");
+        assert_eq!(migrate(&path).await.unwrap(), None);
+
+        std::fs::write(&path, header()).unwrap();
+        assert_eq!(migrate(&path).await.unwrap(), None);
+        assert!(!backup_path(&path, 1).exists());
+    }
+}
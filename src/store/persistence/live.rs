@@ -0,0 +1,199 @@
+//! The AOF backend in use, swappable while the server runs.
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use tokio::sync::{RwLock, RwLockReadGuard};
+
+use crate::{
+    config::FsyncMode,
+    store::{
+        memory::MemoryStore,
+        persistence::{
+            AofEngine,
+            aof::{Aof, AofStatus, NoopAof},
+            record::Record,
+        },
+        traits::Store,
+    },
+};
+
+pub struct LiveAof {
+    current: RwLock<Arc<dyn Aof>>,
+}
+
+impl LiveAof {
+    pub fn new(aof: Arc<dyn Aof>) -> Self {
+        Self {
+            current: RwLock::new(aof),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::new(Arc::new(NoopAof))
+    }
+
+    /// The backend in use. Swaps wait until every guard is dropped, so a
+    /// write held across its store update and append can't fall between the
+    /// snapshot and the new file.
+    pub async fn current(&self) -> RwLockReadGuard<'_, Arc<dyn Aof>> {
+        self.current.read().await
+    }
+
+    pub async fn append(&self, record: Record) -> Result<()> {
+        self.current().await.append(record).await
+    }
+
+    pub async fn replay_into(&self, store: &dyn Store) -> Result<()> {
+        self.current().await.replay_into(store).await
+    }
+
+    pub async fn set_fsync_mode(&self, mode: FsyncMode) {
+        self.current().await.set_fsync_mode(mode);
+    }
+
+    pub async fn status(&self) -> Option<AofStatus> {
+        self.current().await.status()
+    }
+
+    /// Writes a snapshot of `store` into a new AOF at `path` and appends there
+    /// from now on. The previous backend is flushed and closed only once the
+    /// new file is in place; on error it stays in use.
+    pub async fn start(
+        &self,
+        path: PathBuf,
+        fsync_mode: FsyncMode,
+        store: &MemoryStore,
+    ) -> Result<()> {
+        let mut current = self.current.write().await;
+        let engine = AofEngine::create(path, fsync_mode, snapshot_records(store).await).await?;
+        let previous = std::mem::replace(&mut *current, Arc::new(engine));
+        previous.shutdown().await;
+        Ok(())
+    }
+
+    /// Flushes and closes the current backend; later writes are not persisted.
+    pub async fn stop(&self) {
+        let mut current = self.current.write().await;
+        let previous = std::mem::replace(&mut *current, Arc::new(NoopAof));
+        previous.shutdown().await;
+    }
+
+    pub async fn fsync(&self) -> Result<()> {
+        self.current().await.fsync().await
+    }
+
+    /// Writes a snapshot of `store` to `path`. With the AOF on, this is a
+    /// [`start`](Self::start) on the same file; otherwise the file is written
+    /// and closed without appending to it.
+    pub async fn save(
+        &self,
+        path: PathBuf,
+        fsync_mode: FsyncMode,
+        store: &MemoryStore,
+        append_only: bool,
+    ) -> Result<()> {
+        if append_only {
+            return self.start(path, fsync_mode, store).await;
+        }
+        let engine =
+            AofEngine::create(path, FsyncMode::Always, snapshot_records(store).await).await?;
+        engine.shutdown().await;
+        Ok(())
+    }
+
+    pub async fn shutdown(&self) {
+        self.current().await.shutdown().await;
+    }
+}
+
+async fn snapshot_records(store: &MemoryStore) -> impl Iterator<Item = Record> {
+    store
+        .snapshot()
+        .await
+        .into_iter()
+        .map(|(key, entry)| Record::from_entry(key, entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_util::bytes::Bytes;
+
+    use super::*;
+    use crate::store::types::{Entry, Expiry};
+
+    async fn replayed(path: PathBuf) -> MemoryStore {
+        let store = MemoryStore::new();
+        let engine = AofEngine::open(path, FsyncMode::No).await.unwrap();
+        engine.replay_into(&store).await.unwrap();
+        store
+    }
+
+    #[tokio::test]
+    async fn start_snapshots_store_then_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::new();
+        store
+            .set(
+                Bytes::from_static(b"before"),
+                Entry {
+                    value: Bytes::from_static(b"1").into(),
+                    exp: Expiry::At(u64::MAX),
+                },
+            )
+            .await;
+
+        let aof = LiveAof::disabled();
+        assert_eq!(aof.status().await, None);
+        let first = dir.path().join("first.aof");
+        aof.start(first.clone(), FsyncMode::No, &store)
+            .await
+            .unwrap();
+        aof.append(Record::Set {
+            key: Bytes::from_static(b"after"),
+            value: Bytes::from_static(b"2"),
+            exp_ms: None,
+        })
+        .await
+        .unwrap();
+        assert!(aof.status().await.is_some());
+
+        let restored = replayed(first.clone()).await;
+        assert_eq!(restored.len().await, 2);
+        let before = restored.get(&Bytes::from_static(b"before")).await.unwrap();
+        assert!(matches!(before.exp, Expiry::At(u64::MAX)));
+
+        let second = dir.path().join("second.aof");
+        aof.start(second.clone(), FsyncMode::No, &restored)
+            .await
+            .unwrap();
+        aof.stop().await;
+        aof.append(Record::FlushDb).await.unwrap();
+
+        assert_eq!(replayed(second).await.len().await, 2);
+        assert_eq!(replayed(first).await.len().await, 2);
+    }
+
+    #[tokio::test]
+    async fn save_without_aof_writes_snapshot_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::new();
+        store
+            .set(
+                Bytes::from_static(b"k"),
+                Entry {
+                    value: Bytes::from_static(b"v").into(),
+                    exp: Expiry::None,
+                },
+            )
+            .await;
+
+        let aof = LiveAof::disabled();
+        let path = dir.path().join("snap.aof");
+        aof.save(path.clone(), FsyncMode::No, &store, false)
+            .await
+            .unwrap();
+        assert_eq!(aof.status().await, None);
+        assert_eq!(replayed(path).await.len().await, 1);
+    }
+}
@@ -1,14 +1,16 @@
 pub mod aof;
 pub mod codec;
+pub mod format;
+pub mod live;
 pub mod record;
 
 use std::{
     path::{Path, PathBuf},
     sync::{
         Arc,
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::{Result, anyhow};
@@ -27,21 +29,29 @@ use tokio_util::{
 use crate::{
     config::FsyncMode,
     store::{
-        persistence::{codec::RecordCodec, record::Record},
+        ops::zset::SortedSet,
+        persistence::{
+            aof::AofStatus,
+            codec::RecordCodec,
+            format::{Compatibility, HEADER_LEN},
+            record::Record,
+        },
         traits::Store,
-        types::{Entry, Expiry},
+        types::{Entry, Expiry, Value},
     },
-    utils::pkg::parse_version,
+    telemetry,
+    utils::histogram::LatencyHistogram,
 };
 
-const MAGIC: &[u8] = env!("CARGO_PKG_NAME").as_bytes();
-const VERSION: [u8; 3] = parse_version(env!("CARGO_PKG_VERSION"));
-const HEADER_LEN: usize = MAGIC.len() + VERSION.len();
-
 pub struct AofEngine {
     fsync_mode: std::sync::Mutex<FsyncMode>,
     writer: Arc<Mutex<File>>,
     dirty: Arc<AtomicBool>,
+    size: AtomicU64,
+    base_size: u64,
+    last_write_ok: AtomicBool,
+    write_errors: AtomicU64,
+    fsync_latency: Arc<LatencyHistogram>,
     fsync_handle: std::sync::Mutex<Option<JoinHandle<()>>>,
     cancel: CancellationToken,
 }
@@ -49,6 +59,13 @@ pub struct AofEngine {
 impl AofEngine {
     pub async fn open(path: PathBuf, fsync_mode: FsyncMode) -> Result<Self> {
         ensure_parent_dir(&path).await?;
+        if let Some(backup) = format::migrate(&path).await? {
+            log::info!(
+                "Migrated AOF to format v{}, original kept at {}",
+                format::FORMAT_VERSION,
+                backup.display()
+            );
+        }
 
         let mut file = OpenOptions::new()
             .create(true)
@@ -59,20 +76,22 @@ impl AofEngine {
 
         let metadata = file.metadata().await?;
         if metadata.len() == 0 {
-            file.write_all(MAGIC).await?;
-            file.write_all(&VERSION).await?;
+            file.write_all(&format::header()).await?;
             if matches!(fsync_mode, FsyncMode::Always) {
                 file.sync_data().await?;
             }
         }
 
+        let base_size = file.metadata().await?.len();
         let writer = Arc::new(Mutex::new(file.try_clone().await?));
         let dirty = Arc::new(AtomicBool::new(false));
+        let fsync_latency = Arc::new(LatencyHistogram::new());
         let cancel = CancellationToken::new();
 
         let fsync_handle = if matches!(fsync_mode, FsyncMode::EverySec) {
             let writer = writer.clone();
             let dirty = dirty.clone();
+            let latency = fsync_latency.clone();
             let cancel_clone = cancel.clone();
             Some(tokio::spawn(async move {
                 loop {
@@ -80,8 +99,8 @@ impl AofEngine {
                         _ = cancel_clone.cancelled() => {
                             if dirty.swap(false, Ordering::Relaxed) {
                                 let file = writer.lock().await;
-                                if let Err(err) = file.sync_data().await {
-                                    eprintln!("AOF final fsync error: {err:?}");
+                                if let Err(err) = timed_sync(&file, &latency).await {
+                                    log::error!("AOF final fsync error: {err:?}");
                                 }
                             }
                             return;
@@ -89,8 +108,8 @@ impl AofEngine {
                         _ = tokio::time::sleep(Duration::from_secs(1)) => {
                             if dirty.swap(false, Ordering::Relaxed) {
                                 let file = writer.lock().await;
-                                if let Err(err) = file.sync_data().await {
-                                    eprintln!("AOF fsync error: {err:?}");
+                                if let Err(err) = timed_sync(&file, &latency).await {
+                                    log::error!("AOF fsync error: {err:?}");
                                     dirty.store(true, Ordering::Relaxed);
                                 }
                             }
@@ -106,22 +125,58 @@ impl AofEngine {
             fsync_mode: std::sync::Mutex::new(fsync_mode),
             writer,
             dirty,
+            size: AtomicU64::new(base_size),
+            base_size,
+            last_write_ok: AtomicBool::new(true),
+            write_errors: AtomicU64::new(0),
+            fsync_latency,
             fsync_handle: std::sync::Mutex::new(fsync_handle),
             cancel,
         })
     }
 
+    /// Replaces whatever is at `path` with a fresh file holding `records`,
+    /// then opens it for appending.
+    pub async fn create(
+        path: PathBuf,
+        fsync_mode: FsyncMode,
+        records: impl IntoIterator<Item = Record>,
+    ) -> Result<Self> {
+        ensure_parent_dir(&path).await?;
+        let mut codec = RecordCodec;
+        let mut out = BytesMut::from(&format::header()[..]);
+        for record in records {
+            codec.encode(record, &mut out)?;
+        }
+        replace_file(&path, &out).await?;
+        Self::open(path, fsync_mode).await
+    }
+
     pub async fn append(&self, record: Record) -> Result<()> {
+        let mut span = telemetry::span("aof.append");
         let mut codec = RecordCodec;
         let mut frame = BytesMut::new();
         codec.encode(record, &mut frame)?;
+        span.set_attr("bytes", frame.len());
 
         let mut file = self.writer.lock().await;
-        file.write_all(&frame).await?;
+        // tokio completes writes in the background; flush so they are
+        // visible to readers and their errors are reported here.
+        let written = match file.write_all(&frame).await {
+            Ok(()) => file.flush().await,
+            Err(err) => Err(err),
+        };
+        self.last_write_ok.store(written.is_ok(), Ordering::Relaxed);
+        if written.is_err() {
+            self.write_errors.fetch_add(1, Ordering::Relaxed);
+            span.set_attr("error", true);
+        }
+        written?;
+        self.size.fetch_add(frame.len() as u64, Ordering::Relaxed);
 
         let fsync_mode = *self.fsync_mode.lock().unwrap();
         if matches!(fsync_mode, FsyncMode::Always) {
-            file.sync_data().await?;
+            span.scope(timed_sync(&file, &self.fsync_latency)).await?;
         }
 
         if matches!(fsync_mode, FsyncMode::EverySec) {
@@ -135,6 +190,16 @@ impl AofEngine {
         *self.fsync_mode.lock().unwrap() = mode;
     }
 
+    pub fn status(&self) -> AofStatus {
+        AofStatus {
+            current_size: self.size.load(Ordering::Relaxed),
+            base_size: self.base_size,
+            last_write_ok: self.last_write_ok.load(Ordering::Relaxed),
+            write_errors: self.write_errors.load(Ordering::Relaxed),
+            fsync_latency: self.fsync_latency.snapshot(),
+        }
+    }
+
     pub async fn replay_into(&self, store: &dyn Store) -> Result<()> {
         let mut file = self.writer.lock().await;
         file.seek(std::io::SeekFrom::Start(0)).await?;
@@ -156,21 +221,27 @@ impl AofEngine {
         }
 
         if !buf.is_empty() {
-            eprintln!(
-                "warning: truncated AOF tail detected ({} bytes ignored)",
-                buf.len()
-            );
+            log::warn!("truncated AOF tail detected ({} bytes ignored)", buf.len());
         }
 
         Ok(())
     }
 
+    pub async fn fsync(&self) -> Result<()> {
+        timed_sync(&*self.writer.lock().await, &self.fsync_latency).await?;
+        self.dirty.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
     pub async fn shutdown(&self) {
         self.cancel.cancel();
         let handle = self.fsync_handle.lock().unwrap().take();
         if let Some(handle) = handle {
             handle.await.ok();
         }
+        if let Err(err) = self.writer.lock().await.sync_data().await {
+            log::error!("AOF final fsync error: {err:?}");
+        }
     }
 }
 
@@ -188,11 +259,27 @@ impl aof::Aof for AofEngine {
         self.set_fsync_mode(mode);
     }
 
+    fn status(&self) -> Option<AofStatus> {
+        Some(self.status())
+    }
+
+    async fn fsync(&self) -> Result<()> {
+        self.fsync().await
+    }
+
     async fn shutdown(&self) {
         self.shutdown().await;
     }
 }
 
+async fn timed_sync(file: &File, latency: &LatencyHistogram) -> std::io::Result<()> {
+    let _span = telemetry::span("aof.fsync");
+    let start = Instant::now();
+    let synced = file.sync_data().await;
+    latency.observe(start.elapsed());
+    synced
+}
+
 async fn ensure_parent_dir(path: &Path) -> Result<()> {
     if let Some(parent) = path.parent() {
         tokio::fs::create_dir_all(parent).await?;
@@ -200,27 +287,53 @@ async fn ensure_parent_dir(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Writes `contents` to a temporary file next to `path` and renames it into
+/// place, so readers see either the old file or the complete new one.
+async fn replace_file(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+    let mut file = File::create(&tmp).await?;
+    file.write_all(contents).await?;
+    file.sync_all().await?;
+    drop(file);
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
+
 fn validate_header(raw: &[u8]) -> Result<()> {
-    if raw.len() < HEADER_LEN {
-        return Err(anyhow!("invalid AOF: file too small for header"));
-    }
-    if &raw[..MAGIC.len()] != MAGIC {
-        return Err(anyhow!("invalid AOF: bad magic"));
+    let version = format::read_header(raw)?;
+    match format::compatibility(version)? {
+        Compatibility::Current => Ok(()),
+        Compatibility::Migrate => Err(anyhow!(
+            "AOF format version {version} must be migrated before it is read"
+        )),
     }
-    if raw[MAGIC.len()..HEADER_LEN] != VERSION {
-        return Err(anyhow!("unsupported AOF version: {}", raw[MAGIC.len()]));
+}
+
+fn expiry(exp_ms: Option<u64>) -> Expiry {
+    match exp_ms {
+        Some(at) => Expiry::At(at),
+        None => Expiry::None,
     }
-    Ok(())
 }
 
 async fn apply_record(store: &dyn Store, record: Record) -> Result<()> {
     match record {
         Record::Set { key, value, exp_ms } => {
-            let exp = match exp_ms {
-                Some(at) => Expiry::At(at),
-                None => Expiry::None,
+            let entry = Entry {
+                value: value.into(),
+                exp: expiry(exp_ms),
+            };
+            store.set(key, entry).await;
+        }
+        Record::ZSet { key, value, exp_ms } => {
+            let set = SortedSet::decode(&value).map_err(|err| anyhow!(err))?;
+            let entry = Entry {
+                value: Value::ZSet(Arc::new(set)),
+                exp: expiry(exp_ms),
             };
-            store.set(key, Entry { value, exp }).await;
+            store.set(key, entry).await;
         }
         Record::Del { keys } => {
             store.del(&keys).await;
@@ -250,9 +363,7 @@ mod tests {
         drop(engine);
 
         let raw = std::fs::read(&path).unwrap();
-        assert!(raw.len() >= HEADER_LEN);
-        assert_eq!(&raw[..MAGIC.len()], MAGIC);
-        assert_eq!(&raw[MAGIC.len()..HEADER_LEN], VERSION);
+        assert_eq!(raw, format::header());
     }
 
     #[tokio::test]
@@ -280,6 +391,11 @@ mod tests {
         let store = MemoryStore::new();
         engine.replay_into(&store).await.unwrap();
         assert!(store.is_empty().await);
+
+        let status = engine.status();
+        assert_eq!(status.base_size, HEADER_LEN as u64);
+        assert_eq!(status.current_size, std::fs::metadata(&path).unwrap().len());
+        assert!(status.last_write_ok);
     }
 
     #[tokio::test]
@@ -340,22 +456,45 @@ mod tests {
 
     #[test]
     fn validate_header_bad_magic() {
-        let mut raw = vec![0u8; HEADER_LEN];
-        raw[MAGIC.len()..HEADER_LEN].copy_from_slice(&VERSION);
+        let mut raw = format::header();
+        raw[0] = 0;
         assert!(validate_header(&raw).is_err());
     }
 
     #[test]
-    fn validate_header_bad_version() {
-        let mut raw = Vec::from(MAGIC);
-        raw.extend_from_slice(&[99, 99, 99]);
+    fn validate_header_legacy_version() {
+        let mut raw = Vec::from(format::MAGIC);
+        raw.extend_from_slice(&[0, 1, 0]);
         assert!(validate_header(&raw).is_err());
     }
 
     #[test]
     fn validate_header_ok() {
-        let mut raw = Vec::from(MAGIC);
-        raw.extend_from_slice(&VERSION);
-        assert!(validate_header(&raw).is_ok());
+        assert!(validate_header(&format::header()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn open_migrates_legacy_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.aof");
+        let mut raw = BytesMut::from(format::MAGIC);
+        raw.extend_from_slice(&[0, 1, 0]);
+        RecordCodec
+            .encode(
+                Record::Set {
+                    key: Bytes::from_static(b"k"),
+                    value: Bytes::from_static(b"v"),
+                    exp_ms: None,
+                },
+                &mut raw,
+            )
+            .unwrap();
+        std::fs::write(&path, &raw).unwrap();
+
+        let engine = AofEngine::open(path.clone(), FsyncMode::No).await.unwrap();
+        let store = MemoryStore::new();
+        engine.replay_into(&store).await.unwrap();
+        assert_eq!(store.len().await, 1);
+        assert_eq!(std::fs::read(format::backup_path(&path, 1)).unwrap(), raw);
     }
 }
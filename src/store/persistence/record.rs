@@ -1,6 +1,8 @@
 use anyhow::anyhow;
 use tokio_util::bytes::Bytes;
 
+use crate::store::types::{Entry, Expiry, Value};
+
 #[derive(Clone, Debug)]
 pub enum Record {
     Set {
@@ -15,6 +17,30 @@ pub enum Record {
         items: Vec<(Bytes, Bytes)>,
     },
     FlushDb,
+    /// Like `Set`, with `value` holding an encoded sorted set.
+    ZSet {
+        key: Bytes,
+        value: Bytes,
+        exp_ms: Option<u64>,
+    },
+}
+
+impl Record {
+    /// The record that recreates `entry` under `key`.
+    pub fn from_entry(key: Bytes, entry: Entry) -> Self {
+        let exp_ms = match entry.exp {
+            Expiry::At(ms) => Some(ms),
+            Expiry::None | Expiry::Keep => None,
+        };
+        match entry.value {
+            Value::String(value) => Self::Set { key, value, exp_ms },
+            Value::ZSet(set) => Self::ZSet {
+                key,
+                value: set.encode(),
+                exp_ms,
+            },
+        }
+    }
 }
 
 #[repr(u8)]
@@ -24,6 +50,7 @@ pub enum RecordTag {
     Del = 1,
     MSet = 2,
     FlushDb = 3,
+    ZSet = 4,
 }
 
 impl TryFrom<u8> for RecordTag {
@@ -35,6 +62,7 @@ impl TryFrom<u8> for RecordTag {
             1 => Ok(Self::Del),
             2 => Ok(Self::MSet),
             3 => Ok(Self::FlushDb),
+            4 => Ok(Self::ZSet),
             _ => Err(anyhow!("unknown record tag")),
         }
     }
@@ -47,6 +75,7 @@ impl From<&Record> for RecordTag {
             Record::Del { .. } => Self::Del,
             Record::MSet { .. } => Self::MSet,
             Record::FlushDb => Self::FlushDb,
+            Record::ZSet { .. } => Self::ZSet,
         }
     }
 }
@@ -61,11 +90,12 @@ mod tests {
         assert!(matches!(RecordTag::try_from(1u8), Ok(RecordTag::Del)));
         assert!(matches!(RecordTag::try_from(2u8), Ok(RecordTag::MSet)));
         assert!(matches!(RecordTag::try_from(3u8), Ok(RecordTag::FlushDb)));
+        assert!(matches!(RecordTag::try_from(4u8), Ok(RecordTag::ZSet)));
     }
 
     #[test]
     fn try_from_invalid_tag() {
-        assert!(RecordTag::try_from(4u8).is_err());
+        assert!(RecordTag::try_from(5u8).is_err());
         assert!(RecordTag::try_from(255u8).is_err());
     }
 
@@ -7,10 +7,29 @@ use crate::store::types::Entry;
 pub trait Store: Send + Sync {
     async fn set(&self, key: Bytes, entry: Entry) -> Entry;
     async fn get(&self, key: &Bytes) -> Option<Entry>;
+    /// Read-modify-write of a single key under its lock. `f` sees the live
+    /// entry, or `None` when the key is missing or expired, and whatever it
+    /// leaves behind is stored; leaving `None` deletes the key.
+    async fn update<F, R>(&self, key: &Bytes, f: F) -> R
+    where
+        Self: Sized,
+        F: FnOnce(&mut Option<Entry>) -> R + Send,
+        R: Send;
+    /// Like `update` for `dest`, with `f` also seeing the live entries at
+    /// `keys` in order. Every involved shard stays locked throughout, so no
+    /// other write to the sources or `dest` can land in between.
+    async fn update_from<F, R>(&self, keys: &[Bytes], dest: &Bytes, f: F) -> R
+    where
+        Self: Sized,
+        F: FnOnce(&[Option<Entry>], &mut Option<Entry>) -> R + Send,
+        R: Send;
     async fn del(&self, keys: &[Bytes]) -> i64;
     async fn exists(&self, keys: &[Bytes]) -> i64;
     async fn mget(&self, keys: &[Bytes]) -> Vec<Option<Entry>>;
     async fn mset(&self, items: &[(Bytes, Bytes)]);
+    /// Sets every item only if none of the keys exist, checked and written
+    /// atomically. Returns whether anything was set.
+    async fn msetnx(&self, items: &[(Bytes, Bytes)]) -> bool;
     async fn len(&self) -> usize;
     async fn clear(&self);
     async fn is_empty(&self) -> bool;
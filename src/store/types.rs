@@ -1,11 +1,25 @@
+use std::sync::Arc;
+
 use tokio_util::bytes::Bytes;
 
+use crate::store::ops::zset::SortedSet;
+
+pub const WRONG_TYPE: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+
 #[derive(Clone, Debug)]
 pub struct Entry {
-    pub value: Bytes,
+    pub value: Value,
     pub exp: Expiry,
 }
 
+/// Sorted sets are shared so reads clone them cheaply; writes copy one only
+/// while a reader still holds it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(Bytes),
+    ZSet(Arc<SortedSet>),
+}
+
 #[derive(Clone, Debug)]
 pub enum Expiry {
     Keep,
@@ -13,6 +27,14 @@ pub enum Expiry {
     None,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SetCondition {
+    #[default]
+    Always,
+    IfMissing,
+    IfExists,
+}
+
 impl Entry {
     pub fn is_expired(&self, now: u64) -> bool {
         match self.exp {
@@ -22,13 +44,60 @@ impl Entry {
     }
 }
 
+impl Value {
+    /// Bytes held by the value, as counted for memory accounting.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::String(bytes) => bytes.len(),
+            Self::ZSet(set) => set.memory(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The string payload, or WRONGTYPE for any other kind of value.
+    pub fn as_string(&self) -> Result<&Bytes, &'static str> {
+        match self {
+            Self::String(bytes) => Ok(bytes),
+            Self::ZSet(_) => Err(WRONG_TYPE),
+        }
+    }
+
+    pub fn as_zset(&self) -> Result<&Arc<SortedSet>, &'static str> {
+        match self {
+            Self::ZSet(set) => Ok(set),
+            Self::String(_) => Err(WRONG_TYPE),
+        }
+    }
+}
+
+impl From<Bytes> for Value {
+    fn from(bytes: Bytes) -> Self {
+        Self::String(bytes)
+    }
+}
+
+impl PartialEq<Bytes> for Value {
+    fn eq(&self, other: &Bytes) -> bool {
+        matches!(self, Self::String(bytes) if bytes == other)
+    }
+}
+
+impl PartialEq<&str> for Value {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, Self::String(bytes) if bytes == other.as_bytes())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn make_entry(exp: Expiry) -> Entry {
         Entry {
-            value: Bytes::from_static(b"val"),
+            value: Bytes::from_static(b"val").into(),
             exp,
         }
     }
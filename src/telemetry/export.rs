@@ -0,0 +1,304 @@
+//! Sends finished spans to a JSON-lines file or an OTLP/HTTP collector.
+
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::{Value, json};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    sync::mpsc,
+};
+
+use super::SpanData;
+use crate::config::{AppConfig, TraceExporter};
+
+/// Most spans sent in one write or request.
+const BATCH_SIZE: usize = 512;
+/// How long a partial batch waits for more spans.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub(super) enum Exporter {
+    File { path: PathBuf, file: File },
+    Otlp(Endpoint),
+}
+
+impl Exporter {
+    /// Opens the file or checks the endpoint named in `config`; `None` when
+    /// tracing is off.
+    pub(super) fn from_config(config: &AppConfig) -> Result<Option<Self>> {
+        Ok(match config.trace_exporter {
+            TraceExporter::None => None,
+            TraceExporter::File => Some(Self::File {
+                path: config.trace_file.clone(),
+                file: File::from_std(open(&config.trace_file)?),
+            }),
+            TraceExporter::Otlp => Some(Self::Otlp(Endpoint::parse(&config.trace_endpoint)?)),
+        })
+    }
+
+    async fn export(&mut self, spans: &[SpanData]) -> Result<()> {
+        match self {
+            Self::File { path, file } => {
+                let mut out = String::new();
+                for span in spans {
+                    out.push_str(&file_line(span).to_string());
+                    out.push('\n');
+                }
+                file.write_all(out.as_bytes())
+                    .await
+                    .with_context(|| format!("can't write {}", path.display()))?;
+                file.flush().await?;
+                Ok(())
+            }
+            Self::Otlp(endpoint) => {
+                let body = otlp_request(spans).to_string();
+                tokio::time::timeout(REQUEST_TIMEOUT, endpoint.post(body.as_bytes()))
+                    .await
+                    .map_err(|_| anyhow!("request to {} timed out", endpoint.url))?
+            }
+        }
+    }
+}
+
+fn open(path: &Path) -> Result<std::fs::File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("can't open trace file {}", path.display()))
+}
+
+/// Exports spans from `rx` in batches until every sender is gone.
+pub(super) async fn run(mut exporter: Exporter, mut rx: mpsc::Receiver<SpanData>) {
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let mut failing = false;
+    loop {
+        if rx.recv_many(&mut batch, BATCH_SIZE).await == 0 {
+            return;
+        }
+        let _ = tokio::time::timeout(FLUSH_INTERVAL, async {
+            while batch.len() < BATCH_SIZE {
+                let room = BATCH_SIZE - batch.len();
+                if rx.recv_many(&mut batch, room).await == 0 {
+                    break;
+                }
+            }
+        })
+        .await;
+
+        // Logged once per outage rather than once per batch.
+        match exporter.export(&batch).await {
+            Ok(()) if failing => {
+                log::info!("Span export recovered");
+                failing = false;
+            }
+            Ok(()) => {}
+            Err(err) if !failing => {
+                log::warn!("Span export failed, dropping spans until it recovers: {err:#}");
+                failing = true;
+            }
+            Err(_) => {}
+        }
+        batch.clear();
+    }
+}
+
+fn file_line(span: &SpanData) -> Value {
+    json!({
+        "trace_id": format!("{:032x}", span.trace_id),
+        "span_id": format!("{:016x}", span.span_id),
+        "parent_span_id": span.parent_id.map(|id| format!("{id:016x}")),
+        "name": span.name,
+        "start_unix_nanos": span.start_unix_nanos,
+        "end_unix_nanos": span.end_unix_nanos,
+        "duration_us": (span.end_unix_nanos - span.start_unix_nanos) / 1000,
+        "attributes": span
+            .attributes
+            .iter()
+            .map(|(key, value)| (key.to_string(), Value::from(value.as_str())))
+            .collect::<serde_json::Map<_, _>>(),
+    })
+}
+
+/// An `ExportTraceServiceRequest` in the OTLP/JSON encoding.
+fn otlp_request(spans: &[SpanData]) -> Value {
+    let spans: Vec<_> = spans
+        .iter()
+        .map(|span| {
+            json!({
+                "traceId": format!("{:032x}", span.trace_id),
+                "spanId": format!("{:016x}", span.span_id),
+                "parentSpanId": span.parent_id.map_or(String::new(), |id| format!("{id:016x}")),
+                "name": span.name,
+                // SPAN_KIND_INTERNAL
+                "kind": 1,
+                "startTimeUnixNano": span.start_unix_nanos.to_string(),
+                "endTimeUnixNano": span.end_unix_nanos.to_string(),
+                "attributes": span
+                    .attributes
+                    .iter()
+                    .map(|(key, value)| otlp_attribute(key, value))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    json!({
+        "resourceSpans": [{
+            "resource": {"attributes": [otlp_attribute("service.name", "yars")]},
+            "scopeSpans": [{
+                "scope": {"name": "yars", "version": env!("CARGO_PKG_VERSION")},
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+fn otlp_attribute(key: &str, value: &str) -> Value {
+    json!({"key": key, "value": {"stringValue": value}})
+}
+
+/// An `http://host[:port]/path` collector URL.
+pub(super) struct Endpoint {
+    url: String,
+    authority: String,
+    path: String,
+}
+
+impl Endpoint {
+    fn parse(url: &str) -> Result<Self> {
+        let Some(rest) = url.strip_prefix("http://") else {
+            bail!("trace-endpoint must be an http:// URL: {url}");
+        };
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        if authority.is_empty() {
+            bail!("trace-endpoint has no host: {url}");
+        }
+        let authority = if authority
+            .rsplit_once(':')
+            .is_some_and(|(_, port)| !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()))
+        {
+            authority.to_string()
+        } else {
+            format!("{authority}:80")
+        };
+        Ok(Self {
+            url: url.to_string(),
+            authority,
+            path: if path.is_empty() { "/" } else { path }.to_string(),
+        })
+    }
+
+    /// One HTTP/1.1 POST per batch; collectors answer 200 with an empty
+    /// partial-success object.
+    async fn post(&self, body: &[u8]) -> Result<()> {
+        let mut stream = TcpStream::connect(&self.authority)
+            .await
+            .with_context(|| format!("can't connect to {}", self.url))?;
+        let head = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.path,
+            self.authority,
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(body).await?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        let status_line = response
+            .split(|&b| b == b'\r')
+            .next()
+            .map(String::from_utf8_lossy)
+            .unwrap_or_default();
+        match status_line.split(' ').nth(1) {
+            Some(status) if status.starts_with('2') => Ok(()),
+            _ => bail!("{} answered {status_line:?}", self.url),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    fn span(parent_id: Option<u64>) -> SpanData {
+        SpanData {
+            trace_id: 0xabc,
+            span_id: 0x2a,
+            parent_id,
+            name: "command.parse",
+            start_unix_nanos: 1_000_000,
+            end_unix_nanos: 1_250_000,
+            attributes: vec![("db.operation.name", "get".into())],
+        }
+    }
+
+    #[test]
+    fn file_lines_use_hex_ids() {
+        let line = file_line(&span(Some(7)));
+        assert_eq!(line["trace_id"], "00000000000000000000000000000abc");
+        assert_eq!(line["span_id"], "000000000000002a");
+        assert_eq!(line["parent_span_id"], "0000000000000007");
+        assert_eq!(line["duration_us"], 250);
+        assert_eq!(line["attributes"]["db.operation.name"], "get");
+        assert!(file_line(&span(None))["parent_span_id"].is_null());
+    }
+
+    #[tokio::test]
+    async fn otlp_batches_post_to_the_collector() {
+        let collector = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = Endpoint::parse(&format!(
+            "http://{}/v1/traces",
+            collector.local_addr().unwrap()
+        ))
+        .unwrap();
+        let received = tokio::spawn(async move {
+            let (mut socket, _) = collector.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut chunk = [0u8; 4096];
+            while !request.ends_with(b"}") {
+                let n = socket.read(&mut chunk).await.unwrap();
+                request.extend_from_slice(&chunk[..n]);
+            }
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let mut exporter = Exporter::Otlp(endpoint);
+        exporter.export(&[span(None)]).await.unwrap();
+
+        let request = received.await.unwrap();
+        assert!(request.starts_with("POST /v1/traces HTTP/1.1\r\n"));
+        let body: Value = serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        let resource = &body["resourceSpans"][0];
+        assert_eq!(
+            resource["resource"]["attributes"][0]["value"]["stringValue"],
+            "yars"
+        );
+        let exported = &resource["scopeSpans"][0]["spans"][0];
+        assert_eq!(exported["name"], "command.parse");
+        assert_eq!(exported["parentSpanId"], "");
+        assert_eq!(exported["startTimeUnixNano"], "1000000");
+    }
+
+    #[test]
+    fn endpoints_default_to_port_80() {
+        let endpoint = Endpoint::parse("http://collector").unwrap();
+        assert_eq!(endpoint.authority, "collector:80");
+        assert_eq!(endpoint.path, "/");
+        assert!(Endpoint::parse("https://collector:4318/v1/traces").is_err());
+        assert!(Endpoint::parse("http:///v1/traces").is_err());
+    }
+}
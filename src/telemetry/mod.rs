@@ -0,0 +1,257 @@
+//! Tracing spans for the command path, exported per `trace-exporter`.
+//!
+//! Each command gets a root `command` span whose children cover frame
+//! decoding, parsing, dispatch, shard lock waits and holds, and AOF writes.
+//! The parent of a new span is whichever span the current task is scoped to.
+//! With the exporter off, creating a span costs an atomic load and nothing is
+//! recorded.
+
+mod export;
+
+use std::{
+    fmt::Display,
+    future::Future,
+    ops::{Deref, DerefMut},
+    sync::{
+        Mutex, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::{config::AppConfig, utils::random::random_u64};
+
+/// Finished spans waiting for the exporter; more are dropped rather than
+/// slowing commands down.
+const QUEUE_CAPACITY: usize = 8192;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SINK: RwLock<Option<mpsc::Sender<SpanData>>> = RwLock::new(None);
+static EXPORTER: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+tokio::task_local! {
+    static CURRENT: SpanContext;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SpanContext {
+    trace_id: u128,
+    span_id: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpanData {
+    pub trace_id: u128,
+    pub span_id: u64,
+    pub parent_id: Option<u64>,
+    pub name: &'static str,
+    pub start_unix_nanos: u64,
+    pub end_unix_nanos: u64,
+    pub attributes: Vec<(&'static str, String)>,
+}
+
+struct Recording {
+    data: SpanData,
+    start: Instant,
+}
+
+/// An open span, recorded when dropped.
+pub struct Span(Option<Box<Recording>>);
+
+/// Starts a span, a child of the one the current task is scoped to.
+pub fn span(name: &'static str) -> Span {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return Span(None);
+    }
+    Span::begin(name)
+}
+
+impl Span {
+    fn begin(name: &'static str) -> Self {
+        let parent = CURRENT.try_with(|context| *context).ok();
+        let trace_id = parent.map_or_else(
+            || (random_u64() as u128) << 64 | random_u64() as u128,
+            |parent| parent.trace_id,
+        );
+        Self(Some(Box::new(Recording {
+            data: SpanData {
+                trace_id,
+                span_id: random_u64(),
+                parent_id: parent.map(|parent| parent.span_id),
+                name,
+                start_unix_nanos: unix_nanos(),
+                end_unix_nanos: 0,
+                attributes: Vec::new(),
+            },
+            start: Instant::now(),
+        })))
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.0.is_some()
+    }
+
+    fn context(&self) -> Option<SpanContext> {
+        self.0.as_ref().map(|recording| SpanContext {
+            trace_id: recording.data.trace_id,
+            span_id: recording.data.span_id,
+        })
+    }
+
+    pub fn set_attr(&mut self, key: &'static str, value: impl Display) {
+        if let Some(recording) = &mut self.0 {
+            recording.data.attributes.push((key, value.to_string()));
+        }
+    }
+
+    pub fn with_attr(mut self, key: &'static str, value: impl Display) -> Self {
+        self.set_attr(key, value);
+        self
+    }
+
+    /// Moves the start to now, leaving out time spent before the work began.
+    pub fn restart(&mut self) {
+        if let Some(recording) = &mut self.0 {
+            recording.data.start_unix_nanos = unix_nanos();
+            recording.start = Instant::now();
+        }
+    }
+
+    /// Ends the span without recording it.
+    pub fn discard(mut self) {
+        self.0 = None;
+    }
+
+    /// Runs `fut` with this span as the parent of spans it starts.
+    pub fn scope<F: Future>(&self, fut: F) -> impl Future<Output = F::Output> {
+        let context = self.context();
+        async move {
+            match context {
+                Some(context) => CURRENT.scope(context, fut).await,
+                None => fut.await,
+            }
+        }
+    }
+
+    /// Runs `f` with this span as the parent of spans it starts.
+    pub fn sync_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        match self.context() {
+            Some(context) => CURRENT.sync_scope(context, f),
+            None => f(),
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(recording) = self.0.take() else {
+            return;
+        };
+        let mut data = recording.data;
+        data.end_unix_nanos = data.start_unix_nanos + recording.start.elapsed().as_nanos() as u64;
+        if let Some(sink) = SINK.read().unwrap().as_ref() {
+            let _ = sink.try_send(data);
+        }
+    }
+}
+
+/// A value, such as a lock guard, with a span that ends once the value is
+/// dropped.
+pub struct Traced<T> {
+    // Declared first so it is dropped before the span ends.
+    value: T,
+    _span: Span,
+}
+
+impl<T> Traced<T> {
+    pub fn new(value: T, span: Span) -> Self {
+        Self { value, _span: span }
+    }
+}
+
+impl<T> Deref for Traced<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Traced<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+fn unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos() as u64)
+}
+
+/// Applies the tracing settings in `config`, replacing the exporter. The old
+/// one flushes what it has queued in the background. On error the previous
+/// settings stay in place.
+pub fn configure(config: &AppConfig) -> Result<()> {
+    let exporter = export::Exporter::from_config(config)?;
+    let mut sink = SINK.write().unwrap();
+    *sink = None;
+    ENABLED.store(false, Ordering::Relaxed);
+    let Some(exporter) = exporter else {
+        return Ok(());
+    };
+    let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+    *sink = Some(tx);
+    *EXPORTER.lock().unwrap() = Some(tokio::spawn(export::run(exporter, rx)));
+    ENABLED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Stops tracing and waits for the exporter to send what it has queued.
+pub async fn shutdown() {
+    ENABLED.store(false, Ordering::Relaxed);
+    SINK.write().unwrap().take();
+    let exporter = EXPORTER.lock().unwrap().take();
+    if let Some(exporter) = exporter {
+        let _ = exporter.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn spans_inherit_trace_from_scope() {
+        let root = Span::begin("root");
+        let root_context = root.context().unwrap();
+        let (child, grandchild) = root
+            .scope(async {
+                let child = Span::begin("child");
+                let grandchild = child.sync_scope(|| Span::begin("grandchild"));
+                (child.context().unwrap(), grandchild)
+            })
+            .await;
+        let grandchild = grandchild.0.as_ref().unwrap();
+
+        assert_eq!(root.0.as_ref().unwrap().data.parent_id, None);
+        assert_eq!(child.trace_id, root_context.trace_id);
+        assert_eq!(grandchild.data.trace_id, root_context.trace_id);
+        assert_eq!(grandchild.data.parent_id, Some(child.span_id));
+        assert_ne!(
+            Span::begin("other").context().unwrap().trace_id,
+            root_context.trace_id
+        );
+    }
+
+    #[test]
+    fn disabled_spans_record_nothing() {
+        let mut span = Span(None);
+        span.set_attr("key", "value");
+        assert!(!span.is_recording());
+        assert!(span.sync_scope(|| CURRENT.try_with(|_| ()).is_err()));
+    }
+}
@@ -0,0 +1,76 @@
+//! Lock-free latency histogram with fixed buckets, shaped for Prometheus.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Bucket upper bounds in microseconds, from 10µs to 10s.
+const BOUNDS_US: [u64; 14] = [
+    10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 100_000, 1_000_000, 10_000_000,
+];
+
+#[derive(Default)]
+pub struct LatencyHistogram {
+    /// Per-bucket counts, the last one for values above every bound.
+    buckets: [AtomicU64; BOUNDS_US.len() + 1],
+    sum_us: AtomicU64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HistogramSnapshot {
+    /// Upper bound in seconds and the cumulative count at or below it.
+    pub buckets: Vec<(f64, u64)>,
+    pub count: u64,
+    pub sum_seconds: f64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe(&self, elapsed: Duration) {
+        let us = elapsed.as_micros() as u64;
+        let idx = BOUNDS_US.partition_point(|&bound| bound < us);
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(us, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let mut cumulative = 0;
+        let mut buckets = Vec::with_capacity(BOUNDS_US.len());
+        for (bound, count) in BOUNDS_US.iter().zip(&self.buckets) {
+            cumulative += count.load(Ordering::Relaxed);
+            buckets.push((*bound as f64 / 1e6, cumulative));
+        }
+        HistogramSnapshot {
+            buckets,
+            count: cumulative + self.buckets[BOUNDS_US.len()].load(Ordering::Relaxed),
+            sum_seconds: self.sum_us.load(Ordering::Relaxed) as f64 / 1e6,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_are_cumulative_and_inclusive() {
+        let histogram = LatencyHistogram::new();
+        histogram.observe(Duration::from_micros(10));
+        histogram.observe(Duration::from_micros(11));
+        histogram.observe(Duration::from_millis(3));
+        histogram.observe(Duration::from_secs(60));
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.buckets[0], (0.00001, 1));
+        assert_eq!(snapshot.buckets[1], (0.000025, 2));
+        assert_eq!(snapshot.buckets[7], (0.0025, 2));
+        assert_eq!(snapshot.buckets[8], (0.005, 3));
+        assert_eq!(snapshot.buckets.last(), Some(&(10.0, 3)));
+        assert_eq!(snapshot.count, 4);
+        assert!((snapshot.sum_seconds - 60.003021).abs() < 1e-9);
+    }
+}
@@ -1,2 +1,5 @@
-pub mod pkg;
+pub mod alloc;
+pub mod glob;
+pub mod histogram;
+pub mod random;
 pub mod time;
@@ -4,20 +4,45 @@ use std::time::Duration;
 use tokio::{net::TcpStream, time::timeout};
 use tokio_util::{bytes::Bytes, codec::Framed};
 use yars::{
-    config::{AppConfig, FsyncMode},
+    config::{AppConfig, EvictionPolicy, FsyncMode, LogFormat, LogLevel, TraceExporter},
     net::server::Server,
     protocol::resp::{Frame, RespCodec},
 };
 
+// Not every test binary uses both spawn helpers.
+#[allow(dead_code)]
 pub async fn spawn_server() -> Result<(u16, tokio::task::JoinHandle<Result<()>>)> {
+    spawn_server_with(|_| {}).await
+}
+
+pub async fn spawn_server_with(
+    configure: impl FnOnce(&mut AppConfig),
+) -> Result<(u16, tokio::task::JoinHandle<Result<()>>)> {
     let tmp = tempfile::tempdir()?;
-    let config = AppConfig {
+    let mut config = AppConfig {
+        bind: "127.0.0.1".into(),
+        port: 6379,
         append_only: false,
         aof_path: tmp.path().join("data.aof"),
         fsync_mode: FsyncMode::No,
         config_path: tmp.path().join("yars.toml"),
         data_dir: tmp.path().to_path_buf(),
+        maxmemory: 0,
+        maxmemory_policy: EvictionPolicy::NoEviction,
+        maxclients: 10000,
+        slowlog_log_slower_than: 10000,
+        slowlog_max_len: 128,
+        watch_config: false,
+        shutdown_timeout: 10,
+        log_level: LogLevel::Notice,
+        log_file: None,
+        log_format: LogFormat::Text,
+        metrics_port: 0,
+        trace_exporter: TraceExporter::None,
+        trace_file: tmp.path().join("traces.jsonl"),
+        trace_endpoint: "http://127.0.0.1:4318/v1/traces".into(),
     };
+    configure(&mut config);
 
     let server = Server::bind("127.0.0.1:0", config).await?;
     let port = server.local_addr()?.port();
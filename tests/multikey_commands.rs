@@ -61,15 +61,116 @@ async fn mset_and_mget() {
 }
 
 #[tokio::test]
-async fn mget_returns_empty_array_when_no_keys() {
+async fn mget_without_keys_is_an_arity_error() {
     let (port, handle) = spawn_server().await.unwrap();
     let mut framed = connect(port).await.unwrap();
 
     let response = send_cmd(&mut framed, &["MGET"]).await.unwrap();
-    let Frame::Array(items) = response else {
-        panic!("expected array");
-    };
-    assert!(items.is_empty());
+    assert_eq!(
+        response,
+        Frame::Error("ERR wrong number of arguments for 'mget' command".into())
+    );
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn msetnx_is_all_or_nothing() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    let response = send_cmd(&mut framed, &["MSETNX", "a", "1", "b", "2"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Integer(1));
+
+    let response = send_cmd(&mut framed, &["MSETNX", "b", "3", "c", "4"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Integer(0));
+
+    let response = send_cmd(&mut framed, &["EXISTS", "c"]).await.unwrap();
+    assert_eq!(response, Frame::Integer(0));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn lcs_returns_subsequence_and_length() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    send_cmd(&mut framed, &["MSET", "a", "ohmytext", "b", "mynewtext"])
+        .await
+        .unwrap();
+
+    let response = send_cmd(&mut framed, &["LCS", "a", "b"]).await.unwrap();
+    assert_eq!(response, Frame::BulkString("mytext".into()));
+
+    let response = send_cmd(&mut framed, &["LCS", "a", "b", "LEN"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Integer(6));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn bitop_combines_keys() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    send_cmd(&mut framed, &["MSET", "a", "foof", "b", "abc"])
+        .await
+        .unwrap();
+
+    let response = send_cmd(&mut framed, &["BITOP", "OR", "dest", "a", "b"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Integer(4));
+
+    let response = send_cmd(&mut framed, &["GET", "dest"]).await.unwrap();
+    assert_eq!(response, Frame::BulkString("goof".into()));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn pfadd_pfcount_and_pfmerge() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    let response = send_cmd(&mut framed, &["PFADD", "a", "x", "y", "z"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Integer(1));
+
+    let response = send_cmd(&mut framed, &["PFADD", "a", "x"]).await.unwrap();
+    assert_eq!(response, Frame::Integer(0));
+
+    send_cmd(&mut framed, &["PFADD", "b", "z", "w"])
+        .await
+        .unwrap();
+
+    let response = send_cmd(&mut framed, &["PFCOUNT", "a", "b"]).await.unwrap();
+    assert_eq!(response, Frame::Integer(4));
+
+    let response = send_cmd(&mut framed, &["PFMERGE", "c", "a", "b"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::SimpleString("OK".into()));
+
+    let response = send_cmd(&mut framed, &["PFCOUNT", "c"]).await.unwrap();
+    assert_eq!(response, Frame::Integer(4));
+
+    send_cmd(&mut framed, &["SET", "plain", "value"])
+        .await
+        .unwrap();
+    let response = send_cmd(&mut framed, &["PFCOUNT", "plain"]).await.unwrap();
+    assert_eq!(
+        response,
+        Frame::Error("WRONGTYPE Key is not a valid HyperLogLog string value.".into())
+    );
 
     shutdown_server(port, handle).await.unwrap();
 }
@@ -1,7 +1,12 @@
 mod common;
 
-use common::{connect, send_cmd, shutdown_server, spawn_server};
-use yars::protocol::resp::Frame;
+use common::{connect, send_cmd, shutdown_server, spawn_server, spawn_server_with};
+use futures::{SinkExt, StreamExt};
+use yars::{
+    config::FsyncMode,
+    protocol::resp::Frame,
+    store::{memory::MemoryStore, persistence::AofEngine, traits::Store},
+};
 
 #[tokio::test]
 async fn ping_returns_pong() {
@@ -55,11 +60,28 @@ async fn info_contains_fields() {
         panic!("expected bulk string");
     };
     let info = std::str::from_utf8(&data).unwrap();
+    assert!(info.contains("redis_version:"));
     assert!(info.contains("yars_version"));
-    assert!(info.contains("db_keys"));
     assert!(info.contains("used_memory"));
-    assert!(info.contains("uptime_seconds"));
-    assert!(info.contains("total_commands"));
+    assert!(info.contains("uptime_in_seconds"));
+    assert!(info.contains("connected_clients:1\r\n"));
+
+    send_cmd(&mut framed, &["SET", "k", "v"]).await.unwrap();
+    send_cmd(&mut framed, &["GET", "k"]).await.unwrap();
+    send_cmd(&mut framed, &["GET", "missing"]).await.unwrap();
+    let Frame::BulkString(data) = send_cmd(&mut framed, &["INFO", "stats", "commandstats"])
+        .await
+        .unwrap()
+    else {
+        panic!("expected bulk string");
+    };
+    let info = std::str::from_utf8(&data).unwrap();
+    assert!(info.starts_with("# Stats\r\n"));
+    assert!(info.contains("keyspace_hits:1\r\n"));
+    assert!(info.contains("keyspace_misses:1\r\n"));
+    assert!(info.contains("\r\ncmdstat_get:calls=2,"));
+    assert!(info.contains("\r\ncmdstat_set:calls=1,"));
+    assert!(!info.contains("# Keyspace"));
     shutdown_server(port, handle).await.unwrap();
 }
 
@@ -78,3 +100,591 @@ async fn shutdown_returns_ok_and_stops_server() {
         .unwrap();
     assert!(result.is_ok());
 }
+
+#[tokio::test]
+async fn shutdown_save_writes_snapshot_and_abort_needs_a_shutdown() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+    let response = send_cmd(&mut framed, &["SHUTDOWN", "ABORT"]).await.unwrap();
+    assert_eq!(
+        response,
+        Frame::Error("ERR No shutdown in progress.".into())
+    );
+    let response = send_cmd(&mut framed, &["SHUTDOWN", "SAVE", "NOSAVE"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Error("ERR syntax error".into()));
+
+    send_cmd(&mut framed, &["SET", "k", "v"]).await.unwrap();
+    let Frame::Array(fields) = send_cmd(&mut framed, &["CONFIG", "GET", "dir"])
+        .await
+        .unwrap()
+    else {
+        panic!("expected array");
+    };
+    let [_, Frame::BulkString(dir)] = &fields[..] else {
+        panic!("expected dir");
+    };
+    let dir = std::path::PathBuf::from(std::str::from_utf8(dir).unwrap());
+
+    let response = send_cmd(&mut framed, &["SHUTDOWN", "SAVE", "NOW"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::SimpleString("OK".into()));
+    let result = tokio::time::timeout(tokio::time::Duration::from_secs(5), handle)
+        .await
+        .unwrap();
+    assert!(result.is_ok());
+
+    let restored = MemoryStore::new();
+    let aof = AofEngine::open(dir.join("data.aof"), FsyncMode::No)
+        .await
+        .unwrap();
+    aof.replay_into(&restored).await.unwrap();
+    assert_eq!(restored.len().await, 1);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn maxmemory_noeviction_rejects_writes_and_lru_evicts() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    send_cmd(&mut framed, &["SET", "a", "0123456789"])
+        .await
+        .unwrap();
+    send_cmd(&mut framed, &["SET", "b", "0123456789"])
+        .await
+        .unwrap();
+
+    let Frame::Integer(usage) = send_cmd(&mut framed, &["MEMORY", "USAGE", "a"])
+        .await
+        .unwrap()
+    else {
+        panic!("expected integer");
+    };
+    let limit = (usage + usage / 2).to_string();
+    let response = send_cmd(&mut framed, &["CONFIG", "SET", "maxmemory", &limit])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::SimpleString("OK".into()));
+
+    let response = send_cmd(&mut framed, &["SET", "c", "0123456789"])
+        .await
+        .unwrap();
+    assert_eq!(
+        response,
+        Frame::Error("OOM command not allowed when used memory > 'maxmemory'.".into())
+    );
+
+    let response = send_cmd(&mut framed, &["GET", "a"]).await.unwrap();
+    assert_eq!(response, Frame::BulkString("0123456789".into()));
+
+    send_cmd(
+        &mut framed,
+        &["CONFIG", "SET", "maxmemory-policy", "allkeys-lru"],
+    )
+    .await
+    .unwrap();
+    let response = send_cmd(&mut framed, &["SET", "c", "0123456789"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::SimpleString("OK".into()));
+
+    let response = send_cmd(&mut framed, &["DBSIZE"]).await.unwrap();
+    assert_eq!(response, Frame::Integer(2));
+
+    let Frame::BulkString(info) = send_cmd(&mut framed, &["INFO"]).await.unwrap() else {
+        panic!("expected bulk string");
+    };
+    let info = String::from_utf8_lossy(&info);
+    assert!(info.contains("evicted_keys:1\r\n"));
+    assert!(info.contains("maxmemory_policy:allkeys-lru\r\n"));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn memory_usage_stats_and_doctor() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    send_cmd(&mut framed, &["SET", "k", "value"]).await.unwrap();
+    let Frame::Integer(usage) = send_cmd(&mut framed, &["MEMORY", "USAGE", "k", "SAMPLES", "5"])
+        .await
+        .unwrap()
+    else {
+        panic!("expected integer");
+    };
+    assert!(usage > 6);
+
+    let response = send_cmd(&mut framed, &["MEMORY", "USAGE", "missing"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::NullBulkString);
+
+    let Frame::Array(stats) = send_cmd(&mut framed, &["MEMORY", "STATS"]).await.unwrap() else {
+        panic!("expected array");
+    };
+    let total = stats
+        .chunks(2)
+        .find(|pair| pair[0] == Frame::BulkString("total.allocated".into()))
+        .map(|pair| pair[1].clone());
+    assert_eq!(total, Some(Frame::Integer(usage)));
+
+    let response = send_cmd(&mut framed, &["MEMORY", "DOCTOR"]).await.unwrap();
+    assert!(matches!(response, Frame::BulkString(_)));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn scan_iterates_matching_keys() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    send_cmd(&mut framed, &["MSET", "u:1", "a", "u:2", "b", "other", "c"])
+        .await
+        .unwrap();
+
+    let mut cursor = "0".to_string();
+    let mut keys = Vec::new();
+    loop {
+        let Frame::Array(reply) = send_cmd(
+            &mut framed,
+            &["SCAN", &cursor, "MATCH", "u:*", "COUNT", "1"],
+        )
+        .await
+        .unwrap() else {
+            panic!("expected array");
+        };
+        let [Frame::BulkString(next), Frame::Array(batch)] = reply.as_slice() else {
+            panic!("unexpected SCAN reply: {reply:?}");
+        };
+        keys.extend(batch.iter().cloned());
+        cursor = String::from_utf8(next.to_vec()).unwrap();
+        if cursor == "0" {
+            break;
+        }
+    }
+    keys.sort_by_key(|k| format!("{k:?}"));
+    assert_eq!(
+        keys,
+        vec![
+            Frame::BulkString("u:1".into()),
+            Frame::BulkString("u:2".into())
+        ]
+    );
+
+    let response = send_cmd(&mut framed, &["SCAN", "abc"]).await.unwrap();
+    assert_eq!(response, Frame::Error("ERR invalid cursor".into()));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn client_registry_commands() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut me = connect(port).await.unwrap();
+    let mut other = connect(port).await.unwrap();
+
+    let Frame::Integer(my_id) = send_cmd(&mut me, &["CLIENT", "ID"]).await.unwrap() else {
+        panic!("expected integer id");
+    };
+    let Frame::Integer(other_id) = send_cmd(&mut other, &["CLIENT", "ID"]).await.unwrap() else {
+        panic!("expected integer id");
+    };
+    assert_ne!(my_id, other_id);
+
+    assert_eq!(
+        send_cmd(&mut me, &["CLIENT", "GETNAME"]).await.unwrap(),
+        Frame::NullBulkString
+    );
+    assert_eq!(
+        send_cmd(&mut me, &["CLIENT", "SETNAME", "admin"])
+            .await
+            .unwrap(),
+        Frame::SimpleString("OK".into())
+    );
+    assert_eq!(
+        send_cmd(&mut me, &["CLIENT", "GETNAME"]).await.unwrap(),
+        Frame::BulkString("admin".into())
+    );
+
+    let Frame::BulkString(list) = send_cmd(&mut me, &["CLIENT", "LIST"]).await.unwrap() else {
+        panic!("expected bulk string");
+    };
+    let list = String::from_utf8(list.to_vec()).unwrap();
+    assert_eq!(list.lines().count(), 2);
+    assert!(list.contains(&format!("id={my_id} ")));
+    assert!(list.contains("name=admin "));
+    assert!(list.contains("cmd=client|list "));
+
+    let Frame::BulkString(info) = send_cmd(&mut me, &["CLIENT", "INFO"]).await.unwrap() else {
+        panic!("expected bulk string");
+    };
+    assert!(info.starts_with(format!("id={my_id} ").as_bytes()));
+
+    // CLIENT REPLY OFF swallows replies until ON, whose OK is delivered.
+    send_cmd_no_reply(&mut other, &["CLIENT", "REPLY", "OFF"]).await;
+    send_cmd_no_reply(&mut other, &["SET", "k", "v"]).await;
+    assert_eq!(
+        send_cmd(&mut other, &["CLIENT", "REPLY", "ON"])
+            .await
+            .unwrap(),
+        Frame::SimpleString("OK".into())
+    );
+    assert_eq!(
+        send_cmd(&mut other, &["GET", "k"]).await.unwrap(),
+        Frame::BulkString("v".into())
+    );
+
+    let killed = send_cmd(&mut me, &["CLIENT", "KILL", "ID", &other_id.to_string()])
+        .await
+        .unwrap();
+    assert_eq!(killed, Frame::Integer(1));
+    assert!(other.next().await.is_none());
+    assert_eq!(
+        send_cmd(&mut me, &["CLIENT", "KILL", "USER", "default"])
+            .await
+            .unwrap(),
+        Frame::Integer(0)
+    );
+    assert_eq!(
+        send_cmd(&mut me, &["CLIENT", "KILL", "127.0.0.1:1"])
+            .await
+            .unwrap(),
+        Frame::Error("ERR No such client".into())
+    );
+
+    drop(me);
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn maxclients_rejects_extra_connections() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut first = connect(port).await.unwrap();
+    assert_eq!(
+        send_cmd(&mut first, &["CONFIG", "SET", "maxclients", "1"])
+            .await
+            .unwrap(),
+        Frame::SimpleString("OK".into())
+    );
+
+    let mut second = connect(port).await.unwrap();
+    assert_eq!(
+        second.next().await.unwrap().unwrap(),
+        Frame::Error("ERR max number of clients reached".into())
+    );
+
+    assert_eq!(
+        send_cmd(&mut first, &["CONFIG", "SET", "maxclients", "10"])
+            .await
+            .unwrap(),
+        Frame::SimpleString("OK".into())
+    );
+    drop(first);
+    shutdown_server(port, handle).await.unwrap();
+}
+
+async fn send_cmd_no_reply(
+    framed: &mut tokio_util::codec::Framed<tokio::net::TcpStream, yars::protocol::resp::RespCodec>,
+    parts: &[&str],
+) {
+    let frame = Frame::Array(
+        parts
+            .iter()
+            .map(|p| Frame::BulkString(p.to_string().into()))
+            .collect(),
+    );
+    framed.send(frame).await.unwrap();
+}
+
+#[tokio::test]
+async fn slowlog_records_commands_over_threshold() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    send_cmd(&mut framed, &["SET", "fast", "v"]).await.unwrap();
+    assert_eq!(
+        send_cmd(&mut framed, &["SLOWLOG", "LEN"]).await.unwrap(),
+        Frame::Integer(0)
+    );
+
+    send_cmd(
+        &mut framed,
+        &["CONFIG", "SET", "slowlog-log-slower-than", "0"],
+    )
+    .await
+    .unwrap();
+    send_cmd(&mut framed, &["CLIENT", "SETNAME", "app"])
+        .await
+        .unwrap();
+    send_cmd(&mut framed, &["GET", "fast"]).await.unwrap();
+
+    let Frame::Array(entries) = send_cmd(&mut framed, &["SLOWLOG", "GET", "1"])
+        .await
+        .unwrap()
+    else {
+        panic!("expected array");
+    };
+    assert_eq!(entries.len(), 1);
+    let Frame::Array(entry) = &entries[0] else {
+        panic!("expected entry array");
+    };
+    assert_eq!(
+        entry[3],
+        Frame::Array(vec![
+            Frame::BulkString("GET".into()),
+            Frame::BulkString("fast".into())
+        ])
+    );
+    assert!(matches!(&entry[4], Frame::BulkString(addr) if addr.starts_with(b"127.0.0.1:")));
+    assert_eq!(entry[5], Frame::BulkString("app".into()));
+
+    assert_eq!(
+        send_cmd(&mut framed, &["SLOWLOG", "RESET"]).await.unwrap(),
+        Frame::SimpleString("OK".into())
+    );
+    // The RESET itself is logged once it completes.
+    assert_eq!(
+        send_cmd(&mut framed, &["SLOWLOG", "LEN"]).await.unwrap(),
+        Frame::Integer(1)
+    );
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn monitor_streams_other_clients_commands() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut monitor = connect(port).await.unwrap();
+    let mut client = connect(port).await.unwrap();
+
+    assert_eq!(
+        send_cmd(&mut monitor, &["MONITOR"]).await.unwrap(),
+        Frame::SimpleString("OK".into())
+    );
+    send_cmd(&mut client, &["SET", "k", "a b"]).await.unwrap();
+
+    let Some(Ok(Frame::SimpleString(line))) = monitor.next().await else {
+        panic!("expected monitor line");
+    };
+    assert!(line.contains(" [0 127.0.0.1:"));
+    assert!(line.ends_with(r#"] "SET" "k" "a b""#));
+
+    assert_eq!(
+        send_cmd(&mut monitor, &["GET", "k"]).await.unwrap(),
+        Frame::Error("ERR only QUIT is allowed in MONITOR mode".into())
+    );
+    assert_eq!(
+        send_cmd(&mut monitor, &["QUIT"]).await.unwrap(),
+        Frame::SimpleString("OK".into())
+    );
+    assert!(monitor.next().await.is_none());
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn config_set_appendonly_switches_aof_at_runtime() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+    let persistence = |frame: Frame| {
+        let Frame::BulkString(data) = frame else {
+            panic!("expected bulk string");
+        };
+        String::from_utf8(data.to_vec()).unwrap()
+    };
+
+    send_cmd(&mut framed, &["SET", "before", "1"])
+        .await
+        .unwrap();
+    let response = send_cmd(&mut framed, &["CONFIG", "SET", "appendonly", "yes"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::SimpleString("OK".into()));
+    let Frame::Array(fields) = send_cmd(&mut framed, &["CONFIG", "GET", "dir", "appendfilename"])
+        .await
+        .unwrap()
+    else {
+        panic!("expected array");
+    };
+    let [_, Frame::BulkString(dir), _, Frame::BulkString(filename)] = &fields[..] else {
+        panic!("expected dir and appendfilename");
+    };
+    let path = std::path::Path::new(std::str::from_utf8(dir).unwrap())
+        .join(std::str::from_utf8(filename).unwrap());
+    let snapshot_len = std::fs::metadata(&path).unwrap().len();
+
+    send_cmd(&mut framed, &["SET", "after", "2"]).await.unwrap();
+    assert!(std::fs::metadata(&path).unwrap().len() > snapshot_len);
+    let info = persistence(
+        send_cmd(&mut framed, &["INFO", "persistence"])
+            .await
+            .unwrap(),
+    );
+    assert!(info.contains("aof_enabled:1\r\n"));
+
+    let response = send_cmd(
+        &mut framed,
+        &["CONFIG", "SET", "appendfilename", "other.aof"],
+    )
+    .await
+    .unwrap();
+    assert_eq!(response, Frame::SimpleString("OK".into()));
+    let other = path.with_file_name("other.aof");
+    assert!(std::fs::metadata(&other).unwrap().len() > snapshot_len);
+
+    let response = send_cmd(&mut framed, &["CONFIG", "SET", "appendonly", "no"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::SimpleString("OK".into()));
+    let other_len = std::fs::metadata(&other).unwrap().len();
+    send_cmd(&mut framed, &["SET", "ignored", "3"])
+        .await
+        .unwrap();
+    assert_eq!(std::fs::metadata(&other).unwrap().len(), other_len);
+    let info = persistence(
+        send_cmd(&mut framed, &["INFO", "persistence"])
+            .await
+            .unwrap(),
+    );
+    assert!(info.contains("aof_enabled:0\r\n"));
+
+    shutdown_server(port, handle).await.unwrap();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+async fn http_get(port: u16, request: &str) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port))
+        .await
+        .unwrap();
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test]
+async fn metrics_endpoint_serves_prometheus_text() {
+    let metrics_port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let (port, handle) = spawn_server_with(|config| config.metrics_port = metrics_port)
+        .await
+        .unwrap();
+    let mut framed = connect(port).await.unwrap();
+    send_cmd(&mut framed, &["SET", "k", "v"]).await.unwrap();
+    send_cmd(&mut framed, &["GET", "k"]).await.unwrap();
+
+    let response = http_get(
+        metrics_port,
+        "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n",
+    )
+    .await;
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(head.contains("Content-Type: text/plain; version=0.0.4"));
+    for line in [
+        "yars_command_calls_total{cmd=\"get\"} 1",
+        "yars_command_duration_seconds_count{cmd=\"set\"} 1",
+        "yars_connected_clients 1",
+        "yars_keys 1",
+        "yars_expired_keys_total 0",
+        "yars_aof_enabled 0",
+    ] {
+        assert!(body.lines().any(|l| l == line), "missing {line}");
+    }
+    assert!(body.contains("# TYPE yars_command_duration_seconds histogram\n"));
+    let input = body
+        .lines()
+        .find_map(|l| l.strip_prefix("yars_net_input_bytes_total "))
+        .unwrap();
+    assert!(input.parse::<u64>().unwrap() > 0);
+
+    let response = http_get(metrics_port, "GET /other HTTP/1.1\r\n\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn command_introspection() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    let Frame::Integer(count) = send_cmd(&mut framed, &["COMMAND", "COUNT"]).await.unwrap() else {
+        panic!("COMMAND COUNT should return an integer");
+    };
+    let Frame::Array(all) = send_cmd(&mut framed, &["COMMAND"]).await.unwrap() else {
+        panic!("COMMAND should return an array");
+    };
+    assert_eq!(all.len() as i64, count);
+
+    let response = send_cmd(&mut framed, &["COMMAND", "INFO", "get", "nosuch"])
+        .await
+        .unwrap();
+    let Frame::Array(infos) = response else {
+        panic!("COMMAND INFO should return an array");
+    };
+    let Frame::Array(get) = &infos[0] else {
+        panic!("GET should have an info entry");
+    };
+    assert_eq!(get[0], Frame::BulkString("get".into()));
+    assert_eq!(get[1], Frame::Integer(2));
+    assert_eq!(infos[1], Frame::NullBulkString);
+
+    let response = send_cmd(
+        &mut framed,
+        &["COMMAND", "GETKEYS", "MSET", "a", "1", "b", "2"],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        Frame::Array(vec![
+            Frame::BulkString("a".into()),
+            Frame::BulkString("b".into())
+        ])
+    );
+    let response = send_cmd(&mut framed, &["COMMAND", "GETKEYS", "PING"])
+        .await
+        .unwrap();
+    assert_eq!(
+        response,
+        Frame::Error("ERR The command has no key arguments".into())
+    );
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn container_commands_answer_help() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    let response = send_cmd(&mut framed, &["CONFIG", "NOPE"]).await.unwrap();
+    assert_eq!(
+        response,
+        Frame::Error("ERR unknown subcommand 'NOPE'. Try CONFIG HELP.".into())
+    );
+    for container in ["CLIENT", "COMMAND", "CONFIG", "MEMORY", "SLOWLOG"] {
+        let response = send_cmd(&mut framed, &[container, "HELP"]).await.unwrap();
+        let Frame::Array(lines) = response else {
+            panic!("{container} HELP: {response:?}");
+        };
+        assert_eq!(
+            lines[0],
+            Frame::SimpleString(format!(
+                "{container} <subcommand> [<arg> [value] [opt] ...]. Subcommands are:"
+            ))
+        );
+        assert!(lines.contains(&Frame::SimpleString("HELP".into())));
+    }
+
+    shutdown_server(port, handle).await.unwrap();
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common::{connect, send_cmd, shutdown_server, spawn_server};
+use tokio_util::bytes::Bytes;
+use yars::{
+    protocol::{
+        command::table::{self, CommandHandler, CommandSpec, KeySpec},
+        resp::Frame,
+    },
+    service::{clients::ClientHandle, context::ServerContext, handlers::CommandEffect},
+    store::traits::Store,
+};
+
+struct Strlen2;
+
+#[async_trait]
+impl CommandHandler for Strlen2 {
+    async fn call(&self, ctx: &ServerContext, _: &ClientHandle, args: &[Bytes]) -> CommandEffect {
+        let len = match ctx.store.get(&args[1]).await {
+            Some(entry) => entry.value.len() as i64,
+            None => 0,
+        };
+        CommandEffect::Read(Frame::Integer(len * 2))
+    }
+}
+
+fn spec(name: &'static str) -> CommandSpec {
+    CommandSpec {
+        name,
+        arity: 2,
+        flags: &["readonly", "fast"],
+        acl_categories: &["read", "string", "fast"],
+        keys: KeySpec::FIRST,
+        parse: None,
+        handler: None,
+        group: "string",
+        since: "1.0.0",
+        summary: "Returns twice the length of a string value.",
+        subcommands: &[],
+    }
+}
+
+// The registry is process-wide, so this lives in its own test binary.
+#[tokio::test]
+async fn registered_commands_run_like_builtin_ones() {
+    table::register(spec("strlen2"), Arc::new(Strlen2)).unwrap();
+    assert!(table::register(spec("strlen2"), Arc::new(Strlen2)).is_err());
+    assert!(table::register(spec("get"), Arc::new(Strlen2)).is_err());
+    assert!(table::register(spec("Bad Name"), Arc::new(Strlen2)).is_err());
+
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    send_cmd(&mut framed, &["SET", "k", "abc"]).await.unwrap();
+    let response = send_cmd(&mut framed, &["STRLEN2", "k"]).await.unwrap();
+    assert_eq!(response, Frame::Integer(6));
+    let response = send_cmd(&mut framed, &["strlen2"]).await.unwrap();
+    assert_eq!(
+        response,
+        Frame::Error("ERR wrong number of arguments for 'strlen2' command".into())
+    );
+
+    let response = send_cmd(&mut framed, &["COMMAND", "GETKEYS", "strlen2", "k"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Array(vec![Frame::BulkString("k".into())]));
+    let Frame::Array(infos) = send_cmd(&mut framed, &["COMMAND", "INFO", "strlen2"])
+        .await
+        .unwrap()
+    else {
+        panic!("COMMAND INFO should return an array");
+    };
+    assert!(matches!(&infos[0], Frame::Array(info) if info[1] == Frame::Integer(2)));
+    let Frame::Array(names) = send_cmd(&mut framed, &["COMMAND", "LIST"]).await.unwrap() else {
+        panic!("COMMAND LIST should return an array");
+    };
+    assert!(names.contains(&Frame::BulkString("strlen2".into())));
+
+    shutdown_server(port, handle).await.unwrap();
+}
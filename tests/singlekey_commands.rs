@@ -198,3 +198,443 @@ async fn persist_removes_expiry() {
 
     shutdown_server(port, handle).await.unwrap();
 }
+
+#[tokio::test]
+async fn set_nx_and_xx() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    let response = send_cmd(&mut framed, &["SET", "lock", "a", "NX", "PX", "30000"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::SimpleString("OK".into()));
+
+    let response = send_cmd(&mut framed, &["SET", "lock", "b", "NX", "PX", "30000"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::NullBulkString);
+
+    let response = send_cmd(&mut framed, &["SET", "missing", "v", "XX"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::NullBulkString);
+
+    let response = send_cmd(&mut framed, &["SET", "lock", "c", "XX", "KEEPTTL"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::SimpleString("OK".into()));
+
+    let response = send_cmd(&mut framed, &["PTTL", "lock"]).await.unwrap();
+    assert!(matches!(response, Frame::Integer(t) if t > 0 && t <= 30000));
+
+    let response = send_cmd(&mut framed, &["SET", "lock", "d", "NX", "XX"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Error("ERR syntax error".into()));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn set_get_swaps_value() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    let response = send_cmd(&mut framed, &["SET", "k", "first", "GET"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::NullBulkString);
+
+    let response = send_cmd(&mut framed, &["SET", "k", "second", "GET"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::BulkString("first".into()));
+
+    let response = send_cmd(&mut framed, &["GET", "k"]).await.unwrap();
+    assert_eq!(response, Frame::BulkString("second".into()));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn setex_and_psetex_set_ttl() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    let response = send_cmd(&mut framed, &["SETEX", "k", "10", "v"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::SimpleString("OK".into()));
+    let response = send_cmd(&mut framed, &["TTL", "k"]).await.unwrap();
+    assert!(matches!(response, Frame::Integer(t) if t > 0 && t <= 10));
+
+    let response = send_cmd(&mut framed, &["PSETEX", "p", "5000", "v"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::SimpleString("OK".into()));
+    let response = send_cmd(&mut framed, &["PTTL", "p"]).await.unwrap();
+    assert!(matches!(response, Frame::Integer(t) if t > 0 && t <= 5000));
+
+    let response = send_cmd(&mut framed, &["SETEX", "k", "-1", "v"])
+        .await
+        .unwrap();
+    assert_eq!(
+        response,
+        Frame::Error("ERR invalid expire time in 'setex' command".into())
+    );
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn getex_updates_and_persists_ttl() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    send_cmd(&mut framed, &["SET", "k", "v"]).await.unwrap();
+
+    let response = send_cmd(&mut framed, &["GETEX", "k", "EX", "100"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::BulkString("v".into()));
+    let response = send_cmd(&mut framed, &["TTL", "k"]).await.unwrap();
+    assert!(matches!(response, Frame::Integer(t) if t > 0 && t <= 100));
+
+    let response = send_cmd(&mut framed, &["GETEX", "k", "PERSIST"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::BulkString("v".into()));
+    let response = send_cmd(&mut framed, &["TTL", "k"]).await.unwrap();
+    assert_eq!(response, Frame::Integer(-1));
+
+    let response = send_cmd(&mut framed, &["GETEX", "missing"]).await.unwrap();
+    assert_eq!(response, Frame::NullBulkString);
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn incrby_decrby_and_incrbyfloat() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    let response = send_cmd(&mut framed, &["INCRBY", "n", "10"]).await.unwrap();
+    assert_eq!(response, Frame::Integer(10));
+
+    let response = send_cmd(&mut framed, &["DECRBY", "n", "4"]).await.unwrap();
+    assert_eq!(response, Frame::Integer(6));
+
+    let response = send_cmd(&mut framed, &["INCRBYFLOAT", "n", "1.5"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::BulkString("7.5".into()));
+
+    let response = send_cmd(&mut framed, &["INCRBY", "n", "1"]).await.unwrap();
+    assert_eq!(
+        response,
+        Frame::Error("ERR value is not an integer or out of range".into())
+    );
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn getrange_setrange_and_substr() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    send_cmd(&mut framed, &["SET", "k", "Hello World"])
+        .await
+        .unwrap();
+
+    let response = send_cmd(&mut framed, &["SETRANGE", "k", "6", "Redis"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Integer(11));
+
+    let response = send_cmd(&mut framed, &["GETRANGE", "k", "-5", "-1"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::BulkString("Redis".into()));
+
+    let response = send_cmd(&mut framed, &["SUBSTR", "k", "0", "4"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::BulkString("Hello".into()));
+
+    let response = send_cmd(&mut framed, &["SETRANGE", "pad", "2", "x"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Integer(3));
+    let response = send_cmd(&mut framed, &["GET", "pad"]).await.unwrap();
+    assert_eq!(response, Frame::BulkString("\0\0x".into()));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn setbit_getbit_and_bitcount() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    for offset in ["1", "3", "10"] {
+        let response = send_cmd(&mut framed, &["SETBIT", "flags", offset, "1"])
+            .await
+            .unwrap();
+        assert_eq!(response, Frame::Integer(0));
+    }
+
+    let response = send_cmd(&mut framed, &["GETBIT", "flags", "3"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Integer(1));
+
+    let response = send_cmd(&mut framed, &["BITCOUNT", "flags"]).await.unwrap();
+    assert_eq!(response, Frame::Integer(3));
+
+    let response = send_cmd(&mut framed, &["BITCOUNT", "flags", "0", "3", "BIT"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Integer(2));
+
+    let response = send_cmd(&mut framed, &["BITPOS", "flags", "1", "1"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Integer(10));
+
+    let response = send_cmd(&mut framed, &["STRLEN", "flags"]).await.unwrap();
+    assert_eq!(response, Frame::Integer(2));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn bitfield_incrby_with_overflow() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    let response = send_cmd(
+        &mut framed,
+        &[
+            "BITFIELD", "bf", "INCRBY", "u2", "100", "1", "OVERFLOW", "SAT", "INCRBY", "u2", "102",
+            "1",
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        Frame::Array(vec![Frame::Integer(1), Frame::Integer(1)])
+    );
+
+    let response = send_cmd(&mut framed, &["BITFIELD_RO", "bf", "GET", "u2", "100"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Array(vec![Frame::Integer(1)]));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn geoadd_geodist_geohash_and_geopos() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    let response = send_cmd(
+        &mut framed,
+        &[
+            "GEOADD",
+            "Sicily",
+            "13.361389",
+            "38.115556",
+            "Palermo",
+            "15.087269",
+            "37.502669",
+            "Catania",
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(response, Frame::Integer(2));
+
+    let response = send_cmd(
+        &mut framed,
+        &["GEODIST", "Sicily", "Palermo", "Catania", "km"],
+    )
+    .await
+    .unwrap();
+    assert_eq!(response, Frame::BulkString("166.2742".into()));
+
+    let response = send_cmd(&mut framed, &["GEOHASH", "Sicily", "Palermo", "Nowhere"])
+        .await
+        .unwrap();
+    assert_eq!(
+        response,
+        Frame::Array(vec![
+            Frame::BulkString("sqc8b49rny0".into()),
+            Frame::NullBulkString,
+        ])
+    );
+
+    let response = send_cmd(&mut framed, &["GEOPOS", "Sicily", "Nowhere"])
+        .await
+        .unwrap();
+    assert_eq!(response, Frame::Array(vec![Frame::NullArray]));
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn geosearch_and_geosearchstore() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    send_cmd(
+        &mut framed,
+        &[
+            "GEOADD",
+            "Sicily",
+            "13.361389",
+            "38.115556",
+            "Palermo",
+            "15.087269",
+            "37.502669",
+            "Catania",
+        ],
+    )
+    .await
+    .unwrap();
+
+    let response = send_cmd(
+        &mut framed,
+        &[
+            "GEOSEARCH",
+            "Sicily",
+            "FROMLONLAT",
+            "15",
+            "37",
+            "BYRADIUS",
+            "200",
+            "km",
+            "ASC",
+            "WITHDIST",
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        Frame::Array(vec![
+            Frame::Array(vec![
+                Frame::BulkString("Catania".into()),
+                Frame::BulkString("56.4413".into()),
+            ]),
+            Frame::Array(vec![
+                Frame::BulkString("Palermo".into()),
+                Frame::BulkString("190.4424".into()),
+            ]),
+        ])
+    );
+
+    let response = send_cmd(
+        &mut framed,
+        &[
+            "GEOSEARCHSTORE",
+            "near",
+            "Sicily",
+            "FROMMEMBER",
+            "Catania",
+            "BYBOX",
+            "100",
+            "100",
+            "km",
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(response, Frame::Integer(1));
+
+    let response = send_cmd(
+        &mut framed,
+        &[
+            "GEOSEARCH",
+            "near",
+            "FROMLONLAT",
+            "15",
+            "37",
+            "BYRADIUS",
+            "100",
+            "km",
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        Frame::Array(vec![Frame::BulkString("Catania".into())])
+    );
+
+    shutdown_server(port, handle).await.unwrap();
+}
+
+#[tokio::test]
+async fn string_commands_reject_sorted_sets() {
+    let (port, handle) = spawn_server().await.unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    send_cmd(
+        &mut framed,
+        &["GEOADD", "geo", "13.361389", "38.115556", "Palermo"],
+    )
+    .await
+    .unwrap();
+    send_cmd(&mut framed, &["SET", "s", "text"]).await.unwrap();
+
+    let wrong_type =
+        Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".into());
+    for cmd in [
+        &["GET", "geo"][..],
+        &["APPEND", "geo", "x"],
+        &["SETRANGE", "geo", "0", "x"],
+        &["GETRANGE", "geo", "0", "-1"],
+        &["STRLEN", "geo"],
+        &["INCR", "geo"],
+        &["INCRBYFLOAT", "geo", "1.5"],
+        &["SETBIT", "geo", "0", "1"],
+        &["GETBIT", "geo", "0"],
+        &["BITCOUNT", "geo"],
+        &["BITFIELD", "geo", "GET", "u8", "0"],
+        &["LCS", "geo", "s"],
+        &["SET", "geo", "v", "GET"],
+        &["GETDEL", "geo"],
+        &["PFADD", "geo", "a"],
+        &["GEOADD", "s", "13.361389", "38.115556", "Palermo"],
+    ] {
+        let response = send_cmd(&mut framed, cmd).await.unwrap();
+        assert_eq!(response, wrong_type, "{cmd:?}");
+    }
+
+    let response = send_cmd(&mut framed, &["MGET", "geo", "s"]).await.unwrap();
+    assert_eq!(
+        response,
+        Frame::Array(vec![
+            Frame::NullBulkString,
+            Frame::BulkString("text".into())
+        ])
+    );
+    let response = send_cmd(&mut framed, &["GEOHASH", "geo", "Palermo"])
+        .await
+        .unwrap();
+    assert_eq!(
+        response,
+        Frame::Array(vec![Frame::BulkString("sqc8b49rny0".into())])
+    );
+
+    send_cmd(&mut framed, &["SET", "geo", "plain"])
+        .await
+        .unwrap();
+    let response = send_cmd(&mut framed, &["GET", "geo"]).await.unwrap();
+    assert_eq!(response, Frame::BulkString("plain".into()));
+
+    shutdown_server(port, handle).await.unwrap();
+}
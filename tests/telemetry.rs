@@ -0,0 +1,66 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{connect, send_cmd, shutdown_server, spawn_server_with};
+use serde_json::Value;
+use yars::{protocol::resp::Frame, telemetry};
+
+// Tracing is process-wide, so this lives in its own test binary.
+#[tokio::test]
+async fn command_spans_are_written_to_the_trace_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("spans.jsonl");
+    let (port, handle) = spawn_server_with(|c| c.trace_file = path.clone())
+        .await
+        .unwrap();
+    let mut framed = connect(port).await.unwrap();
+
+    let ok = Frame::SimpleString("OK".into());
+    for args in [
+        &[
+            "CONFIG",
+            "SET",
+            "appendonly",
+            "yes",
+            "appendfsync",
+            "always",
+        ][..],
+        &["CONFIG", "SET", "trace-exporter", "file"],
+        &["SET", "k", "v"],
+    ] {
+        assert_eq!(send_cmd(&mut framed, args).await.unwrap(), ok);
+    }
+    shutdown_server(port, handle).await.unwrap();
+    telemetry::shutdown().await;
+
+    let spans: Vec<Value> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let root = spans
+        .iter()
+        .find(|span| span["name"] == "command" && span["attributes"]["db.operation.name"] == "set")
+        .expect("a root span for SET");
+    assert!(root["parent_span_id"].is_null());
+
+    let by_id: HashMap<&str, &Value> = spans
+        .iter()
+        .filter(|span| span["trace_id"] == root["trace_id"])
+        .map(|span| (span["span_id"].as_str().unwrap(), span))
+        .collect();
+    let parent_name = |name: &str| {
+        let span = by_id.values().find(|span| span["name"] == name).unwrap();
+        by_id[span["parent_span_id"].as_str().unwrap()]["name"]
+            .as_str()
+            .unwrap()
+    };
+    assert_eq!(parent_name("resp.decode"), "command");
+    assert_eq!(parent_name("command.parse"), "command");
+    assert_eq!(parent_name("command.dispatch"), "command");
+    assert_eq!(parent_name("store.lock.wait"), "command.dispatch");
+    assert_eq!(parent_name("store.lock.hold"), "command.dispatch");
+    assert_eq!(parent_name("aof.append"), "command");
+    assert_eq!(parent_name("aof.fsync"), "aof.append");
+}